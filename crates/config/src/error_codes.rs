@@ -0,0 +1,324 @@
+pub struct ErrorCode {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Optional error codes are only reported if they are enabled with --enable-error-code
+    pub enabled_by_default: bool,
+}
+
+const fn code(name: &'static str, description: &'static str) -> ErrorCode {
+    ErrorCode {
+        name,
+        description,
+        enabled_by_default: true,
+    }
+}
+
+const fn optional_code(name: &'static str, description: &'static str) -> ErrorCode {
+    ErrorCode {
+        name,
+        description,
+        enabled_by_default: false,
+    }
+}
+
+/// All error codes that can be used in `# type: ignore[...]` comments and with
+/// --enable-error-code/--disable-error-code. This contains all of Mypy's error codes (even the
+/// ones that are never reported by Zuban), so existing configs keep working.
+pub const ERROR_CODES: &[ErrorCode] = &[
+    code("attr-defined", "Check that attribute exists"),
+    code(
+        "union-attr",
+        "Check that attribute exists in each item of a union",
+    ),
+    code("name-defined", "Check that name is defined"),
+    code(
+        "used-before-def",
+        "Warn about variables that are used before their definition",
+    ),
+    code(
+        "call-arg",
+        "Check number, names and kinds of arguments in calls",
+    ),
+    code("arg-type", "Check argument types in calls"),
+    code(
+        "call-overload",
+        "Check that an overload variant matches arguments",
+    ),
+    code("valid-type", "Check that type (annotation) is valid"),
+    code(
+        "var-annotated",
+        "Require variable annotation if type can't be inferred",
+    ),
+    code(
+        "override",
+        "Check that method override is compatible with base class",
+    ),
+    code("return", "Check that function always returns a value"),
+    code(
+        "return-value",
+        "Check that return value is compatible with signature",
+    ),
+    code(
+        "assignment",
+        "Check that assigned value is compatible with target",
+    ),
+    code(
+        "method-assign",
+        "Check that assignment target is not a method",
+    ),
+    code("type-var", "Check that type variable values are valid"),
+    code("operator", "Check that operator is valid for operands"),
+    code("index", "Check indexing operations"),
+    code(
+        "list-item",
+        "Check list items in a list expression [item, ...]",
+    ),
+    code(
+        "dict-item",
+        "Check dict items in a dict expression {key: value, ...}",
+    ),
+    code("typeddict-item", "Check items when constructing TypedDict"),
+    code(
+        "typeddict-unknown-key",
+        "Check unknown keys when constructing TypedDict",
+    ),
+    code(
+        "typeddict-readonly-mutated",
+        "TypedDict's ReadOnly key is mutated",
+    ),
+    code("has-type", "Check that type of target is known"),
+    code(
+        "import",
+        "Require that imported module can be found or has stubs",
+    ),
+    code(
+        "import-not-found",
+        "Require that imported module can be found",
+    ),
+    code("import-untyped", "Require that imported module has stubs"),
+    optional_code(
+        "import-extension",
+        "Require that imported extension modules have stubs",
+    ),
+    code("no-redef", "Check that each name is defined once"),
+    code("metaclass", "Ensure that metaclass is valid"),
+    code(
+        "untyped-decorator",
+        "Error if an untyped decorator makes a typed function untyped",
+    ),
+    code(
+        "unsafe-overload",
+        "Warn if multiple @overload variants overlap in unsafe ways",
+    ),
+    code(
+        "func-returns-value",
+        "Check that called function returns a value in value context",
+    ),
+    code(
+        "abstract",
+        "Prevent instantiation of classes with abstract attributes",
+    ),
+    code(
+        "type-abstract",
+        "Require only concrete classes where Type[...] is expected",
+    ),
+    code(
+        "safe-super",
+        "Warn about calls to abstract methods with empty/trivial bodies",
+    ),
+    code("valid-newtype", "Check that argument 2 to NewType is valid"),
+    code(
+        "str-format",
+        "Check that string formatting/interpolation is type-safe",
+    ),
+    code(
+        "str-bytes-safe",
+        "Warn about implicit coercions related to bytes and string types",
+    ),
+    code(
+        "exit-return",
+        "Warn about too general return type for '__exit__'",
+    ),
+    code(
+        "name-match",
+        "Check that type definition has consistent naming",
+    ),
+    code("literal-required", "Check that value is a literal"),
+    code(
+        "no-overload-impl",
+        "Check that overloaded functions outside stub files have an implementation",
+    ),
+    code("unused-coroutine", "Ensure that all coroutines are used"),
+    code("top-level-await", "Warn about top level await expressions"),
+    code(
+        "await-not-async",
+        "Warn about \"await\" outside coroutine (\"async def\")",
+    ),
+    code("assert-type", "Check that assert_type() call succeeds"),
+    code(
+        "truthy-function",
+        "Warn about function that always evaluate to true in boolean contexts",
+    ),
+    code(
+        "annotation-unchecked",
+        "Notify about type annotations in unchecked functions",
+    ),
+    code(
+        "prop-decorator",
+        "Decorators on top of @property are not supported",
+    ),
+    code(
+        "empty-body",
+        "A dedicated error code to opt out return errors for empty/trivial bodies",
+    ),
+    code(
+        "overload-overlap",
+        "Warn if multiple @overload variants overlap in unsafe ways",
+    ),
+    code(
+        "overload-cannot-match",
+        "Warn if an @overload signature can never be matched",
+    ),
+    code(
+        "narrowed-type-not-subtype",
+        "Check that TypeIs narrows types",
+    ),
+    code("unused-ignore", "Ensure that all type ignores are used"),
+    code("type-arg", "Check that generic type arguments are present"),
+    code(
+        "no-untyped-def",
+        "Check that every function has an annotation",
+    ),
+    code(
+        "redundant-cast",
+        "Check that cast changes type of expression",
+    ),
+    code(
+        "comparison-overlap",
+        "Check that types in comparisons and 'in' expressions overlap",
+    ),
+    code(
+        "no-untyped-call",
+        "Disallow calling functions without type annotations from annotated functions",
+    ),
+    code(
+        "no-any-return",
+        "Reject returning value with \"Any\" type if return type is not \"Any\"",
+    ),
+    code(
+        "no-any-unimported",
+        "Reject \"Any\" types from unfollowed imports",
+    ),
+    code("explicit-any", "Reject explicit \"Any\" types"),
+    code(
+        "unreachable",
+        "Warn about unreachable statements or expressions",
+    ),
+    code("syntax", "Report syntax errors"),
+    code("misc", "Miscellaneous other checks"),
+    // Zuban specific error codes
+    code(
+        "dunder-contract",
+        "Check that special methods follow the data model",
+    ),
+    code("untyped-attribute", "Reject attributes without annotations"),
+    code("untyped-import", "Reject imports of modules without types"),
+    code(
+        "monkeypatch",
+        "Reject assignments to attributes of modules and classes from the outside",
+    ),
+    code("timeout", "Report functions that took too long to check"),
+    // Optional error codes
+    optional_code(
+        "redundant-self",
+        "Warn about redundant Self type annotations on method first argument",
+    ),
+    optional_code("redundant-expr", "Warn about redundant expressions"),
+    optional_code(
+        "possibly-undefined",
+        "Warn about variables that are defined only in some execution paths",
+    ),
+    optional_code(
+        "truthy-bool",
+        "Warn about expressions that could always evaluate to true in boolean contexts",
+    ),
+    optional_code(
+        "truthy-iterable",
+        "Warn about Iterable expressions that could always evaluate to true in boolean contexts",
+    ),
+    optional_code(
+        "ignore-without-code",
+        "Warn about '# type: ignore' comments which do not have error codes",
+    ),
+    optional_code(
+        "unused-awaitable",
+        "Ensure that all awaitable values are used",
+    ),
+    optional_code(
+        "unimported-reveal",
+        "Require explicit import from typing or typing_extensions for reveal_type",
+    ),
+    optional_code(
+        "explicit-override",
+        "Require @override decorator if method is overriding a base class method",
+    ),
+    optional_code(
+        "mutable-override",
+        "Reject covariant overrides for mutable attributes",
+    ),
+    optional_code(
+        "deprecated",
+        "Warn when importing or using deprecated (overloaded) functions, methods or classes",
+    ),
+    optional_code(
+        "exhaustive-match",
+        "Reject match statements that are not exhaustive",
+    ),
+    optional_code(
+        "unused-variable",
+        "Warn about local variables that are never used",
+    ),
+    optional_code(
+        "unused-parameter",
+        "Warn about parameters that are never used",
+    ),
+    optional_code("unused-import", "Warn about imports that are never used"),
+    optional_code(
+        "shadowed-builtin",
+        "Warn about definitions that shadow builtins",
+    ),
+    optional_code(
+        "stub-lint",
+        "Warn about complex defaults, missing or Any returns and inconsistent overload decorators in local stubs",
+    ),
+    optional_code("stub-docstring", "Warn about docstrings in local stubs"),
+    optional_code(
+        "type-checking-mismatch",
+        "Warn about runtime class members that are incompatible with their TYPE_CHECKING definitions",
+    ),
+    optional_code(
+        "overload-flag",
+        "Warn about overloads that are selected by non-literal flags",
+    ),
+    optional_code(
+        "inferred-never",
+        "Warn about type arguments that are inferred as Never",
+    ),
+    optional_code(
+        "taint",
+        "Reject Untrusted annotated values that are passed to Trusted annotated parameters",
+    ),
+];
+
+pub fn lookup_error_code(name: &str) -> Option<&'static ErrorCode> {
+    ERROR_CODES.iter().find(|code| code.name == name)
+}
+
+/// The codes that are not known. These are ignored with a warning instead of failing, because
+/// they might be defined by Mypy plugins (e.g. `pydantic-field`) or newer Mypy versions.
+pub fn unknown_error_codes<'a>(codes: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    codes
+        .into_iter()
+        .filter(|code| lookup_error_code(code).is_none())
+        .collect()
+}
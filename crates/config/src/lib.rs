@@ -1,3 +1,4 @@
+mod error_codes;
 mod searcher;
 mod venv;
 
@@ -9,7 +10,8 @@ use regex::Regex;
 use toml_edit::{DocumentMut, Item, Table, Value};
 use vfs::{AbsPath, Directory, GlobAbsPath, LocalFS, NormalizedPath, VfsHandler};
 
-pub use searcher::{find_cli_config, find_workspace_config};
+pub use error_codes::{ERROR_CODES, ErrorCode, lookup_error_code, unknown_error_codes};
+pub use searcher::{contains_config_file, find_cli_config, find_workspace_config};
 
 type ConfigResult = anyhow::Result<bool>;
 
@@ -38,7 +40,7 @@ impl Default for DiagnosticConfig {
     }
 }
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, PartialEq)]
 pub struct ProjectOptions {
     pub settings: Settings,
     pub flags: TypeCheckerFlags,
@@ -395,6 +397,7 @@ pub struct TypeCheckerFlags {
     pub no_implicit_reexport: bool,
     pub disable_bytearray_promotion: bool,
     pub disable_memoryview_promotion: bool,
+    pub strict_bytes: bool,
 
     pub enabled_error_codes: Vec<String>,
     pub disabled_error_codes: Vec<String>,
@@ -408,6 +411,19 @@ pub struct TypeCheckerFlags {
     // Non-mypy settings
     pub use_joins: bool,
     pub disallow_deprecated: bool,
+    pub explain_mismatch: bool,
+    pub strict_signature_matching: bool,
+    pub disallow_untyped_attributes: bool,
+    pub union_untyped_attributes: bool,
+    pub disallow_monkeypatching: bool,
+    pub allow_function_attributes: bool,
+    pub strict_numeric_promotion: bool,
+    pub function_timeout: Option<u64>, // In seconds
+    pub untyped_extension_modules: Vec<String>,
+    pub disallow_untyped_imports: bool,
+    pub allowed_untyped_imports: Vec<String>,
+    pub generated_code_markers: Vec<String>,
+    pub generated_code_excludes: Vec<GeneratedCodeExclude>,
 }
 
 impl Default for TypeCheckerFlags {
@@ -440,6 +456,7 @@ impl Default for TypeCheckerFlags {
             no_implicit_reexport: false,
             disable_bytearray_promotion: false,
             disable_memoryview_promotion: false,
+            strict_bytes: false,
             excludes: vec![],
             always_true_symbols: vec![],
             always_false_symbols: vec![],
@@ -449,6 +466,19 @@ impl Default for TypeCheckerFlags {
             case_sensitive: true,
             use_joins: false,
             disallow_deprecated: false,
+            explain_mismatch: false,
+            strict_signature_matching: false,
+            disallow_untyped_attributes: false,
+            union_untyped_attributes: false,
+            disallow_monkeypatching: false,
+            allow_function_attributes: false,
+            strict_numeric_promotion: false,
+            function_timeout: None,
+            untyped_extension_modules: vec![],
+            disallow_untyped_imports: false,
+            allowed_untyped_imports: vec![],
+            generated_code_markers: vec![],
+            generated_code_excludes: vec![],
         }
     }
 }
@@ -476,6 +506,31 @@ impl TypeCheckerFlags {
     pub fn enable_strict_bytes(&mut self) {
         self.disable_bytearray_promotion = true;
         self.disable_memoryview_promotion = true;
+        self.strict_bytes = true;
+    }
+
+    /// Whether an error code is reported, optional error codes need to be enabled explicitly.
+    pub fn is_error_code_enabled(&self, code: &str) -> bool {
+        if self.enabled_error_codes.iter().any(|c| c == code) {
+            return true;
+        }
+        lookup_error_code(code).is_some_and(|c| c.enabled_by_default)
+            && !self.disabled_error_codes.iter().any(|c| c == code)
+    }
+
+    fn enable_error_codes(&mut self, codes: Vec<String>) -> ConfigResult {
+        warn_about_unknown_error_codes(&codes);
+        // The last setting wins, this is especially important for per-module overrides.
+        self.disabled_error_codes.retain(|c| !codes.contains(c));
+        self.enabled_error_codes.extend(codes);
+        Ok(false)
+    }
+
+    fn disable_error_codes(&mut self, codes: Vec<String>) -> ConfigResult {
+        warn_about_unknown_error_codes(&codes);
+        self.enabled_error_codes.retain(|c| !codes.contains(c));
+        self.disabled_error_codes.extend(codes);
+        Ok(false)
     }
 
     pub fn mypy_default() -> Self {
@@ -493,7 +548,7 @@ impl TypeCheckerFlags {
 
     pub fn finalize(mut self) -> FinalizedTypeCheckerFlags {
         if !self.disallow_deprecated {
-            if self.enabled_error_codes.iter().any(|s| s == "deprecated") {
+            if self.is_error_code_enabled("deprecated") {
                 self.disallow_deprecated = true;
             }
         }
@@ -564,6 +619,48 @@ impl std::hash::Hash for ExcludeRegex {
     }
 }
 
+/// Files matching the glob are generated, the reason is shown in the summary. Written as
+/// `"<glob>: <reason>"`, e.g. `"proto/*_pb2.py: generated by protoc"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GeneratedCodeExclude {
+    pub pattern: glob::Pattern,
+    pub reason: String,
+}
+
+impl GeneratedCodeExclude {
+    pub fn new(exclude: &str) -> anyhow::Result<Self> {
+        let Some((glob, reason)) = exclude.split_once(':') else {
+            bail!(r#"Expected "<glob>: <reason>" for a generated code exclude, got "{exclude}""#)
+        };
+        let (glob, reason) = (glob.trim(), reason.trim());
+        if glob.is_empty() || reason.is_empty() {
+            bail!(r#"Expected "<glob>: <reason>" for a generated code exclude, got "{exclude}""#)
+        }
+        Ok(Self {
+            pattern: glob::Pattern::new(glob)?,
+            reason: reason.into(),
+        })
+    }
+
+    pub fn matches(&self, relative_path: &str) -> bool {
+        self.pattern.matches_with(
+            relative_path,
+            glob::MatchOptions {
+                require_literal_separator: true,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+pub fn check_generated_code_marker(marker: String) -> anyhow::Result<String> {
+    // An empty marker would be part of every comment.
+    if marker.trim().is_empty() {
+        bail!("Generated code markers must not be empty")
+    }
+    Ok(marker)
+}
+
 // These are the overrides with the precedence order as described in https://mypy.readthedocs.io/en/stable/config_file.html#config-file-format
 #[derive(PartialOrd, Ord, PartialEq, Eq, Copy, Clone, Debug)]
 enum OverrideKind {
@@ -572,13 +669,13 @@ enum OverrideKind {
     ModuleName,     // e.g. foo.bar (has the highest priority
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 enum OverridePathPart {
     Part(Box<str>),
     Wildcard,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct OverridePath {
     path: Vec<OverridePathPart>,
     kind: OverrideKind,
@@ -669,7 +766,20 @@ enum OverrideIniOrTomlValue {
     Ini(Box<str>),
 }
 
-#[derive(Clone, Debug)]
+impl PartialEq for OverrideIniOrTomlValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            // TOML values are not comparable, but their normalized representations are.
+            (Self::Toml(v1), Self::Toml(v2)) => {
+                v1.clone().decorated("", "").to_string() == v2.clone().decorated("", "").to_string()
+            }
+            (Self::Ini(v1), Self::Ini(v2)) => v1 == v2,
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct OverrideConfig {
     pub module: OverridePath, // Path like foo.bar or foo.bar.*
     // Key/Value mappings
@@ -756,6 +866,22 @@ impl IniOrTomlValue<'_> {
         }
     }
 
+    fn as_u64(&self) -> anyhow::Result<u64> {
+        match self {
+            Self::Toml(v) => v
+                .as_integer()
+                .and_then(|i| u64::try_from(i).ok())
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Expected a positive integer, got {}", v.to_string().trim())
+                }),
+            Self::Ini(s) => s
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Expected a positive integer, got \"{s}\"")),
+            Self::InlineConfigNoValue => bail!("Expected a positive integer"),
+        }
+    }
+
     fn as_str_list(&self, key: &str, split_on: &[char]) -> anyhow::Result<Vec<String>> {
         let split_str = |s| split_and_trim(s, split_on).map(|x| x.to_string()).collect();
         match self {
@@ -837,8 +963,37 @@ pub fn set_flag_and_return_ignore_errors(
         }
         "always_true" => add_list_of_str(&mut flags.always_true_symbols),
         "always_false" => add_list_of_str(&mut flags.always_false_symbols),
-        "enable_error_code" => add_list_of_str(&mut flags.enabled_error_codes),
-        "disable_error_code" => add_list_of_str(&mut flags.disabled_error_codes),
+        "enable_error_code" | "disable_error_code" => {
+            let mut codes = vec![];
+            add_list_of_str(&mut codes)?;
+            if option_name == "enable_error_code" {
+                flags.enable_error_codes(codes)
+            } else {
+                flags.disable_error_codes(codes)
+            }
+        }
+        "untyped_extension_modules" => add_list_of_str(&mut flags.untyped_extension_modules),
+        "allowed_untyped_imports" => add_list_of_str(&mut flags.allowed_untyped_imports),
+        "generated_code_markers" => {
+            let mut markers = vec![];
+            add_list_of_str(&mut markers)?;
+            for marker in markers {
+                flags
+                    .generated_code_markers
+                    .push(check_generated_code_marker(marker)?);
+            }
+            Ok(false)
+        }
+        "generated_code_excludes" => {
+            let mut excludes = vec![];
+            add_list_of_str(&mut excludes)?;
+            for exclude in excludes {
+                flags
+                    .generated_code_excludes
+                    .push(GeneratedCodeExclude::new(&exclude)?);
+            }
+            Ok(false)
+        }
         "strict" => bail!(concat!(
             r#"Setting "strict" not supported in inline configuration: "#,
             r#"specify it in a configuration file instead, or set individual "#,
@@ -899,10 +1054,14 @@ fn set_bool_init_flags(
         "disable_memoryview_promotion" => {
             flags.disable_memoryview_promotion = value.as_bool(invert)?
         }
-        "warn_unused_ignores"
-        | "strict_concatenate"
-        | "strict_bytes"
-        | "strict_equality_for_none" => {
+        "strict_bytes" => {
+            if value.as_bool(invert)? {
+                flags.enable_strict_bytes()
+            } else {
+                flags.strict_bytes = false
+            }
+        }
+        "warn_unused_ignores" | "strict_concatenate" | "strict_equality_for_none" => {
             tracing::warn!("Ignored config value {name}, please contact support if you need them");
         }
         "sqlite_cache" | "incremental" => (), // This doesn't matter
@@ -910,6 +1069,23 @@ fn set_bool_init_flags(
         "force_uppercase_builtins" | "force_union_syntax" | "verbosity" | "color_output" => (),
 
         "extra_checks" => flags.extra_checks = value.as_bool(invert)?,
+        "explain_mismatch" => flags.explain_mismatch = value.as_bool(invert)?,
+        "strict_signature_matching" => flags.strict_signature_matching = value.as_bool(invert)?,
+        "use_joins" => flags.use_joins = value.as_bool(invert)?,
+        "disallow_untyped_attributes" => {
+            flags.disallow_untyped_attributes = value.as_bool(invert)?
+        }
+        "union_untyped_attributes" => flags.union_untyped_attributes = value.as_bool(invert)?,
+        "disallow_monkeypatching" => flags.disallow_monkeypatching = value.as_bool(invert)?,
+        "allow_function_attributes" => flags.allow_function_attributes = value.as_bool(invert)?,
+        "disallow_untyped_imports" => flags.disallow_untyped_imports = value.as_bool(invert)?,
+        "strict_numeric_promotion" => flags.strict_numeric_promotion = value.as_bool(invert)?,
+        "function_timeout" => {
+            if invert {
+                bail!("Can not invert non-boolean key {option_name}")
+            }
+            flags.function_timeout = Some(value.as_u64()?)
+        }
         // These are currently ignored
         "follow_imports" | "follow_imports_for_stubs" => (),
         // Will always be irrelevant
@@ -930,6 +1106,13 @@ fn set_bool_init_flags(
     Ok(false)
 }
 
+fn warn_about_unknown_error_codes(codes: &[String]) {
+    let unknown = unknown_error_codes(codes.iter().map(|c| c.as_str()));
+    if !unknown.is_empty() {
+        tracing::warn!("Ignoring unknown error code(s): {}", unknown.join(", "));
+    }
+}
+
 fn split_and_trim<'a>(s: &'a str, pattern: &'a [char]) -> impl Iterator<Item = &'a str> {
     let mut s = s.trim();
     if let Some(new_s) = s.strip_suffix(pattern) {
@@ -1237,4 +1420,57 @@ mod tests {
         let err = project_options_err(code, false);
         assert_eq!(err.to_string(), "Expected str, got false");
     }
+
+    #[test]
+    fn test_strict_bytes() {
+        let code = "[mypy]\nstrict_bytes = True";
+        let flags = project_options_valid(code, true).flags;
+        assert!(flags.strict_bytes);
+        assert!(flags.disable_bytearray_promotion);
+        assert!(flags.disable_memoryview_promotion);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let code =
+            "[mypy]\ndisable_error_code = operator, unused-ignore\nenable_error_code = operator";
+        let flags = project_options_valid(code, true).flags;
+        assert!(flags.is_error_code_enabled("operator"));
+        assert!(!flags.is_error_code_enabled("unused-ignore"));
+        assert!(!flags.is_error_code_enabled("redundant-expr"));
+
+        // Unknown codes (e.g. of Mypy plugins) are ignored.
+        let code = "[tool.mypy]\nenable_error_code = [\"wrong\", \"redundant-expr\", \"other\"]";
+        let flags = project_options_valid(code, false).flags;
+        assert!(flags.is_error_code_enabled("redundant-expr"));
+        let code = "[mypy]\ndisable_error_code = pydantic-field, metaclass";
+        let flags = project_options_valid(code, true).flags;
+        assert!(!flags.is_error_code_enabled("metaclass"));
+    }
+
+    #[test]
+    fn test_generated_code() {
+        let code = "[mypy]\ngenerated_code_excludes = proto/*_pb2.py: generated by protoc";
+        let flags = project_options_valid(code, true).flags;
+        let [exclude] = flags.generated_code_excludes.as_slice() else {
+            unreachable!()
+        };
+        assert_eq!(exclude.reason, "generated by protoc");
+        assert!(exclude.matches("proto/foo_pb2.py"));
+        assert!(!exclude.matches("proto/nested/foo_pb2.py"));
+        assert!(!exclude.matches("foo_pb2.py"));
+
+        let code = "[tool.mypy]\ngenerated_code_excludes = [\"proto/*_pb2.py\"]";
+        let err = project_options_err(code, false);
+        assert_eq!(
+            err.to_string(),
+            r#"Expected "<glob>: <reason>" for a generated code exclude, got "proto/*_pb2.py""#
+        );
+        let code = "[tool.mypy]\ngenerated_code_excludes = [\"proto/*_pb2.py: \"]";
+        project_options_err(code, false);
+
+        let code = "[tool.mypy]\ngenerated_code_markers = [\"@generated\", \" \"]";
+        let err = project_options_err(code, false);
+        assert_eq!(err.to_string(), "Generated code markers must not be empty");
+    }
 }
@@ -32,6 +32,12 @@ pub fn find_workspace_config(
     Ok(find_mypy_config_file_in_dir(vfs, workspace_dir, false, on_check_path)?.project_options)
 }
 
+/// Whether the directory contains one of the files that can hold a config. In an editor with
+/// multiple workspace folders, a folder like this is checked as its own project.
+pub fn contains_config_file(dir: &Path) -> bool {
+    CONFIG_PATHS.iter().any(|name| dir.join(name).is_file())
+}
+
 pub fn find_cli_config(
     vfs: &dyn VfsHandler,
     current_dir: &AbsPath,
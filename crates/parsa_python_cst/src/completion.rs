@@ -1,13 +1,16 @@
+use std::ops::Range;
+
 use parsa_python::{
     CodeIndex,
     NonterminalType::*,
     PyNode,
-    PyNodeType::{ErrorNonterminal, Nonterminal},
+    PyNodeType::{self, ErrorNonterminal, Nonterminal, Terminal},
+    TerminalType,
 };
 
 use crate::{
-    Atom, ClassDef, DottedImportName, FunctionDef, Lambda, NameDef, Primary, PrimaryOrAtom,
-    PrimaryTarget, PrimaryTargetOrAtom, Tree,
+    Annotation, Atom, ClassDef, DictKeyValue, DottedImportName, FunctionDef, Lambda, NameDef,
+    NamedExpression, Primary, PrimaryOrAtom, PrimaryTarget, PrimaryTargetOrAtom, Tree,
 };
 
 impl Tree {
@@ -195,6 +198,263 @@ impl Tree {
     }
 }
 
+impl Tree {
+    /// Returns the call if the position is at the start of an argument, e.g. `foo(1, ba`.
+    pub fn call_for_argument_at_position(&self, position: CodeIndex) -> Option<CallAtArgument<'_>> {
+        let leaf = self.0.leaf_by_position(position);
+        let mut before = leaf.previous_leaf()?;
+        if leaf.start() < position {
+            if !leaf.is_type(Terminal(TerminalType::Name)) {
+                return None;
+            }
+        } else if before.end() == position && before.is_type(Terminal(TerminalType::Name)) {
+            // The name that is currently typed
+            before = before.previous_leaf()?;
+        }
+        let mut call = before.parent()?;
+        match before.as_code() {
+            "(" => (),
+            "," => {
+                while matches!(
+                    call.type_(),
+                    Nonterminal(arguments | kwargs) | ErrorNonterminal(arguments | kwargs)
+                ) {
+                    call = call.parent()?;
+                }
+            }
+            _ => return None,
+        }
+        call_at_argument(call, before.end(), position)
+    }
+
+    /// Returns where the dict literal is used if the position is at one of its keys, e.g. the
+    /// string in `x: Movie = {"na`. Sets of strings like `{"na"}` are dicts whose first key is
+    /// typed.
+    pub fn dict_literal_key_at_position(&self, position: CodeIndex) -> Option<DictLiteralKey<'_>> {
+        let mut leaf = self.0.leaf_by_position(position);
+        if leaf.start() >= position {
+            leaf = leaf.previous_leaf()?;
+        }
+        let (before, key) = if leaf.end() >= position
+            && matches!(
+                leaf.type_(),
+                Terminal(TerminalType::Name | TerminalType::String)
+            ) {
+            (leaf.previous_leaf()?, Some(leaf))
+        } else {
+            (leaf, None)
+        };
+        if key.is_some_and(|key| !key.is_type(Terminal(TerminalType::String)))
+            || !matches!(before.as_code(), "{" | ",")
+        {
+            return None;
+        }
+        let mut dict = before.parent()?;
+        if is_dict_content(dict) {
+            dict = dict.parent()?;
+        }
+        // A dict that is not closed yet is an error node.
+        if !matches!(dict.type_(), Nonterminal(atom) | ErrorNonterminal(atom))
+            || dict.nth_child(0).as_code() != "{"
+            || dict.iter_children().nth(1).is_some_and(|content| {
+                !is_dict_content(content) && !content.is_type(PyNodeType::Keyword)
+            })
+        {
+            return None;
+        }
+
+        let mut used_keys = vec![];
+        for content in dict.iter_children() {
+            if is_dict_content(content) {
+                for element in content.iter_children().step_by(2) {
+                    if key.is_some_and(|key| element.start() == key.start()) {
+                        continue;
+                    }
+                    let literal = if element.is_type(Nonterminal(dict_key_value)) {
+                        DictKeyValue::new(element)
+                            .key()
+                            .maybe_single_string_literal()
+                    } else if element.is_type(Nonterminal(named_expression)) {
+                        NamedExpression::new(element).maybe_single_string_literal()
+                    } else {
+                        None
+                    };
+                    used_keys.extend(literal.map(|literal| literal.content()));
+                }
+            }
+        }
+
+        let mut node = dict;
+        let mut parent = node.parent()?;
+        // Error nodes are not collapsed, an unclosed dict is wrapped in all expression nodes.
+        while parent.iter_children().count() == 1
+            && match parent.type_() {
+                Nonterminal(expression | named_expression | star_expressions) => true,
+                ErrorNonterminal(arguments | kwargs | kwarg | assignment) => false,
+                ErrorNonterminal(_) => true,
+                _ => false,
+            }
+        {
+            node = parent;
+            parent = node.parent()?;
+        }
+        let context = match parent.type_() {
+            Nonterminal(assignment) | ErrorNonterminal(assignment)
+                if parent.iter_children().last()?.index == node.index =>
+            {
+                let annotation_ = parent
+                    .iter_children()
+                    .find(|n| n.is_type(Nonterminal(annotation)))?;
+                DictLiteralContext::Annotation(Annotation::new(annotation_))
+            }
+            Nonterminal(arguments | kwargs | kwarg)
+            | ErrorNonterminal(arguments | kwargs | kwarg) => {
+                let argument_start =
+                    if matches!(parent.type_(), Nonterminal(kwarg) | ErrorNonterminal(kwarg)) {
+                        parent.start()
+                    } else {
+                        node.start()
+                    };
+                let mut call = parent;
+                while matches!(
+                    call.type_(),
+                    Nonterminal(arguments | kwargs | kwarg)
+                        | ErrorNonterminal(arguments | kwargs | kwarg)
+                ) {
+                    call = call.parent()?;
+                }
+                DictLiteralContext::Argument {
+                    call: call_at_argument(call, argument_start, dict.start())?,
+                    position: dict.start(),
+                }
+            }
+            _ => return None,
+        };
+        Some(DictLiteralKey {
+            context,
+            used_keys,
+            key: key.map(|key| key.start()..key.end()),
+        })
+    }
+}
+
+pub struct DictLiteralKey<'db> {
+    pub context: DictLiteralContext<'db>,
+    /// The string literal keys of the other items.
+    pub used_keys: Vec<&'db str>,
+    /// The range of the string that is typed as a key.
+    pub key: Option<Range<CodeIndex>>,
+}
+
+pub enum DictLiteralContext<'db> {
+    /// E.g. `x: Movie = {}`
+    Annotation(Annotation<'db>),
+    /// E.g. `foo(1, {})`, the position is the start of the dict literal.
+    Argument {
+        call: CallAtArgument<'db>,
+        position: CodeIndex,
+    },
+}
+
+pub enum CallAtArgument<'db> {
+    /// The arguments of the call can be matched with params.
+    Closed {
+        call: Primary<'db>,
+        argument_start: CodeIndex,
+    },
+    /// E.g. `foo(1, ba` while typing, which is an error node. Its arguments are only known
+    /// syntactically.
+    Unclosed(UnclosedCall<'db>),
+}
+
+pub struct UnclosedCall<'db> {
+    pub callee: PrimaryOrAtom<'db>,
+    /// The positional arguments before the argument at the position.
+    pub positional_count: usize,
+    /// The keyword arguments except the one at the position.
+    pub keywords: Vec<&'db str>,
+    /// The keyword of the argument at the position, e.g. `x` in `foo(x={`.
+    pub active_keyword: Option<&'db str>,
+}
+
+/// The call with the argument that starts at `argument_start`, `call` is the node that contains
+/// the arguments.
+fn call_at_argument(
+    call: PyNode,
+    argument_start: CodeIndex,
+    position: CodeIndex,
+) -> Option<CallAtArgument> {
+    if call.iter_children().nth(1)?.as_code() != "(" {
+        return None;
+    }
+    match call.type_() {
+        Nonterminal(primary) => Some(CallAtArgument::Closed {
+            call: Primary::new(call),
+            argument_start,
+        }),
+        ErrorNonterminal(primary) => {
+            let callee = call.nth_child(0);
+            let callee = if callee.is_type(Nonterminal(atom)) {
+                PrimaryOrAtom::Atom(Atom::new(callee))
+            } else if callee.is_type(Nonterminal(primary)) {
+                PrimaryOrAtom::Primary(Primary::new(callee))
+            } else {
+                return None;
+            };
+            let mut unclosed = UnclosedCall {
+                callee,
+                positional_count: 0,
+                keywords: vec![],
+                active_keyword: None,
+            };
+            for argument in call.iter_children().skip(2) {
+                add_unclosed_call_argument(argument, argument_start, position, &mut unclosed);
+            }
+            Some(CallAtArgument::Unclosed(unclosed))
+        }
+        _ => None,
+    }
+}
+
+fn add_unclosed_call_argument<'db>(
+    argument: PyNode<'db>,
+    argument_start: CodeIndex,
+    position: CodeIndex,
+    unclosed: &mut UnclosedCall<'db>,
+) {
+    match argument.type_() {
+        Nonterminal(arguments | kwargs) | ErrorNonterminal(arguments | kwargs) => {
+            for child in argument.iter_children() {
+                add_unclosed_call_argument(child, argument_start, position, unclosed)
+            }
+        }
+        Nonterminal(kwarg) | ErrorNonterminal(kwarg) => {
+            let name = argument.nth_child(0).as_code();
+            if argument.start() >= argument_start && argument.start() <= position {
+                unclosed.active_keyword = Some(name);
+            } else {
+                unclosed.keywords.push(name);
+            }
+        }
+        Nonterminal(starred_expression | double_starred_expression)
+        | ErrorNonterminal(starred_expression | double_starred_expression)
+        | PyNodeType::Keyword => (),
+        _ => {
+            if argument.start() < argument_start {
+                unclosed.positional_count += 1;
+            }
+        }
+    }
+}
+
+fn is_dict_content(node: PyNode) -> bool {
+    matches!(
+        node.type_(),
+        Nonterminal(dict_content | star_named_expressions)
+            | ErrorNonterminal(dict_content | star_named_expressions)
+    )
+}
+
 fn from_import_dots_before_node(leaf: PyNode) -> usize {
     debug_assert!(leaf.is_leaf());
     let count = match leaf.as_code() {
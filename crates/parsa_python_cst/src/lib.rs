@@ -12,7 +12,10 @@ use std::{
 
 pub use bytes::parse_python_bytes_literal;
 use completion::scope_for_node;
-pub use completion::{CompletionNode, RestNode, Scope};
+pub use completion::{
+    CallAtArgument, CompletionNode, DictLiteralContext, DictLiteralKey, RestNode, Scope,
+    UnclosedCall,
+};
 pub use match_stmt::{
     CasePattern, KeyEntryInPattern, LiteralPatternContent, MappingPatternItem, ParamPattern,
     PatternKind, SequencePatternItem, StarPatternContent, SubjectExprContent,
@@ -84,14 +87,7 @@ impl Tree {
     }
 
     pub fn node_end_position_without_whitespace(&self, index: NodeIndex) -> CodeIndex {
-        let node = self.0.node_by_index(index);
-        let mut leaf = node.last_leaf_in_subtree();
-        while leaf.is_type(Terminal(TerminalType::Newline))
-            || leaf.is_type(Terminal(TerminalType::Dedent))
-        {
-            leaf = leaf.previous_leaf().unwrap();
-        }
-        leaf.end()
+        end_without_whitespace(self.0.node_by_index(index))
     }
 
     pub fn type_ignore_comment_for(
@@ -101,13 +97,61 @@ impl Tree {
     ) -> Option<Option<&str>> {
         // Returns Some(None) when there is a type: ignore
         // Returns Some("foo") when there is a type: ignore['foo']
+        let (line_end, _) = self.line_comment(end);
+        Self::type_ignore_comment_for_region(&self.code()[start as usize..line_end as usize])
+    }
+
+    /// Returns the end of the physical line with the position and the range of the comment at
+    /// the end of it. Lines continued with a backslash cannot have comments, so the last line
+    /// of the continuation is used.
+    pub fn line_comment(&self, position: CodeIndex) -> (CodeIndex, Option<Range<CodeIndex>>) {
         let code = self.code();
-        let relevant_region = if let Some(newline) = code[end as usize..].find(['\n', '\r']) {
-            &code[start as usize..end as usize + newline]
-        } else {
-            &code[start as usize..]
-        };
-        Self::type_ignore_comment_for_region(relevant_region)
+        let mut position = position;
+        let mut leaf = self.0.leaf_by_position(position);
+        loop {
+            // Comments and line breaks (except for the last one of a statement) are in the
+            // whitespace before leaves.
+            let prefix_start = leaf.previous_leaf().map_or(0, |l| l.end()).max(position);
+            let ends_line = matches!(
+                leaf.type_(),
+                Terminal(TerminalType::Newline) | Terminal(TerminalType::Endmarker)
+            );
+            if prefix_start > leaf.start() {
+                // The position is within the code of the leaf.
+                let Some(next) = leaf.next_leaf() else {
+                    return (code.len() as CodeIndex, None);
+                };
+                leaf = next;
+                continue;
+            }
+            let prefix = &code[prefix_start as usize..leaf.start() as usize];
+            let line = match prefix.find(['\n', '\r']) {
+                Some(newline) => &prefix[..newline],
+                None if ends_line => prefix,
+                None => {
+                    let Some(next) = leaf.next_leaf() else {
+                        return (leaf.end(), None);
+                    };
+                    leaf = next;
+                    continue;
+                }
+            };
+            let line_end = prefix_start + line.len() as CodeIndex;
+            let comment_start = line.find('#');
+            if comment_start.is_none() && line.trim_end().ends_with('\\') {
+                let newline_len = if prefix[line.len()..].starts_with("\r\n") {
+                    2
+                } else {
+                    1
+                };
+                position = line_end + newline_len;
+                continue;
+            }
+            return (
+                line_end,
+                comment_start.map(|start| prefix_start + start as CodeIndex..line_end),
+            );
+        }
     }
 
     fn type_ignore_comment_for_region(region: &str) -> Option<Option<&str>> {
@@ -136,6 +180,15 @@ impl Tree {
         }
     }
 
+    /// Returns true if a comment before the first statement contains the marker, e.g.
+    /// `# @generated`.
+    pub fn has_comment_marker_at_start(&self, marker: &str) -> bool {
+        self.before_first_statement()
+            .lines()
+            .filter_map(|line| line.trim_start().strip_prefix('#'))
+            .any(|comment| comment.contains(marker))
+    }
+
     fn before_first_statement(&self) -> &str {
         let start = self.0.root_node().nth_child(0).start();
         &self.code()[0..start as usize]
@@ -286,6 +339,318 @@ impl Tree {
         (scope_for_node(left), goto_node)
     }
 
+    /// Returns the ranges of all nodes around the position, from the innermost to the outermost
+    /// node (e.g. name -> expression -> statement -> block -> function -> class). Nodes that
+    /// have the same range as their child are skipped.
+    pub fn selection_ranges(&self, position: CodeIndex) -> Vec<Range<CodeIndex>> {
+        let mut leaf = self.0.leaf_by_position(position);
+        // At the end of a line we want to select what is left of the cursor.
+        if is_whitespace_leaf(leaf)
+            && let Some(previous) = leaf.previous_leaf()
+            && previous.end() == position
+        {
+            leaf = previous;
+        }
+        let mut ranges: Vec<Range<CodeIndex>> = vec![];
+        let mut node = Some(leaf);
+        while let Some(n) = node {
+            if !is_whitespace_leaf(n) {
+                let range = start_without_whitespace(n)..end_without_whitespace(n);
+                if ranges.last() != Some(&range) {
+                    ranges.push(range);
+                }
+            }
+            node = n.parent();
+        }
+        ranges
+    }
+
+    /// Returns the ranges that can be folded: Blocks (starting at the header of their
+    /// statement), match statements, brackets and strings. Ranges on a single line are not
+    /// filtered.
+    pub fn folding_ranges(&self) -> impl Iterator<Item = Range<CodeIndex>> + '_ {
+        self.0.nodes().filter_map(|node| match node.type_() {
+            // Blocks on the same line like `if x: pass` cannot be folded.
+            Nonterminal(block) if node.nth_child(0).is_type(Terminal(TerminalType::Newline)) => {
+                Some(block_header_start(node)..end_without_whitespace(node))
+            }
+            Nonterminal(match_stmt) => Some(node.start()..end_without_whitespace(node)),
+            Terminal(TerminalType::String) => Some(node.start()..node.end()),
+            PyNodeType::Keyword => {
+                let closing = match node.as_code() {
+                    "(" => ")",
+                    "[" => "]",
+                    "{" => "}",
+                    _ => return None,
+                };
+                let mut sibling = node.next_sibling();
+                while let Some(s) = sibling {
+                    if s.as_code() == closing {
+                        return Some(node.start()..s.end());
+                    }
+                    sibling = s.next_sibling();
+                }
+                None
+            }
+            _ => None,
+        })
+    }
+
+    /// The names of all functions and classes, including nested ones.
+    pub fn function_and_class_names(&self) -> impl Iterator<Item = Name<'_>> + '_ {
+        self.0.nodes().filter_map(|node| match node.type_() {
+            Nonterminal(function_def) => Some(FunctionDef::new(node).name()),
+            Nonterminal(class_def) => Some(ClassDef::new(node).name()),
+            _ => None,
+        })
+    }
+
+    /// Returns the innermost call that has the position between its parentheses, together with
+    /// the start of the argument at the position, which is after the `(` or `,` before it.
+    pub fn call_at_position(
+        &self,
+        position: CodeIndex,
+    ) -> Option<(Scope<'_>, Primary<'_>, CodeIndex)> {
+        let mut node = Some(self.0.leaf_by_position(position));
+        while let Some(n) = node {
+            if n.is_type(Nonterminal(primary)) && n.nth_child(1).as_code() == "(" {
+                let open = n.nth_child(1);
+                let close = n.iter_children().last().unwrap();
+                if open.end() <= position && position <= close.start() {
+                    let mut argument_start = open.end();
+                    let args_node = n.nth_child(2);
+                    if args_node.is_type(Nonterminal(arguments)) {
+                        update_argument_start(args_node, position, &mut argument_start);
+                    }
+                    return Some((scope_for_node(n), Primary::new(n), argument_start));
+                }
+            }
+            node = n.parent();
+        }
+        None
+    }
+
+    /// The annotation of the function param whose default starts at the position, e.g. `int` in
+    /// `def f(x: int = None)`.
+    pub fn annotation_of_param_default(&self, default_start: CodeIndex) -> Option<Expression<'_>> {
+        let function = self
+            .0
+            .leaf_by_position(default_start)
+            .parent_until(&[Nonterminal(function_def)])?;
+        FunctionDef::new(function)
+            .params()
+            .iter()
+            .find_map(|param| {
+                if param.default()?.start() != default_start {
+                    return None;
+                }
+                match param.annotation()? {
+                    ParamAnnotation::Annotation(annotation) => Some(annotation.expression()),
+                    ParamAnnotation::StarAnnotation(_) => None,
+                }
+            })
+    }
+
+    /// Finds the part of an annotation that matches the range. An empty range selects the whole
+    /// annotation around the position. Only unions and subscriptions are returned, because
+    /// simple names don't need an alias.
+    pub fn annotation_part_in_range(&self, range: Range<CodeIndex>) -> Option<AnnotationPart<'_>> {
+        let selected = self.code().get(range.start as usize..range.end as usize)?;
+        let start = range.start + (selected.len() - selected.trim_start().len()) as CodeIndex;
+        let end = range.end - (selected.len() - selected.trim_end().len()) as CodeIndex;
+        let mut leaf = self.0.leaf_by_position(start);
+        if is_whitespace_leaf(leaf)
+            && let Some(previous) = leaf.previous_leaf()
+            && previous.end() == start
+        {
+            leaf = previous;
+        }
+        let mut part = None;
+        let mut node = leaf;
+        let annotation_node = loop {
+            if matches!(
+                node.type_(),
+                Nonterminal(annotation | return_annotation | star_annotation)
+            ) {
+                break node;
+            }
+            if start != end && part.is_none() && node.start() == start && node.end() == end {
+                part = Some(node);
+            }
+            node = node.parent()?;
+            if node.is_type(Nonterminal(stmt)) {
+                return None;
+            }
+        };
+        let part = part.unwrap_or_else(|| annotation_node.nth_child(1));
+        if !is_complex_annotation_part(part) {
+            return None;
+        }
+        let mut module_stmt = annotation_node;
+        while let Some(parent) = module_stmt.parent() {
+            if parent.is_type(Nonterminal(file)) {
+                break;
+            }
+            module_stmt = parent;
+        }
+        Some(AnnotationPart {
+            annotation_index: annotation_node.index,
+            range: part.start()..part.end(),
+            code: part.as_code(),
+            names: part
+                .search(&[Terminal(TerminalType::Name)], false)
+                .filter(|name| name.previous_leaf().is_none_or(|l| l.as_code() != "."))
+                .map(Name::new)
+                .collect(),
+            has_string_in_union: part.search(&[Nonterminal(strings)], true).any(|n| {
+                n.parent()
+                    .is_some_and(|p| p.is_type(Nonterminal(bitwise_or)))
+            }),
+            module_stmt_start: module_stmt.start(),
+            module_stmt_is_definition: matches!(
+                module_stmt.nth_child(0).type_(),
+                Nonterminal(function_def | class_def | decorated | async_stmt)
+            ),
+        })
+    }
+
+    /// The annotation parts in the whole file that have exactly the given code, together with
+    /// the index of the annotation they are part of.
+    pub fn annotation_parts_with_code<'x>(
+        &'x self,
+        code: &'x str,
+    ) -> impl Iterator<Item = (NodeIndex, Range<CodeIndex>)> + 'x {
+        self.0
+            .nodes()
+            .filter(|node| {
+                matches!(
+                    node.type_(),
+                    Nonterminal(annotation | return_annotation | star_annotation)
+                )
+            })
+            .flat_map(move |annotation_node| {
+                annotation_node
+                    .search(&[Nonterminal(bitwise_or), Nonterminal(primary)], false)
+                    .filter(move |part| part.as_code() == code && is_complex_annotation_part(*part))
+                    .map(move |part| (annotation_node.index, part.start()..part.end()))
+            })
+    }
+
+    /// The position after the last import at module level that is before the given position.
+    pub fn position_after_last_module_import(&self, before: CodeIndex) -> Option<CodeIndex> {
+        self.0
+            .root_node()
+            .iter_children()
+            .take_while(|stmt_node| stmt_node.end() <= before)
+            .filter(|stmt_node| {
+                let child = stmt_node.nth_child(0);
+                child.is_type(Nonterminal(simple_stmts))
+                    && matches!(
+                        child.nth_child(0).nth_child(0).type_(),
+                        Nonterminal(import_name | import_from)
+                    )
+            })
+            .last()
+            .map(|stmt_node| stmt_node.end())
+    }
+
+    /// The import statements in the module scope. Star imports and statements that are not
+    /// alone on their line (e.g. `import foo; import bar`) are ignored.
+    pub fn module_imports(&self) -> impl Iterator<Item = ModuleImport<'_>> + '_ {
+        self.0.root_node().iter_children().filter_map(|stmt_node| {
+            if !stmt_node.is_type(Nonterminal(stmt)) {
+                return None;
+            }
+            let simple_stmts_node = stmt_node.nth_child(0);
+            if !simple_stmts_node.is_type(Nonterminal(simple_stmts))
+                || simple_stmts_node.iter_children().count() != 2
+            {
+                return None;
+            }
+            let import_node = simple_stmts_node.nth_child(0).nth_child(0);
+            let (keyword_end, names, is_future_import) =
+                if import_node.is_type(Nonterminal(import_from)) {
+                    let import_from_ = ImportFrom::new(import_node);
+                    let ImportFromTargets::Iterator(targets) = import_from_.unpack_targets() else {
+                        return None;
+                    };
+                    let keyword = import_node
+                        .iter_children()
+                        .find(|n| n.as_code() == "import")
+                        .unwrap();
+                    let is_future_import = matches!(
+                        import_from_.level_with_dotted_name(),
+                        (0, Some(dotted)) if dotted.as_code() == "__future__"
+                    );
+                    let names = targets.map(|t| (t.name_def(), t.as_code())).collect();
+                    (keyword.end(), names, is_future_import)
+                } else if import_node.is_type(Nonterminal(import_name)) {
+                    let names = ImportName::new(import_node)
+                        .iter_dotted_as_names()
+                        .map(|d| (d.name_def(), d.as_code()))
+                        .collect();
+                    (import_node.nth_child(0).end(), names, false)
+                } else {
+                    return None;
+                };
+            Some(ModuleImport {
+                range: stmt_node.start()..stmt_node.end(),
+                keyword_part: &self.code()[stmt_node.start() as usize..keyword_end as usize],
+                is_future_import,
+                names,
+            })
+        })
+    }
+
+    /// The position at the end of the block of an `if TYPE_CHECKING:` in the module scope,
+    /// together with the indentation of the block.
+    pub fn module_type_checking_block_end(&self) -> Option<(CodeIndex, &str)> {
+        self.0.root_node().iter_children().find_map(|stmt_node| {
+            if !stmt_node.is_type(Nonterminal(stmt)) {
+                return None;
+            }
+            let if_node = stmt_node.nth_child(0);
+            if !if_node.is_type(Nonterminal(if_stmt)) {
+                return None;
+            }
+            let Some(IfBlockType::If(condition, block_)) =
+                IfStmt::new(if_node).iter_blocks().next()
+            else {
+                unreachable!()
+            };
+            if !matches!(
+                condition.as_code(),
+                "TYPE_CHECKING" | "typing.TYPE_CHECKING"
+            ) {
+                return None;
+            }
+            let mut stmts = block_
+                .node
+                .iter_children()
+                .filter(|n| n.is_type(Nonterminal(stmt)));
+            // Blocks on the same line like `if TYPE_CHECKING: import foo` are not used.
+            let first = stmts.next()?;
+            let last = stmts.last().unwrap_or(first);
+            let line_start = self.code()[..first.start() as usize]
+                .rfind('\n')
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            Some((last.end(), &self.code()[line_start..first.start() as usize]))
+        })
+    }
+
+    /// The position where `from __future__` imports can be added, which is at the start of the
+    /// module after the docstring.
+    pub fn future_import_position(&self) -> CodeIndex {
+        let mut children = self.0.root_node().iter_children();
+        let first = children.next().unwrap();
+        if self.root().docstring().is_some() {
+            children.next().map(|n| n.start()).unwrap_or(first.end())
+        } else {
+            first.start()
+        }
+    }
+
     pub fn filter_all_names<'x>(&'x self) -> impl Iterator<Item = Name<'x>> {
         self.0
             .nodes()
@@ -294,6 +659,107 @@ impl Tree {
     }
 }
 
+pub struct AnnotationPart<'db> {
+    pub annotation_index: NodeIndex,
+    pub range: Range<CodeIndex>,
+    pub code: &'db str,
+    /// The names that are referenced in the part, attribute names are not part of it.
+    pub names: Vec<Name<'db>>,
+    /// Strings cannot be used with `|` at runtime, e.g. in `"Foo" | None`.
+    pub has_string_in_union: bool,
+    /// The start of the statement in the module scope that contains the annotation.
+    pub module_stmt_start: CodeIndex,
+    pub module_stmt_is_definition: bool,
+}
+
+pub struct ModuleImport<'db> {
+    /// The range of the whole statement, including the newline.
+    pub range: Range<CodeIndex>,
+    /// The code before the imported names, e.g. `from foo import`.
+    pub keyword_part: &'db str,
+    pub is_future_import: bool,
+    /// The definitions of the imported names and how they are imported, e.g. `foo as bar`.
+    pub names: Vec<(NameDef<'db>, &'db str)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationUsage {
+    /// Evaluated at runtime without `from __future__ import annotations`
+    Evaluated,
+    /// Annotations of variables in functions are never evaluated.
+    NotEvaluated,
+    /// Inspected at runtime even with `from __future__ import annotations`, like annotations in
+    /// class bodies (dataclasses, pydantic) and annotations of `singledispatch` functions.
+    Inspected,
+}
+
+fn is_complex_annotation_part(node: PyNode) -> bool {
+    match node.type_() {
+        Nonterminal(bitwise_or) => true,
+        Nonterminal(primary) => node.nth_child(1).as_code() == "[",
+        _ => false,
+    }
+}
+
+fn update_argument_start(node: PyNode, position: CodeIndex, argument_start: &mut CodeIndex) {
+    for child in node.iter_children() {
+        if child.start() >= position {
+            break;
+        }
+        if child.is_type(Nonterminal(kwargs)) {
+            update_argument_start(child, position, argument_start)
+        } else if child.as_code() == "," {
+            *argument_start = child.end();
+        }
+    }
+}
+
+fn is_whitespace_leaf(node: PyNode) -> bool {
+    matches!(
+        node.type_(),
+        Terminal(
+            TerminalType::Newline
+                | TerminalType::Indent
+                | TerminalType::Dedent
+                | TerminalType::Endmarker
+        )
+    )
+}
+
+fn start_without_whitespace(node: PyNode) -> CodeIndex {
+    if node.is_type(Nonterminal(block))
+        && let Some(first) = node.iter_children().find(|n| !is_whitespace_leaf(*n))
+    {
+        // Indented blocks start with a newline
+        return first.start();
+    }
+    node.start()
+}
+
+fn end_without_whitespace(node: PyNode) -> CodeIndex {
+    let mut leaf = node.last_leaf_in_subtree();
+    while leaf.is_type(Terminal(TerminalType::Newline))
+        || leaf.is_type(Terminal(TerminalType::Dedent))
+    {
+        leaf = leaf.previous_leaf().unwrap();
+    }
+    leaf.end()
+}
+
+fn block_header_start(block_node: PyNode) -> CodeIndex {
+    // The header consists of all siblings before the block, but stops at the previous block
+    // for e.g. `elif` in an if statement.
+    let mut start = block_node.start();
+    let mut sibling = block_node.previous_sibling();
+    while let Some(s) = sibling
+        && !s.is_type(Nonterminal(block))
+    {
+        start = s.start();
+        sibling = s.previous_sibling();
+    }
+    start
+}
+
 pub fn maybe_type_ignore(text: &str) -> Option<Option<&str>> {
     if let Some(after) = text.strip_prefix("ignore") {
         let after = after.trim_matches(' ');
@@ -675,6 +1141,43 @@ impl<'db> Name<'db> {
         }
     }
 
+    /// Whether the name references a variable, e.g. `foo` in `foo.bar`, but not `bar`.
+    pub fn is_variable_reference(&self) -> bool {
+        let parent = self.node.parent().unwrap();
+        parent.is_type(Nonterminal(atom))
+            || (parent.is_type(Nonterminal(primary)) || parent.is_type(Nonterminal(t_primary)))
+                && parent.nth_child(0).index == self.node.index
+    }
+
+    pub fn annotation_usage(&self) -> Option<AnnotationUsage> {
+        let annotation_node = self.node.parent_until(&[
+            Nonterminal(annotation),
+            Nonterminal(return_annotation),
+            Nonterminal(star_annotation),
+            Nonterminal(stmt),
+        ])?;
+        if annotation_node.is_type(Nonterminal(stmt)) {
+            return None;
+        }
+        let is_variable_annotation = annotation_node
+            .parent()
+            .is_some_and(|p| p.is_type(Nonterminal(assignment)));
+        let scope = scope_for_node(annotation_node);
+        Some(if is_variable_annotation {
+            match scope {
+                Scope::Function(_) | Scope::Lambda(_) => AnnotationUsage::NotEvaluated,
+                Scope::Class(_) => AnnotationUsage::Inspected,
+                Scope::Module => AnnotationUsage::Evaluated,
+            }
+        } else if let Scope::Function(func) = scope
+            && func.is_dispatched_by_annotations()
+        {
+            AnnotationUsage::Inspected
+        } else {
+            AnnotationUsage::Evaluated
+        })
+    }
+
     pub fn is_part_of_primary_ancestors(&self) -> bool {
         let parent = self.node.parent().unwrap();
         if !parent.is_type(Nonterminal(atom)) && !parent.is_type(Nonterminal(primary)) {
@@ -769,8 +1272,12 @@ impl<'db> Name<'db> {
             let name_def_ = n.name_def()?;
             let strings_ = if let Some(func) = name_def_.maybe_name_of_func() {
                 func.docstring()
+            } else if let Some(class) = name_def_.maybe_name_of_class() {
+                class.docstring()
             } else {
-                name_def_.maybe_name_of_class()?.docstring()
+                name_def_
+                    .maybe_annotated_doc()
+                    .or_else(|| name_def_.maybe_attribute_docstring())
             };
             strings::clean_docstring(strings_?)
         };
@@ -929,6 +1436,10 @@ impl<'db> File<'db> {
             .and_then(|d| strings::clean_docstring(d))
             .unwrap_or(Cow::Borrowed(""))
     }
+
+    pub fn search_potential_attribute_assignments(&self) -> PotentialSelfAssignments<'db> {
+        PotentialSelfAssignments(self.node.search(&[Nonterminal(t_primary)], true))
+    }
 }
 
 impl<'db> List<'db> {
@@ -2123,6 +2634,10 @@ impl<'db> ClassDef<'db> {
         self.name_def().name()
     }
 
+    pub fn parent_scope(&self) -> Scope<'db> {
+        scope_for_node(self.node)
+    }
+
     pub fn arguments(&self) -> Option<Arguments<'db>> {
         let mut args = self.node.nth_child(3);
         if args.is_leaf() {
@@ -2284,6 +2799,21 @@ impl<'db> FunctionDef<'db> {
         }
     }
 
+    /// `singledispatch` functions and their registered implementations dispatch on the
+    /// annotations of the first parameter.
+    fn is_dispatched_by_annotations(&self) -> bool {
+        self.maybe_decorated().is_some_and(|decorated| {
+            decorated.decorators().iter().any(|decorator| {
+                let code = decorator.named_expression().as_code();
+                let callee = code.split('(').next().unwrap();
+                matches!(
+                    callee.rsplit('.').next().unwrap().trim(),
+                    "singledispatch" | "singledispatchmethod" | "register"
+                )
+            })
+        })
+    }
+
     pub fn in_conditional_scope(&self) -> bool {
         let parent_block = self.node.parent_until(&[Nonterminal(block)]);
         parent_block.is_some_and(|block_| {
@@ -3849,6 +4379,17 @@ impl<'db> Kwarg<'db> {
         let arg = kwarg_iterator.next().unwrap();
         (Name::new(name), Expression::new(arg))
     }
+
+    /// The call the keyword argument is passed to, which is `None` for keyword arguments in class
+    /// definitions like `class C(metaclass=M)`.
+    pub fn maybe_call(&self) -> Option<Primary<'db>> {
+        let parent = self
+            .node
+            .parent_until(&[Nonterminal(primary), Nonterminal(class_def)])?;
+        parent
+            .is_type(Nonterminal(primary))
+            .then(|| Primary::new(parent))
+    }
 }
 
 impl<'db> StarredExpression<'db> {
@@ -4128,6 +4669,69 @@ impl<'db> NameDef<'db> {
             .then(|| Assignment::new(node))
     }
 
+    /// Returns the string of `Doc("...")` in annotations like `x: Annotated[int, Doc("...")]`
+    /// (PEP 727) for assignments and params.
+    pub fn maybe_annotated_doc(&self) -> Option<Strings<'db>> {
+        let mut target = self.node;
+        let parent = target.parent()?;
+        if parent.is_type(Nonterminal(t_primary)) {
+            target = parent;
+        }
+        let annot = target.next_sibling()?;
+        if !annot.is_type(Nonterminal(annotation)) && !annot.is_type(Nonterminal(star_annotation)) {
+            return None;
+        }
+        annot
+            .search(&[Nonterminal(primary)], false)
+            .find_map(|node| {
+                let primary_ = Primary::new(node);
+                let is_doc = match primary_.first() {
+                    PrimaryOrAtom::Atom(atom_) => atom_.as_code() == "Doc",
+                    PrimaryOrAtom::Primary(p) => {
+                        matches!(p.second(), PrimaryContent::Attribute(n) if n.as_str() == "Doc")
+                    }
+                };
+                if !is_doc {
+                    return None;
+                }
+                let PrimaryContent::Execution(ArgumentsDetails::Node(args)) = primary_.second()
+                else {
+                    return None;
+                };
+                let mut iterator = args.iter();
+                let Some(Argument::Positional(arg)) = iterator.next() else {
+                    return None;
+                };
+                if iterator.next().is_some() {
+                    return None;
+                }
+                arg.expression().maybe_string()
+            })
+    }
+
+    /// Returns the string statement directly after an assignment like `x = 1`, which is a common
+    /// convention to document attributes and module variables.
+    pub fn maybe_attribute_docstring(&self) -> Option<Strings<'db>> {
+        let assignment_ = self.maybe_assignment_definition()?;
+        let stmt_ = assignment_.node.parent_until(&[Nonterminal(stmt)])?;
+        // Only `x = 1` on its own line, not something like `x = 1; y = 2`
+        let simple_stmts_ = stmt_.nth_child(0);
+        if !simple_stmts_.is_type(Nonterminal(simple_stmts))
+            || simple_stmts_.iter_children().count() != 2
+        {
+            return None;
+        }
+        let next = stmt_.next_sibling()?;
+        if !next.is_type(Nonterminal(stmt)) {
+            return None;
+        }
+        let next_simple_stmts = next.nth_child(0);
+        if !next_simple_stmts.is_type(Nonterminal(simple_stmts)) {
+            return None;
+        }
+        StmtLikeContent::from_simple_stmt(next_simple_stmts.nth_child(0)).maybe_string()
+    }
+
     pub fn maybe_import(&self) -> Option<NameImportParent<'db>> {
         let node = self
             .node
@@ -4353,6 +4957,19 @@ impl<'db> NameDef<'db> {
             })
     }
 
+    pub fn is_function_param(&self) -> bool {
+        matches!(
+            self.node.parent().unwrap().type_(),
+            Nonterminal(
+                param_no_default
+                    | param_with_default
+                    | param_maybe_default
+                    | starred_param
+                    | double_starred_param
+            )
+        )
+    }
+
     pub fn maybe_param_annotation(&self) -> Option<ParamAnnotation<'db>> {
         if let Some(next) = self.node.next_sibling() {
             if next.is_type(Nonterminal(annotation)) {
@@ -4696,6 +5313,19 @@ impl<'db> FStringExpr<'db> {
             .map(FStringFormatSpec::new);
         (exprs, format_spec)
     }
+
+    /// Whether the value is formatted with `str()`. This is not the case for conversions like
+    /// `!r` or for self documenting expressions like `{x=}`, which use `repr()`.
+    pub fn uses_str_conversion(&self) -> bool {
+        let mut is_self_documenting = false;
+        for n in self.node.iter_children().skip(2) {
+            if n.is_type(Nonterminal(fstring_conversion)) {
+                return n.nth_child(1).as_code() == "s";
+            }
+            is_self_documenting |= n.as_code() == "=";
+        }
+        !is_self_documenting
+    }
 }
 
 impl<'db> FStringFormatSpec<'db> {
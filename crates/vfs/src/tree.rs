@@ -76,6 +76,18 @@ impl Parent {
         }
     }
 
+    pub fn is_type_checked(&self) -> bool {
+        match self {
+            // The workspace of a file might already be removed.
+            Self::Directory(dir) => dir
+                .upgrade()
+                .is_some_and(|dir| dir.parent.is_type_checked()),
+            Self::Workspace(workspace) => workspace
+                .upgrade()
+                .is_some_and(|workspace| workspace.is_type_checked()),
+        }
+    }
+
     pub fn with_entries<T>(&self, vfs: &dyn VfsHandler, callback: impl FnOnce(&Entries) -> T) -> T {
         match self {
             Self::Directory(dir) => callback(Directory::entries(vfs, &dir.upgrade().unwrap())),
@@ -682,6 +682,28 @@ impl<F: VfsFile> Vfs<F> {
         InvalidationResult::InvalidatedFiles
     }
 
+    /// The paths of loaded files whose content differs from the content on disk. This is only
+    /// necessary if the changes are not reported by the watcher. Only files of type checked
+    /// workspaces are read again, site-packages and typeshed are not expected to change.
+    pub fn paths_changed_on_disk(&self) -> Vec<Arc<NormalizedPath>> {
+        (0..self.files.len())
+            .filter_map(|index| {
+                let file_state = self.files.get(index).unwrap();
+                let path = &file_state.path;
+                if path.is_subfile()
+                    || path.scheme != file_scheme()
+                    || self.in_memory_files.contains_key(path)
+                    || !file_state.file_entry.parent.is_type_checked()
+                {
+                    return None;
+                }
+                let old_code = file_state.code()?;
+                let new_code = self.handler.read_and_watch_file(path);
+                (new_code.as_deref() != Some(old_code)).then(|| path.path.clone())
+            })
+            .collect()
+    }
+
     fn matches_current_dir_entry(&self, old: &DirectoryEntry, new: &DirectoryEntry) -> bool {
         match (old, new) {
             (DirectoryEntry::File(old), DirectoryEntry::File(_)) => {
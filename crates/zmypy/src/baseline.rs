@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Context as _, bail};
+use serde_json::{Value, json};
+use zuban_python::{Diagnostic, Diagnostics, Severity};
+
+const VERSION: u64 = 1;
+
+/// Errors that existed when the baseline was written. Errors are matched by their fingerprint,
+/// which does not contain the position, so moving code around doesn't invalidate the baseline.
+pub(crate) struct Baseline {
+    fingerprints: HashMap<String, usize>,
+}
+
+impl Baseline {
+    pub(crate) fn read(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Could not read baseline {}", path.display()))?;
+        let value: Value = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid baseline {}", path.display()))?;
+        if value["version"].as_u64() != Some(VERSION) {
+            bail!(
+                "Unsupported baseline version in {}, please write it again with --write-baseline",
+                path.display()
+            )
+        }
+        let mut fingerprints = HashMap::<String, usize>::new();
+        for entry in value["diagnostics"].as_array().into_iter().flatten() {
+            let Some(fingerprint) = entry["fingerprint"].as_str() else {
+                bail!(
+                    "Expected a fingerprint for every entry in {}",
+                    path.display()
+                )
+            };
+            *fingerprints.entry(fingerprint.to_owned()).or_default() += 1;
+        }
+        Ok(Self { fingerprints })
+    }
+
+    /// Writes all errors to the baseline file. The file also contains the message, so changes to
+    /// it are reviewable.
+    pub(crate) fn write(path: &Path, issues: &[Diagnostic]) -> anyhow::Result<()> {
+        let entries = issues
+            .iter()
+            .filter(|issue| issue.severity() == Severity::Error)
+            .map(|issue| {
+                json!({
+                    "file": issue.relative_path(),
+                    "code": issue.mypy_error_code(),
+                    "message": issue.message(),
+                    "fingerprint": issue.fingerprint(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let baseline = json!({
+            "version": VERSION,
+            "diagnostics": entries,
+        });
+        std::fs::write(path, format!("{baseline:#}\n"))
+            .with_context(|| format!("Could not write baseline {}", path.display()))
+    }
+
+    /// Removes all errors that are part of the baseline. An error that occurs more often than in
+    /// the baseline is only removed as often as it was recorded.
+    pub(crate) fn apply(mut self, diagnostics: &mut Diagnostics) {
+        diagnostics.retain_errors(
+            |issue| match self.fingerprints.get_mut(&issue.fingerprint()) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            },
+        )
+    }
+}
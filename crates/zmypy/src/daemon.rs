@@ -0,0 +1,422 @@
+//! A daemon keeps the database of a project loaded between checks. It watches the files of the
+//! project and only invalidates what changed, so rechecks after edits are fast. Clients send one
+//! JSON request per connection over a Unix socket and receive one JSON response.
+#![cfg_attr(not(unix), allow(dead_code))] // Only the socket is missing on other platforms
+
+use std::{
+    io::{BufRead as _, BufReader, Write as _},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context as _, bail};
+use clap::{Args, Subcommand};
+use notify::EventKind;
+use serde_json::{Value, json};
+use vfs::{NormalizedPath, SimpleLocalFS};
+use zuban_python::Project;
+
+use crate::{
+    BaselineMode, Cli, CodeFilter, DiagnosticConfig, current_dir,
+    dmypy::{DEFAULT_STATUS_FILE, serve_dmypy},
+    project_from_cli_with_vfs, warn_about_unknown_cli_error_codes, write_diagnostics,
+};
+
+const DEFAULT_SOCKET: &str = ".zuban-daemon.sock";
+/// Clients send their request right after connecting, a connection that stays silent would
+/// otherwise block the daemon for all other clients.
+pub(crate) const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Subcommand)]
+pub enum DaemonCommand {
+    /// Starts the daemon in the foreground, it accepts the same options as `zuban check`
+    Start {
+        #[command(flatten)]
+        socket: SocketOption,
+        #[command(flatten)]
+        cli: Cli,
+    },
+    /// Starts the daemon in the foreground for clients of Mypy's `dmypy`, it accepts the same
+    /// options as `zuban check`
+    Dmypy {
+        /// The status file that tells dmypy clients how to connect
+        #[arg(long, value_name = "FILE", default_value = DEFAULT_STATUS_FILE)]
+        status_file: PathBuf,
+        #[command(flatten)]
+        cli: Cli,
+    },
+    /// Checks the project, only files that changed since the last check are checked again
+    Check(SocketOption),
+    /// Discards everything the daemon has cached and checks the project again
+    Recheck(SocketOption),
+    /// Shows information about the running daemon
+    Status(SocketOption),
+    /// Stops the running daemon
+    Stop(SocketOption),
+}
+
+#[derive(Args)]
+pub struct SocketOption {
+    /// The socket the daemon listens on
+    #[arg(long, value_name = "FILE", default_value = DEFAULT_SOCKET)]
+    socket: PathBuf,
+}
+
+pub fn run_daemon(command: DaemonCommand) -> ExitCode {
+    let result = match command {
+        DaemonCommand::Start { socket, cli } => Daemon::new(cli, current_dir(), None)
+            .and_then(|daemon| serve(&socket.socket, daemon))
+            .map(|()| ExitCode::SUCCESS),
+        DaemonCommand::Dmypy { status_file, cli } => Daemon::new(cli, current_dir(), None)
+            .and_then(|daemon| serve_dmypy(&status_file, daemon))
+            .map(|()| ExitCode::SUCCESS),
+        DaemonCommand::Check(socket) => run_client(&socket.socket, "check"),
+        DaemonCommand::Recheck(socket) => run_client(&socket.socket, "recheck"),
+        DaemonCommand::Status(socket) => run_client(&socket.socket, "status"),
+        DaemonCommand::Stop(socket) => run_client(&socket.socket, "stop"),
+    };
+    result.unwrap_or_else(|err| {
+        eprintln!("{err}");
+        ExitCode::from(2)
+    })
+}
+
+fn run_client(socket: &Path, command: &str) -> anyhow::Result<ExitCode> {
+    let response = send_request(
+        socket,
+        json!({
+            "command": command,
+            "color": colored::control::SHOULD_COLORIZE.should_colorize(),
+        }),
+    )?;
+    if let Some(error) = response["error"].as_str() {
+        bail!("{error}")
+    }
+    print!("{}", response["output"].as_str().unwrap_or_default());
+    Ok(ExitCode::from(
+        response["exit_code"].as_u64().unwrap_or(2) as u8
+    ))
+}
+
+#[cfg(unix)]
+pub(crate) fn send_request(socket: &Path, request: Value) -> anyhow::Result<Value> {
+    let mut stream = std::os::unix::net::UnixStream::connect(socket).with_context(|| {
+        format!(
+            "Could not connect to a daemon on {}, start one with `zuban daemon start`",
+            socket.display()
+        )
+    })?;
+    writeln!(stream, "{request}")?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    serde_json::from_str(&line).context("Invalid response from the daemon")
+}
+
+#[cfg(not(unix))]
+pub(crate) fn send_request(_: &Path, _: Value) -> anyhow::Result<Value> {
+    bail!("The daemon is currently only supported on Unix")
+}
+
+/// Answers requests until a stop request is received.
+#[cfg(unix)]
+pub(crate) fn serve(socket: &Path, mut daemon: Daemon) -> anyhow::Result<()> {
+    use std::os::unix::net::UnixStream;
+
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            bail!("A daemon is already running on {}", socket.display())
+        }
+        // The socket of a daemon that was killed is not removed.
+        std::fs::remove_file(socket)?;
+    }
+    let listener = bind_private_socket(socket)
+        .with_context(|| format!("Could not listen on {}", socket.display()))?;
+    tracing::info!("Daemon is listening on {}", socket.display());
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                tracing::warn!("Could not accept a daemon connection: {err}");
+                continue;
+            }
+        };
+        let mut line = String::new();
+        if let Err(err) = stream
+            .set_read_timeout(Some(REQUEST_READ_TIMEOUT))
+            .and_then(|()| BufReader::new(&stream).read_line(&mut line))
+        {
+            tracing::warn!("Could not read a daemon request: {err}");
+            continue;
+        }
+        let request = serde_json::from_str(&line).unwrap_or(Value::Null);
+        let response = daemon.catch_panic(|daemon| daemon.handle_request(&request));
+        // The client might already be gone, which is not a problem for the daemon.
+        if let Err(err) = writeln!(stream, "{response}") {
+            tracing::warn!("Could not respond to a daemon request: {err}");
+        }
+        if request["command"] == "stop" {
+            break;
+        }
+    }
+    std::fs::remove_file(socket)?;
+    Ok(())
+}
+
+/// Only the user that started the daemon may connect, because the daemon reads files and
+/// reports their contents in diagnostics.
+#[cfg(unix)]
+pub(crate) fn bind_private_socket(
+    socket: &Path,
+) -> std::io::Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt as _, PermissionsExt as _};
+
+    // Restricting the permissions after binding would leave a window in which others can
+    // connect. Therefore the socket is bound in a directory that only we can access and moved to
+    // its place once it is private.
+    let private_dir = socket
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(format!(".zuban-socket-{}", std::process::id()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)?;
+    let private_socket = private_dir.join("socket");
+    let result = std::os::unix::net::UnixListener::bind(&private_socket).and_then(|listener| {
+        std::fs::set_permissions(&private_socket, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&private_socket, socket)?;
+        Ok(listener)
+    });
+    // Also removes the socket if it could not be moved.
+    let _ = std::fs::remove_dir_all(&private_dir);
+    result
+}
+
+#[cfg(not(unix))]
+pub(crate) fn serve(_: &Path, _: Daemon) -> anyhow::Result<()> {
+    bail!("The daemon is currently only supported on Unix")
+}
+
+fn validate_cli(cli: &Cli) -> anyhow::Result<()> {
+    warn_about_unknown_cli_error_codes(cli);
+    if cli.threads > 1 {
+        bail!("The daemon does not support --threads")
+    }
+    Ok(())
+}
+
+struct LastCheck {
+    checked_files: usize,
+    error_count: usize,
+    duration: Duration,
+}
+
+pub(crate) struct Daemon {
+    cli: Cli,
+    current_dir: String,
+    typeshed_path: Option<Arc<NormalizedPath>>,
+    project: Project,
+    diagnostic_config: DiagnosticConfig,
+    /// Changing the config can change everything, so the project is loaded again.
+    config_paths: Vec<PathBuf>,
+    started: Instant,
+    check_count: usize,
+    invalidated_paths: usize,
+    last_check: Option<LastCheck>,
+}
+
+impl Daemon {
+    pub(crate) fn new(
+        cli: Cli,
+        current_dir: String,
+        typeshed_path: Option<Arc<NormalizedPath>>,
+    ) -> anyhow::Result<Self> {
+        validate_cli(&cli)?;
+        let (project, diagnostic_config, config_paths) =
+            Self::load_project(&cli, &current_dir, typeshed_path.clone());
+        Ok(Self {
+            cli,
+            current_dir,
+            typeshed_path,
+            project,
+            diagnostic_config,
+            config_paths,
+            started: Instant::now(),
+            check_count: 0,
+            invalidated_paths: 0,
+            last_check: None,
+        })
+    }
+
+    fn load_project(
+        cli: &Cli,
+        current_dir: &str,
+        typeshed_path: Option<Arc<NormalizedPath>>,
+    ) -> (Project, DiagnosticConfig, Vec<PathBuf>) {
+        let local_fs = SimpleLocalFS::with_watcher(Box::new(|_| ()));
+        let (project, diagnostic_config, config_path) =
+            project_from_cli_with_vfs(cli.clone(), local_fs, current_dir, typeshed_path, |name| {
+                std::env::var(name)
+            });
+        let mut config_paths = vec![];
+        if let Some(config_path) = config_path {
+            let path = PathBuf::from(&**config_path);
+            // Events are reported for the paths without symlinks
+            if let Ok(canonicalized) = std::fs::canonicalize(&path) {
+                config_paths.push(canonicalized);
+            }
+            config_paths.push(path);
+        }
+        (project, diagnostic_config, config_paths)
+    }
+
+    fn reload(&mut self) {
+        tracing::info!("Reloading the project of the daemon");
+        (self.project, self.diagnostic_config, self.config_paths) =
+            Self::load_project(&self.cli, &self.current_dir, self.typeshed_path.clone());
+        self.invalidated_paths = 0;
+    }
+
+    /// Replaces the options of the daemon, which means that the project is loaded again.
+    pub(crate) fn set_cli(&mut self, cli: Cli) -> anyhow::Result<()> {
+        validate_cli(&cli)?;
+        self.cli = cli;
+        self.reload();
+        Ok(())
+    }
+
+    /// Checks these files instead of the files of the config. The project is only loaded again if
+    /// the files change.
+    pub(crate) fn set_files(&mut self, files: Vec<String>) {
+        if self.cli.mypy_options.files != files {
+            self.cli.mypy_options.files = files;
+            self.reload();
+        }
+    }
+
+    pub(crate) fn handle_request(&mut self, request: &Value) -> Value {
+        let color = request["color"].as_bool().unwrap_or(false);
+        let result = match request["command"].as_str() {
+            Some("check") => self.check(color),
+            Some("recheck") => {
+                self.reload();
+                self.check(color)
+            }
+            Some("status") => Ok((self.status(), 0)),
+            Some("stop") => Ok(("Stopped the daemon\n".to_string(), 0)),
+            _ => Err(anyhow::anyhow!("Invalid daemon request {request}")),
+        };
+        match result {
+            Ok((output, exit_code)) => json!({"output": output, "exit_code": exit_code}),
+            Err(err) => json!({"error": err.to_string()}),
+        }
+    }
+
+    /// Runs a request and loads the project again if it panics, so a bug in the type checker
+    /// does not take the daemon down for all following requests.
+    pub(crate) fn catch_panic(&mut self, f: impl FnOnce(&mut Self) -> Value) -> Value {
+        // The state that might be broken after a panic is in the project, which is replaced.
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| f(self))).unwrap_or_else(|_| {
+            // The panic was reported by the panic hook.
+            tracing::warn!("Start panic recovery");
+            colored::control::unset_override();
+            self.reload();
+            json!({
+                "error": format!(
+                    "The daemon paniced and loaded the project again (version {})",
+                    env!("CARGO_PKG_VERSION")
+                )
+            })
+        })
+    }
+
+    /// Invalidates the files the watcher reported since the last request.
+    fn process_file_changes(&mut self) {
+        let mut needs_reload = false;
+        while let Some(event) = self
+            .project
+            .vfs_handler()
+            .notify_receiver()
+            .and_then(|receiver| receiver.try_recv().ok())
+        {
+            match event {
+                Ok(event) => match event.kind {
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
+                        tracing::info!("Notify Event: {event:?}");
+                        for path in event.paths {
+                            if self.config_paths.contains(&path) {
+                                needs_reload = true;
+                            } else if let Some(path) = path.to_str() {
+                                let path = self.project.vfs_handler().unchecked_abs_path(path);
+                                self.project.invalidate_path(&path);
+                                self.invalidated_paths += 1;
+                            }
+                        }
+                    }
+                    _ => tracing::debug!("Ignored Notify Event: {event:?}"),
+                },
+                Err(err) => {
+                    tracing::error!("Reloading, because of a notify event error: {err:?}");
+                    needs_reload = true;
+                }
+            }
+        }
+        if needs_reload {
+            self.reload()
+        }
+    }
+
+    fn check(&mut self, color: bool) -> anyhow::Result<(String, u8)> {
+        self.process_file_changes();
+        let start = Instant::now();
+        let code_filter = CodeFilter::from_cli(&self.cli);
+        let baseline = BaselineMode::from_cli(&self.cli, &self.current_dir)?;
+        let mut diagnostics = self.project.diagnostics()?;
+        code_filter.apply(&mut diagnostics);
+        baseline.apply(&mut diagnostics)?;
+
+        let mut output = vec![];
+        colored::control::set_override(color);
+        let exit_code = write_diagnostics(
+            &mut output,
+            self.cli.mypy_options.output,
+            &diagnostics,
+            &self.diagnostic_config,
+        );
+        colored::control::unset_override();
+
+        self.check_count += 1;
+        self.invalidated_paths = 0;
+        self.last_check = Some(LastCheck {
+            checked_files: diagnostics.checked_files,
+            error_count: diagnostics.error_count(),
+            duration: start.elapsed(),
+        });
+        Ok((String::from_utf8(output)?, exit_code?))
+    }
+
+    fn status(&mut self) -> String {
+        self.process_file_changes();
+        let mut status = format!(
+            "Daemon (pid {}) is running for {}s in {}\n",
+            std::process::id(),
+            self.started.elapsed().as_secs(),
+            self.current_dir,
+        );
+        status += &format!("Checks: {}\n", self.check_count);
+        if let Some(last) = &self.last_check {
+            status += &format!(
+                "Last check: {} files, {} errors, took {:.2}s\n",
+                last.checked_files,
+                last.error_count,
+                last.duration.as_secs_f64(),
+            );
+        }
+        status += &format!(
+            "Changed files since the last check: {}\n",
+            self.invalidated_paths
+        );
+        status
+    }
+}
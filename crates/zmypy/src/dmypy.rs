@@ -0,0 +1,253 @@
+//! Serves clients of Mypy's `dmypy`, so editor integrations and scripts that use `dmypy check`
+//! or `dmypy run` work with Zuban's daemon. The daemon writes a status file like Mypy's daemon,
+//! which tells the client where to connect. Every connection carries one request and one
+//! response, each is a JSON object encoded as base64 and terminated by a space.
+#![cfg_attr(not(unix), allow(dead_code))] // Only the socket is missing on other platforms
+
+use std::path::Path;
+
+use anyhow::bail;
+use clap::Parser as _;
+use serde_json::{Value, json};
+
+use crate::{
+    Cli,
+    daemon::{Daemon, REQUEST_READ_TIMEOUT},
+};
+
+pub(crate) const DEFAULT_STATUS_FILE: &str = ".dmypy.json";
+
+/// Serves requests until a stop request is received.
+#[cfg(unix)]
+pub(crate) fn serve_dmypy(status_file: &Path, daemon: Daemon) -> anyhow::Result<()> {
+    use std::io::Write as _;
+
+    use crate::daemon::bind_private_socket;
+
+    let socket = std::env::temp_dir().join(format!("zuban-dmypy-{}.sock", std::process::id()));
+    // A socket with the same pid can only be left over from a process that was killed.
+    _ = std::fs::remove_file(&socket);
+    let listener = bind_private_socket(&socket)?;
+    let status = json!({
+        "pid": std::process::id(),
+        "connection_name": socket.to_string_lossy(),
+    });
+    std::fs::write(status_file, status.to_string())?;
+    tracing::info!(
+        "Daemon for dmypy clients is listening on {}",
+        socket.display()
+    );
+
+    let mut server = DmypyServer::new(daemon);
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                tracing::warn!("Could not accept a dmypy connection: {err}");
+                continue;
+            }
+        };
+        let request = match stream
+            .set_read_timeout(Some(REQUEST_READ_TIMEOUT))
+            .map_err(anyhow::Error::from)
+            .and_then(|()| read_frame(&mut stream))
+        {
+            Ok(request) => serde_json::from_slice(&request).unwrap_or(Value::Null),
+            Err(err) => {
+                tracing::warn!("Could not read a dmypy request: {err}");
+                continue;
+            }
+        };
+        let response = server.handle_request(&request);
+        if let Err(err) = stream.write_all(&encode_frame(response.to_string().as_bytes())) {
+            tracing::warn!("Could not respond to a dmypy request: {err}");
+        }
+        if request["command"] == "stop" {
+            break;
+        }
+    }
+    std::fs::remove_file(status_file)?;
+    std::fs::remove_file(&socket)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub(crate) fn serve_dmypy(_: &Path, _: Daemon) -> anyhow::Result<()> {
+    bail!("The daemon is currently only supported on Unix")
+}
+
+pub(crate) struct DmypyServer {
+    daemon: Daemon,
+    /// The arguments of the last `dmypy run`, the project is only loaded again if they change or
+    /// if `dmypy check` changed the files in the meantime.
+    run_args: Option<Vec<String>>,
+}
+
+impl DmypyServer {
+    pub(crate) fn new(daemon: Daemon) -> Self {
+        Self {
+            daemon,
+            run_args: None,
+        }
+    }
+
+    /// Answers a request like Mypy's daemon. Clients only stop waiting for responses after a
+    /// response that is marked as final.
+    pub(crate) fn handle_request(&mut self, request: &Value) -> Value {
+        let mut response = match self.handle_command(request) {
+            Ok(response) => response,
+            Err(err) => json!({"error": err.to_string()}),
+        };
+        response["final"] = json!(true);
+        response
+    }
+
+    fn handle_command(&mut self, request: &Value) -> anyhow::Result<Value> {
+        let Some(command) = request["command"].as_str() else {
+            bail!("No command found in request")
+        };
+        match command {
+            "status" => Ok(json!({"pid": std::process::id()})),
+            "stop" => Ok(json!({})),
+            "check" => {
+                let files = string_list(&request["files"])?;
+                self.daemon.set_files(files);
+                self.check(request)
+            }
+            "recheck" => {
+                if request["remove"].is_array() || request["update"].is_array() {
+                    // The changed files are known from watching the file system.
+                    self.check(request)
+                } else {
+                    self.daemon_request("recheck", request)
+                }
+            }
+            "run" => {
+                let args = string_list(&request["args"])?;
+                let cli = match Cli::try_parse_from(
+                    std::iter::once("dmypy run").chain(args.iter().map(|s| s.as_str())),
+                ) {
+                    Ok(cli) => cli,
+                    Err(err) => {
+                        return Ok(json!({"out": "", "err": err.to_string(), "status": 2}));
+                    }
+                };
+                if self.run_args.as_ref() != Some(&args) {
+                    self.daemon.set_cli(cli)?;
+                    self.run_args = Some(args);
+                } else {
+                    // `dmypy check` might have replaced the files of the run.
+                    self.daemon.set_files(cli.mypy_options.files);
+                }
+                self.check(request)
+            }
+            _ => bail!("Unrecognized command '{command}'"),
+        }
+    }
+
+    fn check(&mut self, request: &Value) -> anyhow::Result<Value> {
+        self.daemon_request("check", request)
+    }
+
+    fn daemon_request(&mut self, command: &str, request: &Value) -> anyhow::Result<Value> {
+        let request = json!({
+            "command": command,
+            "color": request["is_tty"].as_bool().unwrap_or(false),
+        });
+        let response = self
+            .daemon
+            .catch_panic(|daemon| daemon.handle_request(&request));
+        if let Some(error) = response["error"].as_str() {
+            bail!("{error}")
+        }
+        Ok(json!({
+            "out": response["output"],
+            "err": "",
+            "status": response["exit_code"],
+        }))
+    }
+}
+
+fn string_list(value: &Value) -> anyhow::Result<Vec<String>> {
+    let Some(list) = value.as_array() else {
+        bail!("Expected a list of strings, but got {value}")
+    };
+    list.iter()
+        .map(|item| match item.as_str() {
+            Some(s) => Ok(s.to_owned()),
+            None => bail!("Expected a list of strings, but got {value}"),
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+fn read_frame(stream: &mut impl std::io::Read) -> anyhow::Result<Vec<u8>> {
+    let mut frame = vec![];
+    let mut buffer = [0; 4096];
+    loop {
+        let n = stream.read(&mut buffer)?;
+        if n == 0 {
+            bail!("The connection was closed before a complete request was received")
+        }
+        frame.extend_from_slice(&buffer[..n]);
+        if let Some(end) = frame.iter().position(|&b| b == b' ') {
+            frame.truncate(end);
+            return decode_base64(&frame);
+        }
+    }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Frames are encoded like Python's `codecs.encode(data, "base64")`, which adds a newline after
+/// every 76 characters.
+pub(crate) fn encode_frame(data: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![];
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = ((bytes[0] as usize) << 16) | ((bytes[1] as usize) << 8) | bytes[2] as usize;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(BASE64_ALPHABET[(n >> (18 - 6 * i)) & 0x3f]);
+            } else {
+                encoded.push(b'=');
+            }
+        }
+    }
+    let mut frame = vec![];
+    for line in encoded.chunks(76) {
+        frame.extend_from_slice(line);
+        frame.push(b'\n');
+    }
+    frame.push(b' ');
+    frame
+}
+
+/// Decodes base64 and ignores whitespace like Python's decoder.
+pub(crate) fn decode_base64(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut decoded = vec![];
+    let mut n = 0;
+    let mut bits = 0;
+    for &b in data {
+        let value = match b {
+            b'\n' | b'\r' | b'=' => continue,
+            _ => match BASE64_ALPHABET.iter().position(|&c| c == b) {
+                Some(value) => value,
+                None => bail!("Invalid base64 character {:?}", b as char),
+            },
+        };
+        n = (n << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((n >> bits) as u8);
+            n &= (1 << bits) - 1;
+        }
+    }
+    Ok(decoded)
+}
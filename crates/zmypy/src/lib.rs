@@ -1,18 +1,29 @@
+mod baseline;
+mod daemon;
+mod dmypy;
+mod sarif;
+
 use std::env::VarError;
+use std::io::Write;
 use std::process::ExitCode;
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use colored::Colorize as _;
 pub use config::DiagnosticConfig;
+pub use daemon::{DaemonCommand, run_daemon};
 pub use zuban_python::Diagnostics;
 
-use config::{ExcludeRegex, ProjectOptions, PythonVersion, find_cli_config};
+use baseline::Baseline;
+use config::{ExcludeRegex, GeneratedCodeExclude, ProjectOptions, PythonVersion, find_cli_config};
 use vfs::{AbsPath, NormalizedPath, SimpleLocalFS, VfsHandler};
-use zuban_python::{Mode, Project};
+use zuban_python::{ApiChange, ApiCompleteness, Diagnostic, Mode, Project, SignatureCompatibility};
 
 use clap::Parser;
 
-#[derive(Parser)]
+#[derive(Parser, Clone)]
 pub struct Cli {
     // Additional options
     /// Enable or disable mypy compatibility. By default disabled and enabled if a Mypy config is found (inverse: --no-mypy-compatible)
@@ -20,6 +31,21 @@ pub struct Cli {
     pub mypy_compatible: bool,
     #[arg(long)]
     pub no_mypy_compatible: bool,
+    /// Only report errors with this error code, applied after checking (may be repeated)
+    #[arg(long, value_name = "CODE")]
+    pub only_code: Vec<String>,
+    /// Don't report errors with this error code, applied after checking (may be repeated)
+    #[arg(long, value_name = "CODE")]
+    pub filter_code: Vec<String>,
+    /// Don't report errors that are recorded in this baseline file
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+    /// Record all current errors in the baseline file instead of reporting them
+    #[arg(long, requires = "baseline")]
+    pub write_baseline: bool,
+    /// Check files with this many independent type checkers in parallel, which needs more memory
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub threads: usize,
     #[command(flatten)]
     pub mypy_options: MypyCli,
 }
@@ -231,6 +257,92 @@ pub struct MypyCli {
     pretty: bool,
     #[arg(long)]
     no_pretty: bool,
+    /// Print the diagnostics in a machine readable format instead: `json` prints one object per
+    /// line, `sarif` prints a SARIF 2.1.0 log
+    #[arg(short = 'O', long, value_enum, value_name = "FORMAT")]
+    output: Option<OutputFormat>,
+    /// Explain type mismatches by showing the steps that were taken while matching the types
+    /// (inverse: --no-explain-mismatch)
+    #[arg(long)]
+    explain_mismatch: bool,
+    #[arg(long)]
+    no_explain_mismatch: bool,
+    /// Do not accept everything for signatures ending with `*args: Any, **kwargs: Any` like Mypy,
+    /// useful to verify overrides exactly (inverse: --no-strict-signature-matching)
+    #[arg(long)]
+    strict_signature_matching: bool,
+    #[arg(long)]
+    no_strict_signature_matching: bool,
+    /// Join the types of conditional branches like Mypy (e.g. `1 if x else ""` becomes `object`)
+    /// instead of creating a union (inverse: --no-use-joins)
+    #[arg(long)]
+    use_joins: bool,
+    #[arg(long)]
+    no_use_joins: bool,
+    /// Disallow instance attributes that are neither annotated in the class body nor on self
+    /// (inverse: --allow-untyped-attributes)
+    #[arg(long)]
+    disallow_untyped_attributes: bool,
+    #[arg(long)]
+    allow_untyped_attributes: bool,
+    /// Infer the union of all assigned types for attributes that are never annotated, instead of
+    /// the type of the first assignment (inverse: --no-union-untyped-attributes)
+    #[arg(long)]
+    union_untyped_attributes: bool,
+    #[arg(long)]
+    no_union_untyped_attributes: bool,
+    /// Disallow assigning to functions and methods of modules, classes and instances
+    /// (inverse: --allow-monkeypatching)
+    #[arg(long)]
+    disallow_monkeypatching: bool,
+    #[arg(long)]
+    allow_monkeypatching: bool,
+    /// Allow assigning and accessing arbitrary attributes of functions
+    /// (inverse: --disallow-function-attributes)
+    #[arg(long)]
+    allow_function_attributes: bool,
+    #[arg(long)]
+    disallow_function_attributes: bool,
+    /// Don't treat int as compatible with float and float as compatible with complex
+    /// (inverse: --no-strict-numeric-promotion)
+    #[arg(long)]
+    strict_numeric_promotion: bool,
+    #[arg(long)]
+    no_strict_numeric_promotion: bool,
+    /// Stop checking a function after the given number of seconds and report that its types
+    /// may be imprecise
+    #[arg(long, value_name = "SECONDS")]
+    function_timeout: Option<u64>,
+    /// Don't report missing stubs for this compiled extension module or package (may be repeated)
+    #[arg(long, value_name = "MODULE")]
+    untyped_extension_module: Vec<String>,
+    /// Report imports of installed packages without a py.typed marker
+    /// (inverse: --allow-untyped-imports)
+    #[arg(long)]
+    disallow_untyped_imports: bool,
+    #[arg(long)]
+    allow_untyped_imports: bool,
+    /// Don't report this untyped package with --disallow-untyped-imports (may be repeated)
+    #[arg(long, value_name = "PACKAGE")]
+    allowed_untyped_import: Vec<String>,
+    /// Don't report errors in files with this marker in a comment before the first statement,
+    /// in addition to "# zuban: generated" (may be repeated)
+    #[arg(long, value_name = "MARKER", value_parser = parse_generated_code_marker)]
+    generated_code_marker: Vec<String>,
+    /// Don't check files matching this glob and report them with the reason in the summary,
+    /// e.g. "proto/*_pb2.py: generated by protoc" (may be repeated)
+    #[arg(long, value_name = "GLOB: REASON", value_parser = GeneratedCodeExclude::new)]
+    generated_code_exclude: Vec<GeneratedCodeExclude>,
+}
+
+fn parse_generated_code_marker(marker: &str) -> anyhow::Result<String> {
+    config::check_generated_code_marker(marker.to_owned())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    Json,
+    Sarif,
 }
 
 pub fn run(cli: Cli) -> ExitCode {
@@ -242,10 +354,134 @@ pub fn run(cli: Cli) -> ExitCode {
     }
     */
 
+    with_exit_code(cli, current_dir(), None)
+}
+
+fn current_dir() -> String {
     let current_dir = std::env::current_dir().expect("Expected a valid working directory");
     const CWD_ERROR: &str = "Expected valid unicode in working directory";
-    let current_dir = current_dir.into_os_string().into_string().expect(CWD_ERROR);
-    with_exit_code(cli, current_dir, None)
+    current_dir.into_os_string().into_string().expect(CWD_ERROR)
+}
+
+/// Compares the exported API of the packages in two directories, prints the changes and returns
+/// a failing exit code if there are breaking changes.
+pub fn api_diff(old: &str, new: &str) -> ExitCode {
+    match api_changes(old, new, None) {
+        Ok(changes) => {
+            let mut breaking = false;
+            for change in &changes {
+                if change.is_breaking() {
+                    breaking = true;
+                    println!("{}", change.to_string().red());
+                } else {
+                    println!("{change}");
+                }
+            }
+            ExitCode::from(breaking as u8)
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Prints the exported symbols of the package in a directory that are not completely typed and the
+/// share of completely typed symbols. Fails if any symbol is incomplete.
+pub fn verify_types(directory: &str) -> ExitCode {
+    match api_completeness(directory, None) {
+        Ok(completeness) => {
+            for symbol in &completeness.incomplete {
+                println!("{}", symbol.to_string().red());
+            }
+            let summary = format!(
+                "{} of {} exported symbols are completely typed ({:.1}%)",
+                completeness.symbol_count - completeness.incomplete.len(),
+                completeness.symbol_count,
+                completeness.percentage(),
+            );
+            if completeness.incomplete.is_empty() {
+                println!("{}", summary.green().bold());
+            } else {
+                println!("{}", summary.red().bold());
+            }
+            ExitCode::from(!completeness.incomplete.is_empty() as u8)
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Prints if the callable at the dotted path `source` can be used where the callable at `target`
+/// is expected, together with an explanation if not. Fails if it is not assignable.
+pub fn check_signature(directory: &str, source: &str, target: &str) -> ExitCode {
+    match signature_compatibility(directory, source, target, None) {
+        Ok(result) => {
+            println!("{source}: {}", result.source);
+            println!("{target}: {}", result.target);
+            if result.assignable {
+                let summary = format!("{source} is assignable to {target}");
+                println!("{}", summary.green().bold());
+            } else {
+                let summary = format!("{source} is not assignable to {target}");
+                println!("{}", summary.red().bold());
+                for line in &result.explanation {
+                    println!("  {line}");
+                }
+            }
+            ExitCode::from(!result.assignable as u8)
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn signature_compatibility(
+    directory: &str,
+    source: &str,
+    target: &str,
+    typeshed_path: Option<Arc<NormalizedPath>>,
+) -> anyhow::Result<SignatureCompatibility> {
+    let (mut project, _) = project_from_cli(
+        Cli::parse_from(["", "--explain-mismatch"]),
+        directory,
+        typeshed_path,
+        |name| std::env::var(name),
+    );
+    project.check_signature(source, target)
+}
+
+fn api_completeness(
+    directory: &str,
+    typeshed_path: Option<Arc<NormalizedPath>>,
+) -> anyhow::Result<ApiCompleteness> {
+    let (mut project, _) =
+        project_from_cli(Cli::parse_from([""]), directory, typeshed_path, |name| {
+            std::env::var(name)
+        });
+    project.api_completeness()
+}
+
+fn api_changes(
+    old: &str,
+    new: &str,
+    typeshed_path: Option<Arc<NormalizedPath>>,
+) -> anyhow::Result<Vec<ApiChange>> {
+    let project = |directory| {
+        project_from_cli(
+            Cli::parse_from([""]),
+            directory,
+            typeshed_path.clone(),
+            |name| std::env::var(name),
+        )
+        .0
+    };
+    let old_api = project(old).exported_api()?;
+    project(new).api_diff(&old_api)
 }
 
 fn with_exit_code(
@@ -253,19 +489,12 @@ fn with_exit_code(
     current_dir: String,
     typeshed_path: Option<Arc<NormalizedPath>>,
 ) -> ExitCode {
+    let output = cli.mypy_options.output;
     with_diagnostics_from_cli(cli, current_dir, typeshed_path, |diagnostics, config| {
-        let stdout = std::io::stdout();
-        for diagnostic in diagnostics.issues.iter() {
-            diagnostic
-                .write_colored(&mut stdout.lock(), config)
-                .unwrap()
-        }
-        if diagnostics.error_count() > 0 {
-            println!("{}", diagnostics.summary().red().bold());
-        } else {
-            println!("{}", diagnostics.summary().green().bold());
-        }
-        ExitCode::from((diagnostics.error_count() > 0) as u8)
+        let exit_code =
+            write_diagnostics(&mut std::io::stdout().lock(), output, &diagnostics, config)
+                .expect("Writing to stdout should not fail");
+        ExitCode::from(exit_code)
     })
     .unwrap_or_else(|err| {
         eprintln!("{err}");
@@ -273,6 +502,61 @@ fn with_exit_code(
     })
 }
 
+/// Writes the diagnostics in the given output format and returns the exit code.
+fn write_diagnostics(
+    writer: &mut dyn Write,
+    output: Option<OutputFormat>,
+    diagnostics: &Diagnostics,
+    config: &DiagnosticConfig,
+) -> std::io::Result<u8> {
+    let exit_code = (diagnostics.error_count() > 0) as u8;
+    match output {
+        Some(OutputFormat::Json) => {
+            for diagnostic in diagnostics.issues.iter() {
+                writeln!(writer, "{}", diagnostic_as_json(diagnostic))?;
+            }
+            return Ok(exit_code);
+        }
+        Some(OutputFormat::Sarif) => {
+            writeln!(
+                writer,
+                "{:#}",
+                sarif::diagnostics_as_sarif(&diagnostics.issues)
+            )?;
+            return Ok(exit_code);
+        }
+        None => (),
+    }
+    for diagnostic in diagnostics.issues.iter() {
+        diagnostic.write_colored(writer, config)?
+    }
+    if diagnostics.error_count() > 0 {
+        writeln!(writer, "{}", diagnostics.summary().red().bold())?;
+    } else {
+        writeln!(writer, "{}", diagnostics.summary().green().bold())?;
+    }
+    Ok(exit_code)
+}
+
+fn diagnostic_as_json(diagnostic: &Diagnostic) -> String {
+    let (message, notes) = diagnostic.message_and_notes();
+    let start = diagnostic.start_position();
+    let end = diagnostic.end_position();
+    let severity = diagnostic.kind_name();
+    serde_json::json!({
+        "file": diagnostic.relative_path(),
+        "line": start.line_one_based(),
+        "column": start.code_points_column() + 1,
+        "end_line": end.line_one_based(),
+        "end_column": end.code_points_column() + 1,
+        "severity": severity,
+        "code": (severity == "error").then(|| diagnostic.mypy_error_code()),
+        "message": message,
+        "notes": notes,
+    })
+    .to_string()
+}
+
 pub fn with_diagnostics_from_cli<T>(
     cli: Cli,
     current_dir: String,
@@ -280,10 +564,122 @@ pub fn with_diagnostics_from_cli<T>(
     callback: impl FnOnce(Diagnostics, &DiagnosticConfig) -> T,
 ) -> anyhow::Result<T> {
     tracing::info!("Checking in {current_dir}");
-    let (mut project, diagnostic_config) =
-        project_from_cli(cli, &current_dir, typeshed_path, |name| std::env::var(name));
-    let diagnostics = project.diagnostics();
-    Ok(callback(diagnostics?, &diagnostic_config))
+    warn_about_unknown_cli_error_codes(&cli);
+    let code_filter = CodeFilter::from_cli(&cli);
+    let baseline = BaselineMode::from_cli(&cli, &current_dir)?;
+    let (mut projects, diagnostic_config) =
+        projects_from_cli(cli, &current_dir, typeshed_path, |name| std::env::var(name));
+    let mut diagnostics = check_projects(&mut projects)?;
+    code_filter.apply(&mut diagnostics);
+    baseline.apply(&mut diagnostics)?;
+    Ok(callback(diagnostics, &diagnostic_config))
+}
+
+fn warn_about_unknown_cli_error_codes(cli: &Cli) {
+    let options = &cli.mypy_options;
+    let unknown = config::unknown_error_codes(
+        options
+            .enable_error_code
+            .iter()
+            .chain(&options.disable_error_code)
+            .map(|code| code.as_str()),
+    );
+    if !unknown.is_empty() {
+        eprintln!(
+            "Warning: Ignoring unknown error code(s): {}",
+            unknown.join(", ")
+        );
+    }
+}
+
+enum BaselineMode {
+    None,
+    Read(Baseline),
+    Write(PathBuf),
+}
+
+impl BaselineMode {
+    fn from_cli(cli: &Cli, current_dir: &str) -> anyhow::Result<Self> {
+        let Some(path) = &cli.baseline else {
+            return Ok(Self::None);
+        };
+        let path = Path::new(current_dir).join(path);
+        Ok(if cli.write_baseline {
+            Self::Write(path)
+        } else {
+            Self::Read(Baseline::read(&path)?)
+        })
+    }
+
+    fn apply(self, diagnostics: &mut Diagnostics) -> anyhow::Result<()> {
+        match self {
+            Self::None => (),
+            Self::Read(baseline) => baseline.apply(diagnostics),
+            Self::Write(path) => {
+                Baseline::write(&path, &diagnostics.issues)?;
+                tracing::info!("Wrote {} errors to the baseline", diagnostics.error_count());
+                diagnostics.retain_errors(|_| false);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The error codes selected with --only-code and --filter-code. Unlike --disable-error-code this
+/// does not change how files are checked, it only hides errors in the output.
+struct CodeFilter {
+    only: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl CodeFilter {
+    fn from_cli(cli: &Cli) -> Self {
+        Self {
+            only: cli.only_code.clone(),
+            exclude: cli.filter_code.clone(),
+        }
+    }
+
+    fn apply(&self, diagnostics: &mut Diagnostics) {
+        if self.only.is_empty() && self.exclude.is_empty() {
+            return;
+        }
+        diagnostics.retain_error_codes(|code| {
+            (self.only.is_empty() || self.only.iter().any(|c| c == code))
+                && !self.exclude.iter().any(|c| c == code)
+        })
+    }
+}
+
+/// Creates one project per thread of --threads.
+fn projects_from_cli(
+    cli: Cli,
+    current_dir: &str,
+    typeshed_path: Option<Arc<NormalizedPath>>,
+    lookup_env_var: impl Fn(&str) -> Result<String, VarError>,
+) -> (Vec<Project>, DiagnosticConfig) {
+    let threads = cli.threads.max(1);
+    let mut projects = Vec::with_capacity(threads);
+    for _ in 1..threads {
+        let (project, _) = project_from_cli(
+            cli.clone(),
+            current_dir,
+            typeshed_path.clone(),
+            &lookup_env_var,
+        );
+        projects.push(project);
+    }
+    let (project, diagnostic_config) =
+        project_from_cli(cli, current_dir, typeshed_path, lookup_env_var);
+    projects.push(project);
+    (projects, diagnostic_config)
+}
+
+fn check_projects(projects: &mut [Project]) -> anyhow::Result<Diagnostics<'_>> {
+    match projects {
+        [project] => project.diagnostics(),
+        projects => Project::diagnostics_in_parallel(projects),
+    }
 }
 
 fn project_from_cli(
@@ -292,7 +688,24 @@ fn project_from_cli(
     typeshed_path: Option<Arc<NormalizedPath>>,
     lookup_env_var: impl Fn(&str) -> Result<String, VarError>,
 ) -> (Project, DiagnosticConfig) {
-    let local_fs = SimpleLocalFS::without_watcher();
+    let (project, diagnostic_config, _) = project_from_cli_with_vfs(
+        cli,
+        SimpleLocalFS::without_watcher(),
+        current_dir,
+        typeshed_path,
+        lookup_env_var,
+    );
+    (project, diagnostic_config)
+}
+
+/// Like [`project_from_cli`], but also returns the path of the config file that was used.
+fn project_from_cli_with_vfs(
+    cli: Cli,
+    local_fs: SimpleLocalFS,
+    current_dir: &str,
+    typeshed_path: Option<Arc<NormalizedPath>>,
+    lookup_env_var: impl Fn(&str) -> Result<String, VarError>,
+) -> (Project, DiagnosticConfig, Option<Arc<AbsPath>>) {
     let current_dir = local_fs.unchecked_abs_path(current_dir);
     let mut found = find_cli_config(
         &local_fs,
@@ -302,6 +715,10 @@ fn project_from_cli(
         cli.mypy_compatible && !cli.no_mypy_compatible,
     )
     .unwrap_or_else(|err| panic!("Problem parsing Mypy config: {err}"));
+    if let Some(config_path) = &found.config_path {
+        // This does nothing without a watcher
+        local_fs.watch(&**config_path);
+    }
     let mut options = found.project_options;
     if let Some(typeshed_path) = typeshed_path {
         options.settings.typeshed_path = Some(typeshed_path);
@@ -324,6 +741,7 @@ fn project_from_cli(
     (
         Project::new(Box::new(local_fs), options, Mode::LanguageServer),
         found.diagnostic_config,
+        found.config_path,
     )
 }
 
@@ -408,6 +826,26 @@ fn apply_mypy_flags(
     apply!(diagnostic_config, show_error_end, hide_error_end);
     apply!(diagnostic_config, show_error_codes, hide_error_codes);
     apply!(diagnostic_config, pretty, no_pretty);
+    apply!(flags, explain_mismatch, no_explain_mismatch);
+    apply!(
+        flags,
+        strict_signature_matching,
+        no_strict_signature_matching
+    );
+    apply!(flags, use_joins, no_use_joins);
+    apply!(flags, disallow_untyped_attributes, allow_untyped_attributes);
+    apply!(flags, union_untyped_attributes, no_union_untyped_attributes);
+    apply!(flags, disallow_monkeypatching, allow_monkeypatching);
+    apply!(
+        flags,
+        allow_function_attributes,
+        disallow_function_attributes
+    );
+    apply!(flags, disallow_untyped_imports, allow_untyped_imports);
+    apply!(flags, strict_numeric_promotion, no_strict_numeric_promotion);
+    if let Some(timeout) = cli.function_timeout {
+        flags.function_timeout = Some(timeout);
+    }
 
     apply!(flags, allow_redefinition, disallow_redefinition);
     if cli.allow_redefinition_new {
@@ -458,6 +896,22 @@ fn apply_mypy_flags(
         .flags
         .always_false_symbols
         .extend(cli.always_false);
+    project_options
+        .flags
+        .untyped_extension_modules
+        .extend(cli.untyped_extension_module);
+    project_options
+        .flags
+        .allowed_untyped_imports
+        .extend(cli.allowed_untyped_import);
+    project_options
+        .flags
+        .generated_code_markers
+        .extend(cli.generated_code_marker);
+    project_options
+        .flags
+        .generated_code_excludes
+        .extend(cli.generated_code_exclude);
 
     if cli.ignore_excludes_from_config {
         // This is for testing, so we can test all files
@@ -492,6 +946,8 @@ fn apply_mypy_flags(
 mod tests {
     use std::path::Path;
 
+    use zuban_python::CompletenessProblem;
+
     use super::*;
 
     fn diagnostics_with_env_lookup(
@@ -499,14 +955,19 @@ mod tests {
         directory: &str,
         lookup_env_var: impl Fn(&str) -> Result<String, VarError>,
     ) -> anyhow::Result<Vec<String>> {
-        let (mut project, diagnostic_config) = project_from_cli(
+        warn_about_unknown_cli_error_codes(&cli);
+        let code_filter = CodeFilter::from_cli(&cli);
+        let baseline = BaselineMode::from_cli(&cli, directory)?;
+        let (mut projects, diagnostic_config) = projects_from_cli(
             cli,
             directory,
             Some(test_utils::typeshed_path()),
             lookup_env_var,
         );
-        let diagnostics = project.diagnostics();
-        let mut diagnostics = diagnostics?
+        let mut diagnostics = check_projects(&mut projects)?;
+        code_filter.apply(&mut diagnostics);
+        baseline.apply(&mut diagnostics)?;
+        let mut diagnostics = diagnostics
             .issues
             .iter()
             .map(|d| d.as_string(&diagnostic_config))
@@ -560,6 +1021,441 @@ mod tests {
         assert_eq!(d(), vec![NOT_CALLABLE.to_string()]);
     }
 
+    #[test]
+    fn test_generated_code_excludes() {
+        logging_config::setup_logging_for_tests();
+        let test_dir = test_utils::write_files_from_fixture(
+            r#"
+            [file foo.py]
+            x: int = ""
+
+            [file proto/foo_pb2.py]
+            x: int = ""
+
+            [file proto/bar_pb2.py]
+            x: int = ""
+            "#,
+            false,
+        );
+        let summary = |args: &[&str]| {
+            let (mut project, _) = project_from_cli(
+                Cli::parse_from(args),
+                test_dir.path(),
+                Some(test_utils::typeshed_path()),
+                |_| Err(VarError::NotPresent),
+            );
+            project.diagnostics().unwrap().summary()
+        };
+        assert_eq!(
+            summary(&[""]),
+            "Found 3 errors in 3 files (checked 3 source files)"
+        );
+        assert_eq!(
+            summary(&[
+                "",
+                "--generated-code-exclude",
+                "proto/*_pb2.py: generated by protoc"
+            ]),
+            "Found 1 error in 1 file (checked 1 source file) \
+             (skipped 2 generated files: generated by protoc (2))"
+        );
+
+        assert!(Cli::try_parse_from(["", "--generated-code-exclude", "proto/*_pb2.py"]).is_err());
+        assert!(Cli::try_parse_from(["", "--generated-code-marker", ""]).is_err());
+    }
+
+    #[test]
+    fn test_json_output() {
+        logging_config::setup_logging_for_tests();
+        let test_dir = test_utils::write_files_from_fixture(
+            r#"
+            [file foo.py]
+            x: int = ""
+            reveal_type(x)
+            "#,
+            false,
+        );
+        let (mut project, _) = project_from_cli(
+            Cli::parse_from([""]),
+            test_dir.path(),
+            Some(test_utils::typeshed_path()),
+            |_| Err(VarError::NotPresent),
+        );
+        let diagnostics = project.diagnostics().unwrap();
+        let lines = diagnostics
+            .issues
+            .iter()
+            .map(diagnostic_as_json)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                r#"{"code":"assignment","column":10,"end_column":12,"end_line":1,"file":"foo.py","line":1,"message":"Incompatible types in assignment (expression has type \"str\", variable has type \"int\")","notes":[],"severity":"error"}"#,
+                r#"{"code":null,"column":13,"end_column":14,"end_line":2,"file":"foo.py","line":2,"message":"Revealed type is \"builtins.int\"","notes":[],"severity":"note"}"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sarif_output() {
+        logging_config::setup_logging_for_tests();
+        let test_dir = test_utils::write_files_from_fixture(
+            r#"
+            [file foo.py]
+            x: int = ""
+            1()
+            "#,
+            false,
+        );
+        let (mut project, _) = project_from_cli(
+            Cli::parse_from([""]),
+            test_dir.path(),
+            Some(test_utils::typeshed_path()),
+            |_| Err(VarError::NotPresent),
+        );
+        let diagnostics = project.diagnostics().unwrap();
+        let sarif = sarif::diagnostics_as_sarif(&diagnostics.issues);
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        let rule_ids = rules.iter().map(|r| &r["id"]).collect::<Vec<_>>();
+        assert_eq!(rule_ids, ["assignment", "operator"]);
+        assert_eq!(rules[0]["defaultConfiguration"]["level"], "error");
+        assert_eq!(
+            rules[0]["shortDescription"]["text"],
+            "Check that assigned value is compatible with target"
+        );
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1]["ruleId"], "operator");
+        assert_eq!(results[1]["ruleIndex"], 1);
+        assert_eq!(results[1]["level"], "error");
+        assert_eq!(results[1]["message"]["text"], r#""int" not callable"#);
+        assert_eq!(
+            results[1]["locations"][0]["physicalLocation"],
+            serde_json::json!({
+                "artifactLocation": {"uri": "foo.py"},
+                "region": {"startLine": 2, "startColumn": 1, "endLine": 2, "endColumn": 4},
+            })
+        );
+    }
+
+    #[test]
+    fn test_only_and_filter_code() {
+        logging_config::setup_logging_for_tests();
+        let test_dir = test_utils::write_files_from_fixture(
+            r#"
+            [file pyproject.toml]
+            [tool.mypy]
+            strict = true
+
+            [file foo.py]
+            1()
+            def foo(x) -> int: return 1
+            "#,
+            false,
+        );
+        let d = |cli_args: &[&str]| diagnostics(Cli::parse_from(cli_args), test_dir.path());
+
+        const NOT_CALLABLE: &str = "foo.py:1: error: \"int\" not callable  [operator]";
+        const UNTYPED_DEF: &str = "foo.py:2: error: Function is missing a type annotation for one or more arguments  [no-untyped-def]";
+        assert_eq!(d(&["", "--only-code", "no-untyped-def"]), [UNTYPED_DEF]);
+        assert_eq!(d(&["", "--filter-code", "no-untyped-def"]), [NOT_CALLABLE]);
+        assert_eq!(
+            d(&[
+                "",
+                "--only-code",
+                "operator",
+                "--only-code",
+                "no-untyped-def"
+            ]),
+            [NOT_CALLABLE, UNTYPED_DEF]
+        );
+        assert!(d(&["", "--only-code", "operator", "--filter-code", "operator"]).is_empty());
+    }
+
+    #[test]
+    fn test_enable_and_disable_error_codes() {
+        logging_config::setup_logging_for_tests();
+        let test_dir = test_utils::write_files_from_fixture(
+            r#"
+            [file pyproject.toml]
+            [tool.mypy]
+            disable_error_code = ["operator"]
+
+            [[tool.mypy.overrides]]
+            module = "bar"
+            enable_error_code = "operator"
+
+            [file foo.py]
+            1()
+
+            [file bar.py]
+            1()
+            "#,
+            false,
+        );
+        let d = |cli_args: &[&str]| diagnostics(Cli::parse_from(cli_args), test_dir.path());
+
+        const BAR: &str = "bar.py:1: error: \"int\" not callable  [operator]";
+        const FOO: &str = "foo.py:1: error: \"int\" not callable  [operator]";
+        assert_eq!(d(&[""]), [BAR]);
+        assert_eq!(d(&["", "--enable-error-code", "operator"]), [BAR, FOO]);
+
+        // Unknown codes (e.g. of Mypy plugins) are ignored.
+        assert_eq!(
+            d(&[
+                "",
+                "--disable-error-code",
+                "operator",
+                "--enable-error-code",
+                "pydantic-field",
+            ]),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_baseline() {
+        logging_config::setup_logging_for_tests();
+        let test_dir = test_utils::write_files_from_fixture(
+            r#"
+            [file foo.py]
+            1()
+            1()
+            "#,
+            false,
+        );
+        let d = |cli_args: &[&str]| diagnostics(Cli::parse_from(cli_args), test_dir.path());
+
+        assert!(d(&["", "--baseline", "baseline.json", "--write-baseline"]).is_empty());
+        assert!(d(&["", "--baseline", "baseline.json"]).is_empty());
+
+        // Moving the errors around is fine, but new errors are reported.
+        let foo = Path::new(test_dir.path()).join("foo.py");
+        std::fs::write(&foo, "\n\n1()\n''()\n1()\n1()\n").unwrap();
+        assert_eq!(
+            d(&["", "--baseline", "baseline.json"]),
+            [
+                "foo.py:4: error: \"str\" not callable  [operator]",
+                "foo.py:6: error: \"int\" not callable  [operator]",
+            ]
+        );
+        assert_eq!(d(&[""]).len(), 4);
+
+        let err = expect_diagnostics_error(
+            Cli::parse_from(["", "--baseline", "missing.json"]),
+            test_dir.path(),
+        );
+        assert!(err.starts_with("Could not read baseline"), "{err}");
+    }
+
+    #[test]
+    fn test_threads() {
+        logging_config::setup_logging_for_tests();
+        let test_dir = test_utils::write_files_from_fixture(
+            r#"
+            [file a.py]
+            from b import B
+            B().f()()
+
+            [file b.py]
+            class B:
+                def f(self) -> int: ...
+
+            [file c.py]
+            import a
+            a.B().g()
+
+            [file d.py]
+            1()
+            "#,
+            false,
+        );
+        let d = |cli_args: &[&str]| diagnostics(Cli::parse_from(cli_args), test_dir.path());
+
+        let expected = d(&[""]);
+        assert_eq!(
+            expected,
+            [
+                "a.py:2: error: \"int\" not callable  [operator]",
+                "c.py:2: error: \"B\" has no attribute \"g\"  [attr-defined]",
+                "d.py:1: error: \"int\" not callable  [operator]",
+            ]
+        );
+        assert_eq!(d(&["", "--threads", "2"]), expected);
+        assert_eq!(d(&["", "--threads", "8"]), expected);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_daemon() {
+        use serde_json::json;
+
+        logging_config::setup_logging_for_tests();
+        let test_dir = test_utils::write_files_from_fixture(
+            r#"
+            [file foo.py]
+            1()
+            "#,
+            false,
+        );
+        let mut daemon = daemon::Daemon::new(
+            Cli::parse_from([""]),
+            test_dir.path().to_owned(),
+            Some(test_utils::typeshed_path()),
+        )
+        .unwrap();
+        let mut request = |command: &str| daemon.handle_request(&json!({"command": command}));
+
+        let response = request("check");
+        assert_eq!(response["exit_code"], 1);
+        let output = response["output"].as_str().unwrap();
+        assert!(output.contains("\"int\" not callable"), "{output}");
+
+        // The daemon picks up changes on disk without being restarted, but the watcher needs some
+        // time to report them.
+        std::fs::write(Path::new(test_dir.path()).join("foo.py"), "''()\n").unwrap();
+        let mut output = String::new();
+        for _ in 0..100 {
+            output = request("check")["output"].as_str().unwrap().to_owned();
+            if output.contains("\"str\" not callable") {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        assert!(output.contains("\"str\" not callable"), "{output}");
+        assert!(!output.contains("\"int\" not callable"), "{output}");
+
+        let response = request("recheck");
+        assert_eq!(response["output"], output.as_str());
+
+        let status = request("status")["output"].as_str().unwrap().to_owned();
+        assert!(status.contains("Last check: 1 files, 1 errors"), "{status}");
+        assert!(request("invalid")["error"].is_string());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_daemon_socket() {
+        use serde_json::json;
+
+        logging_config::setup_logging_for_tests();
+        let test_dir = test_utils::write_files_from_fixture(
+            r#"
+            [file foo.py]
+            1()
+            "#,
+            false,
+        );
+        let socket = Path::new(test_dir.path()).join("daemon.sock");
+        let server = std::thread::spawn({
+            let socket = socket.clone();
+            let current_dir = test_dir.path().to_owned();
+            move || {
+                let daemon = daemon::Daemon::new(
+                    Cli::parse_from([""]),
+                    current_dir,
+                    Some(test_utils::typeshed_path()),
+                )?;
+                daemon::serve(&socket, daemon)
+            }
+        });
+        let request = |command: &str| daemon::send_request(&socket, json!({"command": command}));
+
+        // Wait until the daemon listens
+        let mut response = request("check");
+        for _ in 0..100 {
+            if response.is_ok() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            response = request("check");
+        }
+        assert_eq!(response.unwrap()["exit_code"], 1);
+
+        assert_eq!(request("stop").unwrap()["exit_code"], 0);
+        server.join().unwrap().unwrap();
+        assert!(!socket.exists());
+        assert!(request("status").is_err());
+    }
+
+    #[test]
+    fn test_dmypy_frames() {
+        assert_eq!(dmypy::encode_frame(br#"{"a": 1}"#), b"eyJhIjogMX0=\n ");
+        // Python adds a newline after 76 characters
+        let data = (0..100).collect::<Vec<u8>>();
+        let frame = dmypy::encode_frame(&data);
+        assert_eq!(
+            String::from_utf8(frame.clone()).unwrap(),
+            "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8gISIjJCUmJygpKissLS4vMDEyMzQ1Njc4\nOTo7PD0+P0BBQkNERUZHSElKS0xNTk9QUVJTVFVWV1hZWltcXV5fYGFiYw==\n "
+        );
+        assert_eq!(
+            dmypy::decode_base64(&frame[..frame.len() - 1]).unwrap(),
+            data
+        );
+        assert!(dmypy::decode_base64(b"a b").is_err());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_dmypy_requests() {
+        use serde_json::json;
+
+        logging_config::setup_logging_for_tests();
+        let test_dir = test_utils::write_files_from_fixture(
+            r#"
+            [file foo.py]
+            1()
+
+            [file bar.py]
+            ''()
+            "#,
+            false,
+        );
+        let daemon = daemon::Daemon::new(
+            Cli::parse_from([""]),
+            test_dir.path().to_owned(),
+            Some(test_utils::typeshed_path()),
+        )
+        .unwrap();
+        let mut server = dmypy::DmypyServer::new(daemon);
+        let mut request = |request| server.handle_request(&request);
+
+        let response = request(json!({"command": "status"}));
+        assert_eq!(response["pid"], std::process::id());
+        assert_eq!(response["final"], true);
+
+        let response = request(json!({"command": "check", "files": ["foo.py"], "is_tty": false}));
+        assert_eq!(response["status"], 1);
+        let out = response["out"].as_str().unwrap();
+        assert!(out.contains("\"int\" not callable"), "{out}");
+        assert!(!out.contains("\"str\" not callable"), "{out}");
+
+        let response = request(json!({"command": "run", "version": "1.15.0", "args": ["bar.py"]}));
+        assert_eq!(response["status"], 1);
+        let out = response["out"].as_str().unwrap();
+        assert!(out.contains("\"str\" not callable"), "{out}");
+        assert!(!out.contains("\"int\" not callable"), "{out}");
+
+        let response = request(json!({"command": "recheck", "remove": [], "update": ["bar.py"]}));
+        assert_eq!(response["out"], out);
+
+        // A check in between replaces the files, the same run needs to check bar.py again.
+        request(json!({"command": "check", "files": ["foo.py"], "is_tty": false}));
+        let response = request(json!({"command": "run", "args": ["bar.py"]}));
+        assert_eq!(response["out"], out);
+
+        let response = request(json!({"command": "run", "args": ["--invalid-flag"]}));
+        assert_eq!(response["status"], 2);
+        assert!(response["err"].as_str().unwrap().contains("--invalid-flag"));
+
+        assert!(request(json!({"command": "suggest"}))["error"].is_string());
+        assert!(request(json!({}))["error"].is_string());
+        assert_eq!(request(json!({"command": "stop"})), json!({"final": true}));
+    }
+
     #[test]
     fn test_pyproject_should_be_ignored_if_no_relevant_entry() {
         logging_config::setup_logging_for_tests();
@@ -1053,4 +1949,134 @@ mod tests {
             ["m.py:4: note: Revealed type is \"builtins.int\""]
         );
     }
+
+    #[test]
+    fn test_api_diff() {
+        logging_config::setup_logging_for_tests();
+        let old = test_utils::write_files_from_fixture(
+            r#"
+            [file pkg/__init__.py]
+            from typing import Self
+            def f(x: int) -> int: return x
+            def removed() -> None: ...
+            def _private(x: int) -> None: ...
+            def optional(x: int) -> None: ...
+            def widened(x: int) -> None: ...
+            def narrowed(x: int | str) -> None: ...
+            class C:
+                def method(self) -> str: return ""
+                def other(self, x: int) -> None: ...
+                def copy(self) -> Self: return self
+                def replace(self) -> Self: return self
+            "#,
+            false,
+        );
+        let new = test_utils::write_files_from_fixture(
+            r#"
+            [file pkg/__init__.py]
+            from typing import Self
+            def f(x: str) -> int: return 1
+            def _private(x: str) -> None: ...
+            def added() -> None: ...
+            def optional(x: int, y: int = 1, *, z: str = "") -> None: ...
+            def widened(x: int | str) -> None: ...
+            def narrowed(x: int) -> None: ...
+            class C:
+                def method(self) -> str: return ""
+                def other(self, x: object, y: int = 0) -> None: ...
+                def copy(self, deep: bool = False) -> Self: return self
+                def replace(self) -> "C": return self
+            "#,
+            false,
+        );
+        let changes = api_changes(old.path(), new.path(), Some(test_utils::typeshed_path()))
+            .unwrap()
+            .into_iter()
+            .map(|change| (change.path().to_owned(), change.is_breaking()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                ("pkg.C.copy".to_owned(), false),
+                ("pkg.C.other".to_owned(), false),
+                ("pkg.C.replace".to_owned(), true),
+                ("pkg.added".to_owned(), false),
+                ("pkg.f".to_owned(), true),
+                ("pkg.narrowed".to_owned(), true),
+                ("pkg.optional".to_owned(), false),
+                ("pkg.removed".to_owned(), true),
+                ("pkg.widened".to_owned(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_api_completeness() {
+        logging_config::setup_logging_for_tests();
+        let dir = test_utils::write_files_from_fixture(
+            r#"
+            [file pkg/__init__.py]
+            from typing import Any
+            def typed(x: int) -> int: return x
+            def explicit_any(x: Any) -> Any: return x
+            def untyped(x): return x
+            def _private(x): ...
+            class C:
+                def method(self) -> str: return ""
+                def incomplete(self, x: int): ...
+            "#,
+            false,
+        );
+        let completeness = api_completeness(dir.path(), Some(test_utils::typeshed_path())).unwrap();
+        let incomplete = completeness
+            .incomplete
+            .iter()
+            .map(|symbol| (symbol.path.as_str(), symbol.problem))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            incomplete,
+            [
+                ("pkg.C.incomplete", CompletenessProblem::MissingAnnotation),
+                ("pkg.untyped", CompletenessProblem::MissingAnnotation),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_signature_compatibility() {
+        logging_config::setup_logging_for_tests();
+        let dir = test_utils::write_files_from_fixture(
+            r#"
+            [file pkg/__init__.py]
+            from typing import Protocol
+            class Handler(Protocol):
+                def __call__(self, event: str, *, retries: int) -> None: ...
+            def good(event: str, *, retries: int = 0) -> None: ...
+            def bad(event: int, *, retries: int) -> None: ...
+            "#,
+            false,
+        );
+        let check = |source| {
+            signature_compatibility(
+                dir.path(),
+                source,
+                "pkg.Handler",
+                Some(test_utils::typeshed_path()),
+            )
+            .unwrap()
+        };
+        assert!(check("pkg.good").assignable);
+        let bad = check("pkg.bad");
+        assert!(!bad.assignable);
+        assert!(!bad.explanation.is_empty());
+        assert!(
+            signature_compatibility(
+                dir.path(),
+                "pkg.missing",
+                "pkg.Handler",
+                Some(test_utils::typeshed_path())
+            )
+            .is_err()
+        );
+    }
 }
@@ -0,0 +1,88 @@
+//! Converts diagnostics to a [SARIF 2.1.0](https://docs.oasis-open.org/sarif/sarif/v2.1.0/)
+//! log, which is understood by GitHub code scanning and other static analysis tooling.
+
+use std::collections::BTreeMap;
+
+use config::lookup_error_code;
+use serde_json::{Value, json};
+use zuban_python::Diagnostic;
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+pub(crate) fn diagnostics_as_sarif(diagnostics: &[Diagnostic]) -> Value {
+    // Every error code is a rule. Rules are sorted by code, so their indexes are stable. A rule
+    // is an error if any of its diagnostics is one.
+    let mut rules: BTreeMap<&str, &str> = BTreeMap::new();
+    for diagnostic in diagnostics {
+        let rule_level = rules.entry(diagnostic.mypy_error_code()).or_insert("note");
+        if level(diagnostic) == "error" {
+            *rule_level = "error";
+        }
+    }
+    let rule_index = |code| rules.keys().position(|c| *c == code).unwrap();
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let code = diagnostic.mypy_error_code();
+            let start = diagnostic.start_position();
+            let end = diagnostic.end_position();
+            json!({
+                "ruleId": code,
+                "ruleIndex": rule_index(code),
+                "level": level(diagnostic),
+                "message": {"text": diagnostic.message()},
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": diagnostic.relative_path().replace('\\', "/"),
+                        },
+                        "region": {
+                            "startLine": start.line_one_based(),
+                            "startColumn": start.code_points_column() + 1,
+                            "endLine": end.line_one_based(),
+                            "endColumn": end.code_points_column() + 1,
+                        },
+                    },
+                }],
+                "partialFingerprints": {"zuban/v1": diagnostic.fingerprint()},
+            })
+        })
+        .collect::<Vec<_>>();
+    let rules = rules
+        .into_iter()
+        .map(|(code, level)| {
+            let mut rule = json!({
+                "id": code,
+                "name": code,
+                "defaultConfiguration": {"level": level},
+            });
+            if let Some(error_code) = lookup_error_code(code) {
+                rule["shortDescription"] = json!({"text": error_code.description});
+            }
+            rule
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "zuban",
+                    "informationUri": "https://zubanls.com",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    })
+}
+
+fn level(diagnostic: &Diagnostic) -> &'static str {
+    match diagnostic.kind_name() {
+        "error" => "error",
+        _ => "note",
+    }
+}
@@ -17,8 +17,39 @@ enum Commands {
     Check(#[command(flatten)] zmypy::Cli),
     /// Type checks files like you would do when calling `mypy`
     Mypy(#[command(flatten)] zmypy::MypyCli),
+    /// Compares the exported typed API of two versions of a package and reports breaking changes
+    ApiDiff {
+        /// The directory with the old version
+        old: String,
+        /// The directory with the new version
+        new: String,
+    },
+    /// Reports exported symbols of a package that are not completely typed
+    VerifyTypes {
+        /// The directory with the package
+        directory: String,
+    },
+    /// Checks if a callable can be used where another callable is expected and explains why not
+    CheckSignature {
+        /// The dotted path of the passed callable, e.g. `package.module.handler`
+        source: String,
+        /// The dotted path of the expected callable or callback protocol
+        target: String,
+        /// The directory with the project
+        #[arg(long, default_value = ".")]
+        directory: String,
+    },
+    /// Keeps a project loaded in the background to make repeated checks fast
+    Daemon {
+        #[command(subcommand)]
+        command: zmypy::DaemonCommand,
+    },
     /// Starts an LSP server
-    Server {},
+    Server {
+        /// Logs memory usage per module and cache statistics of the loaded project
+        #[arg(long)]
+        profile: bool,
+    },
 }
 
 fn main() -> ExitCode {
@@ -32,10 +63,43 @@ fn main() -> ExitCode {
         Commands::Mypy(mypy_options) => run_check(zmypy::Cli {
             mypy_compatible: true,
             no_mypy_compatible: false,
+            only_code: vec![],
+            filter_code: vec![],
+            baseline: None,
+            write_baseline: false,
+            threads: 1,
             mypy_options,
         }),
         Commands::Check(zmypy_config) => run_check(zmypy_config),
-        Commands::Server {} => match run_server() {
+        Commands::ApiDiff { old, new } => {
+            if let Err(err) = logging_config::setup_logging_without_printing_errors_by_default() {
+                panic!("{err}")
+            };
+            zmypy::api_diff(&old, &new)
+        }
+        Commands::VerifyTypes { directory } => {
+            if let Err(err) = logging_config::setup_logging_without_printing_errors_by_default() {
+                panic!("{err}")
+            };
+            zmypy::verify_types(&directory)
+        }
+        Commands::CheckSignature {
+            source,
+            target,
+            directory,
+        } => {
+            if let Err(err) = logging_config::setup_logging_without_printing_errors_by_default() {
+                panic!("{err}")
+            };
+            zmypy::check_signature(&directory, &source, &target)
+        }
+        Commands::Daemon { command } => {
+            if let Err(err) = logging_config::setup_logging_without_printing_errors_by_default() {
+                panic!("{err}")
+            };
+            zmypy::run_daemon(command)
+        }
+        Commands::Server { profile } => match run_server(profile) {
             Ok(()) => ExitCode::from(0),
             Err(err) => {
                 eprintln!("{err}");
@@ -45,8 +109,9 @@ fn main() -> ExitCode {
     }
 }
 
-fn run_server() -> anyhow::Result<()> {
+fn run_server(profile: bool) -> anyhow::Result<()> {
     logging_config::setup_logging(None)?;
+    zubanls::PROFILE.store(profile, std::sync::atomic::Ordering::Relaxed);
 
     // Logging to stderr.
     tracing::info!("Starting the Zuban Language Server");
@@ -0,0 +1,576 @@
+use std::{cell::RefCell, collections::BTreeMap, fmt};
+
+use anyhow::{anyhow, bail};
+use parsa_python_cst::{FunctionDef, FunctionParent, ParamKind, Scope};
+
+use crate::{
+    database::Database,
+    diagnostics::IssueKind,
+    file::PythonFile,
+    format_data::FormatData,
+    inference_state::InferenceState,
+    matching::{ErrorTypes, GotType, LookupKind, Match, ResultContext, record_match_explanation},
+    node_ref::NodeRef,
+    select_files::relevant_files,
+    type_::{AnyCause, CallableLike, CallableParams, Type},
+    type_helpers::{Instance, InstanceLookupOptions},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiSymbol {
+    /// The dotted path, e.g. `package.module.Class.method`
+    pub path: String,
+    pub signature: Box<str>,
+    definition: Option<ApiDefinition>,
+}
+
+/// The signature of a plain function or method as it is written down, which allows re-creating
+/// the function in another version of its module to compare it with the subtype relation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ApiDefinition {
+    module: String,
+    /// The class at the module level that defines the method
+    class: Option<String>,
+    /// Everything after the name of the function without the body, e.g. `(x: int, y=...) -> int`
+    signature: Box<str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiChange {
+    Removed {
+        path: String,
+        signature: Box<str>,
+    },
+    Changed {
+        path: String,
+        old: Box<str>,
+        new: Box<str>,
+        breaking: bool,
+    },
+    Added {
+        path: String,
+        signature: Box<str>,
+    },
+}
+
+impl ApiChange {
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            Self::Removed { .. } => true,
+            Self::Changed { breaking, .. } => *breaking,
+            Self::Added { .. } => false,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Removed { path, .. } | Self::Changed { path, .. } | Self::Added { path, .. } => {
+                path
+            }
+        }
+    }
+}
+
+impl fmt::Display for ApiChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Removed { path, signature } => write!(f, "Removed {path}: {signature}"),
+            Self::Changed { path, old, new, .. } => write!(f, "Changed {path}: {old} -> {new}"),
+            Self::Added { path, signature } => write!(f, "Added {path}: {signature}"),
+        }
+    }
+}
+
+/// Compares two API surfaces, the resulting changes are sorted by path. Every changed signature
+/// is considered breaking, [`classify_api_changes`] refines that for functions.
+pub fn diff_apis(old: &[ApiSymbol], new: &[ApiSymbol]) -> Vec<ApiChange> {
+    let to_map = |symbols: &[ApiSymbol]| -> BTreeMap<String, Box<str>> {
+        symbols
+            .iter()
+            .map(|s| (s.path.clone(), s.signature.clone()))
+            .collect()
+    };
+    let old = to_map(old);
+    let mut new = to_map(new);
+    let mut changes = vec![];
+    for (path, old_signature) in old {
+        match new.remove(&path) {
+            None => changes.push(ApiChange::Removed {
+                path,
+                signature: old_signature,
+            }),
+            Some(new_signature) if new_signature != old_signature => {
+                changes.push(ApiChange::Changed {
+                    path,
+                    old: old_signature,
+                    new: new_signature,
+                    breaking: true,
+                })
+            }
+            Some(_) => (),
+        }
+    }
+    changes.extend(
+        new.into_iter()
+            .map(|(path, signature)| ApiChange::Added { path, signature }),
+    );
+    changes.sort_by(|c1, c2| c1.path().cmp(c2.path()));
+    changes
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletenessProblem {
+    /// A parameter, return or attribute annotation is missing.
+    MissingAnnotation,
+    /// Part of the type comes from a module that could not be resolved.
+    UnresolvedImport,
+    /// The type contains an `Any` that was not written down explicitly.
+    ImplicitAny,
+}
+
+impl fmt::Display for CompletenessProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::MissingAnnotation => "missing annotation",
+            Self::UnresolvedImport => "type from an unresolved import",
+            Self::ImplicitAny => "implicit Any",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncompleteSymbol {
+    pub path: String,
+    pub signature: Box<str>,
+    pub problem: CompletenessProblem,
+}
+
+impl fmt::Display for IncompleteSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({})", self.path, self.signature, self.problem)
+    }
+}
+
+/// How completely the exported API is typed, similar to pyright's `--verifytypes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiCompleteness {
+    pub symbol_count: usize,
+    /// Sorted by path
+    pub incomplete: Vec<IncompleteSymbol>,
+}
+
+impl ApiCompleteness {
+    pub fn percentage(&self) -> f64 {
+        if self.symbol_count == 0 {
+            return 100.;
+        }
+        let complete = self.symbol_count - self.incomplete.len();
+        complete as f64 * 100. / self.symbol_count as f64
+    }
+}
+
+/// The result of checking if a callable can be used where another callable is expected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureCompatibility {
+    pub source: Box<str>,
+    pub target: Box<str>,
+    pub assignable: bool,
+    /// Notes and the decisions of the matcher, only available if the source is not assignable.
+    pub explanation: Vec<String>,
+}
+
+pub(crate) fn exported_api(db: &Database) -> anyhow::Result<Vec<ApiSymbol>> {
+    let mut symbols = vec![];
+    for_each_exported_symbol(db, |path, t| {
+        symbols.push(ApiSymbol {
+            signature: format_signature(db, t),
+            definition: api_definition(db, t),
+            path,
+        })
+    })?;
+    symbols.sort_by(|s1, s2| s1.path.cmp(&s2.path));
+    Ok(symbols)
+}
+
+/// A changed function is not breaking if the new version can be used wherever the old one was
+/// expected, e.g. because an optional param was added or a param was widened. The old signature
+/// is re-created at the end of the new version of its module, so the names in its annotations
+/// refer to the new versions of the same classes. Old methods are re-created in a subclass of the
+/// new class and both methods are bound to an instance of that subclass, so `self` and `Self`
+/// refer to the same class.
+pub(crate) fn classify_api_changes(
+    db: &mut Database,
+    old: &[ApiSymbol],
+    changes: &mut [ApiChange],
+) -> anyhow::Result<()> {
+    let mut by_module: BTreeMap<&str, Vec<(usize, String, &ApiDefinition)>> = BTreeMap::new();
+    for (i, change) in changes.iter().enumerate() {
+        let ApiChange::Changed { path, .. } = change else {
+            continue;
+        };
+        let Some(definition) = old
+            .iter()
+            .find(|symbol| symbol.path == *path)
+            .and_then(|symbol| symbol.definition.as_ref())
+        else {
+            continue;
+        };
+        by_module
+            .entry(definition.module.as_str())
+            .or_default()
+            .push((i, format!("__zuban_old_api_{i}"), definition));
+    }
+    let mut stored = vec![];
+    for (module, definitions) in &by_module {
+        let files = relevant_files(db)?;
+        let Some(file) = files.iter().find(|f| f.qualified_name(db) == *module) else {
+            continue;
+        };
+        let mut code = file.tree.code().to_owned();
+        code.push('\n');
+        for (i, name, definition) in definitions {
+            let signature = &definition.signature;
+            code += &match &definition.class {
+                Some(class) => format!(
+                    "class {name}({class}):\n    def {}{signature}: ...\n",
+                    method_name(&changes[*i])
+                ),
+                None => format!("def {name}{signature}: ...\n"),
+            };
+        }
+        let path = file.file_path_with_scheme(db).clone();
+        db.store_in_memory_file(path.clone(), code.into());
+        stored.push(path);
+    }
+
+    let files = relevant_files(db)?;
+    for (module, definitions) in &by_module {
+        let Some(file) = files.iter().find(|f| f.qualified_name(db) == *module) else {
+            continue;
+        };
+        let i_s = &InferenceState::new(db, file);
+        for (i, name, definition) in definitions {
+            let Some(old_t) = file
+                .lookup(db, |_| (), name)
+                .into_maybe_inferred()
+                .map(|inf| inf.as_type(i_s))
+            else {
+                continue;
+            };
+            let (old_t, new_t) = if definition.class.is_some() {
+                let Some(class) = old_t.maybe_type_of_class(db) else {
+                    continue;
+                };
+                let instance = Instance::new(class, None);
+                let method = method_name(&changes[*i]);
+                let lookup = |options: InstanceLookupOptions| {
+                    instance
+                        .lookup(i_s, method, options)
+                        .lookup
+                        .into_maybe_inferred()
+                        .map(|inf| inf.as_type(i_s))
+                };
+                let (Some(old_t), Some(new_t)) = (
+                    lookup(InstanceLookupOptions::new(&|_| ())),
+                    lookup(InstanceLookupOptions::new(&|_| ()).with_skip_first_of_mro(db, &class)),
+                ) else {
+                    continue;
+                };
+                (old_t, new_t)
+            } else {
+                let Ok((_, new_t)) = lookup_callable(db, &files, changes[*i].path()) else {
+                    continue;
+                };
+                (old_t, new_t)
+            };
+            if let ApiChange::Changed { breaking, .. } = &mut changes[*i] {
+                *breaking = !old_t.is_simple_super_type_of(i_s, &new_t).bool();
+            }
+        }
+    }
+    for path in stored {
+        db.close_in_memory_file(&path).map_err(|err| anyhow!(err))?;
+    }
+    Ok(())
+}
+
+fn method_name(change: &ApiChange) -> &str {
+    change.path().rsplit('.').next().unwrap()
+}
+
+pub(crate) fn api_completeness(db: &Database) -> anyhow::Result<ApiCompleteness> {
+    let mut symbol_count = 0;
+    let mut incomplete = vec![];
+    for_each_exported_symbol(db, |path, t| {
+        symbol_count += 1;
+        if let Some(problem) = completeness_problem(db, t) {
+            incomplete.push(IncompleteSymbol {
+                signature: format_signature(db, t),
+                path,
+                problem,
+            })
+        }
+    })?;
+    incomplete.sort_by(|s1, s2| s1.path.cmp(&s2.path));
+    Ok(ApiCompleteness {
+        symbol_count,
+        incomplete,
+    })
+}
+
+/// Checks if the callable at the dotted path `source` (e.g. `package.module.Class.method`) is
+/// assignable to the callable at `target`. Callback protocols are used as the expected type.
+pub(crate) fn check_signature(
+    db: &Database,
+    source: &str,
+    target: &str,
+) -> anyhow::Result<SignatureCompatibility> {
+    let files = relevant_files(db)?;
+    let (file, source_t) = lookup_callable(db, &files, source)?;
+    let (_, target_t) = lookup_callable(db, &files, target)?;
+    let i_s = &InferenceState::new(db, file);
+    let (matches, mut explanation) =
+        record_match_explanation(|| target_t.is_simple_super_type_of(i_s, &source_t));
+    if let Match::False { reason, .. } = &matches {
+        let notes = RefCell::new(vec![]);
+        let error_types = ErrorTypes {
+            expected: &target_t,
+            got: GotType::Type(&source_t),
+            matcher: None,
+            reason,
+            // The explanation is returned on its own.
+            explanation: &[],
+        };
+        error_types.add_mismatch_notes(db, |issue| {
+            if let IssueKind::Note(note) = issue {
+                notes.borrow_mut().push(String::from(note))
+            }
+        });
+        explanation.splice(0..0, notes.into_inner());
+    }
+    Ok(SignatureCompatibility {
+        source: format_signature(db, &source_t),
+        target: format_signature(db, &target_t),
+        assignable: matches.bool(),
+        explanation,
+    })
+}
+
+fn lookup_callable<'db>(
+    db: &'db Database,
+    files: &[&'db PythonFile],
+    path: &str,
+) -> anyhow::Result<(&'db PythonFile, Type)> {
+    // The longest module name wins, because packages are also modules.
+    let (file, rest) = files
+        .iter()
+        .filter_map(|file| {
+            let module = file.qualified_name(db);
+            let rest = path.strip_prefix(&module)?.strip_prefix('.')?;
+            Some((*file, rest, module.len()))
+        })
+        .max_by_key(|(_, _, len)| *len)
+        .map(|(file, rest, _)| (file, rest))
+        .ok_or_else(|| anyhow!("Cannot find a checked module for {path}"))?;
+    let i_s = &InferenceState::new(db, file);
+    let mut names = rest.split('.');
+    let mut t = file
+        .lookup(db, |_| (), names.next().unwrap())
+        .into_maybe_inferred()
+        .ok_or_else(|| anyhow!("Cannot find {path}"))?
+        .as_type(i_s);
+    for name in names {
+        t = t
+            .lookup(
+                i_s,
+                file,
+                name,
+                LookupKind::Normal,
+                &mut ResultContext::Unknown,
+                &|_| (),
+                &|_| (),
+            )
+            .into_maybe_inferred()
+            .ok_or_else(|| anyhow!("Cannot find {path}"))?
+            .as_type(i_s);
+    }
+    if let Type::Type(inner) = &t
+        && inner.maybe_class(db).is_some_and(|c| c.is_protocol(db))
+    {
+        return Ok((file, inner.as_ref().clone()));
+    }
+    match t.maybe_callable(i_s) {
+        Some(CallableLike::Callable(c)) => Ok((file, Type::Callable(c))),
+        Some(CallableLike::Overload(o)) => Ok((file, Type::FunctionOverload(o))),
+        None => bail!("{path} is not callable, it has type {}", t.format_short(db)),
+    }
+}
+
+fn for_each_exported_symbol(
+    db: &Database,
+    mut callback: impl FnMut(String, &Type),
+) -> anyhow::Result<()> {
+    for file in relevant_files(db)? {
+        let module = file.qualified_name(db);
+        if module.split('.').any(|part| part.starts_with('_')) {
+            continue;
+        }
+        add_module_symbols(db, file, &module, &mut callback);
+    }
+    Ok(())
+}
+
+fn add_module_symbols(
+    db: &Database,
+    file: &PythonFile,
+    module: &str,
+    callback: &mut impl FnMut(String, &Type),
+) {
+    let i_s = &InferenceState::new(db, file);
+    for (name, _) in file.symbol_table.iter() {
+        if !file.is_name_exported_for_star_import(db, name) {
+            continue;
+        }
+        let t = file.lookup(db, |_| (), name).into_inferred().as_type(i_s);
+        let path = format!("{module}.{name}");
+        if let Type::Type(inner) = &t
+            && let Some(class) = inner.maybe_class(db)
+            && class.node_ref.file_index() == file.file_index
+        {
+            for (member, _) in class.class_storage.class_symbol_table.iter() {
+                if member.starts_with('_') && !is_public_dunder(member) {
+                    continue;
+                }
+                let Some(inf) = t
+                    .lookup(
+                        i_s,
+                        file,
+                        member,
+                        LookupKind::Normal,
+                        &mut ResultContext::Unknown,
+                        &|_| (),
+                        &|_| (),
+                    )
+                    .into_maybe_inferred()
+                else {
+                    continue;
+                };
+                callback(format!("{path}.{member}"), &inf.as_type(i_s))
+            }
+        }
+        callback(path, &t)
+    }
+}
+
+fn api_definition(db: &Database, t: &Type) -> Option<ApiDefinition> {
+    let Type::Callable(c) = t else {
+        return None;
+    };
+    let node_ref = NodeRef::from_link(db, c.defined_at);
+    let func = node_ref.maybe_function()?;
+    // Decorators might change the signature and are therefore not re-created.
+    if !matches!(func.parent(), FunctionParent::Normal) {
+        return None;
+    }
+    let class = match func.parent_scope() {
+        Scope::Module => None,
+        Scope::Class(class) if matches!(class.parent_scope(), Scope::Module) => {
+            Some(class.name().as_code().to_owned())
+        }
+        _ => return None,
+    };
+    Some(ApiDefinition {
+        module: node_ref.file.qualified_name(db),
+        class,
+        signature: signature_code(func).into(),
+    })
+}
+
+fn signature_code(func: FunctionDef) -> String {
+    let mut params = vec![];
+    let mut had_star = false;
+    let mut previous_kind = None;
+    for param in func.params().iter() {
+        let kind = param.kind();
+        if previous_kind == Some(ParamKind::PositionalOnly) && kind != ParamKind::PositionalOnly {
+            params.push("/".to_string());
+        }
+        let prefix = match kind {
+            ParamKind::Star => {
+                had_star = true;
+                "*"
+            }
+            ParamKind::KeywordOnly if !had_star => {
+                had_star = true;
+                params.push("*".to_string());
+                ""
+            }
+            ParamKind::StarStar => "**",
+            _ => "",
+        };
+        let mut code = format!("{prefix}{}", param.name_def().as_code());
+        if let Some(annotation) = param.annotation() {
+            code += ": ";
+            code += match annotation.maybe_starred() {
+                Ok(star_expr) => star_expr.as_code(),
+                Err(expr) => expr.as_code(),
+            };
+        }
+        if param.default().is_some() {
+            // Defaults might refer to names that only exist in the old version.
+            code += " = ...";
+        }
+        params.push(code);
+        previous_kind = Some(kind);
+    }
+    if previous_kind == Some(ParamKind::PositionalOnly) {
+        params.push("/".to_string());
+    }
+    let type_params = func.type_params().map(|t| t.as_code()).unwrap_or_default();
+    let return_annotation = func
+        .return_annotation()
+        .map(|r| format!(" -> {}", r.expression().as_code()))
+        .unwrap_or_default();
+    format!("{type_params}({}){return_annotation}", params.join(", "))
+}
+
+fn completeness_problem(db: &Database, t: &Type) -> Option<CompletenessProblem> {
+    // The most actionable problem wins, a missing annotation is usually also the cause of the
+    // other Anys in a signature.
+    let mut problem = None;
+    let mut add_cause = |cause: &AnyCause| {
+        let new = match cause {
+            AnyCause::Explicit => return,
+            AnyCause::Unannotated => CompletenessProblem::MissingAnnotation,
+            AnyCause::ModuleNotFound => CompletenessProblem::UnresolvedImport,
+            _ => CompletenessProblem::ImplicitAny,
+        };
+        if problem.is_none() || new == CompletenessProblem::MissingAnnotation {
+            problem = Some(new)
+        }
+    };
+    t.find_in_type(db, &mut |t| {
+        match t {
+            Type::Any(cause) => add_cause(cause),
+            Type::Callable(c) => {
+                if let CallableParams::Any(cause) = &c.params {
+                    add_cause(cause)
+                }
+            }
+            _ => (),
+        }
+        false
+    });
+    problem
+}
+
+fn is_public_dunder(name: &str) -> bool {
+    name.starts_with("__") && name.ends_with("__") && name != "__slots__"
+}
+
+fn format_signature(db: &Database, t: &Type) -> Box<str> {
+    match t {
+        Type::Callable(c) => c.format_pretty(&FormatData::new_short(db)),
+        _ => t.format_short(db),
+    }
+}
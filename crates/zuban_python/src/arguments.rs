@@ -1,8 +1,8 @@
 use std::{mem, sync::Arc};
 
 use parsa_python_cst::{
-    ArgsIterator, Argument as CSTArgument, ArgumentsDetails, Comprehension, Expression,
-    NamedExpression, NodeIndex, Primary, PrimaryContent,
+    ArgsIterator, Argument as CSTArgument, ArgumentsDetails, AtomContent, Comprehension,
+    Expression, NamedExpression, NodeIndex, Primary, PrimaryContent,
 };
 
 use crate::{
@@ -46,6 +46,11 @@ pub(crate) trait Args<'db>: std::fmt::Debug {
         // This is a bit special, but we use this to reset the type cache of the expressions to
         // avoid overload context inference issues.
     }
+    fn reset_points_from_backup_except_names(&self, backup: &Option<PointsBackup>) {
+        // Arguments that are just names are inferred the same way regardless of the context,
+        // so there is no need to infer them again for every overload item.
+        self.reset_points_from_backup(backup)
+    }
 
     fn has_a_union_argument(&self, i_s: &InferenceState<'db, '_>) -> bool {
         for arg in self.iter(i_s.mode) {
@@ -200,6 +205,45 @@ impl<'db: 'a, 'a> Args<'db> for SimpleArgs<'db, 'a> {
         self.file.points.reset_from_backup(backup.as_ref().unwrap());
     }
 
+    fn reset_points_from_backup_except_names(&self, backup: &Option<PointsBackup>) {
+        let ArgumentsDetails::Node(arguments) = self.details else {
+            return self.reset_points_from_backup(backup);
+        };
+        let backup = backup.as_ref().unwrap();
+        let inference = self.file.inference(&self.i_s);
+        let names: Vec<_> = arguments
+            .iter()
+            .filter_map(|arg| {
+                let expr = match arg {
+                    CSTArgument::Positional(named_expr) => named_expr.expression(),
+                    CSTArgument::Keyword(kwarg) => kwarg.unpack().1,
+                    CSTArgument::Star(_) | CSTArgument::StarStar(_) => return None,
+                };
+                let AtomContent::Name(name) = expr.maybe_unpacked_atom()? else {
+                    return None;
+                };
+                // A name that points to a partial (e.g. `x = []` before `x.append(1)`) or to a
+                // definition within the arguments (e.g. a walrus) is reset together with its
+                // definition.
+                let definition = inference
+                    .check_point_cache(name.index())?
+                    .maybe_saved_link();
+                if definition.is_some_and(|link| {
+                    NodeRef::from_link(self.i_s.db, link)
+                        .point()
+                        .maybe_specific()
+                        .is_some_and(|specific| specific.is_partial())
+                        || link.file == self.file.file_index
+                            && backup.range.contains(&link.node_index)
+                }) {
+                    return None;
+                }
+                Some(name.index())
+            })
+            .collect();
+        self.file.points.reset_from_backup_except(backup, &names);
+    }
+
     fn maybe_simple_args(&self) -> Option<&SimpleArgs<'_, '_>> {
         Some(self)
     }
@@ -336,6 +380,11 @@ impl<'db> Args<'db> for CombinedArgs<'db, '_> {
         self.args1.reset_points_from_backup(backup);
         self.args2.reset_points_from_backup(backup);
     }
+
+    fn reset_points_from_backup_except_names(&self, backup: &Option<PointsBackup>) {
+        self.args1.reset_points_from_backup_except_names(backup);
+        self.args2.reset_points_from_backup_except_names(backup);
+    }
 }
 
 impl<'db, 'a> CombinedArgs<'db, 'a> {
@@ -465,6 +514,10 @@ pub(crate) enum InferredArg<'a> {
 pub(crate) struct Arg<'db, 'a> {
     pub kind: ArgKind<'db, 'a>,
     pub index: usize,
+    // The index of the item in a fixed length tuple that was unpacked with `*`.
+    pub unpacked_index: Option<usize>,
+    // A key of a TypedDict unpacked with `**` that is not required there.
+    pub is_not_required_key: bool,
 }
 
 impl<'db> Arg<'db, '_> {
@@ -522,7 +575,7 @@ impl<'db> Arg<'db, '_> {
         })
     }
 
-    fn as_node_ref(&self) -> Result<NodeRef<'_>, CustomAddIssue<'_>> {
+    pub(crate) fn as_node_ref(&self) -> Result<NodeRef<'_>, CustomAddIssue<'_>> {
         match &self.kind {
             ArgKind::Positional(PositionalArg { node_ref, .. })
             | ArgKind::Keyword(KeywordArg { node_ref, .. })
@@ -539,6 +592,11 @@ impl<'db> Arg<'db, '_> {
         }
     }
 
+    pub(crate) fn should_add_issue(&self, i_s: &InferenceState) -> bool {
+        // Custom issue callbacks decide on their own if an issue is added.
+        self.as_node_ref().is_err() || i_s.should_add_issue()
+    }
+
     pub(crate) fn add_argument_issue(
         &self,
         i_s: &InferenceState,
@@ -855,6 +913,7 @@ impl<'db: 'a, 'a> Iterator for ArgIteratorBase<'db, 'a> {
                                 }
                                 _ => Some(BaseArgReturn::ArgsKwargs(ArgsKwargsIterator::Args {
                                     iterator: inf.iter(i_s, node_ref, IterCause::VariadicUnpack),
+                                    item_index: 0,
                                     node_ref,
                                     position: i + 1,
                                 })),
@@ -1043,6 +1102,8 @@ impl<'db, 'a> Iterator for ArgIterator<'db, 'a> {
                     Some(Arg {
                         kind,
                         index: self.counter,
+                        unpacked_index: None,
+                        is_not_required_key: false,
                     })
                 }
                 Some(BaseArgReturn::ArgsKwargs(args_kwargs)) => {
@@ -1063,6 +1124,7 @@ impl<'db, 'a> Iterator for ArgIterator<'db, 'a> {
             },
             ArgsKwargsIterator::Args {
                 mut iterator,
+                item_index,
                 node_ref,
                 position,
             } => match iterator.next_as_argument(self.current.expect_i_s()) {
@@ -1075,6 +1137,7 @@ impl<'db, 'a> Iterator for ArgIterator<'db, 'a> {
                     if !arbitrary_len {
                         self.args_kwargs_iterator = ArgsKwargsIterator::Args {
                             iterator,
+                            item_index: item_index + 1,
                             node_ref,
                             position,
                         };
@@ -1088,6 +1151,8 @@ impl<'db, 'a> Iterator for ArgIterator<'db, 'a> {
                             is_keyword: None,
                         },
                         index,
+                        unpacked_index: (!arbitrary_len).then_some(item_index),
+                        is_not_required_key: false,
                     })
                 }
                 Some(UnpackedArgument::WithUnpack(with_unpack)) => {
@@ -1117,6 +1182,8 @@ impl<'db, 'a> Iterator for ArgIterator<'db, 'a> {
                         is_keyword: Some(None),
                     },
                     index,
+                    unpacked_index: None,
+                    is_not_required_key: false,
                 })
             }
             ArgsKwargsIterator::TypedDict {
@@ -1129,10 +1196,10 @@ impl<'db, 'a> Iterator for ArgIterator<'db, 'a> {
                 let index = self.counter;
                 self.counter += 1;
                 let ms = typed_dict.members(db);
-                let Some((name, t)) = ms
+                let Some((name, t, required)) = ms
                     .named
                     .get(iterator_index)
-                    .map(|member| (member.name, member.type_.clone()))
+                    .map(|member| (member.name, member.type_.clone(), member.required))
                 else {
                     if let Some(e) = &ms.extra_items {
                         return Some(Arg {
@@ -1144,6 +1211,8 @@ impl<'db, 'a> Iterator for ArgIterator<'db, 'a> {
                                 is_keyword: Some(None),
                             },
                             index,
+                            unpacked_index: None,
+                            is_not_required_key: false,
                         });
                     }
                     return self.next();
@@ -1164,6 +1233,8 @@ impl<'db, 'a> Iterator for ArgIterator<'db, 'a> {
                         is_keyword: Some(Some(name)),
                     },
                     index,
+                    unpacked_index: None,
+                    is_not_required_key: !required,
                 })
             }
             ArgsKwargsIterator::WithUnpack {
@@ -1193,6 +1264,8 @@ impl<'db, 'a> Iterator for ArgIterator<'db, 'a> {
                         },
                         // counter was increased before
                         index,
+                        unpacked_index: Some(before_iterator_index - 1),
+                        is_not_required_key: false,
                     })
                 } else {
                     if !with_unpack.before.is_empty() {
@@ -1205,6 +1278,8 @@ impl<'db, 'a> Iterator for ArgIterator<'db, 'a> {
                             node_ref,
                         },
                         index,
+                        unpacked_index: None,
+                        is_not_required_key: false,
                     })
                 }
             }
@@ -1216,6 +1291,7 @@ impl<'db, 'a> Iterator for ArgIterator<'db, 'a> {
 enum ArgsKwargsIterator<'a> {
     Args {
         iterator: IteratorContent,
+        item_index: usize,
         position: usize,
         node_ref: NodeRef<'a>,
     },
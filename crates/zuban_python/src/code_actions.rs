@@ -0,0 +1,622 @@
+use std::ops::Range;
+
+use config::PythonVersion;
+pub use lsp_types::CodeActionKind;
+use parsa_python_cst::{
+    AnnotationUsage, AssignmentContent, AssignmentRightSide, CodeIndex, DefiningStmt, NodeIndex,
+    ParamKind, Scope, Target,
+};
+use utils::{FastHashMap, FastHashSet};
+
+use crate::{
+    InputPosition,
+    completion::{Completion, CompletionResolver},
+    database::{Database, Specific},
+    diagnostics::{Diagnostic, Issue, IssueKind},
+    file::{File as _, PythonFile},
+};
+
+pub(crate) struct CodeEdit {
+    pub range: Range<CodeIndex>,
+    pub new_text: String,
+}
+
+/// Extracts a union or a subscription in an annotation into a type alias at module level. All
+/// annotations after the alias that use the same type are rewritten to use the alias.
+pub(crate) fn extract_type_alias(
+    db: &Database,
+    file: &PythonFile,
+    range: Range<CodeIndex>,
+) -> Option<Vec<CodeEdit>> {
+    let python_version = db.project.settings.python_version_or_default();
+    if python_version < PythonVersion::new(3, 10) {
+        // TypeAlias and unions with `|` are not available at runtime.
+        return None;
+    }
+    let uses_type_stmt = python_version >= PythonVersion::new(3, 12);
+    let part = file.tree.annotation_part_in_range(range)?;
+    // Annotations that are not at module level are only inferred when the file is checked.
+    file.ensure_calculated_diagnostics(db).ok()?;
+    if !is_annotation_without_type_vars(file, part.annotation_index) {
+        return None;
+    }
+    if part.has_string_in_union && !uses_type_stmt {
+        return None;
+    }
+    for name in &part.names {
+        let name = name.as_code();
+        if let Some(definition) = file.lookup_symbol(name) {
+            // The value of a type statement is evaluated lazily, assignments need the names
+            // defined before.
+            if !uses_type_stmt && definition.node_start_position() >= part.module_stmt_start {
+                return None;
+            }
+        } else if db.python_state.builtins().lookup_symbol(name).is_none() {
+            return None;
+        }
+    }
+
+    let alias_name = (1..)
+        .map(|i| match i {
+            1 => "Alias".to_string(),
+            _ => format!("Alias{i}"),
+        })
+        .find(|alias_name| {
+            file.tree
+                .filter_all_names()
+                .all(|name| name.as_code() != alias_name)
+        })
+        .unwrap();
+    let mut insertion = if uses_type_stmt {
+        format!("type {alias_name} = {}\n", part.code)
+    } else {
+        format!("{alias_name}: TypeAlias = {}\n", part.code)
+    };
+    if part.module_stmt_is_definition {
+        insertion += "\n\n";
+    }
+    let mut edits = vec![];
+    if !uses_type_stmt && file.lookup_symbol("TypeAlias").is_none() {
+        let import = "from typing import TypeAlias\n";
+        match file
+            .tree
+            .position_after_last_module_import(part.module_stmt_start)
+        {
+            Some(position) if position != part.module_stmt_start => edits.push(CodeEdit {
+                range: position..position,
+                new_text: import.to_string(),
+            }),
+            _ => insertion = format!("{import}\n{insertion}"),
+        }
+    }
+    edits.push(CodeEdit {
+        range: part.module_stmt_start..part.module_stmt_start,
+        new_text: insertion,
+    });
+    for (annotation_index, range) in file.tree.annotation_parts_with_code(part.code) {
+        // Annotations before the alias are evaluated before it is defined.
+        if range.start >= part.module_stmt_start
+            && (range == part.range || is_annotation_without_type_vars(file, annotation_index))
+        {
+            edits.push(CodeEdit {
+                range,
+                new_text: alias_name.clone(),
+            })
+        }
+    }
+    Some(edits)
+}
+
+#[derive(Default)]
+struct Usages {
+    in_annotations: bool,
+    in_evaluated_annotations: bool,
+    at_runtime: bool,
+}
+
+/// Moves the imports in the module scope that are only used in annotations into an
+/// `if TYPE_CHECKING:` block. `from __future__ import annotations` is added if such an import is
+/// used in annotations that are evaluated at runtime. Imports used in annotations that are
+/// inspected at runtime (e.g. by dataclasses or `get_type_hints`) are kept.
+pub(crate) fn move_imports_to_type_checking_block(
+    db: &Database,
+    file: &PythonFile,
+) -> Option<Vec<CodeEdit>> {
+    if file.is_stub() {
+        return None;
+    }
+    let imports: Vec<_> = file.tree.module_imports().collect();
+    let import_name_defs: FastHashSet<NodeIndex> = imports
+        .iter()
+        .flat_map(|import| import.names.iter().map(|(name_def, _)| name_def.index()))
+        .collect();
+    // Annotations might be resolved by `get_type_hints` anywhere in the module.
+    let inspects_annotations = file
+        .tree
+        .filter_all_names()
+        .any(|name| name.as_code() == "get_type_hints");
+    let mut usages: FastHashMap<&str, Usages> = FastHashMap::default();
+    for name in file.tree.filter_all_names() {
+        if name.is_variable_reference() {
+            let usage = usages.entry(name.as_code()).or_default();
+            match name.annotation_usage() {
+                // The import is needed at runtime, even if the annotation is a string.
+                Some(AnnotationUsage::Inspected) => usage.at_runtime = true,
+                Some(AnnotationUsage::Evaluated) if inspects_annotations => usage.at_runtime = true,
+                Some(AnnotationUsage::Evaluated) => {
+                    usage.in_annotations = true;
+                    usage.in_evaluated_annotations = true;
+                }
+                Some(AnnotationUsage::NotEvaluated) => usage.in_annotations = true,
+                None => usage.at_runtime = true,
+            }
+        } else if let Some(name_def) = name.name_def()
+            && !import_name_defs.contains(&name_def.index())
+        {
+            // The name is redefined, so the import might be needed.
+            usages.entry(name.as_code()).or_default().at_runtime = true;
+        }
+    }
+    for name in file.maybe_dunder_all(db).unwrap_or_default() {
+        usages.entry(name.as_str(db)).or_default().at_runtime = true;
+    }
+
+    let has_future_annotations = imports.iter().any(|import| {
+        import.is_future_import && import.names.iter().any(|(_, code)| *code == "annotations")
+    });
+    let mut needs_future_annotations = false;
+    let mut moved_imports = vec![];
+    let mut edits = vec![];
+    for import in &imports {
+        if import.is_future_import {
+            continue;
+        }
+        let (moved, kept): (Vec<_>, Vec<_>) = import.names.iter().partition(|(name_def, code)| {
+            let is_explicit_reexport = code
+                .split_once(" as ")
+                .is_some_and(|(name, alias)| name.trim() == alias.trim());
+            usages.get(name_def.as_code()).is_some_and(|usage| {
+                if usage.in_annotations && !usage.at_runtime && !is_explicit_reexport {
+                    needs_future_annotations |= usage.in_evaluated_annotations;
+                    true
+                } else {
+                    false
+                }
+            })
+        });
+        if moved.is_empty() {
+            continue;
+        }
+        let join = |names: Vec<&(_, &str)>| {
+            let codes: Vec<_> = names.into_iter().map(|(_, code)| *code).collect();
+            format!("{} {}\n", import.keyword_part, codes.join(", "))
+        };
+        moved_imports.push(join(moved));
+        edits.push(CodeEdit {
+            range: import.range.clone(),
+            new_text: if kept.is_empty() {
+                String::new()
+            } else {
+                join(kept)
+            },
+        });
+    }
+    if moved_imports.is_empty() {
+        return None;
+    }
+
+    if needs_future_annotations
+        && !has_future_annotations
+        && db.project.settings.python_version_or_default() < PythonVersion::new(3, 14)
+    {
+        let position = file.tree.future_import_position();
+        edits.push(CodeEdit {
+            range: position..position,
+            new_text: "from __future__ import annotations\n".to_string(),
+        });
+    }
+    if let Some((position, indentation)) = file.tree.module_type_checking_block_end() {
+        edits.push(CodeEdit {
+            range: position..position,
+            new_text: moved_imports
+                .iter()
+                .map(|import| format!("{indentation}{import}"))
+                .collect(),
+        });
+    } else {
+        let mut new_text = String::new();
+        if file.lookup_symbol("TYPE_CHECKING").is_none() {
+            new_text += "from typing import TYPE_CHECKING\n";
+        }
+        new_text += "\nif TYPE_CHECKING:\n";
+        for import in &moved_imports {
+            new_text += "    ";
+            new_text += import;
+        }
+        let position = imports.last().unwrap().range.end;
+        edits.push(CodeEdit {
+            range: position..position,
+            new_text,
+        });
+    }
+    // Insertions are placed before replacements at the same position.
+    edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+    Some(edits)
+}
+
+/// Replaces the names and imports in the range that cannot be resolved with similarly spelled
+/// names. Every suggestion is a separate fix.
+pub(crate) fn fix_spelling(
+    db: &Database,
+    file: &PythonFile,
+    range: Range<CodeIndex>,
+) -> Vec<(String, CodeEdit)> {
+    let mut fixes = vec![];
+    for diagnostic in diagnostics_in_range(db, file, range) {
+        let Some((name_range, suggestions)) = diagnostic.spelling_suggestions() else {
+            continue;
+        };
+        for suggestion in suggestions {
+            fixes.push((
+                format!("Change to \"{suggestion}\""),
+                CodeEdit {
+                    range: name_range.clone(),
+                    new_text: suggestion,
+                },
+            ))
+        }
+    }
+    fixes
+}
+
+/// Fixes for the issues in the range: Making a param with a `None` default optional, adding a
+/// missing keyword argument to a call, importing a name from `typing`, removing unused imports
+/// and variables, prefixing unused params with an underscore and ignoring the error with a
+/// `# type: ignore[code]` comment.
+pub(crate) fn quick_fixes(
+    db: &Database,
+    file: &PythonFile,
+    range: Range<CodeIndex>,
+) -> Vec<(String, Vec<CodeEdit>)> {
+    let code = file.tree.code();
+    let mut fixes = vec![];
+    let mut ignored_lines = FastHashSet::default();
+    for diagnostic in diagnostics_in_range(db, file, range) {
+        let issue = diagnostic.issue;
+        match &issue.kind {
+            IssueKind::IncompatibleDefaultArgument {
+                argument_name, got, ..
+            } if &**got == "None" => {
+                if let Some(annotation) =
+                    file.tree.annotation_of_param_default(issue.start_position)
+                {
+                    fixes.push((
+                        format!("Make \"{argument_name}\" optional"),
+                        make_optional(db, file, annotation.start()..annotation.end()),
+                    ));
+                }
+            }
+            IssueKind::MissingNamedArgument { name, .. } => {
+                if let Some(position) = call_arguments_end(file, issue) {
+                    let arguments = code[..position as usize].trim_end();
+                    let separator = match arguments.chars().last() {
+                        Some('(') => "",
+                        Some(',') => " ",
+                        _ => ", ",
+                    };
+                    fixes.push((
+                        format!("Add missing argument \"{name}\""),
+                        vec![CodeEdit {
+                            range: position..position,
+                            new_text: format!("{separator}{name}=..."),
+                        }],
+                    ));
+                }
+            }
+            IssueKind::UnusedImport { name } => {
+                if let Some(edit) = remove_unused_import(file, issue.start_position) {
+                    fixes.push((format!("Remove unused import \"{name}\""), vec![edit]));
+                }
+            }
+            IssueKind::UnusedVariable { name } => {
+                if let Some(edit) = remove_unused_assignment_target(file, issue.start_position) {
+                    fixes.push((format!("Remove assignment to \"{name}\""), vec![edit]));
+                }
+            }
+            IssueKind::UnusedParameter { name }
+                if is_positional_only_param(file, issue.start_position) =>
+            {
+                fixes.push((
+                    format!("Rename \"{name}\" to \"_{name}\""),
+                    vec![CodeEdit {
+                        range: issue.start_position..issue.start_position,
+                        new_text: "_".to_string(),
+                    }],
+                ))
+            }
+            IssueKind::NameError { name }
+                if !name.starts_with('_')
+                    && db.python_state.typing().lookup_symbol(name).is_some() =>
+            {
+                fixes.push((
+                    format!("Import \"{name}\" from \"typing\""),
+                    vec![add_import(
+                        file,
+                        issue.start_position,
+                        &format!("from typing import {name}\n"),
+                    )],
+                ));
+            }
+            _ => (),
+        }
+        let Some(error_code) = issue.kind.mypy_error_code() else {
+            continue;
+        };
+        // The ignore needs to be at the end of the line where the issue ends.
+        let (line_end, comment) = file.tree.line_comment(issue.end_position);
+        if !ignored_lines.insert((line_end, error_code)) {
+            continue;
+        }
+        let Some(edit) = type_ignore_edit(code, line_end, comment, error_code) else {
+            continue;
+        };
+        fixes.push((
+            format!("Ignore \"{error_code}\" errors on this line"),
+            vec![edit],
+        ));
+    }
+    fixes
+}
+
+/// Adds the error code to an existing `# type: ignore[...]` comment or adds a new one. Ignores
+/// without codes already ignore everything.
+fn type_ignore_edit(
+    code: &str,
+    line_end: CodeIndex,
+    comment: Option<Range<CodeIndex>>,
+    error_code: &str,
+) -> Option<CodeEdit> {
+    let insert = |position: CodeIndex, new_text| {
+        Some(CodeEdit {
+            range: position..position,
+            new_text,
+        })
+    };
+    let Some(comment) = comment else {
+        return insert(line_end, format!("  # type: ignore[{error_code}]"));
+    };
+    let comment_code = &code[comment.start as usize..comment.end as usize];
+    // Ignores are only found in a chain of type comments, e.g. `# type: int  # type: ignore`.
+    for (hash, _) in comment_code.match_indices('#') {
+        let rest = comment_code[hash + 1..].trim_start_matches(' ');
+        let Some(type_comment) = rest.strip_prefix("type:") else {
+            // Ignores are placed before other comments like `# noqa`.
+            return insert(
+                comment.start + hash as CodeIndex,
+                format!("# type: ignore[{error_code}]  "),
+            );
+        };
+        let Some(after_ignore) = type_comment.trim_start_matches(' ').strip_prefix("ignore") else {
+            continue;
+        };
+        if !after_ignore.trim_start_matches(' ').starts_with('[') {
+            return None;
+        }
+        let codes_end = after_ignore.find(']')?;
+        let position = (comment.end as usize - after_ignore.len() + codes_end) as CodeIndex;
+        return insert(position, format!(", {error_code}"));
+    }
+    insert(line_end, format!("  # type: ignore[{error_code}]"))
+}
+
+fn diagnostics_in_range<'db>(
+    db: &'db Database,
+    file: &'db PythonFile,
+    range: Range<CodeIndex>,
+) -> impl Iterator<Item = Diagnostic<'db>> {
+    file.diagnostics(db).into_iter().filter(move |diagnostic| {
+        let issue = diagnostic.issue;
+        issue.end_position >= range.start && issue.start_position <= range.end
+    })
+}
+
+/// Wraps an annotation in `Optional[...]` or adds `| None` if the Python version allows it.
+fn make_optional(db: &Database, file: &PythonFile, range: Range<CodeIndex>) -> Vec<CodeEdit> {
+    let annotation = &file.tree.code()[range.start as usize..range.end as usize];
+    if db.project.settings.python_version_or_default() >= PythonVersion::new(3, 10) {
+        return vec![CodeEdit {
+            range,
+            new_text: format!("{annotation} | None"),
+        }];
+    }
+    let mut edits = vec![];
+    if file.lookup_symbol("Optional").is_none() {
+        edits.push(add_import(
+            file,
+            range.start,
+            "from typing import Optional\n",
+        ));
+    }
+    edits.push(CodeEdit {
+        range,
+        new_text: format!("Optional[{annotation}]"),
+    });
+    edits
+}
+
+/// Adds the import after the last import at module level before the position.
+fn add_import(file: &PythonFile, before: CodeIndex, import: &str) -> CodeEdit {
+    let position = file
+        .tree
+        .position_after_last_module_import(before)
+        .unwrap_or_else(|| file.tree.future_import_position());
+    CodeEdit {
+        range: position..position,
+        new_text: import.to_string(),
+    }
+}
+
+/// Removes the import of the name that starts at the position. Other names imported by the same
+/// statement are kept.
+fn remove_unused_import(file: &PythonFile, name_start: CodeIndex) -> Option<CodeEdit> {
+    let import = file.tree.module_imports().find(|import| {
+        import
+            .names
+            .iter()
+            .any(|(name_def, _)| name_def.start() == name_start)
+    })?;
+    let kept: Vec<_> = import
+        .names
+        .iter()
+        .filter(|(name_def, _)| name_def.start() != name_start)
+        .map(|(_, code)| *code)
+        .collect();
+    Some(CodeEdit {
+        range: import.range,
+        new_text: if kept.is_empty() {
+            String::new()
+        } else {
+            format!("{} {}\n", import.keyword_part, kept.join(", "))
+        },
+    })
+}
+
+/// Removes the target of an assignment like `x = foo()` to a name that starts at the position.
+/// The right side is kept, because it might have side effects.
+/// Other parameters cannot be renamed, because callers might pass them as keyword arguments and
+/// overrides or protocols are matched by their names.
+fn is_positional_only_param(file: &PythonFile, name_start: CodeIndex) -> bool {
+    let Some(name) = file
+        .tree
+        .filter_all_names()
+        .find(|name| name.start() == name_start)
+    else {
+        return false;
+    };
+    let Scope::Function(func) = name.parent_scope() else {
+        return false;
+    };
+    func.params().iter().any(|param| {
+        param.name_def().name_index() == name.index() && param.kind() == ParamKind::PositionalOnly
+    })
+}
+
+fn remove_unused_assignment_target(file: &PythonFile, name_start: CodeIndex) -> Option<CodeEdit> {
+    let name = file
+        .tree
+        .filter_all_names()
+        .find(|name| name.start() == name_start)?;
+    let DefiningStmt::Assignment(assignment) = name.name_def()?.expect_defining_stmt() else {
+        return None;
+    };
+    let right_side = match assignment.unpack() {
+        AssignmentContent::Normal(mut targets, right_side) => {
+            let target = targets.next()?;
+            if targets.next().is_some() || !matches!(target, Target::Name(_)) {
+                return None;
+            }
+            right_side
+        }
+        AssignmentContent::WithAnnotation(Target::Name(_), _, Some(right_side)) => right_side,
+        _ => return None,
+    };
+    let right_side_start = match right_side {
+        AssignmentRightSide::StarExpressions(star_exprs) => star_exprs.start(),
+        AssignmentRightSide::YieldExpr(yield_expr) => yield_expr.start(),
+    };
+    Some(CodeEdit {
+        range: assignment.start()..right_side_start,
+        new_text: String::new(),
+    })
+}
+
+/// The position of the closing parenthesis of the call that the issue is reported on.
+fn call_arguments_end(file: &PythonFile, issue: &Issue) -> Option<CodeIndex> {
+    let position = issue.end_position.checked_sub(1)?;
+    let (_, call, _) = file.tree.call_at_position(position)?;
+    (call.start() == issue.start_position).then_some(position)
+}
+
+/// Finds the names that are similar to a name or an import that cannot be resolved. The
+/// candidates are the names that are completed at its position: The names in scope, the exports
+/// of a module or the available modules. Returns the range of the unresolved name as well.
+pub(crate) fn spelling_suggestions(
+    db: &Database,
+    file: &PythonFile,
+    issue: &Issue,
+) -> Option<(Range<CodeIndex>, Vec<String>)> {
+    let unresolved = match &issue.kind {
+        IssueKind::NameError { name } | IssueKind::ImportAttributeError { name, .. } => &**name,
+        IssueKind::ModuleNotFound { module_name } => module_name.rsplit('.').next().unwrap(),
+        _ => return None,
+    };
+    // Issues of imports are sometimes on the whole import statement.
+    let name = file.tree.filter_all_names().find(|name| {
+        name.start() >= issue.start_position
+            && name.end() <= issue.end_position
+            && name.as_code() == unresolved
+    })?;
+    let candidates = CompletionResolver::complete(
+        db,
+        file,
+        InputPosition::NthUTF8Byte(name.end() as usize),
+        false,
+        |_, completion: &dyn Completion| completion.label().to_owned(),
+    )
+    .ok()?;
+    let max_distance = (unresolved.chars().count() / 3).max(1);
+    let mut suggestions: Vec<_> = candidates
+        .into_iter()
+        .filter(|candidate| {
+            // Keyword arguments and dict keys are completed as well.
+            candidate != unresolved && candidate.chars().all(|c| c.is_alphanumeric() || c == '_')
+        })
+        .filter_map(|candidate| {
+            let distance = edit_distance(unresolved, &candidate);
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+    suggestions.sort();
+    suggestions.dedup();
+    Some((
+        name.start()..name.end(),
+        suggestions
+            .into_iter()
+            .take(3)
+            .map(|(_, candidate)| candidate)
+            .collect(),
+    ))
+}
+
+/// The case insensitive edit distance, where swapping two adjacent characters is a single edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = distances[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution
+                .min(distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+fn is_annotation_without_type_vars(file: &PythonFile, annotation_index: NodeIndex) -> bool {
+    file.points
+        .get(annotation_index)
+        .maybe_calculated_and_specific()
+        == Some(Specific::AnnotationOrTypeCommentWithoutTypeVars)
+}
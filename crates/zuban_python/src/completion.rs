@@ -2,8 +2,8 @@ use std::{borrow::Cow, collections::HashSet};
 
 pub use lsp_types::CompletionItemKind;
 use parsa_python_cst::{
-    ClassDef, CompletionNode, FunctionDef, NAME_DEF_TO_NAME_DIFFERENCE, NameDef, NodeIndex,
-    RestNode, Scope,
+    CallAtArgument, ClassDef, CompletionNode, DictLiteralContext, DictLiteralKey, FunctionDef,
+    NAME_DEF_TO_NAME_DIFFERENCE, NameDef, NodeIndex, RestNode, Scope,
 };
 use vfs::{Directory, DirectoryEntry, Entries, FileIndex, Parent};
 
@@ -11,16 +11,20 @@ use crate::{
     InputPosition,
     database::{ClassKind, Database, ParentScope, PointKind},
     debug,
-    file::{ClassNodeRef, File as _, FuncNodeRef, PythonFile, is_reexport_issue},
+    file::{
+        ClassNodeRef, File as _, FuncNodeRef, PythonFile, is_reexport_issue,
+        use_cached_annotation_type,
+    },
     goto::{PositionalDocument, unpack_union_types, with_i_s_non_self},
     imports::{ImportResult, global_import},
     inference_state::InferenceState,
     inferred::Inferred,
     lines::BytePositionInfos,
-    name::Range,
+    name::{Name, Range, TreeName},
     node_ref::NodeRef,
     recoverable_error,
-    type_::{CallableParam, Enum, EnumMemberDefinition, FunctionKind, Namespace, Type},
+    signature_help::CallInfos,
+    type_::{CallableParam, Enum, EnumMemberDefinition, FunctionKind, Namespace, ParamType, Type},
     type_helpers::{Class, Function, TypeOrClass, is_private},
 };
 
@@ -119,6 +123,13 @@ impl<'db, C: for<'a> Fn(Range, &dyn Completion) -> T, T> CompletionResolver<'db,
                 self.add_attribute_completions(inf)
             }
             CompletionNode::Global => {
+                let cursor = self.infos.node.cursor_position.byte;
+                if let Some(dict_key) = file.tree.dict_literal_key_at_position(cursor) {
+                    self.add_typed_dict_key_completions(dict_key)
+                }
+                if let Some(call) = file.tree.call_for_argument_at_position(cursor) {
+                    self.add_keyword_argument_completions(call)
+                }
                 let reachable_scopes = &mut ScopesIterator {
                     file,
                     only_reachable: true,
@@ -217,7 +228,10 @@ impl<'db, C: for<'a> Fn(Range, &dyn Completion) -> T, T> CompletionResolver<'db,
                 self.add_submodule_completions(file)
             }
             Some(ImportResult::Namespace(namespace)) => self.add_namespace_completions(&namespace),
-            None | Some(ImportResult::PyTypedMissing) => (),
+            None
+            | Some(ImportResult::PyTypedMissing | ImportResult::ExtensionModuleWithoutStub(_)) => {
+                ()
+            }
         }
     }
 
@@ -385,6 +399,108 @@ impl<'db, C: for<'a> Fn(Range, &dyn Completion) -> T, T> CompletionResolver<'db,
         }
     }
 
+    fn add_keyword_argument_completions(&mut self, call: CallAtArgument) {
+        let db = self.infos.db;
+        let file = self.infos.file;
+        let cursor = self.infos.node.cursor_position.byte;
+        with_i_s_non_self(db, file, self.infos.scope, |i_s| {
+            let Some(call_infos) = CallInfos::for_call_at_argument(i_s, file, call, cursor) else {
+                return;
+            };
+            // Only the params of overloads that accept the other arguments are completed.
+            for params in call_infos.matching_callables(db) {
+                for param in params {
+                    if !matches!(
+                        param.type_,
+                        ParamType::PositionalOrKeyword(_) | ParamType::KeywordOnly(_)
+                    ) {
+                        continue;
+                    }
+                    let Some(name) = param.name.as_ref().map(|name| name.as_str(db)) else {
+                        continue;
+                    };
+                    let label = format!("{name}=");
+                    if call_infos.keywords().iter().any(|keyword| keyword == name)
+                        || !self.maybe_add_cow(Cow::Owned(label.clone()))
+                    {
+                        continue;
+                    }
+                    let result =
+                        (self.on_result)(self.replace_range, &KeywordArgumentCompletion { label });
+                    // Keyword arguments are listed first and in the order of the parameters.
+                    let priority = CompletionSortPriority::KeywordArgument(self.items.len());
+                    self.items.push((priority, result))
+                }
+            }
+        })
+    }
+
+    fn add_typed_dict_key_completions(&mut self, dict_key: DictLiteralKey) {
+        let db = self.infos.db;
+        let file = self.infos.file;
+        let mut typed_dicts = vec![];
+        with_i_s_non_self(db, file, self.infos.scope, |i_s| {
+            let mut add_typed_dicts = |t: &Type| {
+                for t in t.iter_with_unpacked_unions(db) {
+                    if let Type::TypedDict(td) = t {
+                        typed_dicts.push(td.clone())
+                    }
+                }
+            };
+            match dict_key.context {
+                DictLiteralContext::Annotation(annotation) => {
+                    add_typed_dicts(&use_cached_annotation_type(db, file, annotation))
+                }
+                DictLiteralContext::Argument { call, position } => {
+                    let Some(call_infos) =
+                        CallInfos::for_call_at_argument(i_s, file, call, position)
+                    else {
+                        return;
+                    };
+                    for params in call_infos.matching_callables(db) {
+                        if let Some(t) = call_infos
+                            .active_param(db, params)
+                            .and_then(|index| params[index].type_.maybe_type())
+                        {
+                            add_typed_dicts(t)
+                        }
+                    }
+                }
+            }
+        });
+
+        // The key that is typed is replaced completely, including its closing quote.
+        let cursor = self.infos.node.cursor_position.byte;
+        let (start, end, quote) = match &dict_key.key {
+            Some(key) => {
+                let code = &file.tree.code()[key.start as usize..key.end as usize];
+                let quote = code
+                    .chars()
+                    .find(|c| matches!(c, '"' | '\''))
+                    .unwrap_or('"');
+                (key.start, key.end.max(cursor), quote)
+            }
+            None => (cursor, cursor, '"'),
+        };
+        let range = (
+            file.byte_to_position_infos(db, start),
+            file.byte_to_position_infos(db, end),
+        );
+        for td in &typed_dicts {
+            for member in td.members(db).named.iter() {
+                let name = member.name.as_str(db);
+                let label = format!("{quote}{name}{quote}");
+                if dict_key.used_keys.contains(&name)
+                    || !self.maybe_add_cow(Cow::Owned(label.clone()))
+                {
+                    continue;
+                }
+                let result = (self.on_result)(range, &TypedDictKeyCompletion { label });
+                self.items.push((CompletionSortPriority::Literal, result))
+            }
+        }
+    }
+
     fn add_for_mro(&mut self, i_s: &InferenceState, t: &Type, is_instance: bool) {
         if let Type::Self_ = t {
             if let Some(cls) = i_s.current_class() {
@@ -485,6 +601,7 @@ impl<'db, C: for<'a> Fn(Range, &dyn Completion) -> T, T> CompletionResolver<'db,
                         file: self.infos.file,
                         name: symbol,
                         kind: CompletionItemKind::FIELD,
+                        name_def: None,
                     },
                 );
                 self.items
@@ -550,6 +667,7 @@ impl<'db, C: for<'a> Fn(Range, &dyn Completion) -> T, T> CompletionResolver<'db,
                 file,
                 name,
                 kind,
+                name_def: Some(name_def),
             },
         );
         self.items
@@ -702,7 +820,7 @@ pub trait Completion {
     fn deprecated(&self) -> bool {
         false
     }
-    fn documentation(&self) -> Option<&str> {
+    fn documentation(&self) -> Option<Cow<'_, str>> {
         None
     }
 }
@@ -712,6 +830,7 @@ struct CompletionTreeName<'db> {
     file: &'db PythonFile,
     name: &'db str,
     kind: CompletionItemKind,
+    name_def: Option<NameDef<'db>>,
 }
 
 impl<'db> Completion for CompletionTreeName<'db> {
@@ -726,6 +845,12 @@ impl<'db> Completion for CompletionTreeName<'db> {
     fn file_path(&self) -> Option<&str> {
         Some(self.file.file_path(self.db))
     }
+
+    fn documentation(&self) -> Option<Cow<'_, str>> {
+        let name = TreeName::with_unknown_parent_scope(self.db, self.file, self.name_def?.name());
+        let documentation = Name::TreeName(name).documentation();
+        (!documentation.is_empty()).then_some(documentation)
+    }
 }
 
 #[expect(dead_code)]
@@ -779,6 +904,42 @@ impl Completion for KeywordCompletion {
     }
 }
 
+struct KeywordArgumentCompletion {
+    label: String,
+}
+
+impl Completion for KeywordArgumentCompletion {
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn kind(&self) -> CompletionItemKind {
+        CompletionItemKind::VARIABLE
+    }
+
+    fn file_path(&self) -> Option<&str> {
+        None
+    }
+}
+
+struct TypedDictKeyCompletion {
+    label: String,
+}
+
+impl Completion for TypedDictKeyCompletion {
+    fn label(&self) -> &str {
+        &self.label
+    }
+
+    fn kind(&self) -> CompletionItemKind {
+        CompletionItemKind::FIELD
+    }
+
+    fn file_path(&self) -> Option<&str> {
+        None
+    }
+}
+
 struct EnumMemberCompletion<'db> {
     db: &'db Database,
     enum_: &'db Enum,
@@ -821,8 +982,8 @@ impl Completion for NamedTupleMemberCompletion<'_> {
 
 #[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone)]
 enum CompletionSortPriority<'db> {
-    //Literal,    // e.g. TypedDict literal
-    //NamedParam, // e.g. def foo(*, bar) => `foo(b` completes to bar=
+    Literal,                // e.g. TypedDict literal
+    KeywordArgument(usize), // e.g. def foo(*, bar) => `foo(b` completes to bar=
     EnumMember,
     Default(&'db str),
     Dunder(&'db str), // e.g. __eq__
@@ -430,8 +430,14 @@ impl Points {
     }
 
     pub fn reset_from_backup(&self, backup: &PointsBackup) {
+        self.reset_from_backup_except(backup, &[])
+    }
+
+    pub fn reset_from_backup_except(&self, backup: &PointsBackup, keep: &[NodeIndex]) {
         for (i, points_index) in backup.range.clone().enumerate() {
-            self.0[points_index as usize].set(backup.points[i].get());
+            if !keep.contains(&points_index) {
+                self.0[points_index as usize].set(backup.points[i].get());
+            }
         }
     }
 }
@@ -1156,6 +1162,12 @@ impl Database {
                 .use_cached_class_infos(&new_db)
                 .set_promote_to(None);
         }
+        if new_db.project.flags.strict_numeric_promotion {
+            let s = &new_db.python_state;
+            for cls in [s.int(), s.bool(), s.float()] {
+                cls.use_cached_class_infos(&new_db).set_promote_to(None);
+            }
+        }
         new_db
     }
 
@@ -1,12 +1,14 @@
-use std::{collections::HashMap, io::Write, sync::Arc};
+use std::{collections::HashMap, hash::Hasher as _, io::Write, ops::Range, sync::Arc};
 
 use colored::{ColoredString, Colorize as _};
 use config::DiagnosticConfig;
+use fnv::FnvHasher;
 use parsa_python_cst::{CodeIndex, NodeIndex, Tree};
 use utils::InsertOnlyVec;
 
 use crate::{
     PythonVersion, TypeCheckerFlags,
+    code_actions::spelling_suggestions,
     database::{Database, PointLink},
     file::{File, GenericCounts, OVERLAPPING_REVERSE_TO_NORMAL_METHODS, PythonFile},
     lines::PositionInfos,
@@ -41,6 +43,7 @@ pub(crate) enum IssueKind {
     NameError { name: Box<str> },
     ReadingDeletedVariable,
     ArgumentIssue(Box<str>),
+    MissingNamedArgument { name: Box<str>, function: Option<Box<str>> },
     ArgumentTypeIssue(Box<str>),
     TooFewArguments(Box<str>),
     TooManyArguments(Box<str>),
@@ -87,12 +90,14 @@ pub(crate) enum IssueKind {
     IncompatibleConditionalFunctionSignaturePretty { original: Box<str>, redefinition: Box<str> },
     NameUsedBeforeDefinition { name: Box<str> },
     ModuleNotFound { module_name: Box<str> },
+    ExtensionModuleWithoutStub { module_name: Box<str> },
     NoParentModule,
     TypeNotFound,
     UnexpectedTypeDeclaration,
     TypeArgumentIssue { class: Box<str>, counts: GenericCounts },
     TypeAliasArgumentIssue { counts: GenericCounts },
     NotCallable { type_: Box<str> },
+    NoneInUnionNotCallable { union: Box<str> },
     UnknownFunctionNotCallable,
     AnyNotCallable,
     UnpackNotIterable { type_: Box<str> },
@@ -122,6 +127,8 @@ pub(crate) enum IssueKind {
     StmtOutsideFunction { keyword: &'static str },
     AsyncOutsideAsyncFunction { keyword: &'static str },
     YieldOrYieldFromInsideComprehension { keyword: &'static str },
+    WalrusInComprehensionInClassBody,
+    WalrusRebindsComprehensionIterationVariable { name: Box<str> },
     AwaitOutsideFunction,
     AwaitOutsideCoroutine,
     YieldFromInAsyncFunction,
@@ -440,6 +447,7 @@ pub(crate) enum IssueKind {
     EnumUnexpectedArguments { name: Box<str> },
     EnumAttemptedReuseOfMemberName { member_name: Box<str>, enum_name: Box<str> },
     EnumIndexShouldBeAString { actual: Box<str> },
+    EnumHasNoMemberNamed { enum_name: Box<str>, name: Box<str> }, // Not in mypy
     EnumCannotBeGeneric,
     EnumReusedMemberName { enum_name: Box<str>, member_name: Box<str> },
     EnumWithMembersNotExtendable  { name: Box<str> },
@@ -492,6 +500,29 @@ pub(crate) enum IssueKind {
     NonOverlappingEqualityCheck { left_type: Box<str>, right_type: Box<str> }, // From --strict-equality
     NonOverlappingIdentityCheck { left_type: Box<str>, right_type: Box<str> }, // From --strict-equality
     NonOverlappingContainsCheck { element_type: Box<str>, container_type: Box<str> }, // From --strict-equality
+    UnusedVariable { name: Box<str> }, // From --enable-error-code=unused-variable
+    UnusedParameter { name: Box<str> }, // From --enable-error-code=unused-parameter
+    UnusedImport { name: Box<str> }, // From --enable-error-code=unused-import
+    ShadowedBuiltin { name: Box<str> }, // From --enable-error-code=shadowed-builtin
+    MatchIsNotExhaustive { type_: Box<str> }, // From --enable-error-code=exhaustive-match
+    StubComplexDefault, // From --enable-error-code=stub-lint
+    StubMissingReturnAnnotation { name: Box<str> }, // From --enable-error-code=stub-lint
+    StubDunderReturnsAny { name: Box<str> }, // From --enable-error-code=stub-lint
+    StubInconsistentOverloadDecorators { name: Box<str> }, // From --enable-error-code=stub-lint
+    StubDocstring, // From --enable-error-code=stub-docstring
+    TypeCheckingMismatch { name: Box<str>, runtime: Box<str>, declared: Box<str> }, // From --enable-error-code=type-checking-mismatch
+    NonLiteralOverloadFlag { param: Box<str>, function: Box<str>, overload_index: usize }, // From --enable-error-code=overload-flag
+    InferredNeverTypeArgument { type_var: Box<str>, function: Box<str> }, // From --enable-error-code=inferred-never
+    UntrustedArgument { name: Box<str>, param: Box<str> }, // From --enable-error-code=taint
+    InvalidDunderReturnType { name: Box<str>, got: Box<str>, expected: Box<str> }, // Not in mypy
+    InvalidExitSignature { name: Box<str> }, // Not in mypy
+    EqWithoutHashMakesUnhashable { class: Box<str>, base_class: Box<str> }, // Not in mypy
+    UntypedAttribute { name: Box<str>, class: Box<str> }, // From --disallow-untyped-attributes
+    FunctionTimedOut { name: Box<str> }, // From --function-timeout
+    Monkeypatching { name: Box<str> }, // From --disallow-monkeypatching
+    UntypedImport { module_name: Box<str> }, // From --disallow-untyped-imports
+    StrBytesSafe, // From --strict-bytes
+    NotImplementedOutsideOfBinaryDunder { name: Box<str> }, // Not in mypy
 
     InvariantNote { actual: &'static str, maybe: &'static str },
     AnnotationInUntypedFunction,
@@ -507,6 +538,8 @@ impl IssueKind {
             | InvalidSyntaxInTypeComment { .. }
             | InvalidSyntaxInTypeAnnotation
             | TypeIgnoreWithErrorCodeNotSupportedForModules { .. }
+            | WalrusInComprehensionInClassBody
+            | WalrusRebindsComprehensionIterationVariable { .. }
             | DirectiveSyntaxError(..) => "syntax",
             AttributeError { .. }
             | ImportAttributeError { .. }
@@ -521,7 +554,10 @@ impl IssueKind {
             | UnionAttributeErrorOfUpperBound(..)
             | NotIterableMissingIterInUnion { .. } => "union-attr",
             ArgumentTypeIssue(_) | SuperArgument1MustBeTypeObject { .. } => "arg-type",
-            ArgumentIssue { .. } | TooManyArguments { .. } | TooFewArguments { .. } => "call-arg",
+            ArgumentIssue { .. }
+            | MissingNamedArgument { .. }
+            | TooManyArguments { .. }
+            | TooFewArguments { .. } => "call-arg",
             InvalidType(_) => "valid-type",
             IncompatibleReturn { .. }
             | IncompatibleImplicitReturn { .. }
@@ -547,6 +583,7 @@ impl IssueKind {
             | UnsupportedIn { .. }
             | UnsupportedOperandForUnary { .. }
             | NotCallable { .. }
+            | NoneInUnionNotCallable { .. }
             | UnknownFunctionNotCallable => "operator",
             TypeArgumentIssue { .. } | MissingTypeParameters { .. } => "type-arg",
             ModuleNotFound { module_name } => {
@@ -556,6 +593,7 @@ impl IssueKind {
                     "import-not-found"
                 }
             }
+            ExtensionModuleWithoutStub { .. } => "import-extension",
             ListItemMismatch { .. } => "list-item",
             SetItemMismatch { .. } => "arg-type", // This has no error code in Mypy currently.
             DictMemberMismatch { .. } | UnpackedDictMemberMismatch { .. } => "dict-item",
@@ -610,6 +648,31 @@ impl IssueKind {
             | NonOverlappingContainsCheck { .. }
             | NonOverlappingIdentityCheck { .. } => "comparison-overlap",
             UnimportedRevealType => "unimported-reveal",
+            UnusedVariable { .. } => "unused-variable",
+            UnusedParameter { .. } => "unused-parameter",
+            UnusedImport { .. } => "unused-import",
+            ShadowedBuiltin { .. } => "shadowed-builtin",
+            MatchIsNotExhaustive { .. } => "exhaustive-match",
+            StubComplexDefault
+            | StubMissingReturnAnnotation { .. }
+            | StubDunderReturnsAny { .. }
+            | StubInconsistentOverloadDecorators { .. } => "stub-lint",
+            StubInconsistentOverloadDecorators { name } => format!(
+                r#"Overloaded function "{name}" in a stub does not use the same decorators on all signatures"#
+            ),
+            StubDocstring => "stub-docstring",
+            TypeCheckingMismatch { .. } => "type-checking-mismatch",
+            NonLiteralOverloadFlag { .. } => "overload-flag",
+            InferredNeverTypeArgument { .. } => "inferred-never",
+            UntrustedArgument { .. } => "taint",
+            InvalidDunderReturnType { .. }
+            | InvalidExitSignature { .. }
+            | EqWithoutHashMakesUnhashable { .. } => "dunder-contract",
+            UntypedAttribute { .. } => "untyped-attribute",
+            FunctionTimedOut { .. } => "timeout",
+            Monkeypatching { .. } => "monkeypatch",
+            UntypedImport { .. } => "untyped-import",
+            StrBytesSafe => "str-bytes-safe",
             DisallowedAnyExplicit => "explicit-any",
 
             _ => "misc",
@@ -622,31 +685,22 @@ impl IssueKind {
         Some(match &self {
             TypedDictExtraKey { .. } | TypedDictHasNoKey { .. } => "typeddict-item",
             CannotAssignToAMethod => "assignment",
-            ModuleNotFound { .. } => "import",
+            ModuleNotFound { .. } | ExtensionModuleWithoutStub { .. } => "import",
             OverloadUnmatchable { .. } | DecoratorOnTopOfPropertyNotSupported => "misc",
             _ => return None,
         })
     }
 
     pub(crate) fn should_be_reported(&self, flags: &TypeCheckerFlags) -> bool {
-        if !flags.disabled_error_codes.is_empty() {
-            let should_not_report = |code| {
-                if let Some(code) = code
-                    && flags.disabled_error_codes.iter().any(|c| c == code)
-                    && !flags.enabled_error_codes.iter().any(|c| c == code)
-                {
-                    return true;
-                }
-                false
-            };
-            if should_not_report(self.mypy_error_code()) {
-                return false;
-            }
-            if should_not_report(self.mypy_error_supercode()) {
-                return false;
-            }
-        }
-        true
+        // Optional error codes are enabled by their flags (e.g. --disallow-deprecated) and not
+        // only by --enable-error-code, so only explicitly disabled codes are filtered here.
+        let is_disabled = |code: Option<&str>| {
+            code.is_some_and(|code| {
+                flags.disabled_error_codes.iter().any(|c| c == code)
+                    && !flags.is_error_code_enabled(code)
+            })
+        };
+        !is_disabled(self.mypy_error_code()) && !is_disabled(self.mypy_error_supercode())
     }
 }
 
@@ -734,6 +788,48 @@ impl<'db> Diagnostic<'db> {
         self.issue.kind.mypy_error_code().unwrap_or("note")
     }
 
+    /// Similarly spelled names that are available if a name or an import cannot be resolved,
+    /// e.g. `print` for `pritn`, together with the range of the name.
+    pub(crate) fn spelling_suggestions(&self) -> Option<(Range<CodeIndex>, Vec<String>)> {
+        // Names in type comments and string annotations are not replaced.
+        if self.file.super_file.is_some() {
+            return None;
+        }
+        spelling_suggestions(self.db, self.file, self.issue)
+    }
+
+    /// The path of the file relative to its workspace, as it is shown in the output.
+    pub fn relative_path(&self) -> &'db str {
+        let original_file = self.file.original_file(self.db);
+        let path = self
+            .db
+            .file_path(original_file.file_index)
+            .trim_start_matches(&***original_file.file_entry(self.db).parent.workspace_path());
+        self.db
+            .vfs
+            .handler
+            .strip_separator_prefix(path)
+            .unwrap_or(path)
+    }
+
+    /// A fingerprint that stays the same across runs and machines, which makes it usable for
+    /// baselines and external tooling. It is derived from the path, the error code, the message
+    /// and the code under the issue, but not from the position, so it survives unrelated edits
+    /// that move the issue around.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = FnvHasher::default();
+        for part in [
+            self.relative_path(),
+            self.mypy_error_code(),
+            &self.message(),
+            self.code_under_issue(),
+        ] {
+            hasher.write(part.as_bytes());
+            hasher.write_u8(0);
+        }
+        format!("{:016x}", hasher.finish())
+    }
+
     pub fn is_mypy_semanal_error(&self) -> bool {
         // Mypy has semanal-*.test tests that only use Mypy's semantic analysis part instead of
         // full type checking, which leads to not all errors being relevant. Here we filter only
@@ -923,6 +1019,10 @@ impl<'db> Diagnostic<'db> {
                 r#"Name "{name}" is used before definition"#
             ),
             ArgumentIssue(s) | ArgumentTypeIssue(s) | InvalidType(s) => s.clone().into(),
+            MissingNamedArgument { name, function } => match function {
+                Some(function) => format!(r#"Missing named argument "{name}" for {function}"#),
+                None => format!(r#"Missing named argument "{name}""#),
+            },
             TooManyArguments(rest) => format!("Too many arguments{rest}"),
             TooFewArguments(rest) => format!("Too few arguments{rest}"),
             IncompatibleDefaultArgument {argument_name, got, expected} => {
@@ -1008,8 +1108,14 @@ impl<'db> Diagnostic<'db> {
 
                 }
             }
+            ExtensionModuleWithoutStub{module_name} => format!(
+                "Extension module {module_name:?} has no type stubs (add a .pyi file next to it)"
+            ),
             NoParentModule => "No parent module -- cannot perform relative import".to_string(),
             NotCallable{type_} => format!("{type_} not callable"),
+            NoneInUnionNotCallable{union} => format!(
+                r#"Item "None" of "{union}" is not callable"#
+            ),
             UnknownFunctionNotCallable => "Cannot call function of unknown type".to_string(),
             AnyNotCallable => "Any(...) is no longer supported. Use cast(Any, ...) instead".to_string(),
             UnpackNotIterable{type_} => format!("{type_} object is not iterable"),
@@ -1084,6 +1190,9 @@ impl<'db> Diagnostic<'db> {
             EnumIndexShouldBeAString { actual } => format!(
                 r#"Enum index should be a string (actual index type "{actual}")"#
             ),
+            EnumHasNoMemberNamed { enum_name, name } => format!(
+                r#"Enum "{enum_name}" has no member named "{name}""#
+            ),
             EnumCannotBeGeneric => "Enum class cannot be generic".to_string(),
             EnumReusedMemberName { enum_name, member_name } => format!(
                 r#"Attempted to reuse member name "{member_name}" in Enum definition "{enum_name}""#
@@ -1207,6 +1316,60 @@ impl<'db> Diagnostic<'db> {
             NonOverlappingContainsCheck { element_type, container_type } => format!(
                 r#"Non-overlapping container check (element type: "{element_type}", container item type: "{container_type}")"#
             ),
+            UnusedVariable { name } => format!(
+                r#"Local variable "{name}" is assigned to but never used"#
+            ),
+            UnusedParameter { name } => format!(r#"Parameter "{name}" is never used"#),
+            UnusedImport { name } => format!(r#""{name}" is imported but never used"#),
+            ShadowedBuiltin { name } => format!(r#"Name "{name}" shadows a builtin"#),
+            MatchIsNotExhaustive { type_ } => format!(
+                r#"Unhandled case for values of type "{type_}""#
+            ),
+            StubComplexDefault => "Only simple default values are allowed in stubs".to_string(),
+            StubMissingReturnAnnotation { name } => format!(
+                r#"Function "{name}" in a stub is missing a return type annotation"#
+            ),
+            StubDunderReturnsAny { name } => format!(
+                r#"Special method "{name}" in a stub should not return "Any""#
+            ),
+            StubDocstring => "Docstrings should not be included in stubs".to_string(),
+            TypeCheckingMismatch { name, runtime, declared } => format!(
+                r#"Runtime definition of "{name}" ({runtime}) is incompatible with its TYPE_CHECKING definition of type "{declared}""#
+            ),
+            NonLiteralOverloadFlag { param, function, overload_index } => format!(
+                r#"Non-literal bool for "{param}" selects overload {overload_index} of "{function}", pass a literal to select a specific overload"#
+            ),
+            InferredNeverTypeArgument { type_var, function } => format!(
+                r#"Type argument "{type_var}" of {function} was inferred as "Never", because nothing constrains it"#
+            ),
+            UntrustedArgument { name, param } => format!(
+                r#"Untrusted value "{name}" is passed to Trusted parameter "{param}" without sanitizing it"#
+            ),
+            InvalidDunderReturnType { name, got, expected } => format!(
+                r#"Return type "{got}" of "{name}" must be a subtype of "{expected}""#
+            ),
+            InvalidExitSignature { name } => format!(
+                r#""{name}" must accept 3 positional arguments (exception type, value and traceback)"#
+            ),
+            UntypedAttribute { name, class } => format!(
+                r#"Attribute "{name}" of "{class}" needs a type annotation"#
+            ),
+            FunctionTimedOut { name } => format!(
+                r#"Analysis of function "{name}" timed out, types may be imprecise"#
+            ),
+            Monkeypatching { name } => format!(
+                r#"Assigning to "{name}" is not allowed (monkeypatching is disabled)"#
+            ),
+            UntypedImport { module_name } => format!(
+                r#"Module "{module_name}" is installed, but has no py.typed marker (untyped imports are disabled)"#
+            ),
+            NotImplementedOutsideOfBinaryDunder { name } => format!(
+                r#""NotImplemented" can only be returned from binary dunder methods like "__add__", not from "{name}""#
+            ),
+            StrBytesSafe => r#"If x = b'abc' then f"{x}" produces "b'abc'", not "abc". If this is desired behavior, use f"{x!r}". Otherwise, decode the bytes"#.to_string(),
+            EqWithoutHashMakesUnhashable { class, base_class } => format!(
+                r#""{class}" defines "__eq__" but not "__hash__", which makes it unhashable even though base class "{base_class}" is hashable"#
+            ),
             UnimportedRevealType => {
                 let module = if self.db.project.settings.python_version_or_default() < PythonVersion::new(3, 11) {
                     "typing_extensions"
@@ -1246,6 +1409,11 @@ impl<'db> Diagnostic<'db> {
             YieldOrYieldFromInsideComprehension { keyword } => format!(
                 "{keyword:?} inside comprehension or generator expression"
             ),
+            WalrusInComprehensionInClassBody =>
+                "Assignment expression within a comprehension cannot be used in a class body".to_string(),
+            WalrusRebindsComprehensionIterationVariable { name } => format!(
+                r#"Assignment expression cannot rebind comprehension iteration variable "{name}""#
+            ),
             AwaitOutsideFunction => r#""await" outside function"#.to_string(),
             AwaitOutsideCoroutine => r#""await" outside coroutine ("async def")"#.to_string(),
             YieldFromInAsyncFunction => r#""yield from" in async function"#.to_string(),
@@ -2011,18 +2179,25 @@ impl<'db> Diagnostic<'db> {
         msg
     }
 
+    /// The message without the notes that belong to it, the notes are returned separately.
+    pub fn message_and_notes(&self) -> (String, Vec<String>) {
+        let mut additional_notes = vec![];
+        let message = self.message_with_notes(&mut additional_notes);
+        (message, additional_notes)
+    }
+
+    /// Either "error" or "note", like it is printed in front of the message.
+    pub fn kind_name(&self) -> &'static str {
+        match &self.issue.kind {
+            IssueKind::AnnotationInUntypedFunction
+            | IssueKind::Note(_)
+            | IssueKind::InvariantNote { .. } => "note",
+            _ => "error",
+        }
+    }
+
     fn message_formatting_options(&self, config: &DiagnosticConfig) -> MessageFormattingInfos<'db> {
-        let original_file = self.file.original_file(self.db);
-        let path = self
-            .db
-            .file_path(original_file.file_index)
-            .trim_start_matches(&***original_file.file_entry(self.db).parent.workspace_path());
-        let path = self
-            .db
-            .vfs
-            .handler
-            .strip_separator_prefix(path)
-            .unwrap_or(path);
+        let path = self.relative_path();
         let mut additional_notes = vec![];
         let error = self.message_with_notes(&mut additional_notes);
 
@@ -2043,12 +2218,7 @@ impl<'db> Diagnostic<'db> {
         MessageFormattingInfos {
             error,
             additional_notes,
-            kind: match &self.issue.kind {
-                IssueKind::AnnotationInUntypedFunction
-                | IssueKind::Note(_)
-                | IssueKind::InvariantNote { .. } => "note",
-                _ => "error",
-            },
+            kind: self.kind_name(),
             path,
             line_number_infos,
         }
@@ -2,7 +2,9 @@ use std::{
     borrow::Cow,
     cell::Cell,
     collections::{HashMap, HashSet},
+    ops::Range,
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use config::TypeCheckerFlags;
@@ -11,13 +13,14 @@ use parsa_python_cst::*;
 use super::{
     ClassNodeRef, FuncNodeRef, OtherDefinitionIterator, first_defined_name,
     flow_analysis::FLOW_ANALYSIS, inference::await_, on_argument_type_error,
+    utils::is_unannotated_self_attribute,
 };
 use crate::{
     Mode,
     arguments::{CombinedArgs, InitSubclassArgs, KnownArgs, NoArgs, SimpleArgs},
     database::{
         ClassKind, ComplexPoint, Database, Locality, MetaclassState, OverloadImplementation,
-        ParentScope, Point, Specific,
+        ParentScope, Point, PointKind, Specific,
     },
     debug,
     diagnostics::{Issue, IssueKind},
@@ -30,6 +33,7 @@ use crate::{
         ErrorStrs, Generic, Generics, LookupKind, Match, Matcher, OnTypeError,
         ReplaceSelfInMatcher, ResultContext,
     },
+    new_class,
     node_ref::NodeRef,
     params::{Param, WrappedParamType, WrappedStar, matches_params},
     recoverable_error,
@@ -55,6 +59,12 @@ const IGNORED_INHERITANCE_NAMES: [&str; 5] = [
     "__class_getitem__",
 ];
 
+thread_local! {
+    // The wall-clock budget of the function body that is currently checked (--function-timeout).
+    // The bool is set once the timeout has been reported.
+    static FUNCTION_DEADLINE: Cell<Option<(Instant, bool)>> = const { Cell::new(None) };
+}
+
 lazy_static::lazy_static! {
     static ref FORWARD_OP_METHODS: HashSet<&'static str> = HashSet::from([
         "__add__",
@@ -141,6 +151,9 @@ impl Inference<'_, '_, '_> {
     pub fn calculate_module_diagnostics(&self) -> Result<(), ()> {
         let result = self.ensure_module_symbols_flow_analysis();
         self.file.process_delayed_diagnostics(self.i_s.db);
+        self.check_unused_names();
+        self.check_shadowed_builtins();
+        self.check_stub_docstrings();
         result
     }
 
@@ -198,9 +211,285 @@ impl Inference<'_, '_, '_> {
                     )
                 }
             }
+            if !self.i_s.db.project.settings.mypy_compatible
+                && let Some(name_ref) = self.file.lookup_symbol("__dir__")
+            {
+                let actual = name_ref.infer_name_of_definition_by_index(self.i_s);
+                let actual = actual.as_cow_type(self.i_s);
+                if !self
+                    .i_s
+                    .db
+                    .python_state
+                    .valid_module_dir_supertype
+                    .is_simple_super_type_of(self.i_s, &actual)
+                    .bool()
+                {
+                    name_ref.add_issue(
+                        self.i_s,
+                        IssueKind::InvalidSpecialMethodSignature {
+                            type_: actual.format_short(self.i_s.db),
+                            special_method: "__dir__",
+                        },
+                    )
+                }
+            }
         })
     }
 
+    fn check_unused_names(&self) {
+        let db = self.i_s.db;
+        let flags = self.flags();
+        let check_variables = flags.is_error_code_enabled("unused-variable");
+        let check_params = flags.is_error_code_enabled("unused-parameter");
+        // Imports in packages and modules with __all__ are usually meant to be re-exported.
+        let check_imports = flags.is_error_code_enabled("unused-import")
+            && !self.file.file_entry_and_is_package(db).1
+            && self.file.lookup_symbol("__all__").is_none();
+        if self.file.is_stub() || !check_variables && !check_params && !check_imports {
+            return;
+        }
+        let file_index = self.file.file_index;
+        // The name binder redirects all references to the first definition of a symbol, which
+        // means that a symbol is used if any reference points to its first definition.
+        let mut used = HashSet::new();
+        let mut scopes_with_locals_call = HashSet::new();
+        let mut global_or_nonlocal_names = HashSet::new();
+        for name in self.file.tree.filter_all_names() {
+            let Some(name_def) = name.name_def() else {
+                let point = self.file.points.get(name.index());
+                if point.calculated()
+                    && point.kind() == PointKind::Redirect
+                    && point.file_index() == file_index
+                {
+                    used.insert(point.node_index());
+                }
+                if name.as_code() == "locals"
+                    && let Scope::Function(func) = name.parent_scope()
+                {
+                    scopes_with_locals_call.insert(func.index());
+                }
+                continue;
+            };
+            match name_def.parent() {
+                NameDefParent::GlobalStmt | NameDefParent::NonlocalStmt => {
+                    if let Scope::Function(func) = name.parent_scope() {
+                        global_or_nonlocal_names.insert((func.index(), name.as_code()));
+                    }
+                }
+                NameDefParent::Primary => (),
+                NameDefParent::Other => match name_def.expect_defining_stmt() {
+                    // Augmented assignments and del statements read the symbol they redefine.
+                    DefiningStmt::DelStmt(_) => {
+                        used.insert(first_defined_name(self.file, name.index()));
+                    }
+                    DefiningStmt::Assignment(assignment)
+                        if matches!(assignment.unpack(), AssignmentContent::AugAssign(..)) =>
+                    {
+                        used.insert(first_defined_name(self.file, name.index()));
+                    }
+                    _ => (),
+                },
+            }
+        }
+        // Names in annotation strings and type comments are resolved in sub files, which look up
+        // the symbols of this module.
+        for sub_file in self.file.sub_files_recursively(db) {
+            for name in sub_file.tree.filter_all_names() {
+                if matches!(name.parent(), NameParent::Atom(_))
+                    && let Some(symbol) = self.file.lookup_symbol(name.as_code())
+                {
+                    used.insert(first_defined_name(self.file, symbol.node_index));
+                }
+            }
+        }
+
+        let mut params_are_checked = HashMap::new();
+        for name in self.file.tree.filter_all_names() {
+            let Some(name_def) = name.name_def() else {
+                continue;
+            };
+            if name.as_code().starts_with('_')
+                || used.contains(&name.index())
+                || self.file.points.get(name.index()).maybe_specific()
+                    != Some(Specific::FirstNameOfNameDef)
+                || !matches!(name_def.parent(), NameDefParent::Other)
+            {
+                continue;
+            }
+            let issue = match (name_def.expect_type(), name.parent_scope()) {
+                (TypeLike::ParamName(_), Scope::Function(func)) if check_params => {
+                    if !*params_are_checked
+                        .entry(func.index())
+                        .or_insert_with(|| should_check_unused_params(func))
+                        || is_first_param_of_method(func, name)
+                        || scopes_with_locals_call.contains(&func.index())
+                    {
+                        continue;
+                    }
+                    IssueKind::UnusedParameter {
+                        name: name.as_code().into(),
+                    }
+                }
+                (TypeLike::ImportFromAsName(_) | TypeLike::DottedAsName(_), Scope::Module)
+                    if check_imports =>
+                {
+                    let Some(import) = name_def.maybe_import() else {
+                        continue;
+                    };
+                    if import.is_stub_reexport() || is_future_import(&import) {
+                        continue;
+                    }
+                    IssueKind::UnusedImport {
+                        name: name.as_code().into(),
+                    }
+                }
+                (TypeLike::Assignment(_) | TypeLike::Other, Scope::Function(func))
+                    if check_variables =>
+                {
+                    if scopes_with_locals_call.contains(&func.index())
+                        || global_or_nonlocal_names.contains(&(func.index(), name.as_code()))
+                    {
+                        continue;
+                    }
+                    match name_def.expect_defining_stmt() {
+                        DefiningStmt::Assignment(assignment) => {
+                            if matches!(
+                                assignment.unpack(),
+                                AssignmentContent::WithAnnotation(_, _, None)
+                            ) {
+                                // A bare annotation like `x: int` does not assign anything.
+                                continue;
+                            }
+                        }
+                        DefiningStmt::Walrus(_) | DefiningStmt::TryStmt(_) => (),
+                        // Loop variables, with items, etc. are often not needed and not reported.
+                        _ => continue,
+                    }
+                    IssueKind::UnusedVariable {
+                        name: name.as_code().into(),
+                    }
+                }
+                _ => continue,
+            };
+            self.add_issue(name.index(), issue);
+        }
+    }
+
+    fn check_shadowed_builtins(&self) {
+        let db = self.i_s.db;
+        if self.file.is_stub() || !self.flags().is_error_code_enabled("shadowed-builtin") {
+            return;
+        }
+        let builtins = db.python_state.builtins();
+        let is_builtin = |name: &str| {
+            builtins.lookup_symbol(name).is_some_and(|node_ref| {
+                // Imports in builtins.pyi (e.g. `from typing import Any`) are not re-exported.
+                node_ref
+                    .maybe_name()
+                    .and_then(|n| n.name_def())
+                    .is_some_and(|name_def| name_def.maybe_import().is_none())
+            })
+        };
+        for name in self.file.tree.filter_all_names() {
+            let Some(name_def) = name.name_def() else {
+                continue;
+            };
+            let name_str = name.as_code();
+            if name_str.starts_with('_')
+                || self.file.points.get(name.index()).maybe_specific()
+                    != Some(Specific::FirstNameOfNameDef)
+                || !matches!(name_def.parent(), NameDefParent::Other)
+                || matches!(name_def.expect_type(), TypeLike::ParamName(_))
+                || !is_builtin(name_str)
+            {
+                continue;
+            }
+            // Class attributes are always accessed through the class and are therefore not
+            // really shadowing a builtin.
+            let scope = if let Some(func) = name_def.maybe_name_of_func() {
+                func.parent_scope()
+            } else if let Some(class) = name_def.maybe_name_of_class() {
+                class.parent_scope()
+            } else {
+                name.parent_scope()
+            };
+            if matches!(scope, Scope::Class(_)) {
+                continue;
+            }
+            self.add_issue(
+                name.index(),
+                IssueKind::ShadowedBuiltin {
+                    name: name_str.into(),
+                },
+            );
+        }
+    }
+
+    fn check_stub_docstrings(&self) {
+        if !self.file.is_stub() || !self.flags().is_error_code_enabled("stub-docstring") {
+            return;
+        }
+        let add_issue = |docstring: Option<Strings>| {
+            if let Some(docstring) = docstring {
+                self.add_issue(docstring.index(), IssueKind::StubDocstring)
+            }
+        };
+        add_issue(self.file.tree.root().docstring());
+        for name in self.file.tree.filter_all_names() {
+            let Some(name_def) = name.name_def() else {
+                continue;
+            };
+            if let Some(func) = name_def.maybe_name_of_func() {
+                add_issue(func.docstring())
+            } else if let Some(class) = name_def.maybe_name_of_class() {
+                add_issue(class.docstring())
+            }
+        }
+    }
+
+    fn check_stub_function(&self, function: Function) {
+        let i_s = self.i_s;
+        for param in function.node().params().iter() {
+            if let Some(default) = param.default()
+                && !is_simple_stub_default(default)
+            {
+                self.add_issue(default.index(), IssueKind::StubComplexDefault)
+            }
+        }
+        let name = function.name();
+        if let Some(ComplexPoint::FunctionOverload(o)) = function.node_ref.maybe_complex() {
+            let mut decorators = o
+                .iter_functions()
+                .map(|c| overload_item_decorators(i_s.db, c));
+            let first = decorators.next().unwrap();
+            if decorators.any(|other| other != first) {
+                function.add_issue_onto_start_including_decorator(
+                    i_s,
+                    IssueKind::StubInconsistentOverloadDecorators { name: name.into() },
+                )
+            }
+        }
+        if function.return_annotation().is_none() {
+            // The return type of __init__ is always None and therefore often omitted.
+            if name != "__init__" {
+                function.add_issue_for_declaration(
+                    i_s,
+                    IssueKind::StubMissingReturnAnnotation { name: name.into() },
+                )
+            }
+        } else if name.starts_with("__")
+            && name.ends_with("__")
+            // `def __getattr__(name: str) -> Any` marks incomplete stubs.
+            && !matches!(name, "__getattr__" | "__getattribute__")
+            && matches!(function.return_type(i_s).as_ref(), Type::Any(AnyCause::Explicit))
+        {
+            function.add_issue_for_declaration(
+                i_s,
+                IssueKind::StubDunderReturnsAny { name: name.into() },
+            )
+        }
+    }
+
     fn check_assignment(&self, assignment: Assignment, class: Option<Class>) {
         self.ensure_cached_assignment(assignment);
 
@@ -346,27 +635,58 @@ impl Inference<'_, '_, '_> {
         }
     }
 
+    /// Remembers unreachable code, so language server clients can gray it out.
+    pub(super) fn add_unreachable_range(&self, range: Range<CodeIndex>) {
+        if self.i_s.db.mode == Mode::LanguageServer && self.i_s.should_add_issue() {
+            self.file.unreachable_ranges.write().unwrap().push(range)
+        }
+    }
+
+    pub(super) fn add_unreachable_block(&self, block: Block) {
+        let mut stmts = block.iter_stmt_likes();
+        let Some(first) = stmts.next().map(|stmt_like| stmt_like.parent_index) else {
+            return;
+        };
+        let last = stmts
+            .last()
+            .map_or(first, |stmt_like| stmt_like.parent_index);
+        self.add_unreachable_range(
+            self.file.tree.node_start_position(first)
+                ..self.file.tree.node_end_position_without_whitespace(last),
+        )
+    }
+
     fn calc_stmts_diagnostics(
         &self,
         stmts: StmtLikeIterator,
         class: Option<Class>,
         func: Option<&Function>,
     ) {
+        let mut unreachable_range: Option<Range<CodeIndex>> = None;
         for stmt_like in stmts {
             let point = self.point(stmt_like.parent_index);
             if point.calculated() {
                 debug_assert_eq!(point.specific(), Specific::Analyzed);
                 continue;
             }
+            if let Some(func) = func
+                && self.function_timed_out(func)
+            {
+                return;
+            }
             if self.is_unreachable() {
+                let start = self.file.tree.node_start_position(stmt_like.parent_index);
+                let end = self
+                    .file
+                    .tree
+                    .node_end_position_without_whitespace(stmt_like.parent_index);
                 if self.stmt_is_allowed_when_unreachable(stmt_like.node) {
+                    if let Some(range) = &mut unreachable_range {
+                        range.end = end;
+                    }
                     continue;
                 } else {
-                    let start = self.file.tree.node_start_position(stmt_like.parent_index);
-                    let end = self
-                        .file
-                        .tree
-                        .node_end_position_without_whitespace(stmt_like.parent_index);
+                    unreachable_range.get_or_insert(start..end).end = end;
                     self.add_unreachable_error(start, end);
                     /*
                     if self.flags().mypy_compatible {
@@ -389,6 +709,32 @@ impl Inference<'_, '_, '_> {
             }
             self.handle_stmt_like(stmt_like, class, func)
         }
+        // Loops are not rechecked, so code in loops might just be unreachable because types are
+        // not widened yet.
+        if let Some(range) = unreachable_range
+            && !FLOW_ANALYSIS.with(|fa| fa.is_in_loop())
+        {
+            self.add_unreachable_range(range)
+        }
+    }
+
+    fn function_timed_out(&self, func: &Function) -> bool {
+        let Some((deadline, reported)) = FUNCTION_DEADLINE.get() else {
+            return false;
+        };
+        if Instant::now() < deadline {
+            return false;
+        }
+        if !reported {
+            FUNCTION_DEADLINE.set(Some((deadline, true)));
+            func.add_issue_for_declaration(
+                self.i_s,
+                IssueKind::FunctionTimedOut {
+                    name: func.name().into(),
+                },
+            );
+        }
+        true
     }
 
     fn handle_stmt_like(
@@ -917,6 +1263,103 @@ impl Inference<'_, '_, '_> {
                 self.file_path()
             );
         }
+        self.check_type_checking_overrides(&i_s, c, block);
+    }
+
+    /// Members that are defined in `if TYPE_CHECKING:` are used for type checking, while the
+    /// runtime definitions in the `else` branch are never checked. This reports runtime
+    /// definitions that clearly do not match, e.g. a literal of the wrong type.
+    fn check_type_checking_overrides(&self, i_s: &InferenceState, class: Class, block: Block) {
+        if !self.flags().is_error_code_enabled("type-checking-mismatch") {
+            return;
+        }
+        let db = i_s.db;
+        for stmt_like in block.iter_stmt_likes() {
+            let StmtLikeContent::IfStmt(if_stmt) = stmt_like.node else {
+                continue;
+            };
+            let mut blocks = if_stmt.iter_blocks();
+            let Some(first_block) = blocks.next() else {
+                continue;
+            };
+            if self
+                .point(first_block.first_leaf_index())
+                .maybe_calculated_and_specific()
+                != Some(Specific::IfBranchAlwaysReachableInTypeCheckingBlock)
+            {
+                continue;
+            }
+            let IfBlockType::If(_, type_checking_block) = first_block else {
+                unreachable!()
+            };
+            for runtime_block in blocks {
+                let runtime_block = match runtime_block {
+                    IfBlockType::If(_, block) => block,
+                    IfBlockType::Else(else_block) => else_block.block(),
+                };
+                for stmt_like in runtime_block.iter_stmt_likes() {
+                    let (name, runtime) = match stmt_like.node {
+                        StmtLikeContent::FunctionDef(func) => (func.name(), None),
+                        StmtLikeContent::ClassDef(cls) => (cls.name(), None),
+                        StmtLikeContent::Assignment(assignment) => {
+                            match assignment.maybe_simple_type_expression_assignment() {
+                                Some((name_def, None, expr)) if expr.is_literal_value() => {
+                                    (name_def.name(), Some(expr))
+                                }
+                                _ => continue,
+                            }
+                        }
+                        _ => continue,
+                    };
+                    // Only members that are defined in the `if TYPE_CHECKING:` block are compared.
+                    let Some(index) = class
+                        .class_storage
+                        .class_symbol_table
+                        .lookup_symbol(name.as_code())
+                    else {
+                        continue;
+                    };
+                    let definition = NodeRef::new(self.file, index);
+                    if !(type_checking_block.start()..type_checking_block.end())
+                        .contains(&definition.node_start_position())
+                    {
+                        continue;
+                    }
+                    let declared = definition.infer_name_of_definition_by_index(i_s);
+                    let declared = declared.as_cow_type(i_s);
+                    if matches!(declared.as_ref(), Type::Any(_)) {
+                        continue;
+                    }
+                    let runtime = match runtime {
+                        Some(expr) => {
+                            let t = self.infer_expression(expr).as_type(i_s);
+                            if declared.is_simple_super_type_of(i_s, &t).bool() {
+                                continue;
+                            }
+                            format!(
+                                r#"type "{}""#,
+                                t.avoid_implicit_literal(db).format_short(db)
+                            )
+                        }
+                        // Functions and classes are callable, which is only a problem if the
+                        // member is not.
+                        None if declared.maybe_callable(i_s).is_some() => continue,
+                        None => match stmt_like.node {
+                            StmtLikeContent::FunctionDef(_) => "a function".to_string(),
+                            _ => "a class".to_string(),
+                        },
+                    };
+                    self.add_issue(
+                        name.index(),
+                        IssueKind::TypeCheckingMismatch {
+                            name: name.as_code().into(),
+                            runtime: runtime.into(),
+                            declared: declared.format_short(db),
+                        },
+                    );
+                }
+            }
+        }
     }
 
     pub fn ensure_class_diagnostics(&self, class_node_ref: ClassNodeRef) {
@@ -1029,6 +1472,39 @@ impl Inference<'_, '_, '_> {
                 )
             }
         }
+        if !db.project.settings.mypy_compatible
+            && !matches!(class_infos.class_kind, ClassKind::Protocol)
+            && let Some(eq_index) = c.class_storage.class_symbol_table.lookup_symbol("__eq__")
+            && c.class_storage
+                .class_symbol_table
+                .lookup_symbol("__hash__")
+                .is_none()
+        {
+            // Defining __eq__ implicitly sets __hash__ to None.
+            let hashable_base = c.mro(db).find_map(|(_, base)| match base {
+                TypeOrClass::Class(base)
+                    if base.node_ref != c.node_ref
+                        && !base.is_object_class(db)
+                        && base
+                            .class_storage
+                            .class_symbol_table
+                            .lookup_symbol("__hash__")
+                            .is_some() =>
+                {
+                    Some(base.name().into())
+                }
+                _ => None,
+            });
+            if let Some(base_class) = hashable_base {
+                NodeRef::new(self.file, eq_index).add_issue(
+                    self.i_s,
+                    IssueKind::EqWithoutHashMakesUnhashable {
+                        class: c.name().into(),
+                        base_class,
+                    },
+                )
+            }
+        }
         if matches!(class_infos.class_kind, ClassKind::Protocol) {
             for (name, name_index) in c.class_storage.self_symbol_table.iter() {
                 if c.class_storage
@@ -1055,6 +1531,41 @@ impl Inference<'_, '_, '_> {
                 check_protocol_type_var_variances(self.i_s, c)
             }
         }
+        if self.flags().disallow_untyped_attributes {
+            self.check_untyped_attributes(c)
+        }
+    }
+
+    fn check_untyped_attributes(&self, c: Class) {
+        for (name, name_index) in c.class_storage.self_symbol_table.iter() {
+            let from = NodeRef::new(self.file, *name_index);
+            if !is_unannotated_self_attribute(from)
+                || c.class_storage
+                    .class_symbol_table
+                    .lookup_symbol(name)
+                    .is_some()
+            {
+                continue;
+            }
+            let in_bases = c
+                .instance()
+                .lookup(
+                    self.i_s,
+                    name,
+                    InstanceLookupOptions::new(&|_| ()).with_skip_first_of_mro(self.i_s.db, &c),
+                )
+                .lookup
+                .is_some();
+            if !in_bases {
+                from.add_issue(
+                    self.i_s,
+                    IssueKind::UntypedAttribute {
+                        name: name.into(),
+                        class: c.name().into(),
+                    },
+                )
+            }
+        }
     }
 
     fn check_class_keyword_params(&self, c: Class, arguments: Option<Arguments>) {
@@ -1303,6 +1814,9 @@ impl Inference<'_, '_, '_> {
             return Err(());
         }
         body_ref.set_point(Point::new_calculating());
+        let outer_deadline = self.flags().function_timeout.map(|seconds| {
+            FUNCTION_DEADLINE.replace(Some((Instant::now() + Duration::from_secs(seconds), false)))
+        });
         FLOW_ANALYSIS.with(|fa| {
             let unreachable = fa.with_new_func_frame_and_return_unreachable(self.i_s.db, || {
                 if func_node.is_empty_generator_function() {
@@ -1320,6 +1834,9 @@ impl Inference<'_, '_, '_> {
             };
             body_ref.set_point(Point::new_specific(specific, Locality::Todo));
         });
+        if let Some(outer_deadline) = outer_deadline {
+            FUNCTION_DEADLINE.set(outer_deadline)
+        }
         Ok(())
     }
 
@@ -1329,6 +1846,9 @@ impl Inference<'_, '_, '_> {
         let i_s = self.i_s;
 
         let (name_def, type_params, params, return_annotation, body) = function.node().unpack();
+        if self.file.is_stub() && self.flags().is_error_code_enabled("stub-lint") {
+            self.check_stub_function(function);
+        }
 
         let mut is_overload_member = false;
         if let Some(ComplexPoint::FunctionOverload(o)) = function.node_ref.maybe_complex() {
@@ -1783,7 +2303,8 @@ impl Inference<'_, '_, '_> {
                 }
                 "exit" => {
                     // Check the return type of __exit__
-                    self.check_magic_exit(function)
+                    self.check_magic_exit(function);
+                    self.check_dunder_contract(function, magic_name);
                 }
                 "getattr" => {
                     let func_type = function.as_type(i_s, FirstParamProperties::None);
@@ -1809,11 +2330,90 @@ impl Inference<'_, '_, '_> {
                     // Check reverse magic methods like __rmul__
                     self.check_overlapping_op_methods(function, magic_name);
                     self.check_inplace_methods(function, magic_name);
+                    self.check_dunder_contract(function, magic_name);
                 }
             }
         }
     }
 
+    fn check_dunder_contract(&self, function: Function, magic_name: &str) {
+        // Mypy only checks dunder methods that are defined on object (through override checks).
+        // Python however also relies on the signatures of a few other dunder methods.
+        let i_s = self.i_s;
+        let db = i_s.db;
+        if db.project.settings.mypy_compatible {
+            return;
+        }
+        if matches!(magic_name, "exit" | "aexit") && !exit_accepts_exception_args(function.node()) {
+            function.add_issue_for_declaration(
+                i_s,
+                IssueKind::InvalidExitSignature {
+                    name: function.name().into(),
+                },
+            );
+        }
+        let Some(return_annotation) = function.return_annotation() else {
+            return;
+        };
+        if function.is_async() {
+            return;
+        }
+        let s = &db.python_state;
+        let return_type = function.return_type(i_s);
+        let is_valid = |expected: &Type| {
+            return_type.iter_with_unpacked_unions(db).all(|t| {
+                matches!(t, Type::Never(_)) || expected.is_simple_super_type_of(i_s, t).bool()
+            })
+        };
+        let expected = match magic_name {
+            "exit" => {
+                if return_type.iter_with_unpacked_unions(db).all(|t| {
+                    matches!(t, Type::None | Type::Any(_) | Type::Never(_))
+                        || s.bool_type().is_simple_super_type_of(i_s, t).bool()
+                }) {
+                    return;
+                }
+                "bool | None".into()
+            }
+            "iter" => {
+                if return_type
+                    .iter_with_unpacked_unions(db)
+                    .any(|t| matches!(t, Type::Self_))
+                {
+                    // Returning Self is very common for iterators and if __next__ is missing,
+                    // there are already errors when iterating.
+                    return;
+                }
+                let iterator = new_class!(s.iterator_link(), Type::Any(AnyCause::Explicit));
+                if is_valid(&iterator) {
+                    return;
+                }
+                iterator.format_short(db)
+            }
+            _ => {
+                let expected = match magic_name {
+                    "bool" => s.bool_type(),
+                    "len" | "index" | "int" => s.int_type(),
+                    "float" => s.float_type(),
+                    "bytes" => s.bytes_type(),
+                    _ => return,
+                };
+                if is_valid(&expected) {
+                    return;
+                }
+                expected.format_short(db)
+            }
+        };
+        self.add_issue(
+            return_annotation.expression().index(),
+            IssueKind::InvalidDunderReturnType {
+                name: function.name().into(),
+                got: return_type.format_short(db),
+                expected,
+            },
+        )
+    }
+
     fn calc_overload_implementation_diagnostics(
         &self,
         overload_item: &CallableContent,
@@ -1887,6 +2487,19 @@ impl Inference<'_, '_, '_> {
                 if let Some(star_exprs) = return_stmt.star_expressions() {
                     let inf =
                         self.infer_star_expressions(star_exprs, &mut ResultContext::new_known(&t));
+                    if inf.is_not_implemented(i_s) {
+                        if !i_s.db.project.settings.mypy_compatible
+                            && !is_binary_dunder_method(func.name())
+                        {
+                            self.add_issue(
+                                star_exprs.index(),
+                                IssueKind::NotImplementedOutsideOfBinaryDunder {
+                                    name: func.name().into(),
+                                },
+                            );
+                        }
+                        return;
+                    }
                     if self.flags().warn_return_any
                         && inf.as_cow_type(i_s).is_any()
                         && t.as_ref() != &i_s.db.python_state.object_type()
@@ -2207,6 +2820,72 @@ impl Inference<'_, '_, '_> {
     }
 }
 
+fn is_simple_stub_default(default: Expression) -> bool {
+    // Like flake8-pyi, this allows `...`, None, bools, numbers and short strings.
+    let atom = match default.unpack() {
+        ExpressionContent::ExpressionPart(ExpressionPart::Atom(atom)) => atom,
+        ExpressionContent::ExpressionPart(ExpressionPart::Factor(factor)) => {
+            let (operator, part) = factor.unpack();
+            return operator.as_code() == "-"
+                && matches!(part, ExpressionPart::Atom(atom) if is_short_number(atom));
+        }
+        _ => return false,
+    };
+    match atom.unpack() {
+        AtomContent::Ellipsis | AtomContent::NoneLiteral | AtomContent::Bool(_) => true,
+        AtomContent::Int(_) | AtomContent::Float(_) | AtomContent::Complex(_) => {
+            is_short_number(atom)
+        }
+        AtomContent::Strings(_) | AtomContent::Bytes(_) => atom.as_code().len() <= 50,
+        _ => false,
+    }
+}
+
+fn is_short_number(atom: Atom) -> bool {
+    matches!(
+        atom.unpack(),
+        AtomContent::Int(_) | AtomContent::Float(_) | AtomContent::Complex(_)
+    ) && atom.as_code().len() <= 10
+}
+
+fn overload_item_decorators<'db>(db: &'db Database, c: &CallableContent) -> Vec<&'db str> {
+    // `@overload` and `@deprecated` are expected to differ. Inconsistent `@staticmethod` and
+    // `@classmethod` decorators are reported in all files.
+    let Some(decorated) = NodeRef::from_link(db, c.defined_at)
+        .maybe_function()
+        .and_then(|func| func.maybe_decorated())
+    else {
+        return vec![];
+    };
+    let mut decorators: Vec<_> = decorated
+        .decorators()
+        .iter()
+        .map(|decorator| decorator.named_expression().as_code())
+        .filter(|code| {
+            let callee = code.split('(').next().unwrap();
+            let name = callee.rsplit('.').next().unwrap().trim();
+            !matches!(
+                name,
+                "overload" | "deprecated" | "staticmethod" | "classmethod"
+            )
+        })
+        .collect();
+    decorators.sort_unstable();
+    decorators
+}
+
+/// Binary dunders are allowed to return `NotImplemented` to fall back to the reflected operation.
+pub(crate) fn is_binary_dunder_method(name: &str) -> bool {
+    if FORWARD_OP_METHODS.contains(name) {
+        return true;
+    }
+    name.strip_prefix("__")
+        .and_then(|n| n.strip_suffix("__"))
+        .is_some_and(|n| {
+            REVERSE_OP_METHODS.contains(n) || INPLACE_TO_NORMAL_METHODS.contains_key(n)
+        })
+}
+
 fn valid_raise_type(i_s: &InferenceState, from: NodeRef, t: &Type, allow_none: bool) -> bool {
     let db = i_s.db;
     let check = |cls: Class| cls.incomplete_mro(db) || cls.is_base_exception(db);
@@ -2392,9 +3071,7 @@ fn find_and_check_override(
             && from
                 .file
                 .flags(i_s.db)
-                .enabled_error_codes
-                .iter()
-                .any(|c| c == "explicit-override")
+                .is_error_code_enabled("explicit-override")
         {
             from.add_issue(
                 i_s,
@@ -2698,7 +3375,25 @@ pub(super) fn check_override(
                         expected: original_t.format_short(i_s.db),
                         base_class: original_class_name(i_s.db, &original_class).into(),
                     },
-                )
+                );
+                if !i_s.db.project.settings.mypy_compatible
+                    && matches!(
+                        original_t.as_ref(),
+                        Type::Callable(_) | Type::FunctionOverload(_)
+                    )
+                    && override_t.maybe_callable(i_s).is_none()
+                {
+                    from.add_issue(
+                        i_s,
+                        IssueKind::Note(
+                            format!(
+                                r#""{name}" shadows a method of base class "{}" with a non-callable value"#,
+                                original_class_name(i_s.db, &original_class),
+                            )
+                            .into(),
+                        ),
+                    )
+                }
             }
         }
         if !emitted {
@@ -3092,6 +3787,76 @@ pub fn check_multiple_inheritance<'x, BASES: Iterator<Item = TypeOrClass<'x>>>(
     }
 }
 
+fn exit_accepts_exception_args(func: FunctionDef) -> bool {
+    let mut positional = 0;
+    let mut required_positional = 0;
+    let mut has_star_args = false;
+    // The first param is self.
+    for param in func.params().iter().skip(1) {
+        match param.kind() {
+            ParamKind::PositionalOnly | ParamKind::PositionalOrKeyword => {
+                positional += 1;
+                if param.default().is_none() {
+                    required_positional += 1;
+                }
+            }
+            ParamKind::Star => has_star_args = true,
+            ParamKind::KeywordOnly => {
+                if param.default().is_none() {
+                    return false;
+                }
+            }
+            ParamKind::StarStar => (),
+        }
+    }
+    required_positional <= 3 && (positional >= 3 || has_star_args)
+}
+
+fn should_check_unused_params(func: FunctionDef) -> bool {
+    // Parameters are often required by an interface. Dunder methods, overrides, overloads and
+    // functions with an empty body are therefore never checked.
+    let name = func.name().as_code();
+    if name.starts_with("__") && name.ends_with("__") {
+        return false;
+    }
+    if matches!(
+        func.trivial_body_state(),
+        TrivialBodyState::Known(true) | TrivialBodyState::RaiseExpr(_)
+    ) {
+        return false;
+    }
+    if let Some(decorated) = func.maybe_decorated() {
+        for decorator in decorated.decorators().iter() {
+            let code = decorator.named_expression().as_code();
+            let code = code.split('(').next().unwrap_or(code);
+            let last = code.rsplit('.').next().unwrap_or(code).trim();
+            if matches!(last, "override" | "overload" | "abstractmethod") {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn is_first_param_of_method(func: FunctionDef, name: Name) -> bool {
+    matches!(func.parent_scope(), Scope::Class(_))
+        && func
+            .params()
+            .iter()
+            .next()
+            .is_some_and(|param| param.name_def().name_index() == name.index())
+}
+
+fn is_future_import(import: &NameImportParent) -> bool {
+    match import {
+        NameImportParent::ImportFromAsName(imp) => imp.import_from().is_some_and(|import_from| {
+            let (level, dotted_name) = import_from.level_with_dotted_name();
+            level == 0 && dotted_name.is_some_and(|n| n.as_code() == "__future__")
+        }),
+        NameImportParent::DottedAsName(_) => false,
+    }
+}
+
 #[inline]
 fn check_for_missing_annotations(
     i_s: &InferenceState,
@@ -53,7 +53,7 @@ use super::{
     inference::{AssignKind, Inference, instantiate_except},
     name_binder::{Truthiness, is_expr_part_reachable_for_name_binder},
     on_argument_type_error,
-    utils::func_of_self_symbol,
+    utils::{func_of_self_symbol, is_unannotated_self_attribute},
 };
 
 type Entries = Vec<Entry>;
@@ -505,6 +505,13 @@ impl FlowAnalysis {
                                 return None;
                             }
                         }
+                        if is_rebound_by_nonlocal_after(
+                            func_node_ref.file,
+                            link.node_index,
+                            func_node_ref.node_index,
+                        ) {
+                            return None;
+                        }
                         Some(entry.clone())
                     })
                 })
@@ -576,12 +583,16 @@ impl FlowAnalysis {
             drop(tos_frame);
             // Currently we don't recheck loops so we should not report unreachable frames, because
             // they might just be fine if types are widened for example.
-            if self.loop_details.borrow().is_none() {
+            if !self.is_in_loop() {
                 callback()
             }
         }
     }
 
+    pub fn is_in_loop(&self) -> bool {
+        self.loop_details.borrow().is_some()
+    }
+
     fn merge_conditional(
         &self,
         i_s: &InferenceState,
@@ -1264,6 +1275,18 @@ fn split_off_singleton(
     Some((truthy, falsey))
 }
 
+/// Checks if a nested function uses `nonlocal` to assign to the name after the given node.
+fn is_rebound_by_nonlocal_after(
+    file: &PythonFile,
+    name_index: NodeIndex,
+    after: NodeIndex,
+) -> bool {
+    file.nonlocal_names.iter().any(|nonlocal| {
+        nonlocal.binds == name_index
+            && OtherDefinitionIterator::new(&file.points, nonlocal.name).any(|index| index > after)
+    })
+}
+
 fn narrow_is_or_eq(
     i_s: &InferenceState,
     key: FlowKey,
@@ -1325,36 +1348,7 @@ fn narrow_is_or_eq(
             if is_eq && (!literal1.implicit || has_explicit_literal(i_s.db, checking_t))
                 || !is_eq && matches!(literal1.kind, LiteralKind::Bool(_)) =>
         {
-            let mut true_type = Type::Never(NeverCause::Other);
-            let mut false_type = Type::Never(NeverCause::Other);
-            let true_literal = || {
-                let mut new_literal = literal1.clone();
-                new_literal.implicit = false;
-                Type::Literal(new_literal)
-            };
-            for sub_t in checking_t.iter_with_unpacked_unions(i_s.db) {
-                match sub_t {
-                    Type::Literal(literal2) if literal1.value(i_s.db) == literal2.value(i_s.db) => {
-                        true_type.union_in_place(true_literal())
-                    }
-                    _ => {
-                        if let Some((truthy, falsey)) =
-                            maybe_split_bool_from_literal(i_s.db, sub_t, &literal1.kind)
-                        {
-                            true_type.union_in_place(truthy);
-                            false_type.union_in_place(falsey);
-                            continue;
-                        }
-                        if has_custom_eq(i_s, sub_t) {
-                            return None;
-                        }
-                        if sub_t.is_simple_super_type_of(i_s, other_t).bool() {
-                            true_type.union_in_place(other_t.clone())
-                        }
-                        false_type.union_in_place(sub_t.clone())
-                    }
-                }
-            }
+            let (true_type, false_type) = split_off_literal(i_s, checking_t, literal1)?;
             Some((
                 Frame::from_type(key.clone(), true_type),
                 Frame::from_type(key, false_type),
@@ -1388,6 +1382,92 @@ fn narrow_is_or_eq(
     }
 }
 
+fn split_off_literal(
+    i_s: &InferenceState,
+    checking_t: &Type,
+    literal: &Literal,
+) -> Option<(Type, Type)> {
+    let mut true_type = Type::Never(NeverCause::Other);
+    let mut false_type = Type::Never(NeverCause::Other);
+    let other_t = Type::Literal(literal.clone());
+    let true_literal = || {
+        let mut new_literal = literal.clone();
+        new_literal.implicit = false;
+        Type::Literal(new_literal)
+    };
+    for sub_t in checking_t.iter_with_unpacked_unions(i_s.db) {
+        match sub_t {
+            Type::Literal(literal2) if literal.value(i_s.db) == literal2.value(i_s.db) => {
+                true_type.union_in_place(true_literal())
+            }
+            _ => {
+                if let Some((truthy, falsey)) =
+                    maybe_split_bool_from_literal(i_s.db, sub_t, &literal.kind)
+                {
+                    true_type.union_in_place(truthy);
+                    false_type.union_in_place(falsey);
+                    continue;
+                }
+                if has_custom_eq(i_s, sub_t) {
+                    return None;
+                }
+                if sub_t.is_simple_super_type_of(i_s, &other_t).bool() {
+                    true_type.union_in_place(other_t.clone())
+                }
+                false_type.union_in_place(sub_t.clone())
+            }
+        }
+    }
+    Some((true_type, false_type))
+}
+
+fn split_off_literal_items(
+    i_s: &InferenceState,
+    checking_t: &Type,
+    items: &[Type],
+) -> Option<(Type, Type)> {
+    // `x in ("a", Color.RED)` works like `x == "a" or x == Color.RED`, so the items are split
+    // off one after another.
+    let mut truthy = Type::Never(NeverCause::Other);
+    let mut falsey = checking_t.clone();
+    for item in items {
+        let (true_t, false_t) = match item {
+            Type::Literal(literal) => {
+                let mut literal = literal.clone();
+                literal.implicit = false;
+                if matches_only_via_promotion(i_s, &falsey, &literal) {
+                    // `x: float` with `x in (1, 2)` could still be `1.0`.
+                    return None;
+                }
+                split_off_literal(i_s, &falsey, &literal)?
+            }
+            Type::EnumMember(member) => {
+                let mut member = member.clone();
+                member.implicit = false;
+                split_off_enum_member(i_s, &falsey, &member, true)?
+            }
+            _ => return None,
+        };
+        truthy.union_in_place(true_t);
+        falsey = false_t;
+    }
+    if matches!(truthy, Type::Never(_)) {
+        // Non-overlapping container checks are reported elsewhere.
+        return None;
+    }
+    Some((truthy, falsey))
+}
+
+fn matches_only_via_promotion(i_s: &InferenceState, t: &Type, literal: &Literal) -> bool {
+    let literal_t = Type::Literal(literal.clone());
+    t.iter_with_unpacked_unions(i_s.db).any(|sub_t| {
+        sub_t.is_simple_super_type_of(i_s, &literal_t).bool()
+            && !sub_t
+                .is_super_type_of(i_s, &mut Matcher::with_ignored_promotions(), &literal_t)
+                .bool()
+    })
+}
+
 fn maybe_split_bool_from_literal(
     db: &Database,
     t: &Type,
@@ -1879,11 +1959,34 @@ impl Inference<'_, '_, '_> {
                 return Err(());
             }
         }
-        Ok(Some(
-            self.file
-                .inference(i_s)
-                .infer_name_of_definition_by_index(self_symbol),
-        ))
+        let inference = self.file.inference(i_s);
+        let first = inference.infer_name_of_definition_by_index(self_symbol);
+        if !self.flags().union_untyped_attributes || !is_unannotated_self_attribute(name_node_ref) {
+            return Ok(Some(first));
+        }
+        // Widening assignments save their own type (see widens_untyped_self_attribute), the
+        // attribute is the union of all of them.
+        let mut result = first;
+        for other in OtherDefinitionIterator::new(&self.file.points, self_symbol) {
+            let other_func_def = func_of_self_symbol(self.file, other);
+            let other_func =
+                Function::new(NodeRef::new(self.file, other_func_def.index()), Some(c));
+            if !matches!(other_func.first_param_kind(self.i_s), FirstParamKind::Self_) {
+                continue;
+            }
+            if other_func_def.index() != func_def.index() {
+                // Errors are fine here, the function might already be calculating.
+                let _ = FLOW_ANALYSIS
+                    .with(|fa| self.ensure_func_diagnostics_for_self_attribute(fa, other_func));
+            }
+            let other_name_def = NodeRef::new(self.file, other).name_def_ref_of_name();
+            if other_name_def.point().calculated()
+                && let Some(inf) = inference.check_point_cache(other_name_def.node_index)
+            {
+                result = result.simplified_union(i_s, inf);
+            }
+        }
+        Ok(Some(result))
     }
 
     fn ensure_func_diagnostics_for_self_attribute(
@@ -1941,10 +2044,11 @@ impl Inference<'_, '_, '_> {
 
             let false_frame = fa.with_frame(false_frame, || {
                 else_inf = Some(
-                    if let Some(if_inf) = if_inf
-                        .as_ref()
-                        .filter(|_| !use_else_context && !result_context.has_explicit_type())
-                    {
+                    if let Some(if_inf) = if_inf.as_ref().filter(|_| {
+                        !use_else_context
+                            && !result_context.has_explicit_type()
+                            && !self.keeps_literal_precision(else_.maybe_unpacked_atom())
+                    }) {
                         // Mypy passes the context without literals here.
                         self.infer_expression_with_context(
                             else_,
@@ -2099,6 +2203,10 @@ impl Inference<'_, '_, '_> {
             .point(if_block.first_leaf_index())
             .maybe_calculated_and_specific();
         if name_binder_check == Some(Specific::IfBranchAfterAlwaysReachableInNameBinder) {
+            self.add_unreachable_block(match if_block {
+                IfBlockType::If(_, block) => block,
+                IfBlockType::Else(else_block) => else_block.block(),
+            });
             return self.process_ifs(if_blocks, class, func);
         }
 
@@ -2119,6 +2227,7 @@ impl Inference<'_, '_, '_> {
                         self.process_ifs(if_blocks, class, func)
                     }
                     Some(Specific::IfBranchAlwaysUnreachableInNameBinder) => {
+                        self.add_unreachable_block(block);
                         self.process_ifs(if_blocks, class, func)
                     }
                     _ => {
@@ -2486,8 +2595,11 @@ impl Inference<'_, '_, '_> {
         func: Option<&Function>,
     ) {
         let (subject_expr, case_blocks) = match_stmt.unpack();
+        let mut check_exhaustiveness =
+            !self.is_unreachable() && self.flags().is_error_code_enabled("exhaustive-match");
         let (subject_key, inf) = match subject_expr.unpack() {
             SubjectExprContent::NamedExpression(ne) => {
+                check_exhaustiveness &= self.is_annotated_name(ne);
                 let k = self.key_from_namedexpression(ne);
                 (
                     k.key.map(|key| SubjectKey::Expr {
@@ -2497,26 +2609,45 @@ impl Inference<'_, '_, '_> {
                     k.inf,
                 )
             }
-            SubjectExprContent::Tuple(iterator) => (
-                None,
-                self.infer_tuple_iterator(iterator, &mut ResultContext::Unknown),
-            ),
+            SubjectExprContent::Tuple(iterator) => {
+                check_exhaustiveness = false;
+                (
+                    None,
+                    self.infer_tuple_iterator(iterator, &mut ResultContext::Unknown),
+                )
+            }
         };
-        self.process_match_cases(inf, subject_key.as_ref(), case_blocks, class, func);
+        self.process_match_cases(
+            match_stmt,
+            inf,
+            check_exhaustiveness,
+            subject_key.as_ref(),
+            case_blocks,
+            class,
+            func,
+        );
     }
 
     fn process_match_cases<'x>(
         &self,
+        match_stmt: MatchStmt,
         subject: Inferred,
+        check_exhaustiveness: bool,
         subject_key: Option<&SubjectKey>,
         mut case_blocks: impl Iterator<Item = CaseBlock<'x>>,
         class: Option<Class>,
         func: Option<&Function>,
     ) {
         let Some(case_block) = case_blocks.next() else {
+            if check_exhaustiveness {
+                self.check_match_exhaustiveness(match_stmt, subject);
+            }
             return;
         };
         let (case_pattern, guard, block) = case_block.unpack();
+        // Values that match the pattern of a case with a guard are not necessarily handled and
+        // are therefore still part of the subject of the next case.
+        let guarded_subject = guard.is_some().then(|| subject.clone());
         let frames = self.find_guards_in_case_pattern(subject, subject_key, case_pattern);
         FLOW_ANALYSIS.with(|fa| {
             let (mut truthy_frame, mut falsey_frame) =
@@ -2540,12 +2671,64 @@ impl Inference<'_, '_, '_> {
                 self.calc_block_diagnostics(block, class, func)
             });
             let false_frame = fa.with_frame(falsey_frame, || {
-                self.process_match_cases(frames.falsey_t, subject_key, case_blocks, class, func)
+                self.process_match_cases(
+                    match_stmt,
+                    guarded_subject.unwrap_or(frames.falsey_t),
+                    check_exhaustiveness,
+                    subject_key,
+                    case_blocks,
+                    class,
+                    func,
+                )
             });
             fa.merge_conditional(self.i_s, true_frame, false_frame);
         });
     }
 
+    fn is_annotated_name(&self, named_expr: NamedExpression) -> bool {
+        // Only the exhaustiveness of annotated subjects is checked, inferred types are often
+        // not precise enough.
+        let NamedExpressionContent::Expression(expr) = named_expr.unpack() else {
+            return false;
+        };
+        let Some(AtomContent::Name(name)) = expr.maybe_unpacked_atom() else {
+            return false;
+        };
+        let p = self.point(name.index());
+        if !p.calculated()
+            || p.kind() != PointKind::Redirect
+            || p.file_index() != self.file.file_index
+        {
+            return false;
+        }
+        let first = p.node_index();
+        std::iter::once(first)
+            .chain(OtherDefinitionIterator::new(&self.file.points, first))
+            .any(|index| {
+                Name::by_index(&self.file.tree, index)
+                    .name_def()
+                    .is_some_and(|name_def| {
+                        name_def.maybe_param_annotation().is_some()
+                            || name_def
+                                .maybe_assignment_definition()
+                                .is_some_and(|assignment| assignment.maybe_annotation().is_some())
+                    })
+            })
+    }
+
+    fn check_match_exhaustiveness(&self, match_stmt: MatchStmt, unhandled: Inferred) {
+        let unhandled = unhandled.as_cow_type(self.i_s);
+        // Nothing is known about Any, so it is not reported.
+        if !matches!(unhandled.as_ref(), Type::Never(_) | Type::Any(_)) {
+            self.add_issue(
+                match_stmt.index(),
+                IssueKind::MatchIsNotExhaustive {
+                    type_: unhandled.format_short(self.i_s.db),
+                },
+            )
+        }
+    }
+
     fn check_conjunction(
         &self,
         and: Conjunction,
@@ -2653,10 +2836,16 @@ impl Inference<'_, '_, '_> {
             left_frames.falsey = FLOW_ANALYSIS.with(|fa| {
                 let left_t = left_inf.as_cow_type(self.i_s);
                 fa.with_frame(left_frames.falsey, || {
-                    right_infos = Some(self.find_guards_in_expression_parts_with_context(
-                        right,
-                        &mut ResultContext::new_known(&left_t),
-                    ));
+                    right_infos = Some(
+                        if self.keeps_literal_precision(right.maybe_unpacked_atom()) {
+                            self.find_guards_in_expression_parts_with_context(right, result_context)
+                        } else {
+                            self.find_guards_in_expression_parts_with_context(
+                                right,
+                                &mut ResultContext::new_known(&left_t),
+                            )
+                        },
+                    );
                 })
             });
         }
@@ -2677,6 +2866,25 @@ impl Inference<'_, '_, '_> {
         (inf, left_frames, right_frames)
     }
 
+    /// Mypy infers the second branch of ternaries and `or` defaults with the type of the first
+    /// branch as context, which widens literals (`1 if c else 2` becomes `int`). Without joins we
+    /// infer literals on their own, so the resulting union keeps them.
+    fn keeps_literal_precision(&self, atom: Option<AtomContent>) -> bool {
+        !self.flags().use_joins
+            && matches!(
+                atom,
+                Some(
+                    AtomContent::Int(_)
+                        | AtomContent::Float(_)
+                        | AtomContent::Complex(_)
+                        | AtomContent::Strings(_)
+                        | AtomContent::Bytes(_)
+                        | AtomContent::Bool(_)
+                        | AtomContent::NoneLiteral
+                )
+            )
+    }
+
     #[inline]
     fn maybe_propagate_parent_union(
         &self,
@@ -4163,6 +4371,39 @@ impl Inference<'_, '_, '_> {
             }
         };
         let db = self.i_s.db;
+        if !db.project.settings.mypy_compatible
+            && let Some(ComparisonKey::Normal(left_key)) = &left.key
+        {
+            // Containers of literals narrow the left side to these literals. Only fixed length
+            // tuples contain all of their items, so other containers only narrow the positive
+            // branch.
+            let right_t = right.inf.as_cow_type(self.i_s);
+            let (items, narrows_negative) = match right_t.as_ref() {
+                Type::Tuple(tup) => match &tup.args {
+                    TupleArgs::FixedLen(items) => (items.to_vec(), true),
+                    _ => (vec![], false),
+                },
+                t => (
+                    stdlib_container_item(db, t)
+                        .map(|item| item.iter_with_unpacked_unions(db).cloned().collect())
+                        .unwrap_or_default(),
+                    false,
+                ),
+            };
+            if !items.is_empty()
+                && let Some((truthy, falsey)) =
+                    split_off_literal_items(self.i_s, &left.inf.as_cow_type(self.i_s), &items)
+            {
+                return maybe_invert(
+                    Frame::from_type(left_key.clone(), truthy),
+                    match narrows_negative {
+                        true => Frame::from_type(left_key.clone(), falsey),
+                        false => Frame::new_conditional(),
+                    },
+                    left.parent_unions.take(),
+                );
+            }
+        }
         if let Some(container_item) = stdlib_container_item(db, &right.inf.as_cow_type(self.i_s))
             && let Some(ComparisonKey::Normal(left_key)) = &left.key
         {
@@ -15,7 +15,7 @@ use crate::{
     inference_state::InferenceState,
     inferred::Inferred,
     node_ref::NodeRef,
-    type_::{LookupResult, Type},
+    type_::{AnyCause, LookupResult, Type},
 };
 
 use super::{PythonFile, python_file::StarImport};
@@ -68,8 +68,14 @@ impl PythonFile {
                     namespace_import_with_unloaded_file(db, self, namespace, name.as_str())
                 }
                 ImportResult::PyTypedMissing => Some(ImportResult::PyTypedMissing),
+                // The error was already added for the compiled parent module.
+                ImportResult::ExtensionModuleWithoutStub(_) => {
+                    return base.import(db, self, name.as_str());
+                }
             };
-            if let Some(imported) = &result {
+            if let Some(ImportResult::ExtensionModuleWithoutStub(module_name)) = &result {
+                self.add_extension_module_without_stub(db, name, module_name);
+            } else if let Some(imported) = &result {
                 debug!(
                     "Imported {:?} for {:?}",
                     imported.debug_info(db),
@@ -99,9 +105,7 @@ impl PythonFile {
                     infer_name(base, name)
                 } else {
                     let result = self.global_import(db, name);
-                    if result.is_none() {
-                        self.add_module_not_found(db, name)
-                    }
+                    self.add_issue_for_failed_global_import(db, name, &result);
                     result
                 }
             }
@@ -138,9 +142,7 @@ impl PythonFile {
         let result = match dotted_as_name.unpack() {
             DottedAsNameContent::Simple(name_def, rest) => {
                 let result = self.global_import(db, name_def.name());
-                if result.is_none() {
-                    self.add_module_not_found(db, name_def.name())
-                }
+                self.add_issue_for_failed_global_import(db, name_def.name(), &result);
                 if let Some(rest) = rest
                     && result.is_some()
                 {
@@ -213,7 +215,7 @@ impl PythonFile {
             Some(ImportResult::Namespace { .. }) => {
                 Point::new_specific(Specific::ModuleNotFound, Locality::Todo)
             }
-            Some(ImportResult::PyTypedMissing) => {
+            Some(ImportResult::PyTypedMissing | ImportResult::ExtensionModuleWithoutStub(_)) => {
                 Point::new_specific(Specific::ModuleNotFound, Locality::Todo)
             }
             None => Point::new_specific(Specific::ModuleNotFound, Locality::Todo),
@@ -241,6 +243,57 @@ impl PythonFile {
         self.star_import_file(db, star_import)
     }
 
+    fn add_issue_for_failed_global_import(
+        &self,
+        db: &Database,
+        name: Name,
+        result: &Option<ImportResult>,
+    ) {
+        match result {
+            None => self.add_module_not_found(db, name),
+            Some(ImportResult::ExtensionModuleWithoutStub(module_name)) => {
+                self.add_extension_module_without_stub(db, name, module_name)
+            }
+            Some(ImportResult::PyTypedMissing) => {
+                if self.disallows_untyped_import(db, name.as_str()) {
+                    NodeRef::new(self, name.index()).add_type_issue(
+                        db,
+                        IssueKind::UntypedImport {
+                            module_name: name.as_str().into(),
+                        },
+                    );
+                }
+            }
+            Some(_) => (),
+        }
+    }
+
+    pub(crate) fn disallows_untyped_import(&self, db: &Database, module_name: &str) -> bool {
+        let flags = self.flags(db);
+        flags.disallow_untyped_imports
+            && !is_in_module_list(&flags.allowed_untyped_imports, module_name)
+    }
+
+    pub(super) fn add_extension_module_without_stub(
+        &self,
+        db: &Database,
+        name: Name,
+        module_name: &str,
+    ) {
+        let flags = self.flags(db);
+        if flags.ignore_missing_imports
+            || is_in_module_list(&flags.untyped_extension_modules, module_name)
+        {
+            return;
+        }
+        NodeRef::new(self, name.index()).add_type_issue(
+            db,
+            IssueKind::ExtensionModuleWithoutStub {
+                module_name: module_name.into(),
+            },
+        );
+    }
+
     pub(super) fn add_module_not_found(&self, db: &Database, name: Name) {
         if !self.flags(db).ignore_missing_imports {
             NodeRef::new(self, name.index()).add_type_issue(
@@ -263,6 +316,7 @@ impl PythonFile {
             ImportResult::Namespace(ns) => {
                 LookupResult::UnknownName(Inferred::from_type(Type::Namespace(ns.clone())))
             }
+            ImportResult::ExtensionModuleWithoutStub(_) => LookupResult::any(AnyCause::FromError),
             ImportResult::PyTypedMissing => unreachable!(),
         })
     }
@@ -347,7 +401,7 @@ impl PythonFile {
                         }
                     }
                 }
-                ImportResult::PyTypedMissing => (),
+                ImportResult::PyTypedMissing | ImportResult::ExtensionModuleWithoutStub(_) => (),
             },
         }
     }
@@ -433,6 +487,16 @@ fn in_partial_stubs(db: &Database, file_entry: &FileEntry) -> bool {
         })
 }
 
+/// Checks if the module is one of the modules or a submodule of one of them.
+fn is_in_module_list(modules: &[String], module_name: &str) -> bool {
+    modules.iter().any(|allowed| {
+        module_name == *allowed
+            || module_name
+                .strip_prefix(allowed.as_str())
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
 pub(super) fn is_package_name(file_entry: &FileEntry) -> bool {
     &*file_entry.name == "__init__.py" || &*file_entry.name == "__init__.pyi"
 }
@@ -443,10 +507,12 @@ fn cache_import_results(node_ref: NodeRef, result: &Option<ImportResult>) {
             node_ref.set_point(Point::new_file_reference(*f, Locality::Complex))
         }
         Some(ImportResult::Namespace(n)) => node_ref.insert_type(Type::Namespace(n.clone())),
-        Some(ImportResult::PyTypedMissing) => node_ref.set_point(Point::new_specific(
-            Specific::AnyDueToError,
-            Locality::Complex,
-        )),
+        Some(ImportResult::PyTypedMissing | ImportResult::ExtensionModuleWithoutStub(_)) => {
+            node_ref.set_point(Point::new_specific(
+                Specific::AnyDueToError,
+                Locality::Complex,
+            ))
+        }
         None => node_ref.set_point(Point::new_specific(
             Specific::ModuleNotFound,
             Locality::Complex,
@@ -9,10 +9,12 @@ use super::{
     name_resolution::{ModuleAccessDetail, NameResolution},
     on_argument_type_error, process_unfinished_partials,
     type_computation::ANNOTATION_TO_EXPR_DIFFERENCE,
-    utils::{func_of_self_symbol, infer_dict_like},
+    utils::{func_of_self_symbol, infer_dict_like, is_unannotated_self_attribute},
 };
 use crate::{
-    arguments::{Args, InferredArg, KnownArgs, KnownArgsWithCustomAddIssue, NoArgs, SimpleArgs},
+    arguments::{
+        Arg, ArgKind, Args, InferredArg, KnownArgs, KnownArgsWithCustomAddIssue, NoArgs, SimpleArgs,
+    },
     database::{ComplexPoint, Database, Locality, Mode, Point, PointKind, PointLink, Specific},
     debug,
     diagnostics::{Issue, IssueKind},
@@ -36,14 +38,16 @@ use crate::{
     params::matches_simple_params,
     recoverable_error,
     type_::{
-        AnyCause, CallableContent, CallableParam, CallableParams, DbString, IterCause, IterInfos,
-        Literal, LiteralKind, LookupResult, NeverCause, ParamType, StarParamType,
-        StarStarParamType, StringSlice, Tuple, TupleArgs, TupleUnpack, Type, UnionEntry, UnionType,
-        Variance, WithUnpack, dataclass_converter_fields_lookup,
+        AnyCause, CallableContent, CallableLike, CallableParam, CallableParams, DbString,
+        Intersection, IterCause, IterInfos, Literal, LiteralKind, LookupResult, NeverCause,
+        ParamType, StarParamType, StarStarParamType, StringSlice, Tuple, TupleArgs, TupleUnpack,
+        Type, UnionEntry, UnionType, Variance, WithUnpack, attrs_evolve,
+        dataclass_converter_fields_lookup,
     },
     type_helpers::{
-        Class, ClassLookupOptions, FirstParamKind, Function, GeneratorType, Instance,
+        Callable, Class, ClassLookupOptions, FirstParamKind, Function, GeneratorType, Instance,
         InstanceLookupOptions, LookupDetails, TypeOrClass, cache_class_name, is_private,
+        method_assignment_issue,
     },
     utils::debug_indent,
 };
@@ -1681,10 +1685,7 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
                         c.class(i_s.db)
                             .instance()
                             .check_set_descriptor_and_return_should_narrow(
-                                i_s,
-                                node_ref,
-                                name_def.name(),
-                                value,
+                                i_s, node_ref, name_str, value,
                             )
                             & save_narrowed.get(),
                     );
@@ -1727,10 +1728,7 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
                     } else {
                         save_narrowed.set(
                             inst.check_set_descriptor_and_return_should_narrow(
-                                i_s,
-                                node_ref,
-                                name_def.name(),
-                                value,
+                                i_s, node_ref, name_str, value,
                             ) & save_narrowed.get(),
                         );
                     }
@@ -1787,7 +1785,7 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
                 if let Some(inf) = lookup_details.lookup.maybe_inferred()
                     && inf.as_cow_type(i_s).is_func_or_overload_not_any_callable()
                 {
-                    from.add_issue(i_s, IssueKind::CannotAssignToAMethod);
+                    from.add_issue(i_s, method_assignment_issue(i_s, name_str));
                 }
                 lookup = lookup_details.lookup;
                 attr_kind = lookup_details.attr_kind;
@@ -1807,6 +1805,17 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
             });
             let inf = lookup.into_inferred();
             let mut declaration_t = inf.as_cow_type(i_s);
+            if matches!(t, Type::Module(_))
+                && i_s.flags().disallow_monkeypatching
+                && declaration_t.is_func_or_overload_not_any_callable()
+            {
+                from.add_issue(
+                    i_s,
+                    IssueKind::Monkeypatching {
+                        name: name_str.into(),
+                    },
+                );
+            }
             if attr_kind == AttributeKind::Final {
                 from.add_issue(
                     i_s,
@@ -1984,12 +1993,16 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
         save: impl FnOnce(NodeIndex, &Inferred),
     ) {
         let i_s = self.i_s;
+        // Saving is delayed, because a widening assignment saves its own type instead of the
+        // type of the first definition.
+        let pending_save = Cell::new(None);
+        let widens = Cell::new(false);
         self.assign_to_name_def_or_self_name_def(
             name_def,
             from,
             value,
             assign_kind,
-            save,
+            |index, inf| pending_save.set(Some((index, inf.clone()))),
             Some(&|| {
                 in_class.instance().lookup(
                     i_s,
@@ -1998,9 +2011,17 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
                         .with_skip_first_self_variables(),
                 )
             }),
-            |_, declaration_t| {
+            |first_name_link, declaration_t| {
                 let current_t = value.as_cow_type(i_s);
                 self.narrow_or_widen_self_target(primary_target, declaration_t, &current_t, || {
+                    if !declaration_t
+                        .is_simple_super_type_of(i_s, &current_t)
+                        .bool()
+                        && self.widens_untyped_self_attribute(in_class, name_def, first_name_link)
+                    {
+                        widens.set(true);
+                        return RedefinitionResult::RedefinitionAllowed;
+                    }
                     RedefinitionResult::TypeMismatch(self.check_assignment_type(
                         value,
                         declaration_t,
@@ -2011,9 +2032,41 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
                 })
             },
         );
+        if widens.get() {
+            // The union of all assignments is created in self_lookup_with_flow_analysis.
+            let t = value
+                .as_cow_type(i_s)
+                .into_owned()
+                .avoid_implicit_literal(i_s.db);
+            save(name_def.index(), &Inferred::from_type(t));
+        } else if let Some((index, inf)) = pending_save.take() {
+            save(index, &inf);
+        }
         in_class.check_self_definition(i_s, |issue| from.add_issue(i_s, issue), name_def.as_code());
     }
 
+    /// With --union-untyped-attributes an attribute that is never annotated gets the union of all
+    /// the types assigned to it, instead of the type of the first assignment.
+    fn widens_untyped_self_attribute(
+        &self,
+        in_class: Class,
+        name_def: NameDef,
+        first_name_link: PointLink,
+    ) -> bool {
+        let i_s = self.i_s;
+        self.flags().union_untyped_attributes
+            && is_unannotated_self_attribute(NodeRef::from_link(i_s.db, first_name_link))
+            && in_class
+                .instance()
+                .lookup(
+                    i_s,
+                    name_def.as_code(),
+                    InstanceLookupOptions::new(&|_| ()).with_skip_first_self_variables(),
+                )
+                .lookup
+                .is_none()
+    }
+
     pub fn assign_any_to_target(&self, target: Target, n: NodeRef) {
         // Just assign targets in normal mode, so that we have at least assigned
         // something to these names.
@@ -2841,6 +2894,13 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
             has_default: param.default().is_some(),
             might_have_type_vars: true,
         };
+        // If the return type is a type var that is not solved yet, it is solved by the lambda
+        // body, which should therefore not be inferred with an Any context.
+        let return_solved_by_body = matches!(
+            result_context,
+            ResultContext::WithMatcher { matcher, type_: Type::Callable(c) }
+                if matcher.is_uncalculated_type_var(&c.return_type)
+        );
         result_context
             .with_type_if_exists_and_replace_type_var_likes(self.i_s, |type_| {
                 if let Type::Callable(c) = type_ {
@@ -2848,13 +2908,18 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
                     let (params, expr) = lambda.unpack();
                     let inference = self.file.inference(&i_s);
                     check_defaults();
-                    let result = inference.flow_analysis_for_lambda_body(
-                        expr,
-                        // This cannot be ResultContext::Known, because that would imply that a
-                        // value is expected and there would be an error:
-                        //     `does not return a value (it only ever returns None`
-                        &mut ResultContext::KnownLambdaReturn(&c.return_type),
-                    );
+                    let result = if return_solved_by_body {
+                        inference
+                            .flow_analysis_for_lambda_body(expr, &mut ResultContext::ExpectUnused)
+                    } else {
+                        inference.flow_analysis_for_lambda_body(
+                            expr,
+                            // This cannot be ResultContext::Known, because that would imply that a
+                            // value is expected and there would be an error:
+                            //     `does not return a value (it only ever returns None`
+                            &mut ResultContext::KnownLambdaReturn(&c.return_type),
+                        )
+                    };
                     let mut c = (**c).clone();
                     c.guard = None;
 
@@ -3423,12 +3488,369 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
     ) -> Inferred {
         let f = self.file;
         let args = SimpleArgs::new(*self.i_s, f, node_index, details);
-        base.execute_with_details(
+        if base.is_name_defined_in_module(self.i_s.db, "attr", "evolve") {
+            return attrs_evolve(
+                self.i_s,
+                base,
+                &args,
+                result_context,
+                OnTypeError::new(&on_argument_type_error),
+            );
+        }
+        let result = base.execute_with_details(
             self.i_s,
             &args,
             result_context,
             OnTypeError::new(&on_argument_type_error),
-        )
+        );
+        if !self.i_s.db.project.settings.mypy_compatible {
+            if base.is_name_defined_in_module(self.i_s.db, "builtins", "getattr")
+                && let Some(t) = self.getattr_with_literal_name(&args)
+            {
+                return Inferred::from_type(t);
+            }
+            if base.is_name_defined_in_module(self.i_s.db, "builtins", "setattr") {
+                self.check_setattr_with_literal_name(&args, NodeRef::new(f, node_index))
+            }
+            if base.is_name_defined_in_module(self.i_s.db, "builtins", "delattr") {
+                self.check_delattr_with_literal_name(&args, NodeRef::new(f, node_index))
+            }
+            if base.is_name_defined_in_module(self.i_s.db, "builtins", "dict")
+                && let Some(t) = self.dict_merged_with_typed_dict(&args)
+            {
+                return Inferred::from_type(t);
+            }
+            if base.is_name_defined_in_module(self.i_s.db, "copy", "replace") {
+                self.check_copy_replace_changes(&args)
+            }
+            if base.is_name_defined_in_module(self.i_s.db, "functools", "partial")
+                && let Some(t) = self.partial_with_remaining_callable(&args, &result)
+            {
+                return Inferred::from_type(t);
+            }
+        }
+        result
+    }
+
+    fn partial_with_remaining_callable(
+        &self,
+        args: &SimpleArgs,
+        partial: &Inferred,
+    ) -> Option<Type> {
+        // `functools.partial(func, *args, **kwargs)` accepts anything in typeshed. For simple
+        // callables the bound arguments are checked and the result is additionally typed as the
+        // callable of the remaining params. Type vars that are solved by the bound arguments are
+        // specialized, all others stay generic in the remaining callable.
+        let i_s = self.i_s;
+        let mut iterator = args.iter(i_s.mode);
+        let ArgKind::Positional(func) = iterator.next()?.kind else {
+            return None;
+        };
+        let func = func.infer(&mut ResultContext::Unknown);
+        // Classes and other objects with a single signature are handled like functions.
+        let Some(CallableLike::Callable(c)) = func.as_cow_type(i_s).maybe_callable(i_s) else {
+            return None;
+        };
+        let CallableParams::Simple(params) = &c.params else {
+            return None;
+        };
+        let mut remaining = params.to_vec();
+        let mut keywords = vec![];
+        for arg in iterator {
+            match arg.kind {
+                ArgKind::Positional(_) => {
+                    let first = remaining.first()?;
+                    if matches!(first.type_, ParamType::Star(_)) {
+                        continue;
+                    }
+                    if !matches!(
+                        first.type_,
+                        ParamType::PositionalOnly(_) | ParamType::PositionalOrKeyword(_)
+                    ) {
+                        return None;
+                    }
+                    remaining.remove(0);
+                }
+                ArgKind::Keyword(kw) => keywords.push(kw.key),
+                _ => return None,
+            }
+        }
+        for key in keywords {
+            let Some(index) = remaining.iter().position(|p| {
+                matches!(
+                    p.type_,
+                    ParamType::PositionalOrKeyword(_) | ParamType::KeywordOnly(_)
+                ) && p.name.as_ref().is_some_and(|n| n.as_str(i_s.db) == key)
+            }) else {
+                if remaining
+                    .iter()
+                    .any(|p| matches!(p.type_, ParamType::StarStar(_)))
+                {
+                    continue;
+                }
+                return None;
+            };
+            remaining[index].has_default = true;
+            // Params after a param that was given by keyword can only be passed by keyword.
+            for param in &mut remaining[index..] {
+                if let ParamType::PositionalOrKeyword(t) = &param.type_ {
+                    param.type_ = ParamType::KeywordOnly(t.clone());
+                }
+            }
+        }
+        let mut remaining_callable = c.as_ref().clone();
+        remaining_callable.params = CallableParams::new_simple(remaining.into());
+        remaining_callable.type_vars = i_s.db.python_state.empty_type_var_likes.clone();
+
+        // The arguments are checked against the original callable where every param is
+        // optional. The first param is for the function that is passed to partial.
+        let mut check_params = vec![CallableParam::new_anonymous(ParamType::PositionalOnly(
+            i_s.db.python_state.object_type(),
+        ))];
+        check_params.extend(params.iter().map(|p| CallableParam {
+            has_default: true,
+            ..p.clone()
+        }));
+        let mut check_callable = c.as_ref().clone();
+        check_callable.params = CallableParams::new_simple(check_params.into());
+        check_callable.return_type = Type::Callable(Arc::new(remaining_callable));
+        let specialized = Callable::new(&check_callable, None)
+            .execute(
+                i_s,
+                args,
+                OnTypeError::new(&on_argument_type_error),
+                &mut ResultContext::Unknown,
+            )
+            .as_type(i_s);
+        if !matches!(specialized, Type::Callable(_)) {
+            return None;
+        }
+        Some(Type::Intersection(Intersection::new(Arc::new([
+            specialized,
+            partial.as_type(i_s),
+        ]))))
+    }
+
+    fn check_setattr_with_literal_name(&self, args: &SimpleArgs, from: NodeRef) {
+        // `setattr(obj, "name", value)` is checked like `obj.name = value`. Unknown attributes
+        // are only reported for slotted and final classes, because all other classes might have
+        // been extended dynamically.
+        let i_s = self.i_s;
+        let mut iterator = args.iter(i_s.mode);
+        let mut next_positional = || {
+            let arg = iterator.next()?;
+            matches!(arg.kind, ArgKind::Positional(_)).then(|| self.matched_argument(arg))?
+        };
+        let (Some(obj), Some(name), Some(value)) =
+            (next_positional(), next_positional(), next_positional())
+        else {
+            return;
+        };
+        let Some(name) = name.maybe_string_literal(i_s) else {
+            return;
+        };
+        let name_str = name.as_str(i_s.db);
+        for t in obj.as_cow_type(i_s).iter_with_unpacked_unions(i_s.db) {
+            let class = match t {
+                Type::Class(c) => c.class(i_s.db),
+                Type::Dataclass(d) => d.class(i_s.db),
+                _ => continue,
+            };
+            let instance = Instance::new(class, None);
+            let is_known = instance
+                .lookup(i_s, name_str, InstanceLookupOptions::new(&|_| ()))
+                .lookup
+                .is_some();
+            if !is_known
+                && class.class_storage.slots.is_none()
+                && !class.use_cached_class_infos(i_s.db).is_final
+            {
+                continue;
+            }
+            let _ =
+                instance.check_set_descriptor_and_return_should_narrow(i_s, from, name_str, &value);
+        }
+    }
+
+    fn check_delattr_with_literal_name(&self, args: &SimpleArgs, from: NodeRef) {
+        // `delattr(obj, "name")` is checked like `del obj.name`, with the same restriction to
+        // slotted and final classes for unknown attributes as setattr.
+        let i_s = self.i_s;
+        let mut iterator = args.iter(i_s.mode);
+        let mut next_positional = || {
+            let arg = iterator.next()?;
+            matches!(arg.kind, ArgKind::Positional(_)).then(|| self.matched_argument(arg))?
+        };
+        let (Some(obj), Some(name)) = (next_positional(), next_positional()) else {
+            return;
+        };
+        let Some(name) = name.maybe_string_literal(i_s) else {
+            return;
+        };
+        let name_str = name.as_str(i_s.db);
+        let full_type = obj.as_cow_type(i_s);
+        for t in full_type.iter_with_unpacked_unions(i_s.db) {
+            let class = match t {
+                Type::Class(c) => c.class(i_s.db),
+                Type::Dataclass(d) => d.class(i_s.db),
+                _ => continue,
+            };
+            let details = Instance::new(class, None).lookup(
+                i_s,
+                name_str,
+                InstanceLookupOptions::new(&|_| ()),
+            );
+            if details.lookup.is_some() {
+                if details.attr_kind.is_read_only_property()
+                    && class.maybe_named_tuple_base(i_s.db).is_some()
+                {
+                    from.add_issue(i_s, IssueKind::NamedTupleAttributeCannotBeDeleted);
+                }
+            } else if class.class_storage.slots.is_some()
+                || class.use_cached_class_infos(i_s.db).is_final
+            {
+                add_attribute_error(i_s, from, &full_type, t, name_str);
+            }
+        }
+    }
+
+    fn check_copy_replace_changes(&self, args: &SimpleArgs) {
+        // `copy.replace(obj, **changes)` accepts any keyword arguments in typeshed. The changes
+        // are checked against the `__replace__` method of obj, which is synthesized for
+        // dataclasses and NamedTuples.
+        let i_s = self.i_s;
+        let Some(first) = args.iter(i_s.mode).next() else {
+            return;
+        };
+        let ArgKind::Positional(positional) = &first.kind else {
+            return;
+        };
+        let obj = positional.infer(&mut ResultContext::Unknown);
+        for t in obj.as_cow_type(i_s).iter_with_unpacked_unions(i_s.db) {
+            let Some(replace) = t
+                .lookup(
+                    i_s,
+                    self.file,
+                    "__replace__",
+                    LookupKind::Normal,
+                    &mut ResultContext::Unknown,
+                    &|_| (),
+                    &|_| (),
+                )
+                .into_maybe_inferred()
+            else {
+                continue;
+            };
+            if let Type::Callable(c) = replace.as_cow_type(i_s).as_ref()
+                && let CallableParams::Simple(params) = &c.params
+            {
+                // The bound method does not have a param for obj, so we add it here.
+                let mut params = params.to_vec();
+                params.insert(
+                    0,
+                    CallableParam::new_anonymous(ParamType::PositionalOnly(t.clone())),
+                );
+                let mut replace_func = c.as_ref().clone();
+                replace_func.params = CallableParams::new_simple(params.into());
+                Callable::new(&replace_func, None).execute(
+                    i_s,
+                    args,
+                    OnTypeError::new(&on_argument_type_error),
+                    &mut ResultContext::Unknown,
+                );
+            }
+        }
+    }
+
+    /// The argument as it was inferred while matching the arguments of the call. Arguments are
+    /// not inferred again, because that would be slower and could report issues twice.
+    fn matched_argument(&self, arg: Arg) -> Option<Inferred> {
+        let expr = match arg.kind {
+            ArgKind::Positional(positional) => positional.named_expr.expression(),
+            ArgKind::Keyword(keyword) => keyword.expression,
+            _ => return None,
+        };
+        self.check_point_cache(expr.index())
+    }
+
+    fn dict_merged_with_typed_dict(&self, args: &SimpleArgs) -> Option<Type> {
+        // `dict(td, extra=1)` matches `SupportsKeysAndGetItem[str, _VT]` in typeshed, which makes
+        // the values `object`. The values are however known from the TypedDict's items.
+        let i_s = self.i_s;
+        let mut iterator = args.iter(i_s.mode);
+        let first = iterator.next()?;
+        if !matches!(first.kind, ArgKind::Positional(_)) {
+            return None;
+        }
+        let typed_dict = self
+            .matched_argument(first)?
+            .as_cow_type(i_s)
+            .maybe_typed_dict(i_s.db)?;
+        let mut value_t = typed_dict.union_of_all_types(i_s);
+        let mut had_keyword = false;
+        for arg in iterator {
+            if !matches!(arg.kind, ArgKind::Keyword(_)) {
+                return None;
+            }
+            had_keyword = true;
+            value_t.union_in_place(
+                self.matched_argument(arg)?
+                    .as_type(i_s)
+                    .avoid_implicit_literal(i_s.db),
+            );
+        }
+        had_keyword.then(|| {
+            new_class!(
+                i_s.db.python_state.dict_node_ref().as_link(),
+                i_s.db.python_state.str_type(),
+                value_t,
+            )
+        })
+    }
+
+    fn getattr_with_literal_name(&self, args: &SimpleArgs) -> Option<Type> {
+        // `getattr(obj, "name")` and `getattr(obj, "name", default)` return Any in typeshed. If
+        // the name is a literal and all parts of obj have that attribute, we can do better.
+        let i_s = self.i_s;
+        let mut iterator = args.iter(i_s.mode);
+        let mut next_positional = || {
+            let arg = iterator.next()?;
+            Some(match arg.kind {
+                ArgKind::Positional(_) => self.matched_argument(arg),
+                _ => None,
+            })
+        };
+        let obj = next_positional()??;
+        let name = next_positional()??.maybe_string_literal(i_s)?;
+        let default = match next_positional() {
+            Some(default) => Some(default?),
+            None => None,
+        };
+        if iterator.next().is_some() {
+            return None;
+        }
+        let mut result = Type::Never(NeverCause::Other);
+        for t in obj.as_cow_type(i_s).iter_with_unpacked_unions(i_s.db) {
+            if t.is_any() {
+                return None;
+            }
+            let inf = t
+                .lookup(
+                    i_s,
+                    self.file,
+                    name.as_str(i_s.db),
+                    LookupKind::Normal,
+                    &mut ResultContext::Unknown,
+                    &|_| (),
+                    &|_| (),
+                )
+                .into_maybe_inferred()?;
+            result.union_in_place(inf.as_type(i_s));
+        }
+        if let Some(default) = default {
+            result.union_in_place(default.as_type(i_s));
+        }
+        Some(result)
     }
 
     pub fn infer_primary_or_atom(&self, p: PrimaryOrAtom) -> Inferred {
@@ -3760,11 +4182,14 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
             match content {
                 FStringContent::FStringExpr(e) => {
                     let (expressions, spec) = e.unpack();
+                    let check_bytes = self.flags().strict_bytes && e.uses_str_conversion();
                     for expr in expressions.iter() {
-                        is_string_literal &= self
-                            .infer_expression(expr)
-                            .as_cow_type(self.i_s)
-                            .is_allowed_as_literal_string(true);
+                        let inf = self.infer_expression(expr);
+                        let t = inf.as_cow_type(self.i_s);
+                        is_string_literal &= t.is_allowed_as_literal_string(true);
+                        if check_bytes && self.is_bytes_like_for_formatting(&t) {
+                            self.add_issue(expr.index(), IssueKind::StrBytesSafe)
+                        }
                     }
                     if let Some(spec) = spec {
                         is_string_literal &= self
@@ -3779,6 +4204,15 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
         is_string_literal
     }
 
+    fn is_bytes_like_for_formatting(&self, t: &Type) -> bool {
+        let bytes_link = self.i_s.db.python_state.bytes_node_ref().as_link();
+        t.iter_with_unpacked_unions(self.i_s.db).any(|t| match t {
+            Type::Class(c) => c.link == bytes_link,
+            Type::Literal(l) => matches!(l.kind, LiteralKind::Bytes(_)),
+            _ => false,
+        })
+    }
+
     pub fn infer_primary_target(
         &self,
         primary_target: PrimaryTarget,
@@ -3962,8 +4396,24 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
                 .file
                 .inference(&InferenceState::new(self.i_s.db, node_ref.file))
                 .with_correct_context(global_redirect, |inference| {
+                    let name_ref = node_ref.name_ref_of_name_def();
+                    let p = name_ref.point();
                     let ensure_flow_analysis = || {
                         if inference.ensure_module_symbols_flow_analysis().is_err() {
+                            // Modules that import each other's classes are very common. Imports
+                            // only need flow analysis because of star imports, but if the import
+                            // is the only definition of a name, it can be followed directly
+                            // instead of waiting for the flow analysis of the module to finish.
+                            if p.calculated()
+                                && p.node_index() == name_ref.node_index
+                                && node_ref.expect_name_def().maybe_import().is_some()
+                            {
+                                debug!(
+                                    "Resolved import {} while in an import cycle",
+                                    node_ref.as_code()
+                                );
+                                return None;
+                            }
                             add_issue(IssueKind::CannotDetermineType {
                                 for_: node_ref.as_code().into(),
                             });
@@ -3971,7 +4421,6 @@ impl<'db, 'file> Inference<'db, 'file, '_> {
                         }
                         None
                     };
-                    let p = node_ref.name_ref_of_name_def().point();
                     if p.calculated()
                         && p.needs_flow_analysis()
                         && let Some(result) = ensure_flow_analysis()
@@ -11,7 +11,9 @@ mod type_computation;
 mod type_var_finder;
 mod utils;
 
-pub(crate) use diagnostics::{OVERLAPPING_REVERSE_TO_NORMAL_METHODS, check_multiple_inheritance};
+pub(crate) use diagnostics::{
+    OVERLAPPING_REVERSE_TO_NORMAL_METHODS, check_multiple_inheritance, is_binary_dunder_method,
+};
 pub(crate) use file_state::File;
 pub(crate) use flow_analysis::{FLOW_ANALYSIS, RedefinitionResult, process_unfinished_partials};
 use inference::Inference;
@@ -20,7 +22,7 @@ pub(crate) use name_binder::{
     FUNC_TO_RETURN_OR_YIELD_DIFF, FUNC_TO_TYPE_VAR_DIFF, GLOBAL_NONLOCAL_TO_NAME_DIFFERENCE,
     func_parent_scope,
 };
-pub(crate) use name_resolution::is_reexport_issue;
+pub(crate) use name_resolution::{PointResolution, is_reexport_issue};
 pub(crate) use python_file::{
     ComplexValues, OtherDefinitionIterator, PythonFile, dotted_path_from_dir,
 };
@@ -32,7 +34,7 @@ pub(crate) use type_computation::{
     use_cached_annotation_or_type_comment, use_cached_annotation_type,
     use_cached_param_annotation_type, use_cached_simple_generic_type,
 };
-pub(crate) use type_var_finder::TypeVarFinder;
+pub(crate) use type_var_finder::{BaseLookup, TypeVarFinder};
 pub(crate) use utils::{
     infer_index, infer_string_index, on_argument_type_error, should_add_deprecated,
 };
@@ -10,7 +10,10 @@ use crate::{
     },
     debug,
     diagnostics::{Diagnostics, Issue, IssueKind},
-    file::{ComplexValues, python_file::StarImport},
+    file::{
+        ComplexValues,
+        python_file::{NonlocalName, StarImport},
+    },
     python_state::NAME_TO_FUNCTION_DIFF,
     type_::StringSlice,
     utils::SymbolTable,
@@ -70,6 +73,7 @@ pub(crate) struct DbInfos<'db> {
     pub issues: &'db Diagnostics,
     pub star_imports: &'db RefCell<Vec<StarImport>>,
     pub all_imports: &'db RefCell<Vec<NodeIndex>>,
+    pub nonlocal_names: &'db RefCell<Vec<NonlocalName>>,
     pub file_index: FileIndex,
     pub is_stub: bool,
 }
@@ -253,13 +257,36 @@ impl<'db> NameBinder<'db> {
         }
     }
 
-    fn add_new_walrus_definition(&mut self, name_def: NameDef<'db>) {
-        if matches!(self.kind, NameBinderKind::Comprehension) {
-            // Walrus `:=` operators are available outside of comprehensions and therefore need to
-            // be added to the parent.
-            unsafe { &mut *self.parent.unwrap() }.add_new_walrus_definition(name_def)
-        } else {
-            self.add_new_definition(name_def, Point::new_uncalculated())
+    fn add_new_walrus_definition(&mut self, name_def: NameDef<'db>, in_comprehension: bool) {
+        match self.kind {
+            NameBinderKind::Comprehension => {
+                // The only names defined in comprehensions are its iteration variables.
+                if self
+                    .symbol_table
+                    .lookup_symbol(name_def.as_code())
+                    .is_some()
+                {
+                    self.add_issue(
+                        name_def.index(),
+                        IssueKind::WalrusRebindsComprehensionIterationVariable {
+                            name: name_def.as_code().into(),
+                        },
+                    );
+                }
+                // Walrus `:=` operators are available outside of comprehensions and therefore
+                // need to be added to the parent.
+                unsafe { &mut *self.parent.unwrap() }.add_new_walrus_definition(name_def, true)
+            }
+            NameBinderKind::Class if in_comprehension => {
+                // This is a syntax error at runtime, but we still define the name to avoid
+                // follow-up errors.
+                self.add_issue(
+                    name_def.index(),
+                    IssueKind::WalrusInComprehensionInClassBody,
+                );
+                self.add_new_definition(name_def, Point::new_uncalculated())
+            }
+            _ => self.add_new_definition(name_def, Point::new_uncalculated()),
         }
     }
 
@@ -1153,6 +1180,12 @@ impl<'db> NameBinder<'db> {
                                                     Locality::NameBinder,
                                                 ),
                                             );
+                                            self.db_infos.nonlocal_names.borrow_mut().push(
+                                                NonlocalName {
+                                                    name: name.index(),
+                                                    binds: parent,
+                                                },
+                                            );
                                         }
                                     } else {
                                         self.add_point_definition(
@@ -1247,7 +1280,7 @@ impl<'db> NameBinder<'db> {
                 InterestingNode::Walrus(walrus) => {
                     let (name_def, expr) = walrus.unpack();
                     self.index_non_block_node_full(&expr, ordered, cause);
-                    self.add_new_walrus_definition(name_def)
+                    self.add_new_walrus_definition(name_def, false)
                 }
             }
         }
@@ -1343,6 +1376,10 @@ impl<'db> NameBinder<'db> {
         if let Some(parent) = self.parent {
             let parent = unsafe { &*parent };
             let name_str = name.as_code();
+            if matches!(parent.kind, NameBinderKind::Class) {
+                // Class scopes are skipped when looking up nonlocal names.
+                return parent.lookup_nonlocal_in_parents(name);
+            }
             if !matches!(parent.kind, NameBinderKind::Function { .. }) {
                 self.add_issue(
                     name.index(),
@@ -15,6 +15,7 @@ use crate::{
     inference_state::InferenceState,
     inferred::Inferred,
     node_ref::NodeRef,
+    statistics::record_point_cache_lookup,
     type_::{LookupResult, Type},
     utils::is_magic_method,
 };
@@ -239,7 +240,9 @@ impl<'db, 'file, 'i_s> NameResolution<'db, 'file, 'i_s> {
             |imp_result: LoadedImportResult| match imp_result.into_import_result() {
                 ImportResult::File(file_index) => Inferred::new_file_reference(file_index),
                 ImportResult::Namespace(ns) => Inferred::from_type(Type::Namespace(ns)),
-                ImportResult::PyTypedMissing => Inferred::new_any_from_error(),
+                ImportResult::PyTypedMissing | ImportResult::ExtensionModuleWithoutStub(_) => {
+                    Inferred::new_any_from_error()
+                }
             };
         Some(match from_first_part {
             ImportResult::File(file_index) => {
@@ -249,6 +252,20 @@ impl<'db, 'file, 'i_s> NameResolution<'db, 'file, 'i_s> {
                     (PointResolution::Inferred(inf), None)
                 } else {
                     let import_file = self.i_s.db.loaded_python_file(*file_index);
+                    if import_file.lookup_symbol(name).is_none()
+                        && let Some(ImportResult::ExtensionModuleWithoutStub(module_name)) =
+                            import_file.sub_module(self.i_s.db, name).as_deref()
+                    {
+                        self.file.add_extension_module_without_stub(
+                            self.i_s.db,
+                            import_name,
+                            module_name,
+                        );
+                        return Some((
+                            PointResolution::Inferred(Inferred::new_any_from_error()),
+                            None,
+                        ));
+                    }
                     return self
                         .with_new_file(import_file)
                         .resolve_module_access(name, |kind| {
@@ -265,7 +282,7 @@ impl<'db, 'file, 'i_s> NameResolution<'db, 'file, 'i_s> {
                 )?)),
                 None,
             ),
-            ImportResult::PyTypedMissing => (
+            ImportResult::PyTypedMissing | ImportResult::ExtensionModuleWithoutStub(_) => (
                 PointResolution::Inferred(Inferred::new_any_from_error()),
                 None,
             ),
@@ -302,12 +319,7 @@ impl<'db, 'file, 'i_s> NameResolution<'db, 'file, 'i_s> {
         let builtins = i_s.db.python_state.builtins();
         let point = match name_str {
             "reveal_type" => {
-                if self
-                    .flags()
-                    .enabled_error_codes
-                    .iter()
-                    .any(|code| code == "unimported-reveal")
-                {
+                if self.flags().is_error_code_enabled("unimported-reveal") {
                     self.add_issue(save_to_index, IssueKind::UnimportedRevealType);
                 }
                 Point::new_specific(Specific::RevealTypeFunction, Locality::Todo)
@@ -424,6 +436,7 @@ impl<'db, 'file, 'i_s> NameResolution<'db, 'file, 'i_s> {
         narrow_name: impl Fn(&InferenceState, NodeRef, PointLink) -> Option<Inferred>,
     ) -> Option<PointResolution<'file>> {
         let point = self.point(node_index);
+        record_point_cache_lookup(point.calculated());
         self.resolve_point_internal(node_index, point, false, narrow_name)
     }
 
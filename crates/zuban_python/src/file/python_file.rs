@@ -35,10 +35,19 @@ use crate::{
     inference_state::InferenceState,
     lines::{BytePositionInfos, NewlineIndices, PositionInfos},
     node_ref::NodeRef,
-    type_::{DbString, LookupResult},
+    type_::{DbString, LookupResult, Type},
     utils::SymbolTable,
 };
 
+/// Files with this comment before the first statement are generated and not reported.
+const GENERATED_CODE_MARKER: &str = "zuban: generated";
+
+thread_local! {
+    // The files whose `__all__` is currently calculated. Modules can refer to each other's
+    // `__all__` (e.g. `__all__ += other.__all__` in both), which would otherwise recurse forever.
+    static DUNDER_ALL_IN_PROGRESS: RefCell<Vec<FileIndex>> = const { RefCell::new(vec![]) };
+}
+
 #[derive(Default, Debug, Clone)]
 pub(crate) struct ComplexValues(InsertOnlyVec<ComplexPoint>);
 
@@ -95,12 +104,14 @@ pub(crate) struct PythonFile {
     pub issues: Diagnostics,
     pub star_imports: Box<[StarImport]>,
     pub all_imports: Box<[NodeIndex]>,
+    pub nonlocal_names: Box<[NonlocalName]>,
     sub_files: RwLock<HashMap<CodeIndex, FileIndex>>,
     pub(crate) super_file: Option<SuperFile>,
     stub_cache: Option<StubCache>,
     pub ignore_type_errors: bool,
     flags: Option<FinalizedTypeCheckerFlags>,
     pub(super) delayed_diagnostics: RwLock<VecDeque<DelayedDiagnostic>>,
+    pub(super) unreachable_ranges: RwLock<Vec<Range<CodeIndex>>>,
 
     newline_indices: NewlineIndices,
 }
@@ -117,12 +128,14 @@ impl Clone for PythonFile {
             issues: self.issues.clone(),
             star_imports: self.star_imports.clone(),
             all_imports: self.all_imports.clone(),
+            nonlocal_names: self.nonlocal_names.clone(),
             sub_files: RwLock::new(self.sub_files.read().unwrap().clone()),
             super_file: self.super_file,
             stub_cache: self.stub_cache.clone(),
             ignore_type_errors: self.ignore_type_errors,
             flags: self.flags.clone(),
             delayed_diagnostics: RwLock::new(self.delayed_diagnostics.read().unwrap().clone()),
+            unreachable_ranges: RwLock::new(self.unreachable_ranges.read().unwrap().clone()),
             newline_indices: self.newline_indices.clone(),
         }
     }
@@ -216,6 +229,7 @@ impl vfs::VfsFile for PythonFile {
     fn invalidate_references_to(&mut self, file_index: Option<FileIndex>) {
         self.points.invalidate_references_to(file_index);
         self.issues.invalidate_non_name_binder_issues();
+        self.unreachable_ranges.get_mut().unwrap().clear();
         if let Some(cache) = self.stub_cache.as_mut() {
             *cache = StubCache::default();
         }
@@ -229,6 +243,15 @@ pub(crate) struct StarImport {
     pub(super) star_node: NodeIndex,
 }
 
+/// A `nonlocal` name in a nested function that binds a name of an outer function.
+#[derive(Debug, Clone)]
+pub(crate) struct NonlocalName {
+    /// The name in the `nonlocal` statement.
+    pub name: NodeIndex,
+    /// The first definition of the name in the outer function.
+    pub binds: NodeIndex,
+}
+
 impl StarImport {
     pub fn in_module_scope(&self) -> bool {
         self.scope == 0
@@ -287,6 +310,13 @@ impl<'db> PythonFile {
             tree.mypy_inline_config_directives(),
         );
         ignore_type_errors |= directives_info.ignore_errors;
+        // Errors in generated code cannot be fixed by the user.
+        ignore_type_errors |= tree.has_comment_marker_at_start(GENERATED_CODE_MARKER)
+            || project_options
+                .flags
+                .generated_code_markers
+                .iter()
+                .any(|marker| tree.has_comment_marker_at_start(marker));
         let points = Points::new(tree.length());
         Self::new_internal(
             file_index,
@@ -314,6 +344,7 @@ impl<'db> PythonFile {
         let complex_points = Default::default();
         let star_imports: RefCell<Vec<StarImport>> = Default::default();
         let all_imports: RefCell<Vec<NodeIndex>> = Default::default();
+        let nonlocal_names: RefCell<Vec<NonlocalName>> = Default::default();
         let symbol_table = NameBinder::with_global_binder(
             DbInfos {
                 // TODO this does not use flags of the super file. Is this an issue?
@@ -325,6 +356,7 @@ impl<'db> PythonFile {
                 issues: &issues,
                 star_imports: &star_imports,
                 all_imports: &all_imports,
+                nonlocal_names: &nonlocal_names,
                 file_index,
                 is_stub,
             },
@@ -339,6 +371,7 @@ impl<'db> PythonFile {
             complex_points,
             star_imports: star_imports.into_inner().into_boxed_slice(),
             all_imports: all_imports.into_inner().into_boxed_slice(),
+            nonlocal_names: nonlocal_names.into_inner().into_boxed_slice(),
             issues,
             newline_indices: NewlineIndices::new(),
             sub_files: Default::default(),
@@ -347,6 +380,7 @@ impl<'db> PythonFile {
             ignore_type_errors,
             flags,
             delayed_diagnostics: Default::default(),
+            unreachable_ranges: Default::default(),
         }
     }
 
@@ -412,6 +446,16 @@ impl<'db> PythonFile {
             .calculate_module_diagnostics()
     }
 
+    /// The statements that are unreachable because of narrowing or checks of the Python version
+    /// and the platform. They are only collected for the language server.
+    pub fn unreachable_ranges(&self, db: &Database) -> Vec<Range<CodeIndex>> {
+        let result = self.ensure_calculated_diagnostics(db);
+        debug_assert!(result.is_ok());
+        let mut ranges = self.unreachable_ranges.read().unwrap().clone();
+        ranges.sort_by_key(|range| range.start);
+        ranges
+    }
+
     pub fn ensure_module_symbols_flow_analysis(&self, db: &Database) -> Result<(), ()> {
         self.inference(&InferenceState::new(db, self))
             .ensure_module_symbols_flow_analysis()
@@ -451,6 +495,17 @@ impl<'db> PythonFile {
         self.sub_files.write().unwrap().insert(start, f.file_index);
         f
     }
+    /// The files of annotation strings and type comments, including their own sub files.
+    pub(super) fn sub_files_recursively(&self, db: &'db Database) -> Vec<&'db Self> {
+        let mut result = vec![];
+        for &file_index in self.sub_files.read().unwrap().values() {
+            let file = db.loaded_python_file(file_index);
+            result.push(file);
+            result.extend(file.sub_files_recursively(db));
+        }
+        result
+    }
+
     pub(super) fn ensure_forward_reference_file(
         &self,
         db: &'db Database,
@@ -493,7 +548,7 @@ impl<'db> PythonFile {
                     assert_ne!(file_index, self.file_index);
                     Some(file_index)
                 }
-                ImportResult::Namespace(_) => None,
+                ImportResult::Namespace(_) | ImportResult::ExtensionModuleWithoutStub(_) => None,
                 ImportResult::PyTypedMissing => unreachable!(),
             }
         });
@@ -518,7 +573,9 @@ impl<'db> PythonFile {
         } else {
             match ImportResult::import_stub_for_non_stub_package(db, self, parent_dir, name)? {
                 ImportResult::File(file_index) => file_index,
-                ImportResult::Namespace(_) => return None,
+                ImportResult::Namespace(_) | ImportResult::ExtensionModuleWithoutStub(_) => {
+                    return None;
+                }
                 ImportResult::PyTypedMissing => unreachable!(),
             }
         };
@@ -531,46 +588,53 @@ impl<'db> PythonFile {
     }
 
     pub fn maybe_dunder_all(&self, db: &Database) -> Option<&[DbString]> {
-        self.maybe_dunder_all
-            .get_or_init(|| {
-                self.symbol_table
-                    .lookup_symbol("__all__")
-                    .and_then(|dunder_all_index| {
-                        let name_def = NodeRef::new(self, dunder_all_index)
-                            .expect_name()
-                            .name_def()
-                            .unwrap();
-                        if let Some((_, _, expr)) =
-                            name_def
-                                .maybe_assignment_definition()
-                                .and_then(|assignment| {
-                                    assignment.maybe_simple_type_expression_assignment()
-                                })
-                        {
-                            let base = maybe_dunder_all_names(vec![], self.file_index, expr)?;
-                            self.gather_dunder_all_modifications(db, dunder_all_index, base)
-                        } else if let Some(NameImportParent::ImportFromAsName(as_name)) =
-                            name_def.maybe_import()
-                        {
-                            let i_s = InferenceState::new(db, self);
-                            let inference = self.inference(&i_s);
-                            inference.infer_name_def(name_def);
-                            // Just take the __all__ from the now calculated file. The exact
-                            // position doesn't matter anymore, because that is calculated by
-                            // exactly this method.
-                            let name_def_point =
-                                NodeRef::new(self, as_name.name_def().index()).point();
-                            let base = name_def_point
-                                .as_redirected_node_ref(db)
-                                .file
-                                .maybe_dunder_all(db)?;
-                            self.gather_dunder_all_modifications(db, dunder_all_index, base.into())
-                        } else {
-                            None
-                        }
-                    })
+        if let Some(dunder_all) = self.maybe_dunder_all.get() {
+            return dunder_all.as_deref();
+        }
+        if is_calculating_dunder_all(self.file_index) {
+            return None;
+        }
+        // This is intentionally not calculated in the initializer of the OnceLock, because it
+        // might need the `__all__` of other modules, which might need this one again.
+        DUNDER_ALL_IN_PROGRESS.with(|files| files.borrow_mut().push(self.file_index));
+        let dunder_all = self.calculate_dunder_all(db);
+        DUNDER_ALL_IN_PROGRESS.with(|files| files.borrow_mut().pop());
+        self.maybe_dunder_all.get_or_init(|| dunder_all).as_deref()
+    }
+
+    fn calculate_dunder_all(&self, db: &Database) -> Option<Box<[DbString]>> {
+        self.symbol_table
+            .lookup_symbol("__all__")
+            .and_then(|dunder_all_index| {
+                let name_def = NodeRef::new(self, dunder_all_index)
+                    .expect_name()
+                    .name_def()
+                    .unwrap();
+                if let Some((_, _, expr)) = name_def
+                    .maybe_assignment_definition()
+                    .and_then(|assignment| assignment.maybe_simple_type_expression_assignment())
+                {
+                    let base = self.maybe_dunder_all_names(db, vec![], expr)?;
+                    self.gather_dunder_all_modifications(db, dunder_all_index, base)
+                } else if let Some(NameImportParent::ImportFromAsName(as_name)) =
+                    name_def.maybe_import()
+                {
+                    let i_s = InferenceState::new(db, self);
+                    let inference = self.inference(&i_s);
+                    inference.infer_name_def(name_def);
+                    // Just take the __all__ from the now calculated file. The exact
+                    // position doesn't matter anymore, because that is calculated by
+                    // exactly this method.
+                    let name_def_point = NodeRef::new(self, as_name.name_def().index()).point();
+                    let base = name_def_point
+                        .as_redirected_node_ref(db)
+                        .file
+                        .maybe_dunder_all(db)?;
+                    self.gather_dunder_all_modifications(db, dunder_all_index, base.into())
+                } else {
+                    None
+                }
             })
-            .as_deref()
     }
 
     pub fn is_name_exported_for_star_import(&self, db: &Database, name: &str) -> bool {
@@ -599,14 +663,29 @@ impl<'db> PythonFile {
         let check_multi_def = |dunder_all: Vec<DbString>, name: Name| -> Option<Vec<DbString>> {
             let name_def = name.name_def().unwrap();
             let assignment = name_def.maybe_assignment_definition()?;
-            if let AssignmentContent::AugAssign(_, _, right_side) = assignment.unpack() {
-                maybe_dunder_all_names(
+            match assignment.unpack() {
+                AssignmentContent::AugAssign(_, _, right_side) => self.maybe_dunder_all_names(
+                    db,
                     dunder_all,
-                    file_index,
                     right_side.maybe_simple_expression()?,
-                )
-            } else {
-                None
+                ),
+                // Conditional definitions like `if sys.platform == ...: __all__ = [...]` are
+                // common, we simply use all the names that are defined in one of them.
+                AssignmentContent::Normal(_, right_side) => {
+                    let names = self.maybe_dunder_all_names(
+                        db,
+                        vec![],
+                        right_side.maybe_simple_expression()?,
+                    )?;
+                    let mut dunder_all = dunder_all;
+                    for name in names {
+                        if !dunder_all.iter().any(|n| n.as_str(db) == name.as_str(db)) {
+                            dunder_all.push(name)
+                        }
+                    }
+                    Some(dunder_all)
+                }
+                _ => None,
             }
         };
 
@@ -626,9 +705,9 @@ impl<'db> PythonFile {
                             .as_python_string(),
                     )?),
                     "extend" => {
-                        return maybe_dunder_all_names(
+                        return self.maybe_dunder_all_names(
+                            db,
                             dunder_all,
-                            file_index,
                             maybe_single?.expression(),
                         );
                     }
@@ -662,6 +741,90 @@ impl<'db> PythonFile {
         Some(dunder_all.into())
     }
 
+    fn maybe_dunder_all_names(
+        &self,
+        db: &Database,
+        result: Vec<DbString>,
+        expr: Expression,
+    ) -> Option<Vec<DbString>> {
+        match expr.unpack() {
+            ExpressionContent::ExpressionPart(part) => {
+                self.maybe_dunder_all_names_of_part(db, result, part)
+            }
+            _ => None,
+        }
+    }
+
+    fn maybe_dunder_all_names_of_part(
+        &self,
+        db: &Database,
+        mut result: Vec<DbString>,
+        part: ExpressionPart,
+    ) -> Option<Vec<DbString>> {
+        let elements = match part {
+            // __all__ = foo.__all__ + ["bar"]
+            ExpressionPart::Sum(sum) => {
+                let op = sum.as_operation();
+                if op.infos.operand != "+" {
+                    return None;
+                }
+                let result = self.maybe_dunder_all_names_of_part(db, result, op.left)?;
+                return self.maybe_dunder_all_names_of_part(db, result, op.right);
+            }
+            // __all__ += submodule.__all__
+            ExpressionPart::Primary(primary) => {
+                let PrimaryContent::Attribute(attr) = primary.second() else {
+                    return None;
+                };
+                if attr.as_code() != "__all__" {
+                    return None;
+                }
+                let i_s = InferenceState::new(db, self);
+                let inf = self.inference(&i_s).infer_primary_or_atom(primary.first());
+                let Type::Module(module) = inf.as_cow_type(&i_s).into_owned() else {
+                    return None;
+                };
+                if module == self.file_index {
+                    return None;
+                }
+                if is_calculating_dunder_all(module) {
+                    // A cycle like `__all__ += other.__all__` in both modules, the names of the
+                    // module that is currently calculated are not known yet.
+                    return Some(result);
+                }
+                result.extend(
+                    db.loaded_python_file(module)
+                        .maybe_dunder_all(db)?
+                        .iter()
+                        .cloned(),
+                );
+                return Some(result);
+            }
+            _ => match part.maybe_unpacked_atom()? {
+                AtomContent::List(list) => list.unpack(),
+                AtomContent::Tuple(tup) => tup.iter(),
+                _ => return None,
+            },
+        };
+
+        for star_like in elements {
+            match star_like {
+                StarLikeExpression::NamedExpression(named_expr) => {
+                    result.push(DbString::from_python_string(
+                        self.file_index,
+                        named_expr
+                            .expression()
+                            .maybe_single_string_literal()?
+                            .as_python_string(),
+                    )?)
+                }
+                StarLikeExpression::StarNamedExpression(_) => return None,
+                _ => unreachable!(),
+            }
+        }
+        Some(result)
+    }
+
     pub fn file_entry(&self, db: &'db Database) -> &'db Arc<FileEntry> {
         db.vfs.file_entry(self.file_index)
     }
@@ -981,35 +1144,6 @@ impl<'code> Iterator for DirectiveSplitter<'_, 'code> {
     }
 }
 
-fn maybe_dunder_all_names(
-    mut result: Vec<DbString>,
-    file_index: FileIndex,
-    expr: Expression,
-) -> Option<Vec<DbString>> {
-    let elements = match expr.maybe_unpacked_atom()? {
-        AtomContent::List(list) => list.unpack(),
-        AtomContent::Tuple(tup) => tup.iter(),
-        _ => return None,
-    };
-
-    for star_like in elements {
-        match star_like {
-            StarLikeExpression::NamedExpression(named_expr) => {
-                result.push(DbString::from_python_string(
-                    file_index,
-                    named_expr
-                        .expression()
-                        .maybe_single_string_literal()?
-                        .as_python_string(),
-                )?)
-            }
-            StarLikeExpression::StarNamedExpression(_) => return None,
-            _ => unreachable!(),
-        }
-    }
-    Some(result)
-}
-
 // An Iterator that goes through all nodes except the given one
 pub(crate) struct OtherDefinitionIterator<'a> {
     points: &'a Points,
@@ -1048,3 +1182,7 @@ impl Iterator for OtherDefinitionIterator<'_> {
 struct StubCache {
     non_stub: OnceLock<Option<FileIndex>>,
 }
+
+fn is_calculating_dunder_all(file_index: FileIndex) -> bool {
+    DUNDER_ALL_IN_PROGRESS.with(|files| files.borrow().contains(&file_index))
+}
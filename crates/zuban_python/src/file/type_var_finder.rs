@@ -20,7 +20,7 @@ use crate::{
 };
 
 #[derive(Debug, Clone, PartialEq)]
-enum BaseLookup {
+pub(crate) enum BaseLookup {
     Module(FileIndex),
     Class(PointLink),
     GenericOrProtocol,
@@ -398,7 +398,7 @@ impl<'db, 'file> NameResolution<'db, 'file, '_> {
         self.point_resolution_to_base_lookup(resolved)
     }
 
-    fn point_resolution_to_base_lookup(&self, resolved: PointResolution) -> BaseLookup {
+    pub(crate) fn point_resolution_to_base_lookup(&self, resolved: PointResolution) -> BaseLookup {
         match resolved {
             PointResolution::NameDef {
                 node_ref,
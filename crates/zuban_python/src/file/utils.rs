@@ -24,6 +24,7 @@ use crate::{
         Type, TypedDict, TypedDictGenerics, UniqueInUnpackedUnionError, check_typed_dict_call,
         infer_typed_dict_arg, maybe_add_extra_keys_issue,
     },
+    type_helpers::{Instance, InstanceLookupOptions},
 };
 
 use super::ClassNodeRef;
@@ -296,6 +297,7 @@ impl<'db> Inference<'db, '_, '_> {
                                     Match::False { reason, .. } => reason,
                                     Match::True { .. } => MismatchReason::None,
                                 },
+                                explanation: &[],
                             };
                             let ErrorStrs { expected, got } = error_types.as_boxed_strs(i_s.db);
                             (expected, got)
@@ -539,13 +541,61 @@ pub fn on_argument_type_error(
     arg: &Arg,
     types: ErrorTypes,
 ) {
+    if !arg.should_add_issue(i_s) {
+        // Avoid formatting the types if the issue is discarded anyway (e.g. while checking
+        // overloads).
+        return;
+    }
     let strings = types.as_boxed_strs(i_s.db);
-    let got = match strings.got.as_ref() {
+    let mut got = match strings.got.as_ref() {
         "ModuleType" => "Module".to_string(),
         got => format!("\"{got}\""),
     };
+    if !i_s.db.project.settings.mypy_compatible
+        && let Some(item) = arg.unpacked_index
+        && matches!(types.got, GotType::Type(_))
+        && let Some(star_t) = arg.maybe_star_type(i_s)
+    {
+        // Mypy only reports the whole tuple, which makes it hard to see which item is wrong.
+        got += &format!(r#" (item {item} of "*{}")"#, star_t.format_short(i_s.db));
+    }
     arg.add_argument_issue(i_s, &got, &strings.expected, error_text);
-    types.add_mismatch_notes(|issue| arg.add_issue(i_s, issue))
+    types.add_mismatch_notes(i_s.db, |issue| arg.add_issue(i_s, issue));
+    add_numeric_protocol_note(i_s, arg, &types)
+}
+
+fn add_numeric_protocol_note(i_s: &InferenceState, arg: &Arg, types: &ErrorTypes) {
+    // Classes that implement `__index__` or `__float__` are not subtypes of int/float, but
+    // often the param should have been annotated with the matching protocol.
+    let (GotType::Type(Type::Class(got)), Type::Class(expected)) = (&types.got, types.expected)
+    else {
+        return;
+    };
+    let py = &i_s.db.python_state;
+    let (method_name, protocol) = if expected.link == py.int_link() {
+        ("__index__", "SupportsIndex")
+    } else if expected.link == py.float_link() {
+        ("__float__", "SupportsFloat")
+    } else {
+        return;
+    };
+    let got = got.class(i_s.db);
+    if Instance::new(got, None)
+        .lookup(i_s, method_name, InstanceLookupOptions::new(&|_| ()))
+        .lookup
+        .is_some()
+    {
+        arg.add_issue(
+            i_s,
+            IssueKind::Note(
+                format!(
+                    r#""{}" implements "{method_name}", consider using "typing.{protocol}" as the parameter type"#,
+                    got.name()
+                )
+                .into(),
+            ),
+        )
+    }
 }
 
 pub fn infer_index(
@@ -663,6 +713,21 @@ pub(super) fn func_of_self_symbol(file: &PythonFile, self_symbol: NodeIndex) ->
         .expect_as_param_of_function()
 }
 
+/// Self attributes without an annotation or a type comment (only the former is known while name
+/// binding).
+pub(super) fn is_unannotated_self_attribute(name_ref: NodeRef) -> bool {
+    name_ref.point().needs_flow_analysis()
+        && !name_ref
+            .expect_name()
+            .maybe_self_assignment_name_on_self_like()
+            .is_some_and(|assignment| {
+                assignment
+                    .suffix()
+                    .split_once('#')
+                    .is_some_and(|(_, comment)| comment.trim_start().starts_with("type:"))
+            })
+}
+
 pub fn should_add_deprecated(
     db: &Database,
     func_or_class: NodeRef,
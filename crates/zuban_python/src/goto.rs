@@ -6,7 +6,7 @@
 use std::{borrow::Cow, cell::Cell, sync::Arc};
 
 use parsa_python_cst::{
-    Atom, DefiningStmt, DottedAsNameContent, DottedImportName, GotoNode, Name as CSTName,
+    Atom, DefiningStmt, DottedAsNameContent, DottedImportName, GotoNode, Kwarg, Name as CSTName,
     NameDefParent, NameImportParent, NameParent, NodeIndex, Primary, PrimaryContent, PrimaryOrAtom,
     PrimaryTarget, PrimaryTargetOrAtom, Scope,
 };
@@ -26,10 +26,14 @@ use crate::{
     inference_state::{InferenceState, Mode},
     inferred::Inferred,
     matching::{LookupKind, ResultContext},
-    name::{ModuleName, Name, NodeName, Range, TreeName},
+    name::{ModuleName, Name, NodeName, Range, TreeName, format_type_pretty},
     node_ref::NodeRef,
+    params::Param as _,
     recoverable_error,
-    type_::{LookupResult, Type, TypeVarLikeName, TypeVarName, UnionType},
+    type_::{
+        CallableLike, CallableParams, DbString, LookupResult, ParamType, Type, TypeVarLikeName,
+        TypeVarName, UnionType,
+    },
     type_helpers::TypeOrClass,
 };
 
@@ -429,7 +433,10 @@ impl<'db, C: for<'a> FnMut(Name<'db, 'a>) -> T, T> GotoResolver<'db, C> {
             None
         };
         match node {
-            GotoNode::Name(name) => lookup_on_name(name),
+            GotoNode::Name(name) => lookup_on_name(name).or_else(|| match name.parent() {
+                NameParent::Kwarg(kwarg) => self.goto_keyword_argument(kwarg),
+                _ => None,
+            }),
             GotoNode::Primary(primary) => match primary.second() {
                 PrimaryContent::Attribute(name) => lookup_on_name(name).or_else(|| {
                     let base = self.infos.infer_primary_or_atom(primary.first());
@@ -523,6 +530,51 @@ impl<'db, C: for<'a> FnMut(Name<'db, 'a>) -> T, T> GotoResolver<'db, C> {
         });
         (!results.is_empty()).then_some(results)
     }
+
+    /// Keyword arguments like `x` in `f(x=1)` land on the param of the callee. For dataclasses
+    /// and similar classes the params of `__init__` are the fields.
+    fn goto_keyword_argument(&mut self, kwarg: Kwarg) -> Option<Vec<T>> {
+        let call = kwarg.maybe_call()?;
+        let key = kwarg.unpack().0.as_code();
+        let db = self.infos.db;
+        let callee = self.infos.infer_primary_or_atom(call.first());
+        let mut results = vec![];
+        with_i_s_non_self(db, self.infos.file, self.infos.scope, |i_s| {
+            let callables = match callee.as_cow_type(i_s).maybe_callable(i_s)? {
+                CallableLike::Callable(c) => vec![c],
+                CallableLike::Overload(overload) => overload.iter_functions().cloned().collect(),
+            };
+            for callable in callables {
+                let CallableParams::Simple(params) = &callable.params else {
+                    continue;
+                };
+                let Some(param) = params.iter().find(|p| {
+                    matches!(
+                        p.type_,
+                        ParamType::PositionalOrKeyword(_) | ParamType::KeywordOnly(_)
+                    ) && p.name(db) == Some(key)
+                }) else {
+                    continue;
+                };
+                // Params of functions and dataclass fields point to their definition, while
+                // params of e.g. functional NamedTuples point to strings.
+                let Some(DbString::StringSlice(slice)) = &param.name else {
+                    continue;
+                };
+                let file = db.loaded_python_file(slice.file_index);
+                if let Some(name) = file.tree.goto_node(slice.start).1.on_name()
+                    && name.start() == slice.start
+                    && name.end() == slice.end
+                {
+                    results.push(self.calculate_return(Name::TreeName(
+                        TreeName::with_unknown_parent_scope(db, file, name),
+                    )))
+                }
+            }
+            Some(())
+        });
+        (!results.is_empty()).then_some(results)
+    }
 }
 
 pub(crate) struct ReferencesResolver<'db, C, T> {
@@ -563,12 +615,26 @@ impl<'db, C: FnMut(Name<'db, '_>) -> T, T> ReferencesResolver<'db, C, T> {
 
                 is_globally_reachable |= match &name {
                     Name::TreeName(tree_name) => {
-                        let mut scopes = ScopesIterator {
-                            file: name.file(),
-                            only_reachable: true,
-                            current: Some(tree_name.parent_scope),
-                        };
-                        !scopes.any(|s| matches!(s, Scope::Function(_) | Scope::Lambda(_)))
+                        let name_def = tree_name.cst_name.name_def();
+                        if name_def.is_some_and(|name_def| {
+                            matches!(name_def.parent(), NameDefParent::Primary)
+                        }) {
+                            // Attributes like `self.foo` can be accessed on any instance,
+                            // regardless of where the class or the method is defined.
+                            true
+                        } else {
+                            let mut scopes = ScopesIterator {
+                                file: name.file(),
+                                only_reachable: true,
+                                current: Some(tree_name.parent_scope),
+                            };
+                            if name_def.is_some_and(|name_def| name_def.is_function_param()) {
+                                // Params are used as keyword arguments wherever the function
+                                // is.
+                                scopes.next();
+                            }
+                            !scopes.any(|s| matches!(s, Scope::Function(_) | Scope::Lambda(_)))
+                        }
                     }
                     _ => true,
                 };
@@ -714,6 +780,53 @@ impl<'db, C: FnMut(Name<'db, '_>) -> T, T> ReferencesResolver<'db, C, T> {
     }
 }
 
+/// Checks if renaming the name would make it shadow an existing name or be shadowed by one.
+/// Attributes and keyword arguments are not checked, because they are not looked up in scopes.
+pub(crate) fn rename_shadows_existing_name(name: &TreeName, new_name: &str) -> bool {
+    let file = name.file;
+    if let Some(name_def) = name.cst_name.name_def() {
+        !matches!(name_def.parent(), NameDefParent::Primary)
+            && scope_defines_name(file, name.parent_scope, new_name)
+    } else if name.cst_name.is_variable_reference() {
+        // Check all the scopes between the usage and the definition it refers to.
+        let scopes = ScopesIterator {
+            file,
+            only_reachable: true,
+            current: Some(name.parent_scope),
+        };
+        for scope in scopes {
+            if scope_defines_name(file, scope, new_name) {
+                return true;
+            }
+            if scope_defines_name(file, scope, name.cst_name.as_code()) {
+                return false;
+            }
+        }
+        false
+    } else {
+        false
+    }
+}
+
+fn scope_defines_name(file: &PythonFile, scope: Scope, name: &str) -> bool {
+    match scope {
+        Scope::Module => file.lookup_symbol(name).is_some(),
+        Scope::Class(class_def) => ClassNodeRef::new(file, class_def.index())
+            .class_storage()
+            .class_symbol_table
+            .lookup_symbol(name)
+            .is_some(),
+        Scope::Function(func) => {
+            let mut found = false;
+            func.on_name_def_in_scope(&mut |name_def| found |= name_def.as_code() == name);
+            found
+        }
+        Scope::Lambda(lambda) => lambda
+            .params()
+            .any(|param| param.name_def().as_code() == name),
+    }
+}
+
 fn to_unique_position(n: &Name) -> (FileIndex, usize) {
     (n.file().file_index, n.name_range().0.byte_position)
 }
@@ -769,6 +882,17 @@ fn follow_goto_if_necessary<'db, 'x>(name: Name<'db, '_>, on_name: &mut impl FnM
 }
 
 impl<'db, C: for<'a> FnMut(ValueName<'db, 'a>) -> T, T> GotoResolver<'db, C> {
+    /// Formats the type of the node under the cursor, which also works for types that are not
+    /// defined anywhere like `Any`.
+    pub fn infer_type_description(&self) -> Option<Box<str>> {
+        let inf = self.infos.infer_position()?;
+        let db = self.infos.db;
+        Some(
+            self.infos
+                .with_i_s(|i_s| format_type_pretty(db, &inf.as_cow_type(i_s))),
+        )
+    }
+
     pub fn infer_definition(&mut self) -> Vec<T> {
         let mut result = vec![];
         let Some(inf) = self.infos.infer_position() else {
@@ -5,7 +5,7 @@ use vfs::{Directory, DirectoryEntry, Entries, FileIndex, WorkspaceKind};
 
 use crate::{
     database::Database,
-    file::PythonFile,
+    file::{PythonFile, dotted_path_from_dir},
     inferred::Inferred,
     type_::{Namespace, Type},
 };
@@ -19,6 +19,7 @@ pub(crate) enum ImportResult {
     File(FileIndex),
     Namespace(Arc<Namespace>), // A Python Namespace package, i.e. a directory
     PyTypedMissing,            // Files exist, but the py.typed marker is missing.
+    ExtensionModuleWithoutStub(Box<str>), // A compiled module (.so/.pyd) without a .pyi next to it.
 }
 
 impl ImportResult {
@@ -39,7 +40,9 @@ impl ImportResult {
             ImportResult::Namespace(namespace) => {
                 Inferred::from_type(Type::Namespace(namespace.clone()))
             }
-            Self::PyTypedMissing => Inferred::new_any_from_error(),
+            Self::PyTypedMissing | Self::ExtensionModuleWithoutStub(_) => {
+                Inferred::new_any_from_error()
+            }
         }
     }
 
@@ -63,6 +66,10 @@ impl ImportResult {
                     .map(|d| Directory::entries(&*db.vfs.handler, d)),
                 name,
             ),
+            // Submodules of compiled modules are just as opaque as the module itself.
+            Self::ExtensionModuleWithoutStub(module_name) => Some(
+                Self::ExtensionModuleWithoutStub(format!("{module_name}.{name}").into()),
+            ),
             Self::PyTypedMissing => unreachable!(),
         }
     }
@@ -129,6 +136,9 @@ impl ImportResult {
                 format!("namespace {}", namespace.debug_path(db))
             }
             Self::PyTypedMissing => "<py.typed missing>".into(),
+            Self::ExtensionModuleWithoutStub(module_name) => {
+                format!("extension module {module_name} without stub")
+            }
         }
     }
 }
@@ -148,6 +158,7 @@ impl LoadedImportResult {
         match &self.0 {
             ImportResult::File(file_index) => db.loaded_python_file(*file_index).qualified_name(db),
             ImportResult::Namespace(ns) => ns.qualified_name(),
+            ImportResult::ExtensionModuleWithoutStub(module_name) => module_name.to_string(),
             ImportResult::PyTypedMissing => unreachable!(),
         }
     }
@@ -275,6 +286,11 @@ pub fn namespace_import(
     namespace_import_with_unloaded_file(db, from_file, namespace, name)?.ensured_loaded_file(db)
 }
 
+fn follows_untyped_imports(db: &Database, from_file: &PythonFile, name: &str) -> bool {
+    // Untyped imports are not followed if they are reported.
+    from_file.flags(db).follow_untyped_imports && !from_file.disallows_untyped_import(db, name)
+}
+
 fn python_import<'x>(
     db: &Database,
     from_file: &PythonFile,
@@ -302,6 +318,7 @@ pub fn python_import_with_needs_exact_case<'x>(
 
     for (dir, needs_py_typed) in dirs {
         let mut had_namespace_dir = false;
+        let mut extension_module = None;
         for entry in &dir.iter() {
             match entry {
                 DirectoryEntry::Directory(dir2) => {
@@ -309,7 +326,7 @@ pub fn python_import_with_needs_exact_case<'x>(
                         let result = load_init_file(db, dir2, from_file.file_index);
                         if let Some(file_index) = result {
                             if needs_py_typed
-                                && !from_file.flags(db).follow_untyped_imports
+                                && !follows_untyped_imports(db, from_file, name)
                                 && Directory::entries(&*db.vfs.handler, dir2)
                                     .search("py.typed")
                                     .is_none()
@@ -327,7 +344,7 @@ pub fn python_import_with_needs_exact_case<'x>(
                     let is_py_file = match_c(db, &file.name, &name_py, needs_exact_case);
                     if check_stubs {
                         if is_py_file || match_c(db, &file.name, &name_pyi, needs_exact_case) {
-                            if needs_py_typed && !from_file.flags(db).follow_untyped_imports {
+                            if needs_py_typed && !follows_untyped_imports(db, from_file, name) {
                                 return Some(ImportResult::PyTypedMissing);
                             }
                             let file_index = db.vfs.ensure_file_index(file);
@@ -341,6 +358,17 @@ pub fn python_import_with_needs_exact_case<'x>(
                         let file_index = db.vfs.ensure_file_index(file);
                         python_file_index = Some((file.clone(), file_index));
                     }
+                    if check_stubs
+                        && db.project.flags.is_error_code_enabled("import-extension")
+                        && is_extension_module(db, &file.name, name, needs_exact_case)
+                    {
+                        extension_module = Some(match file.parent.maybe_dir() {
+                            Ok(parent_dir) => {
+                                format!("{}.{name}", dotted_path_from_dir(&parent_dir))
+                            }
+                            Err(_) => name.to_string(),
+                        });
+                    }
                 }
                 DirectoryEntry::MissingEntry { .. } => (),
             }
@@ -350,6 +378,10 @@ pub fn python_import_with_needs_exact_case<'x>(
             file_entry.add_invalidation(from_file.file_index);
             return Some(ImportResult::File(file_index));
         }
+        if let Some(module_name) = extension_module {
+            // Python would import the compiled module here, but we cannot look into it.
+            return Some(ImportResult::ExtensionModuleWithoutStub(module_name.into()));
+        }
         dir.add_missing_entry(&name_py, from_file.file_index);
         if check_stubs {
             dir.add_missing_entry(&name_pyi, from_file.file_index);
@@ -376,6 +408,20 @@ fn match_c(db: &Database, x: &str, y: &str, needs_exact_case: bool) -> bool {
     }
 }
 
+/// Matches compiled modules like `name.so`, `name.pyd` or `name.cpython-311-x86_64-linux-gnu.so`.
+fn is_extension_module(db: &Database, file_name: &str, name: &str, needs_exact_case: bool) -> bool {
+    let Some(rest) = file_name
+        .strip_suffix(".so")
+        .or_else(|| file_name.strip_suffix(".pyd"))
+    else {
+        return false;
+    };
+    let module_name = rest
+        .split_once('.')
+        .map_or(rest, |(module_name, _)| module_name);
+    match_c(db, module_name, name, needs_exact_case)
+}
+
 fn load_init_file(
     db: &Database,
     content: &Arc<Directory>,
@@ -29,8 +29,9 @@ use crate::{
         AnyCause, CallableContent, CallableLike, CallableParams, ClassGenerics, DbBytes, DbString,
         FunctionKind, FunctionOverload, GenericClass, GenericItem, GenericsList, IterCause,
         IterInfos, Literal as DbLiteral, LiteralKind, LiteralValue, LookupResult, NeverCause,
-        PropertySetter, PropertySetterType, ReplaceTypeVarLikes, Type, TypeVarKind, TypeVarLike,
-        TypeVarLikes, execute_tuple_class, execute_type_of_type, merge_class_type_vars,
+        ParamType, PropertySetter, PropertySetterType, ReplaceTypeVarLikes, Type, TypeVarKind,
+        TypeVarLike, TypeVarLikes, execute_tuple_class, execute_type_of_type,
+        merge_class_type_vars,
     },
     type_helpers::{
         BoundMethod, BoundMethodFunction, Callable, Class, FirstParamProperties, Function,
@@ -286,6 +287,12 @@ impl<'db: 'slf, 'slf> Inferred {
         })
     }
 
+    pub fn is_not_implemented(&self, i_s: &InferenceState) -> bool {
+        self.as_cow_type(i_s)
+            .maybe_class(i_s.db)
+            .is_some_and(|c| c.node_ref == i_s.db.python_state.notimplemented_type_node_ref())
+    }
+
     pub fn maybe_specific(&self, db: &Database) -> Option<Specific> {
         if let InferredState::UnsavedSpecific(specific) = &self.state {
             return Some(*specific);
@@ -2689,10 +2696,12 @@ fn proper_classmethod_callable(
         CallableParams::Simple(params) => {
             let mut vec = params.to_vec();
             // The first argument in a class param is not relevant if we execute descriptors.
-            if vec.is_empty() {
-                return None;
-            }
-            let first_param = vec.remove(0);
+            let first_param = match &vec.first()?.type_ {
+                ParamType::PositionalOnly(_) | ParamType::PositionalOrKeyword(_) => vec.remove(0),
+                // *args receives the class, but still accepts all the other arguments.
+                ParamType::Star(_) => vec[0].clone(),
+                _ => return None,
+            };
 
             callable.params = CallableParams::Simple(Arc::from(vec));
             if let Some(t) = first_param.type_.maybe_positional_type() {
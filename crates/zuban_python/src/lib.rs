@@ -1,7 +1,9 @@
 #![allow(clippy::nonminimal_bool)] // I don't like this rule
 #![allow(clippy::too_many_arguments)] // TODO For now this is easier, but probably enable again
 
+mod api;
 mod arguments;
+mod code_actions;
 mod completion;
 mod database;
 mod diagnostics;
@@ -19,6 +21,9 @@ mod node_ref;
 mod params;
 mod python_state;
 mod select_files;
+mod semantic_tokens;
+mod signature_help;
+mod statistics;
 mod sys_path;
 mod type_;
 mod type_helpers;
@@ -28,24 +33,33 @@ use std::{cell::OnceCell, path::Path};
 
 use ::utils::FastHashMap;
 use anyhow::bail;
+pub use api::{
+    ApiChange, ApiCompleteness, ApiSymbol, CompletenessProblem, IncompleteSymbol,
+    SignatureCompatibility, diff_apis,
+};
+pub use code_actions::CodeActionKind;
 use completion::CompletionResolver;
 pub use completion::{Completion, CompletionItemKind};
 pub use goto::{GotoGoal, ReferencesGoal};
-use goto::{GotoResolver, PositionalDocument, ReferencesResolver};
+use goto::{GotoResolver, PositionalDocument, ReferencesResolver, rename_shadows_existing_name};
 use name::Range;
 use parsa_python_cst::{GotoNode, Tree};
 use vfs::{AbsPath, DirOrFile, FileIndex, LocalFS, PathWithScheme, VfsHandler};
 
+use crate::utils::join_with_commas;
 use config::{ProjectOptions, PythonVersion, Settings, TypeCheckerFlags};
 pub use database::Mode;
 use database::{Database, PythonProject};
-pub use diagnostics::Severity;
+pub use diagnostics::{Diagnostic, Severity};
 use file::File;
 use inference_state::InferenceState;
 use inferred::Inferred;
 pub use lines::PositionInfos;
 use matching::invalidate_protocol_cache;
 pub use name::{Name, SymbolKind, ValueName};
+pub use semantic_tokens::{SemanticToken, SemanticTokenKind};
+pub use signature_help::{Signature, SignatureHelp};
+pub use statistics::{ModuleStatistics, ProjectStatistics, record_point_cache_statistics};
 
 pub struct Project {
     db: Database,
@@ -80,6 +94,17 @@ impl Project {
         self.db.invalidate_path(path)
     }
 
+    /// Invalidates all loaded files that changed on disk and returns how many changed. This is
+    /// only needed if the changes were not reported by a watcher.
+    pub fn invalidate_paths_changed_on_disk(&mut self) -> usize {
+        let paths = self.db.vfs.paths_changed_on_disk();
+        for path in &paths {
+            tracing::info!("Invalidate {path}, because it changed on disk");
+            self.db.invalidate_path(path)
+        }
+        paths.len()
+    }
+
     pub fn into_panic_recovery(self) -> PanicRecovery {
         PanicRecovery {
             vfs: self.db.vfs.into_panic_recovery(),
@@ -130,26 +155,153 @@ impl Project {
         );
         let mut checked_files = 0;
         let mut files_with_errors = 0;
+        let mut generated_reasons: Vec<Box<str>> = vec![];
 
-        let issues = select_files::diagnostics_for_relevant_files(&self.db, |file| {
+        let mut issues = select_files::diagnostics_for_relevant_files(&self.db, |file| {
+            if let Some(reason) = select_files::generated_code_reason(&self.db, file) {
+                generated_reasons.push(reason.into());
+                return vec![];
+            }
             checked_files += 1;
-            let mut issues = file.diagnostics(&self.db).into_vec();
-            issues.sort_by_key(|issue| issue.start_position().byte_position);
+            let issues = file_diagnostics(&self.db, file);
             if !issues.is_empty() {
                 files_with_errors += 1;
             }
             issues
         })?;
+        sort_diagnostics(&mut issues);
         tracing::info!("Checked {checked_files} files ({files_with_errors} files had errors)");
         invalidate_protocol_cache();
         Ok(Diagnostics {
             checked_files,
             files_with_errors,
+            skipped_generated_files: count_by_reason(generated_reasons),
             issues,
             error_count: Default::default(),
         })
     }
 
+    /// Checks the files that [`Self::diagnostics`] would check for the first project, but
+    /// distributes them over all projects, which are checked in parallel. All projects should be
+    /// created with the same options. Since they don't share anything, modules imported by files
+    /// of different projects are inferred once per project, which costs memory and some time, but
+    /// allows using multiple cores.
+    pub fn diagnostics_in_parallel(projects: &mut [Self]) -> anyhow::Result<Diagnostics<'_>> {
+        let projects: &[Self] = projects;
+        let Some(first) = projects.first() else {
+            bail!("Expected at least one project")
+        };
+        let mut generated_reasons: Vec<Box<str>> = vec![];
+        let paths = select_files::relevant_files(&first.db)?
+            .into_iter()
+            .filter(|file| {
+                let reason = select_files::generated_code_reason(&first.db, file);
+                if let Some(reason) = reason {
+                    generated_reasons.push(reason.into());
+                }
+                reason.is_none()
+            })
+            .map(|file| first.db.vfs.file_path(file.file_index).clone())
+            .collect::<Vec<_>>();
+        let issues_per_file = std::thread::scope(|scope| {
+            let threads = projects
+                .iter()
+                .enumerate()
+                .map(|(i, project)| {
+                    let paths = paths.iter().skip(i).step_by(projects.len());
+                    scope.spawn(move || {
+                        let issues = paths
+                            .filter_map(|path| project.diagnostics_for_path(path))
+                            .collect::<Vec<_>>();
+                        invalidate_protocol_cache();
+                        issues
+                    })
+                })
+                .collect::<Vec<_>>();
+            threads
+                .into_iter()
+                .flat_map(|thread| {
+                    thread
+                        .join()
+                        .unwrap_or_else(|err| std::panic::resume_unwind(err))
+                })
+                .collect::<Vec<_>>()
+        });
+        let checked_files = issues_per_file.len();
+        let files_with_errors = issues_per_file
+            .iter()
+            .filter(|issues| !issues.is_empty())
+            .count();
+        let mut issues = issues_per_file.into_iter().flatten().collect();
+        sort_diagnostics(&mut issues);
+        tracing::info!(
+            "Checked {checked_files} files in {} projects ({files_with_errors} files had errors)",
+            projects.len()
+        );
+        Ok(Diagnostics {
+            checked_files,
+            files_with_errors,
+            skipped_generated_files: count_by_reason(generated_reasons),
+            issues,
+            error_count: Default::default(),
+        })
+    }
+
+    fn diagnostics_for_path(
+        &self,
+        path: &PathWithScheme,
+    ) -> Option<Vec<diagnostics::Diagnostic<'_>>> {
+        let DirOrFile::File(file_entry) = self
+            .db
+            .vfs
+            .search_path(self.db.project.flags.case_sensitive, path)?
+        else {
+            return None;
+        };
+        let file_index = self.db.load_file_from_workspace(&file_entry, false)?;
+        Some(file_diagnostics(
+            &self.db,
+            self.db.loaded_python_file(file_index),
+        ))
+    }
+
+    /// The public, typed API of the checked files: module level names that would be exported by
+    /// a star import and the public members of classes defined there, sorted by path.
+    pub fn exported_api(&mut self) -> anyhow::Result<Vec<ApiSymbol>> {
+        api::exported_api(&self.db)
+    }
+
+    /// The changes of [`Self::exported_api`] compared to the API of an older version. Changed
+    /// functions are only breaking if the new version cannot be used where the old one was
+    /// expected.
+    pub fn api_diff(&mut self, old: &[ApiSymbol]) -> anyhow::Result<Vec<ApiChange>> {
+        let new = api::exported_api(&self.db)?;
+        let mut changes = diff_apis(old, &new);
+        api::classify_api_changes(&mut self.db, old, &mut changes)?;
+        Ok(changes)
+    }
+
+    /// Checks that every symbol of [`Self::exported_api`] is fully annotated and does not contain
+    /// an implicit `Any`.
+    pub fn api_completeness(&mut self) -> anyhow::Result<ApiCompleteness> {
+        api::api_completeness(&self.db)
+    }
+
+    /// Checks if the callable at the dotted path `source` can be used where the callable at
+    /// `target` is expected and explains why not.
+    pub fn check_signature(
+        &mut self,
+        source: &str,
+        target: &str,
+    ) -> anyhow::Result<SignatureCompatibility> {
+        api::check_signature(&self.db, source, target)
+    }
+
+    /// Memory usage and cache statistics of everything that was loaded so far.
+    pub fn statistics(&self) -> ProjectStatistics {
+        ProjectStatistics::new(&self.db)
+    }
+
     /// This function is mostly for tests and should therefore not be used for something
     /// stable. We would have to ensure first it works everywhere.
     /// It currently is for example a big issue that HashableRawStr used in the name binder is very
@@ -200,6 +352,22 @@ impl<'project> Document<'project> {
         python_file.diagnostics(&self.project.db)
     }
 
+    /// Code that is never executed, e.g. branches that are unreachable because of narrowing or
+    /// checks of the Python version.
+    pub fn unreachable_ranges(&self) -> Vec<Range<'_>> {
+        let db = &self.project.db;
+        let file = db.loaded_python_file(self.file_index);
+        file.unreachable_ranges(db)
+            .into_iter()
+            .map(|range| {
+                (
+                    file.byte_to_position_infos(db, range.start),
+                    file.byte_to_position_infos(db, range.end),
+                )
+            })
+            .collect()
+    }
+
     fn positional_document(
         &self,
         position: InputPosition,
@@ -269,8 +437,10 @@ impl<'project> Document<'project> {
 
         let mut file_renames: Vec<&'project PathWithScheme> = vec![];
         let mut file_changes = FastHashMap::default();
+        let mut has_conflict = false;
         let references = ReferencesResolver::new(document, |name| match &name {
             Name::TreeName(tree_name) => {
+                has_conflict |= rename_shadows_existing_name(tree_name, new_name);
                 let file_index = tree_name.file.file_index;
                 file_changes
                     .entry(file_index)
@@ -289,6 +459,13 @@ impl<'project> Document<'project> {
                 name.as_code()
             );
         }
+        if has_conflict {
+            bail!(
+                "Cannot rename {:?} to {new_name:?}, because it would conflict with an existing \
+                 definition of {new_name:?}",
+                name.as_code()
+            );
+        }
         let changes: Vec<SingleFileRenameChanges<'project>> = file_changes
             .into_iter()
             .map(|(file_index, changes)| {
@@ -333,18 +510,22 @@ impl<'project> Document<'project> {
             GotoGoal::Indifferent,
             |n: ValueName| {
                 if !only_docstrings {
-                    types.push(
-                        n.maybe_pretty_function_type()
-                            .unwrap_or_else(|| n.type_description())
-                            .into_string(),
-                    );
+                    types.push(n.pretty_type_description().into_string());
                 }
                 n.name.documentation().to_string()
             },
         );
         let mut results = resolver.infer_definition();
+        let mut type_without_definition = None;
         if results.is_empty() {
-            return Ok(None);
+            // Types like `Any` are not defined anywhere, but are still interesting in a hover.
+            if only_docstrings {
+                return Ok(None);
+            }
+            type_without_definition = resolver.infer_type_description();
+            if type_without_definition.is_none() {
+                return Ok(None);
+            }
         }
         let Some(on_symbol_range) = resolver.on_node_range() else {
             // This is probably not reachable
@@ -354,6 +535,7 @@ impl<'project> Document<'project> {
         let resolver = GotoResolver::new(resolver.infos, GotoGoal::Indifferent, |n: Name| {
             n.origin_kind()
         });
+        types.extend(type_without_definition.map(|t| t.into_string()));
         let on_name = resolver.infos.node.on_name();
         let declaration_kinds = resolver.goto(true);
         results.retain(|doc| !doc.is_empty());
@@ -404,6 +586,122 @@ impl<'project> Document<'project> {
         }))
     }
 
+    /// The signatures of the call around the position, with the param of the argument at the
+    /// position as the active param.
+    pub fn signature_help(&self, position: InputPosition) -> anyhow::Result<Option<SignatureHelp>> {
+        let db = &self.project.db;
+        let file = db.loaded_python_file(self.file_index);
+        let position = file.line_column_to_byte(position)?;
+        Ok(signature_help::signature_help(db, file, position.byte))
+    }
+
+    /// Returns the ranges for expanding a selection, from the innermost to the outermost node
+    /// around the position.
+    pub fn selection_ranges(&self, position: InputPosition) -> anyhow::Result<Vec<Range<'_>>> {
+        let db = &self.project.db;
+        let file = db.loaded_python_file(self.file_index);
+        let position = file.line_column_to_byte(position)?;
+        Ok(file
+            .tree
+            .selection_ranges(position.byte)
+            .into_iter()
+            .map(|range| {
+                (
+                    file.byte_to_position_infos(db, range.start),
+                    file.byte_to_position_infos(db, range.end),
+                )
+            })
+            .collect())
+    }
+
+    pub fn folding_ranges(&self) -> Vec<Range<'_>> {
+        let db = &self.project.db;
+        let file = db.loaded_python_file(self.file_index);
+        file.tree
+            .folding_ranges()
+            .map(|range| {
+                (
+                    file.byte_to_position_infos(db, range.start),
+                    file.byte_to_position_infos(db, range.end),
+                )
+            })
+            .collect()
+    }
+
+    /// The ranges of the names of all functions and classes.
+    pub fn function_and_class_name_ranges(&self) -> Vec<Range<'_>> {
+        let db = &self.project.db;
+        let file = db.loaded_python_file(self.file_index);
+        file.tree
+            .function_and_class_names()
+            .map(|name| {
+                (
+                    file.byte_to_position_infos(db, name.start()),
+                    file.byte_to_position_infos(db, name.end()),
+                )
+            })
+            .collect()
+    }
+
+    /// Classifies the names of the file as classes, type variables, functions and parameters.
+    pub fn semantic_tokens(&self) -> Vec<SemanticToken<'_>> {
+        let db = &self.project.db;
+        semantic_tokens::semantic_tokens(db, db.loaded_python_file(self.file_index))
+    }
+
+    /// The code actions for the selected range, e.g. extracting a type alias from an
+    /// annotation or fixes for the diagnostics in the range.
+    pub fn code_actions(
+        &self,
+        start: InputPosition,
+        end: InputPosition,
+    ) -> anyhow::Result<Vec<CodeAction<'_>>> {
+        let db = &self.project.db;
+        let file = db.loaded_python_file(self.file_index);
+        let start = file.line_column_to_byte(start)?.byte;
+        let end = file.line_column_to_byte(end)?.byte;
+        let mut actions: Vec<_> = code_actions::fix_spelling(db, file, start..end)
+            .into_iter()
+            .map(|(title, edit)| (title, CodeActionKind::QUICKFIX, Some(vec![edit])))
+            .collect();
+        actions.extend(
+            code_actions::quick_fixes(db, file, start..end)
+                .into_iter()
+                .map(|(title, edits)| (title, CodeActionKind::QUICKFIX, Some(edits))),
+        );
+        actions.extend([
+            (
+                "Extract type alias".to_string(),
+                CodeActionKind::REFACTOR_EXTRACT,
+                code_actions::extract_type_alias(db, file, start..end),
+            ),
+            (
+                "Move imports only used in annotations into a TYPE_CHECKING block".to_string(),
+                CodeActionKind::SOURCE,
+                code_actions::move_imports_to_type_checking_block(db, file),
+            ),
+        ]);
+        Ok(actions
+            .into_iter()
+            .filter_map(|(title, kind, edits)| {
+                Some(CodeAction {
+                    title,
+                    kind,
+                    edits: edits?
+                        .into_iter()
+                        .map(|edit| CodeActionEdit {
+                            range: (
+                                file.byte_to_position_infos(db, edit.range.start),
+                                file.byte_to_position_infos(db, edit.range.end),
+                            ),
+                            new_text: edit.new_text,
+                        })
+                        .collect(),
+                })
+            })
+            .collect())
+    }
+
     pub fn is_valid_rename_location(
         &self,
         position: InputPosition,
@@ -433,6 +731,17 @@ pub struct DocumentationResult<'a> {
     pub on_symbol_range: Range<'a>,
 }
 
+pub struct CodeAction<'a> {
+    pub title: String,
+    pub kind: CodeActionKind,
+    pub edits: Vec<CodeActionEdit<'a>>,
+}
+
+pub struct CodeActionEdit<'a> {
+    pub range: Range<'a>,
+    pub new_text: String,
+}
+
 #[derive(Debug)]
 pub struct SingleFileRenameChanges<'db> {
     pub path: &'db PathWithScheme,
@@ -548,10 +857,25 @@ impl<'a> Script<'a> {
 pub struct Diagnostics<'a> {
     pub checked_files: usize,
     pub files_with_errors: usize,
+    /// The reasons of the generated code excludes and how many files they skipped, sorted by
+    /// reason.
+    pub skipped_generated_files: Vec<(Box<str>, usize)>,
     pub issues: Vec<diagnostics::Diagnostic<'a>>,
     error_count: OnceCell<usize>,
 }
 
+fn count_by_reason(mut reasons: Vec<Box<str>>) -> Vec<(Box<str>, usize)> {
+    reasons.sort();
+    let mut counted: Vec<(Box<str>, usize)> = vec![];
+    for reason in reasons {
+        match counted.last_mut() {
+            Some((last, count)) if *last == reason => *count += 1,
+            _ => counted.push((reason, 1)),
+        }
+    }
+    counted
+}
+
 impl Diagnostics<'_> {
     pub fn summary(&self) -> String {
         let s_if_plural = |n| match n {
@@ -559,7 +883,7 @@ impl Diagnostics<'_> {
             _ => "s",
         };
         let error_count = self.error_count();
-        if error_count == 0 {
+        let mut summary = if error_count == 0 {
             format!(
                 "Success: no issues found in {checked} source file{checked_s}",
                 checked = self.checked_files,
@@ -574,7 +898,20 @@ impl Diagnostics<'_> {
                 checked = self.checked_files,
                 checked_s = s_if_plural(self.checked_files),
             )
+        };
+        if !self.skipped_generated_files.is_empty() {
+            let skipped: usize = self.skipped_generated_files.iter().map(|(_, n)| n).sum();
+            summary += &format!(
+                " (skipped {skipped} generated file{skipped_s}: {reasons})",
+                skipped_s = s_if_plural(skipped),
+                reasons = join_with_commas(
+                    self.skipped_generated_files
+                        .iter()
+                        .map(|(reason, n)| format!("{reason} ({n})"))
+                ),
+            );
         }
+        summary
     }
 
     pub fn error_count(&self) -> usize {
@@ -586,11 +923,82 @@ impl Diagnostics<'_> {
         })
     }
 
+    /// Keeps only the errors whose error code is accepted by `keep`.
+    pub fn retain_error_codes(&mut self, keep: impl Fn(&str) -> bool) {
+        self.retain_errors(|issue| keep(issue.mypy_error_code()))
+    }
+
+    /// Keeps only the errors accepted by `keep`. Notes at the same position as the error before
+    /// them belong to that error and are removed together with it.
+    pub fn retain_errors(&mut self, mut keep: impl FnMut(&diagnostics::Diagnostic) -> bool) {
+        let mut previous: Option<(&str, usize, bool)> = None;
+        self.issues.retain(|issue| {
+            let path = issue.relative_path();
+            let position = issue.start_position().byte_position;
+            let kept = match previous {
+                Some((p, pos, kept))
+                    if issue.severity() == Severity::Information
+                        && p == path
+                        && pos == position =>
+                {
+                    kept
+                }
+                _ if issue.severity() == Severity::Information => true,
+                _ => keep(issue),
+            };
+            previous = Some((path, position, kept));
+            kept
+        });
+        let mut files_with_errors = self
+            .issues
+            .iter()
+            .filter(|issue| issue.severity() == Severity::Error)
+            .map(|issue| issue.relative_path())
+            .collect::<Vec<_>>();
+        files_with_errors.sort();
+        files_with_errors.dedup();
+        self.files_with_errors = files_with_errors.len();
+        self.error_count = Default::default();
+    }
+
     pub fn sort_issues_by_kind(&mut self) {
         self.issues.sort_by_key(|issue| &issue.issue.kind)
     }
 }
 
+fn file_diagnostics<'db>(
+    db: &'db Database,
+    file: &'db file::PythonFile,
+) -> Vec<diagnostics::Diagnostic<'db>> {
+    let mut issues = file.diagnostics(db).into_vec();
+    issues.sort_by_key(|issue| issue.start_position().byte_position);
+    issues
+}
+
+/// Sorts diagnostics by (file, line, column), so the output does not depend on the order in which
+/// files were checked. The sort is stable and notes are moved together with the issue they follow,
+/// so issues at the same position keep the order in which they were reported.
+fn sort_diagnostics(issues: &mut Vec<diagnostics::Diagnostic>) {
+    let mut groups: Vec<((&str, usize, usize), Vec<diagnostics::Diagnostic>)> = vec![];
+    for issue in std::mem::take(issues) {
+        if issue.severity() == Severity::Information
+            && let Some((_, group)) = groups.last_mut()
+        {
+            group.push(issue);
+            continue;
+        }
+        let position = issue.start_position();
+        let key = (
+            issue.relative_path(),
+            position.line_zero_based(),
+            position.utf8_bytes_column(),
+        );
+        groups.push((key, vec![issue]));
+    }
+    groups.sort_by(|(key1, _), (key2, _)| key1.cmp(key2));
+    *issues = groups.into_iter().flat_map(|(_, group)| group).collect();
+}
+
 pub struct PanicRecovery {
     vfs: vfs::VfsPanicRecovery<Tree>,
     mode: Mode,
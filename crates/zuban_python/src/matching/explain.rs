@@ -0,0 +1,143 @@
+// Records the decisions the matcher makes while checking if a type is assignable to another type.
+// This is essentially the information that is printed with `debug!` while matching, but it is
+// collected in a tree, so it can be shown to a user that wants to know why a type does not match.
+
+use std::{
+    cell::RefCell,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::{Match, MismatchReason};
+
+// Explaining huge types (e.g. big protocols or unions) can create enormous trails, which are
+// not helpful anymore.
+const MAX_EXPLANATION_LINES: usize = 50;
+
+thread_local! {
+    static TRAIL: RefCell<Option<Trail>> = const { RefCell::new(None) };
+}
+
+// The number of trails that are currently recorded on any thread. Matching is a hot path and
+// explaining is very rare, so the thread local is only accessed if this is not zero.
+static RECORDING_TRAILS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Default)]
+struct Trail {
+    entries: Vec<(usize, Option<String>)>,
+    depth: usize,
+}
+
+// Stops recording when the outermost explanation is done, even if matching panics. Otherwise
+// every following match would be recorded.
+struct RecordingGuard;
+
+impl Drop for RecordingGuard {
+    fn drop(&mut self) {
+        TRAIL.with(|trail| trail.borrow_mut().take());
+        RECORDING_TRAILS.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+pub(crate) fn record_match_explanation(callable: impl FnOnce() -> Match) -> (Match, Vec<String>) {
+    let is_nested = TRAIL.with(|trail| {
+        let mut trail = trail.borrow_mut();
+        let is_nested = trail.is_some();
+        if !is_nested {
+            *trail = Some(Trail::default());
+            RECORDING_TRAILS.fetch_add(1, Ordering::Relaxed);
+        }
+        is_nested
+    });
+    if is_nested {
+        // Only the outermost mismatch is explained.
+        return (callable(), vec![]);
+    }
+    let guard = RecordingGuard;
+    let result = callable();
+    let trail = TRAIL.with(|trail| trail.borrow_mut().take()).unwrap();
+    drop(guard);
+    let mut lines: Vec<String> = trail
+        .entries
+        .into_iter()
+        .filter_map(|(depth, line)| Some(format!("{}{}", "  ".repeat(depth), line?)))
+        .collect();
+    if lines.len() > MAX_EXPLANATION_LINES {
+        let omitted = lines.len() - MAX_EXPLANATION_LINES;
+        lines.truncate(MAX_EXPLANATION_LINES);
+        lines.push(format!("... ({omitted} more steps omitted)"));
+    }
+    (result, lines)
+}
+
+/// A step in the matcher decision trail. All steps started while this step is alive are nested
+/// below it.
+#[must_use]
+pub(crate) struct ExplainStep(Option<usize>);
+
+impl ExplainStep {
+    #[inline]
+    pub fn start() -> Self {
+        if RECORDING_TRAILS.load(Ordering::Relaxed) == 0 {
+            return Self(None);
+        }
+        TRAIL.with(|trail| {
+            let mut trail = trail.borrow_mut();
+            let Some(trail) = trail.as_mut() else {
+                return Self(None);
+            };
+            let index = trail.entries.len();
+            trail.entries.push((trail.depth, None));
+            trail.depth += 1;
+            Self(Some(index))
+        })
+    }
+
+    #[inline]
+    pub fn finish(&self, message: impl FnOnce() -> String) {
+        if let Some(index) = self.0 {
+            let message = message();
+            TRAIL.with(|trail| {
+                if let Some(trail) = trail.borrow_mut().as_mut() {
+                    trail.entries[index].1 = Some(message);
+                }
+            })
+        }
+    }
+}
+
+impl Drop for ExplainStep {
+    #[inline]
+    fn drop(&mut self) {
+        if self.0.is_some() {
+            TRAIL.with(|trail| {
+                if let Some(trail) = trail.borrow_mut().as_mut() {
+                    trail.depth -= 1;
+                }
+            })
+        }
+    }
+}
+
+pub(crate) fn format_match_result(m: &Match) -> String {
+    match m {
+        Match::True { with_any: false } => "yes".into(),
+        Match::True { with_any: true } => "yes (because of Any)".into(),
+        Match::False { reason, .. } => match reason {
+            MismatchReason::None => "no".into(),
+            MismatchReason::ConstraintAlreadySet => {
+                "no (type variable was already set to a different type)".into()
+            }
+            MismatchReason::ConstraintMismatch { .. } => {
+                "no (type variable constraint not satisfied)".into()
+            }
+            MismatchReason::ProtocolMismatches { .. } => {
+                "no (protocol members do not match)".into()
+            }
+            MismatchReason::SequenceInsteadOfListNeeded
+            | MismatchReason::MappingInsteadOfDictNeeded => "no (the type is invariant)".into(),
+            MismatchReason::IncompatibleCallableParam { position, .. } => {
+                format!("no (parameter {position} is incompatible)")
+            }
+        },
+    }
+}
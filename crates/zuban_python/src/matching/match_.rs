@@ -65,6 +65,12 @@ pub(crate) enum MismatchReason {
     },
     SequenceInsteadOfListNeeded,
     MappingInsteadOfDictNeeded,
+    IncompatibleCallableParam {
+        position: usize, // 1-based
+        name: Option<Box<str>>,
+        expected: Type,
+        got: Type,
+    },
 }
 
 impl Match {
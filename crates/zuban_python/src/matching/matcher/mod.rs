@@ -949,6 +949,19 @@ impl<'a> Matcher<'a> {
             .unwrap_or_else(|| Cow::Borrowed(t))
     }
 
+    pub fn is_uncalculated_type_var(&self, t: &Type) -> bool {
+        let Type::TypeVar(usage) = t else {
+            return false;
+        };
+        self.find_responsible_type_var_matcher_index(
+            usage.in_definition,
+            usage.temporary_matcher_id,
+        )
+        .is_some_and(|i| {
+            !self.type_var_matchers[i].calculating_type_args[usage.index.as_usize()].calculated()
+        })
+    }
+
     pub fn replace_usage_if_calculated(
         &self,
         db: &Database,
@@ -1555,6 +1568,7 @@ impl<'a> Matcher<'a> {
                     *calc = Default::default();
                 } else {
                     calc.defined_by_result_context = true;
+                    calc.merged_with_argument = false;
                     calc.has_any_in_context = calc.type_.has_any(i_s);
                 }
             }
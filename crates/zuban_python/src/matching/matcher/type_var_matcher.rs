@@ -21,6 +21,8 @@ pub(super) struct CalculatingTypeArg {
     pub(super) type_: Bound,
     pub(super) unresolved_transitive_constraints: Vec<Bound>,
     pub(super) defined_by_result_context: bool,
+    // Whether an argument was merged into a bound that was defined by the result context.
+    pub(super) merged_with_argument: bool,
     pub(super) uninferrable: bool,
     pub(super) has_any_in_context: bool,
 }
@@ -59,7 +61,24 @@ impl CalculatingTypeArg {
             }
             Bound::Uncalculated { .. } => return Match::new_true(),
         };
+        // Only a bound that still comes purely from the context can be dropped. Once an argument
+        // was merged, a conflict is between the arguments themselves.
+        let arg_bound = (self.defined_by_result_context
+            && !self.merged_with_argument
+            && !i_s.db.project.settings.mypy_compatible)
+            .then(|| Bound::new(t.clone(), variance));
         let m = m & self.merge_or_mismatch(i_s, t, variance);
+        if let Some(arg_bound) = arg_bound
+            && (!m.bool() || matches!(self.type_, Bound::Upper(BoundKind::TypeVar(Type::Never(_)))))
+        {
+            // The context is only a hint. If it conflicts with what the arguments tell us, the
+            // arguments win and a mismatch is reported where the result is used.
+            debug!("Dropping the context bound of a type var in favor of the argument");
+            self.type_ = arg_bound;
+            self.defined_by_result_context = false;
+            return Match::new_true();
+        }
+        self.merged_with_argument |= self.defined_by_result_context;
         if !m.bool() && !self.defined_by_result_context {
             self.uninferrable = true;
         }
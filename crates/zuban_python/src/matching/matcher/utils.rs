@@ -6,7 +6,7 @@ use parsa_python_cst::ParamKind;
 use super::{
     super::{
         ArgumentIndexWithParam, FormatData, Generics, Match, Matcher, MismatchReason, OnTypeError,
-        ResultContext, SignatureMatch,
+        ResultContext, SignatureMatch, check_argument_taint, record_match_explanation,
     },
     ReplaceSelfInMatcher,
     type_var_matcher::TypeVarMatcher,
@@ -637,6 +637,10 @@ fn calc_type_vars_with_callback<'db: 'a, 'a>(
         )
     }
     let matches = check_params(&mut matcher);
+    let type_var_likes = matcher
+        .type_var_matchers
+        .first()
+        .map(|m| m.type_var_likes.clone());
     let mut result = matcher.into_type_arguments(i_s, match_in_definition);
     if matches!(result.matches, SignatureMatch::False { .. }) {
         if on_type_error.is_some() {
@@ -651,6 +655,26 @@ fn calc_type_vars_with_callback<'db: 'a, 'a>(
     if had_wrong_init_type_var {
         result.matches = SignatureMatch::False { similar: false };
     }
+    if let Some(on_type_error) = on_type_error
+        && !matches!(result.matches, SignatureMatch::False { .. })
+        && let Some(type_var_likes) = type_var_likes
+        && let Some(type_arguments) = &result.type_arguments
+        && i_s.db.project.flags.is_error_code_enabled("inferred-never")
+    {
+        for (type_var_like, generic_item) in type_var_likes.iter().zip(type_arguments.iter()) {
+            if matches!(
+                generic_item,
+                GenericItem::TypeArg(Type::Never(NeverCause::Inference))
+            ) {
+                add_issue(IssueKind::InferredNeverTypeArgument {
+                    type_var: type_var_like.name(i_s.db).into(),
+                    function: (on_type_error.generate_diagnostic_string)(func_like, i_s.db)
+                        .map(Into::into)
+                        .unwrap_or_else(|| "function".into()),
+                })
+            }
+        }
+    }
     if cfg!(feature = "zuban_debug")
         && let Some(type_arguments) = &result.type_arguments
     {
@@ -785,6 +809,7 @@ pub(crate) fn match_arguments_against_params<
                             reason,
                             got,
                             expected: &expected,
+                            explanation: &[],
                         };
                         (on_type_error.callback)(i_s, &diagnostic_string, arg, error_types)
                     }
@@ -818,7 +843,15 @@ pub(crate) fn match_arguments_against_params<
                 delayed_params.push((i, p.clone()));
                 return;
             }
-            let m = expected.is_super_type_of(i_s, matcher, &value_t);
+            let mut explanation = vec![];
+            let m = if i_s.db.project.flags.explain_mismatch && on_type_error.is_some() {
+                let (m, trail) =
+                    record_match_explanation(|| expected.is_super_type_of(i_s, matcher, &value_t));
+                explanation = trail;
+                m
+            } else {
+                expected.is_super_type_of(i_s, matcher, &value_t)
+            };
             if let Match::False { reason, .. } = &m {
                 debug!(
                     "Mismatch between {:?} and {:?} -> {:?}",
@@ -844,6 +877,7 @@ pub(crate) fn match_arguments_against_params<
                                 reason,
                                 got: GotType::from_arg(i_s, arg, &value_t),
                                 expected: &expected,
+                                explanation: &explanation,
                             };
                             (on_type_error.callback)(i_s, &diagnostic_string, arg, error_types)
                         }
@@ -910,7 +944,10 @@ pub(crate) fn match_arguments_against_params<
                         unreachable!()
                     }
                 };
-                match_arg(argument, p.param.might_have_type_vars(), expected)
+                match_arg(argument, p.param.might_have_type_vars(), expected);
+                if should_generate_errors && i_s.flags().is_error_code_enabled("taint") {
+                    check_argument_taint(i_s, func_like, p.param.name(i_s.db), argument)
+                }
             }
             ParamArgument::ParamSpecArgs(..) => {
                 let ParamArgument::ParamSpecArgs(param_spec, args) = p.argument else {
@@ -998,6 +1035,7 @@ pub(crate) fn match_arguments_against_params<
                                 let Some(on_type_error) = on_type_error else {
                                     return;
                                 };
+                                let mut is_exact_argument = true;
                                 let argument = if index >= 0 {
                                     if args.is_empty() {
                                         too_few_arguments();
@@ -1009,15 +1047,23 @@ pub(crate) fn match_arguments_against_params<
                                     // here, but it is likely that there are some cases that are so
                                     // complicated that it's just not worth it to 100% get the
                                     // index correct.
+                                    is_exact_argument = (index as usize) < args.len();
                                     &args[(index as usize).min(args.len() - 1)]
                                 } else {
                                     let mut index = index + args.len() as isize;
                                     if index < 0 {
+                                        is_exact_argument = false;
                                         index = 0;
                                     }
                                     &args[index as usize]
                                 };
-                                if let Some(star_t) = argument.maybe_star_type(i_s) {
+                                // Items of unpacked fixed length tuples are reported on their
+                                // own if we know exactly which item failed.
+                                let report_item = is_exact_argument
+                                    && argument.unpacked_index.is_some()
+                                    && !i_s.db.project.settings.mypy_compatible;
+                                if !report_item && let Some(star_t) = argument.maybe_star_type(i_s)
+                                {
                                     error_types.got = GotType::Starred(star_t)
                                 }
                                 (on_type_error.callback)(
@@ -1078,10 +1124,11 @@ pub(crate) fn match_arguments_against_params<
             ParamArgument::None => (),
         }
     }
-    let add_missing_kw_issue = |param_name| {
-        let mut s = format!("Missing named argument {:?}", param_name);
-        s += diagnostic_string(" for ").as_deref().unwrap_or("");
-        add_issue(IssueKind::ArgumentIssue(s.into()));
+    let add_missing_kw_issue = |param_name: &str| {
+        add_issue(IssueKind::MissingNamedArgument {
+            name: param_name.into(),
+            function: diagnostic_string(""),
+        });
     };
     if args_with_params.too_many_positional_arguments {
         matches = Match::new_false();
@@ -1156,6 +1203,13 @@ pub(crate) fn match_arguments_against_params<
             add_issue(IssueKind::ArgumentIssue(s.into()));
         };
     }
+    for duplicate in &args_with_params.duplicate_keyword_arguments {
+        matches = Match::new_false();
+        if should_generate_errors {
+            let name = duplicate.keyword_name(i_s.db).unwrap();
+            add_keyword_argument_issue_maybe_multi_value(duplicate, name, true)
+        }
+    }
     if let Some(missing_unpacked_typed_dict_names) = missing_unpacked_typed_dict_names {
         for (missing, required) in missing_unpacked_typed_dict_names {
             if required {
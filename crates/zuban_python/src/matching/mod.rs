@@ -1,12 +1,15 @@
+mod explain;
 mod generic;
 mod generics;
 mod match_;
 mod matcher;
 mod result_context;
+mod taint;
 mod utils;
 
 use std::{borrow::Cow, cell::RefCell, collections::HashMap, sync::Arc};
 
+pub(crate) use explain::{ExplainStep, format_match_result, record_match_explanation};
 pub(crate) use generic::Generic;
 pub(crate) use generics::Generics;
 pub(crate) use match_::{ArgumentIndexWithParam, Match, MismatchReason, SignatureMatch};
@@ -16,6 +19,7 @@ pub(crate) use matcher::{
     calc_func_type_vars, calc_untyped_func_type_vars,
 };
 pub(crate) use result_context::{CouldBeALiteral, ResultContext};
+pub(crate) use taint::check_argument_taint;
 pub(crate) use utils::{
     calculate_property_return, create_signature_without_self_for_callable, match_self_type,
     maybe_class_usage, maybe_replace_class_type_vars, replace_class_type_vars,
@@ -113,7 +117,16 @@ pub fn avoid_protocol_mismatch(
                 had_type_var_matcher,
             );
             let _indent = debug_indent();
+            let explain = ExplainStep::start();
             let result = callable();
+            explain.finish(|| {
+                format!(
+                    r#"Does "{}" implement protocol "{}"? {}"#,
+                    t2.format_short(db),
+                    t1.format_short(db),
+                    format_match_result(&result),
+                )
+            });
             if !had_type_var_matcher {
                 cache
                     .cached
@@ -186,7 +199,10 @@ pub(crate) enum GotType<'t> {
 
 impl<'t> GotType<'t> {
     pub fn from_arg(i_s: &InferenceState, arg: &Arg, value_t: &'t Type) -> Self {
-        if let Some(star_t) = arg.maybe_star_type(i_s) {
+        if arg.unpacked_index.is_some() && !i_s.db.project.settings.mypy_compatible {
+            // The item is reported on its own, see on_argument_type_error
+            Self::Type(value_t)
+        } else if let Some(star_t) = arg.maybe_star_type(i_s) {
             Self::Starred(star_t)
         } else if let Some(double_star_t) = arg.maybe_star_star_type(i_s) {
             Self::DoubleStarred(double_star_t)
@@ -216,6 +232,8 @@ pub(crate) struct ErrorTypes<'a> {
     pub got: GotType<'a>,
     pub expected: &'a Type,
     pub reason: &'a MismatchReason,
+    // The decisions of the matcher, recorded with `--explain-mismatch`.
+    pub explanation: &'a [String],
 }
 
 pub fn format_got_expected(db: &Database, got: &Type, expected: &Type) -> ErrorStrs {
@@ -224,6 +242,7 @@ pub fn format_got_expected(db: &Database, got: &Type, expected: &Type) -> ErrorS
         got: GotType::Type(got),
         expected,
         reason: &MismatchReason::None,
+        explanation: &[],
     }
     .as_boxed_strs(db)
 }
@@ -268,7 +287,7 @@ impl ErrorTypes<'_> {
         }
     }
 
-    pub(crate) fn add_mismatch_notes(&self, add_issue: impl Fn(IssueKind)) {
+    pub(crate) fn add_mismatch_notes(&self, db: &Database, add_issue: impl Fn(IssueKind)) {
         match self.reason {
             MismatchReason::SequenceInsteadOfListNeeded => {
                 add_issue(IssueKind::InvariantNote {
@@ -287,8 +306,30 @@ impl ErrorTypes<'_> {
                     add_issue(IssueKind::Note(note.clone()));
                 }
             }
+            MismatchReason::IncompatibleCallableParam {
+                position,
+                name,
+                expected,
+                got,
+            } => {
+                let ErrorStrs { got, expected } = format_got_expected(db, got, expected);
+                let param = match name {
+                    Some(name) => format!(r#"Parameter {position} ("{name}")"#),
+                    None => format!("Parameter {position}"),
+                };
+                add_issue(IssueKind::Note(
+                    format!(r#"{param} has incompatible type "{got}"; expected "{expected}""#)
+                        .into(),
+                ));
+            }
             _ => (),
         }
+        if !self.explanation.is_empty() {
+            add_issue(IssueKind::Note("Explanation of the mismatch:".into()));
+            for line in self.explanation {
+                add_issue(IssueKind::Note(format!("  {line}").into()));
+            }
+        }
     }
 }
 
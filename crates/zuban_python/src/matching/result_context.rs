@@ -3,9 +3,9 @@ use core::fmt;
 use super::Matcher;
 use crate::{
     InferenceState,
-    database::PointLink,
+    database::{Database, PointLink},
     file::ClassNodeRef,
-    type_::{AnyCause, TupleArgs, Type, UniqueInUnpackedUnionError},
+    type_::{AnyCause, TupleArgs, Type, TypeVarKind, UniqueInUnpackedUnionError},
     type_helpers::Class,
 };
 
@@ -110,24 +110,42 @@ impl<'a> ResultContext<'a, '_> {
         {
             return CouldBeALiteral::No;
         }
-        fn could_be_a_literal(type_: &Type) -> CouldBeALiteral {
+        fn could_be_a_literal(db: &Database, type_: &Type) -> CouldBeALiteral {
             match type_ {
                 Type::Literal(_) | Type::LiteralString { .. } | Type::EnumMember(_) => {
                     CouldBeALiteral::Yes { implicit: false }
                 }
-                Type::Union(items) => {
-                    for t in items.iter() {
-                        if let x @ CouldBeALiteral::Yes { .. } = could_be_a_literal(t) {
-                            return x;
-                        }
-                    }
-                    CouldBeALiteral::No
-                }
+                Type::Union(items) => first_literal(db, items.iter()),
+                // def f(x: T) -> T with T bound to (or constrained by) literals should keep the
+                // literal of the argument, otherwise the call would not match.
+                Type::TypeVar(tv) => match tv.type_var.kind(db) {
+                    TypeVarKind::Bound(bound) => could_be_a_literal(db, bound),
+                    TypeVarKind::Constraints(constraints) => first_literal(db, constraints),
+                    TypeVarKind::Unrestricted => CouldBeALiteral::No,
+                },
                 _ => CouldBeALiteral::No,
             }
         }
-        self.with_type_if_exists_and_replace_type_var_likes(i_s, could_be_a_literal)
-            .unwrap_or(CouldBeALiteral::Yes { implicit: true })
+        fn first_literal<'x>(
+            db: &Database,
+            mut types: impl Iterator<Item = &'x Type>,
+        ) -> CouldBeALiteral {
+            types
+                .find_map(|t| match could_be_a_literal(db, t) {
+                    x @ CouldBeALiteral::Yes { .. } => Some(x),
+                    CouldBeALiteral::No => None,
+                })
+                .unwrap_or(CouldBeALiteral::No)
+        }
+        let result = self
+            .with_type_if_exists_and_replace_type_var_likes(i_s, |t| could_be_a_literal(i_s.db, t));
+        match (result, self) {
+            // Unsolved type vars are replaced with Any, but their bounds can still demand literals.
+            (Some(CouldBeALiteral::No), Self::WithMatcher { type_, .. }) => {
+                could_be_a_literal(i_s.db, type_)
+            }
+            (result, _) => result.unwrap_or(CouldBeALiteral::Yes { implicit: true }),
+        }
     }
 
     pub fn expect_not_none(&mut self) -> bool {
@@ -0,0 +1,297 @@
+// An opt-in check (`--enable-error-code=taint`) that follows values that are marked as
+// `Annotated[str, Untrusted]` into parameters that require `Annotated[str, Trusted]`. The
+// metadata of `Annotated` is not part of our types, so both sides are looked up in the syntax
+// tree and the markers are resolved to their classes. `Untrusted` has to be defined in the same
+// module as the `Trusted` class of the parameter.
+//
+// Untrusted values stay untrusted through operations like `+`, `%`, f-strings, subscripts and
+// calls like `s.strip()` or `str(s)`. Only sanitizers remove the taint, which are functions that
+// return the same `Annotated[str, Trusted]` that the parameter requires.
+
+use parsa_python_cst::{
+    Argument, ArgumentsDetails, AssignmentContent, AtomContent, Expression, ExpressionContent,
+    ExpressionPart, FStringContent, FunctionDef, Name, NameDef, PrimaryContent, PrimaryOrAtom,
+    SliceContent, SliceType, StringType, TypeLike,
+};
+
+use crate::{
+    arguments::{Arg, ArgKind},
+    database::{Database, PointKind, Specific},
+    diagnostics::IssueKind,
+    file::{ClassNodeRef, PythonFile},
+    inference_state::InferenceState,
+    node_ref::NodeRef,
+    type_::Type,
+    type_helpers::FuncLike,
+};
+
+const TRUSTED: &str = "Trusted";
+const UNTRUSTED: &str = "Untrusted";
+// Assignments like `a = b` are followed, but only up to a limit to avoid following cycles.
+const MAX_ASSIGNMENT_DEPTH: usize = 8;
+
+pub(crate) fn check_argument_taint(
+    i_s: &InferenceState,
+    func_like: &dyn FuncLike,
+    param_name: Option<&str>,
+    arg: &Arg,
+) {
+    let Some(param_name) = param_name else {
+        return;
+    };
+    let (file, expr) = match &arg.kind {
+        ArgKind::Positional(positional) => {
+            (positional.node_ref.file, positional.named_expr.expression())
+        }
+        ArgKind::Keyword(keyword) => (keyword.node_ref.file, keyword.expression),
+        _ => return,
+    };
+    let func_ref = NodeRef::from_link(i_s.db, func_like.defined_at());
+    let Some(func) = func_ref.maybe_function() else {
+        return;
+    };
+    let Some(trusted) = trusted_marker_of_param(i_s.db, func_ref.file, func, param_name) else {
+        return;
+    };
+    let checker = TaintChecker { i_s, file, trusted };
+    if let Some(name) = checker.untrusted_name(expr, 0) {
+        arg.add_issue(
+            i_s,
+            IssueKind::UntrustedArgument {
+                name: name.as_str().into(),
+                param: param_name.into(),
+            },
+        )
+    }
+}
+
+fn trusted_marker_of_param<'db>(
+    db: &'db Database,
+    file: &PythonFile,
+    func: FunctionDef,
+    param_name: &str,
+) -> Option<ClassNodeRef<'db>> {
+    let param = func
+        .params()
+        .iter()
+        .find(|param| param.name_def().as_code() == param_name)?;
+    let expr = param.annotation()?.maybe_starred().err()?;
+    annotated_markers(db, file, expr)
+        .into_iter()
+        .find(|marker| marker.name() == TRUSTED)
+}
+
+/// Returns the classes of the markers in annotations like `Annotated[str, Trusted]` or
+/// `Annotated[str, Trusted()]`. `Annotated` and the markers are resolved, so they may also be
+/// imported under a different name.
+fn annotated_markers<'db>(
+    db: &'db Database,
+    file: &PythonFile,
+    expr: Expression,
+) -> Vec<ClassNodeRef<'db>> {
+    let ExpressionContent::ExpressionPart(ExpressionPart::Primary(primary)) = expr.unpack() else {
+        return vec![];
+    };
+    let PrimaryContent::GetItem(SliceType::Slices(slices)) = primary.second() else {
+        return vec![];
+    };
+    let i_s = &InferenceState::new(db, file);
+    let inference = file.inference(i_s);
+    let base = inference.infer_primary_or_atom(primary.first());
+    if base.maybe_saved_specific(db) != Some(Specific::TypingAnnotated) {
+        return vec![];
+    }
+    slices
+        .iter()
+        .skip(1)
+        .filter_map(|slice| {
+            let SliceContent::NamedExpression(named_expr) = slice else {
+                return None;
+            };
+            let marker = match named_expr.expression().unpack() {
+                // For instances like `Trusted()` only the class is relevant.
+                ExpressionContent::ExpressionPart(ExpressionPart::Primary(p))
+                    if matches!(p.second(), PrimaryContent::Execution(_)) =>
+                {
+                    inference.infer_primary_or_atom(p.first())
+                }
+                _ => inference.infer_named_expression(named_expr),
+            };
+            let t = marker.as_cow_type(i_s);
+            let Type::Type(t) = t.as_ref() else {
+                return None;
+            };
+            Some(t.maybe_class(db)?.node_ref.to_db_lifetime(db))
+        })
+        .collect()
+}
+
+struct TaintChecker<'db, 'a> {
+    i_s: &'a InferenceState<'db, 'a>,
+    file: &'a PythonFile,
+    trusted: ClassNodeRef<'db>,
+}
+
+impl<'db, 'a> TaintChecker<'db, 'a> {
+    /// Returns the name that carries an `Untrusted` marker if the expression is derived from such
+    /// a name.
+    fn untrusted_name(&self, expr: Expression<'a>, depth: usize) -> Option<Name<'a>> {
+        match expr.unpack() {
+            ExpressionContent::ExpressionPart(part) => self.untrusted_name_in_part(part, depth),
+            ExpressionContent::Ternary(ternary) => {
+                let (if_, _, else_) = ternary.unpack();
+                self.untrusted_name_in_part(if_, depth)
+                    .or_else(|| self.untrusted_name(else_, depth))
+            }
+            ExpressionContent::Lambda(_) => None,
+        }
+    }
+
+    fn untrusted_name_in_part(&self, part: ExpressionPart<'a>, depth: usize) -> Option<Name<'a>> {
+        match part {
+            ExpressionPart::Atom(atom) => match atom.unpack() {
+                AtomContent::Name(name) => self.untrusted_definition(name, depth).then_some(name),
+                AtomContent::Strings(strings) => strings.iter().find_map(|string| {
+                    let StringType::FString(fstring) = string else {
+                        return None;
+                    };
+                    fstring.iter_content().find_map(|content| match content {
+                        FStringContent::FStringExpr(fstring_expr) => fstring_expr
+                            .unpack()
+                            .0
+                            .iter()
+                            .find_map(|expr| self.untrusted_name(expr, depth)),
+                        FStringContent::FStringString(_) => None,
+                    })
+                }),
+                AtomContent::NamedExpression(named_expr) => {
+                    self.untrusted_name(named_expr.expression(), depth)
+                }
+                _ => None,
+            },
+            ExpressionPart::Primary(primary) => match primary.second() {
+                PrimaryContent::Execution(details) => {
+                    if self.is_sanitizer(primary.first()) {
+                        return None;
+                    }
+                    // Methods of untrusted values like `s.strip()` return untrusted values.
+                    let receiver = match primary.first() {
+                        PrimaryOrAtom::Primary(callee)
+                            if matches!(callee.second(), PrimaryContent::Attribute(_)) =>
+                        {
+                            self.untrusted_name_in_primary_or_atom(callee.first(), depth)
+                        }
+                        _ => None,
+                    };
+                    let ArgumentsDetails::Node(args) = details else {
+                        return receiver;
+                    };
+                    receiver.or_else(|| {
+                        args.iter().find_map(|arg| match arg {
+                            Argument::Positional(named_expr) => {
+                                self.untrusted_name(named_expr.expression(), depth)
+                            }
+                            Argument::Keyword(kwarg) => {
+                                self.untrusted_name(kwarg.unpack().1, depth)
+                            }
+                            Argument::Star(starred) => {
+                                self.untrusted_name(starred.expression(), depth)
+                            }
+                            Argument::StarStar(_) => None,
+                        })
+                    })
+                }
+                PrimaryContent::GetItem(_) => {
+                    self.untrusted_name_in_primary_or_atom(primary.first(), depth)
+                }
+                PrimaryContent::Attribute(_) => None,
+            },
+            ExpressionPart::Sum(sum) => {
+                let op = sum.as_operation();
+                self.untrusted_name_in_part(op.left, depth)
+                    .or_else(|| self.untrusted_name_in_part(op.right, depth))
+            }
+            ExpressionPart::Term(term) => {
+                let op = term.as_operation();
+                self.untrusted_name_in_part(op.left, depth)
+                    .or_else(|| self.untrusted_name_in_part(op.right, depth))
+            }
+            _ => None,
+        }
+    }
+
+    fn untrusted_name_in_primary_or_atom(
+        &self,
+        p: PrimaryOrAtom<'a>,
+        depth: usize,
+    ) -> Option<Name<'a>> {
+        match p {
+            PrimaryOrAtom::Primary(primary) => {
+                self.untrusted_name_in_part(ExpressionPart::Primary(primary), depth)
+            }
+            PrimaryOrAtom::Atom(atom) => {
+                self.untrusted_name_in_part(ExpressionPart::Atom(atom), depth)
+            }
+        }
+    }
+
+    /// Checks if the name is defined with an `Untrusted` marker or assigned from an untrusted
+    /// value.
+    fn untrusted_definition(&self, name: Name<'a>, depth: usize) -> bool {
+        let file = self.file;
+        let point = NodeRef::new(file, name.index()).point();
+        if !point.calculated()
+            || point.kind() != PointKind::Redirect
+            || point.file_index() != file.file_index
+        {
+            return false;
+        }
+        let Some(definition) = Name::maybe_by_index(&file.tree, point.node_index())
+            .and_then(|n| n.name_def())
+            .or_else(|| NameDef::maybe_by_index(&file.tree, point.node_index()))
+        else {
+            return false;
+        };
+        match definition.expect_type() {
+            TypeLike::ParamName(Some(annotation)) => self.is_untrusted(annotation.expression()),
+            TypeLike::Assignment(assignment) => match assignment.unpack() {
+                AssignmentContent::WithAnnotation(_, annotation, _) => {
+                    self.is_untrusted(annotation.expression())
+                }
+                AssignmentContent::Normal(_, right_side) if depth < MAX_ASSIGNMENT_DEPTH => {
+                    right_side
+                        .maybe_simple_expression()
+                        .is_some_and(|expr| self.untrusted_name(expr, depth + 1).is_some())
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn is_untrusted(&self, annotation: Expression) -> bool {
+        annotated_markers(self.i_s.db, self.file, annotation)
+            .iter()
+            .any(|marker| {
+                marker.name() == UNTRUSTED && marker.file_index() == self.trusted.file_index()
+            })
+    }
+
+    /// Sanitizers are functions that return `Annotated[str, Trusted]`.
+    fn is_sanitizer(&self, callee: PrimaryOrAtom) -> bool {
+        let i_s = self.i_s;
+        let inferred = self.file.inference(i_s).infer_primary_or_atom(callee);
+        let t = inferred.as_cow_type(i_s);
+        let Type::Callable(callable) = t.as_ref() else {
+            return false;
+        };
+        let func_ref = NodeRef::from_link(i_s.db, callable.defined_at);
+        func_ref
+            .maybe_function()
+            .and_then(|func| func.return_annotation())
+            .is_some_and(|annotation| {
+                annotated_markers(i_s.db, func_ref.file, annotation.expression())
+                    .contains(&self.trusted)
+            })
+    }
+}
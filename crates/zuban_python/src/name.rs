@@ -35,11 +35,9 @@ impl ValueName<'_, '_> {
         self.type_.format_short(self.name.db())
     }
 
-    pub fn maybe_pretty_function_type(&self) -> Option<Box<str>> {
-        match self.type_ {
-            Type::Callable(c) => Some(c.format_pretty(&FormatData::new_short(self.name.db()))),
-            _ => None,
-        }
+    /// Like the type description, but functions are formatted like their definition.
+    pub fn pretty_type_description(&self) -> Box<str> {
+        format_type_pretty(self.name.db(), self.type_)
     }
 
     /// This is mostly for testing, you should probably not use this.
@@ -51,6 +49,21 @@ impl ValueName<'_, '_> {
     }
 }
 
+/// Formats functions like their definition (every signature of an overload on its own line) and
+/// all other types like in error messages.
+pub(crate) fn format_type_pretty(db: &Database, type_: &Type) -> Box<str> {
+    match type_ {
+        Type::Callable(c) => c.format_pretty(&FormatData::new_short(db)),
+        Type::FunctionOverload(overload) => overload
+            .iter_functions()
+            .map(|c| c.format_pretty(&FormatData::new_short(db)))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .into(),
+        _ => type_.format_short(db),
+    }
+}
+
 impl<'db, 'x> Name<'db, 'x> {
     pub fn name(&self) -> &str {
         match self {
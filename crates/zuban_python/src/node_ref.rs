@@ -294,8 +294,11 @@ impl<'file> NodeRef<'file> {
     }
 
     pub(crate) fn add_issue(&self, i_s: &InferenceState, kind: IssueKind) {
+        if !i_s.should_add_issue() {
+            return;
+        }
         let issue = Issue::from_node_index(&self.file.tree, self.node_index, kind, false);
-        self.file.add_issue(i_s, issue)
+        self.file.add_type_issue(i_s.db, issue)
     }
 
     pub(crate) fn add_type_issue(&self, db: &Database, kind: IssueKind) {
@@ -1,6 +1,7 @@
 use std::{borrow::Cow, iter::Peekable, sync::Arc};
 
 use parsa_python_cst::ParamKind;
+use smallvec::SmallVec;
 
 use crate::{
     arguments::{Arg, ArgKind},
@@ -8,15 +9,19 @@ use crate::{
     debug,
     format_data::{FormatData, ParamsStyle},
     inference_state::InferenceState,
-    matching::{Match, Matcher},
+    matching::{Match, Matcher, MismatchReason},
     type_::{
-        AnyCause, CallableParam, CallableParams, MaybeUnpackGatherer, ParamSpecUsage, ParamType,
-        StarParamType, StarStarParamType, StringSlice, Tuple, TupleArgs, TupleUnpack, Type,
-        TypedDict, TypedDictMember, Variance, WithUnpack, empty_types,
+        AnyCause, CallableParam, CallableParams, DbString, MaybeUnpackGatherer, ParamSpecUsage,
+        ParamType, StarParamType, StarStarParamType, StringSlice, Tuple, TupleArgs, TupleUnpack,
+        Type, TypedDict, TypedDictMember, TypedDictMembers, Variance, WithUnpack, empty_types,
         match_arbitrary_len_vs_unpack, match_tuple_type_arguments,
     },
 };
 
+// Param matching runs for every call and almost never needs more than a few of these temporary
+// entries, so they are kept on the stack instead of allocating.
+pub(crate) type ParamBuffer<T> = SmallVec<[T; 4]>;
+
 pub trait Param<'x>: Copy + std::fmt::Debug {
     fn has_default(&self) -> bool;
     fn name(&self, db: &'x Database) -> Option<&str>;
@@ -132,13 +137,15 @@ pub fn matches_simple_params<
         match_with_variance(i_s, matcher, a, b, variance)
     };
 
-    let mut unused_keyword_params: Vec<P2> = vec![];
-    let mut mismatched_name_pos_params1: Vec<P1> = vec![];
-    let mut mismatched_name_pos_params2: Vec<P2> = vec![];
+    let mut unused_keyword_params: ParamBuffer<P2> = SmallVec::new();
+    let mut mismatched_name_pos_params1: ParamBuffer<P1> = SmallVec::new();
+    let mut mismatched_name_pos_params2: ParamBuffer<P2> = SmallVec::new();
 
     let mut matches = Match::new_true();
     let mut params1 = params1.peekable();
+    let mut position = 0;
     'p1_iter: while let Some(param1) = params1.next() {
+        position += 1;
         if let Some(mut param2) = params2
             .peek()
             .or_else(|| unused_keyword_params.first())
@@ -186,7 +193,14 @@ pub fn matches_simple_params<
                 WrappedParamType::PositionalOnly(t1) => match &specific2 {
                     WrappedParamType::PositionalOnly(t2)
                     | WrappedParamType::PositionalOrKeyword(t2) => {
-                        matches &= match_(i_s, matcher, t1, t2)
+                        matches &= with_param_mismatch_reason(
+                            i_s,
+                            match_(i_s, matcher, t1, t2),
+                            position,
+                            param2.name(i_s.db),
+                            t1,
+                            t2,
+                        )
                     }
                     WrappedParamType::Star(WrappedStar::ArbitraryLen(t2)) => {
                         matches &= match_(i_s, matcher, t1, t2);
@@ -240,7 +254,14 @@ pub fn matches_simple_params<
                                 return Match::new_false();
                             }
                         }
-                        matches &= match_(i_s, matcher, t1, t2)
+                        matches &= with_param_mismatch_reason(
+                            i_s,
+                            match_(i_s, matcher, t1, t2),
+                            position,
+                            name2,
+                            t1,
+                            t2,
+                        )
                     }
                     WrappedParamType::Star(WrappedStar::ArbitraryLen(s2)) => {
                         matches &= match_(i_s, matcher, t1, s2);
@@ -289,7 +310,14 @@ pub fn matches_simple_params<
                     WrappedParamType::PositionalOnly(t2)
                         if matcher.ignore_positional_param_names() =>
                     {
-                        matches &= match_(i_s, matcher, t1, t2)
+                        matches &= with_param_mismatch_reason(
+                            i_s,
+                            match_(i_s, matcher, t1, t2),
+                            position,
+                            param1.name(i_s.db),
+                            t1,
+                            t2,
+                        )
                     }
                     _ => {
                         debug!(
@@ -354,7 +382,14 @@ pub fn matches_simple_params<
                                     match &param2.specific(i_s.db) {
                                         WrappedParamType::PositionalOrKeyword(t2)
                                         | WrappedParamType::KeywordOnly(t2) => {
-                                            matches &= match_(i_s, matcher, t1, t2);
+                                            matches &= with_param_mismatch_reason(
+                                                i_s,
+                                                match_(i_s, matcher, t1, t2),
+                                                position,
+                                                param2.name(i_s.db),
+                                                t1,
+                                                t2,
+                                            );
                                             found = true;
                                             break;
                                         }
@@ -479,7 +514,7 @@ pub fn matches_simple_params<
                             continue;
                         }
                         _ => {
-                            if !matcher.precise_matching
+                            if allows_trivial_suffix(i_s, matcher)
                                 && is_trivial_suffix(i_s.db, specific1, params1.next(), params2)
                             {
                                 debug!("Matched because of trivial suffix");
@@ -505,9 +540,12 @@ pub fn matches_simple_params<
                         ) => matches &= td2.matches(i_s, matcher, td1, true),
                         (WrappedStarStar::UnpackTypedDict(td1), WrappedStarStar::ValueType(t2)) => {
                             if let Some(t2) = t2 {
-                                // TODO extra_items: handle?!
-                                for member in td1.members(i_s.db).named.iter() {
-                                    matches &= member.type_.matches(i_s, matcher, &t2, variance)
+                                let ms = td1.members(i_s.db);
+                                let extra_items = ms.extra_items.as_ref().map(|e| &e.t);
+                                for t1 in ms.named.iter().map(|m| &m.type_).chain(extra_items) {
+                                    if !t1.is_never() {
+                                        matches &= t1.matches(i_s, matcher, &t2, variance)
+                                    }
                                 }
                             }
                         }
@@ -522,11 +560,10 @@ pub fn matches_simple_params<
                     ref specific2 @ (WrappedParamType::PositionalOrKeyword(ref t2)
                     | WrappedParamType::KeywordOnly(ref t2)) => match d1 {
                         WrappedStarStar::UnpackTypedDict(td1) => {
-                            // TODO extra_items: handle?!
                             return matches_simple_params(
                                 i_s,
                                 matcher,
-                                td1.members(i_s.db).named.iter().map(TypedDictMemberParam),
+                                TypedDictMemberParam::iter_params(td1.members(i_s.db)),
                                 params2,
                                 variance,
                             );
@@ -580,7 +617,7 @@ pub fn matches_simple_params<
                     return matches;
                 }
                 specific1 => {
-                    if !matcher.precise_matching
+                    if allows_trivial_suffix(i_s, matcher)
                         && is_trivial_suffix(i_s.db, specific1, params1.next(), params2)
                     {
                         debug!("Matched because of trivial suffix (too few params)");
@@ -627,6 +664,37 @@ pub fn matches_simple_params<
     matches
 }
 
+// Remembers the parameter pair that caused a mismatch, so it can be shown to the user. This is
+// only done for --explain-mismatch, because it's quite a bit of work that is usually not needed
+// (e.g. while checking overloads).
+fn with_param_mismatch_reason(
+    i_s: &InferenceState,
+    m: Match,
+    position: usize,
+    name: Option<&str>,
+    expected: &Option<Cow<Type>>,
+    got: &Option<Cow<Type>>,
+) -> Match {
+    match (m, expected, got) {
+        (
+            Match::False {
+                reason: MismatchReason::None,
+                similar,
+            },
+            Some(expected),
+            Some(got),
+        ) if i_s.db.project.flags.explain_mismatch => Match::False {
+            reason: MismatchReason::IncompatibleCallableParam {
+                position,
+                name: name.map(Into::into),
+                expected: expected.clone().into_owned(),
+                got: got.clone().into_owned(),
+            },
+            similar,
+        },
+        (m, _, _) => m,
+    }
+}
 fn params1_matches_unpacked_dict<'db: 'x, 'x>(
     i_s: &InferenceState<'db, '_>,
     matcher: &mut Matcher,
@@ -635,35 +703,58 @@ fn params1_matches_unpacked_dict<'db: 'x, 'x>(
     variance: Variance,
 ) -> Match {
     let tdm = u.members(i_s.db);
-    // TODO extra_items: handle?
+    let extra_items = tdm.extra_items.as_ref().filter(|e| !e.t.is_never());
+    let mut unmatched_members: Vec<_> = tdm.named.iter().collect();
     let mut required_members: Vec<_> = tdm.named.iter().filter(|m| m.required).collect();
+    let mut had_star_star = false;
     for param1 in params1 {
         match param1.specific(i_s.db) {
             WrappedParamType::KeywordOnly(t1) => {
-                if let Some(member2) = param1
-                    .name(i_s.db)
-                    .and_then(|name1| u.find_member(i_s.db, name1))
-                {
-                    required_members.retain(|n| n.name == member2.name);
+                let name1 = param1.name(i_s.db);
+                let t2 = if let Some(member2) = name1.and_then(|n| u.find_member(i_s.db, n)) {
+                    required_members.retain(|n| n.name != member2.name);
+                    unmatched_members.retain(|n| n.name != member2.name);
                     // TODO check if param can be optional
-                    if let Some(t1) = t1 {
-                        let m = t1.matches(i_s, matcher, &member2.type_, variance);
-                        if !m.bool() {
-                            debug!(
-                                "Param mismatch because unpacked type mismatched for {:?}",
-                                param1.name(i_s.db)
-                            );
-                            return m;
-                        }
-                    }
+                    &member2.type_
+                } else if let Some(extra_items) = extra_items {
+                    &extra_items.t
                 } else {
                     debug!("Param mismatch because kw name was not found in unpack");
                     return Match::new_false();
+                };
+                if let Some(t1) = t1 {
+                    let m = t1.matches(i_s, matcher, t2, variance);
+                    if !m.bool() {
+                        debug!("Param mismatch because unpacked type mismatched for {name1:?}");
+                        return m;
+                    }
+                }
+            }
+            WrappedParamType::StarStar(WrappedStarStar::ValueType(t1)) => {
+                had_star_star = true;
+                required_members.clear();
+                let Some(t1) = t1 else {
+                    continue;
+                };
+                let remaining_types = unmatched_members
+                    .iter()
+                    .map(|m| &m.type_)
+                    .chain(extra_items.map(|e| &e.t));
+                for t2 in remaining_types {
+                    let m = t1.matches(i_s, matcher, t2, variance);
+                    if !m.bool() {
+                        debug!("Param mismatch because **kwargs mismatched the unpacked type");
+                        return m;
+                    }
                 }
             }
             _ => return Match::new_false(),
         }
     }
+    if extra_items.is_some() && !had_star_star {
+        debug!("Param mismatch because the extra items of the unpack are not accepted");
+        return Match::new_false();
+    }
     if cfg!(debug_assertions) && !required_members.is_empty() {
         debug!(
             "Param mismatch because the required members {:?} were not matched",
@@ -676,6 +767,10 @@ fn params1_matches_unpacked_dict<'db: 'x, 'x>(
     required_members.is_empty().into()
 }
 
+fn allows_trivial_suffix(i_s: &InferenceState, matcher: &Matcher) -> bool {
+    !matcher.precise_matching && !i_s.db.project.flags.strict_signature_matching
+}
+
 fn is_trivial_suffix<'db: 'x + 'y, 'x, 'y, P1: Param<'x>, P2: Param<'y>>(
     db: &'db Database,
     p1: WrappedParamType,
@@ -779,6 +874,8 @@ pub fn has_overlapping_params(
 ) -> bool {
     match (params1, params2) {
         (CallableParams::Simple(params1), CallableParams::Simple(params2)) => {
+            let params1 = flatten_unpacked_params(i_s.db, params1);
+            let params2 = flatten_unpacked_params(i_s.db, params2);
             overload_has_overlapping_params(i_s, matcher, params1.iter(), params2.iter())
         }
         (CallableParams::Any(_), _) | (_, CallableParams::Any(_)) => true,
@@ -786,6 +883,63 @@ pub fn has_overlapping_params(
     }
 }
 
+/// Rewrites `*args: *tuple[...]` to positional params and `**kwargs: Unpack[TD]` to keyword
+/// params, which makes it possible to compare them with normal params.
+fn flatten_unpacked_params<'x>(
+    db: &Database,
+    params: &'x [CallableParam],
+) -> Cow<'x, [CallableParam]> {
+    let needs_flattening = |p: &CallableParam| {
+        matches!(
+            p.type_,
+            ParamType::Star(StarParamType::UnpackedTuple(_))
+                | ParamType::StarStar(StarStarParamType::UnpackTypedDict(_))
+        )
+    };
+    if !params.iter().any(needs_flattening) {
+        return Cow::Borrowed(params);
+    }
+    let positional = |t: &Type| CallableParam::new_anonymous(ParamType::PositionalOnly(t.clone()));
+    let star = |t| CallableParam::new_anonymous(ParamType::Star(StarParamType::ArbitraryLen(t)));
+    let mut result = vec![];
+    for p in params {
+        match &p.type_ {
+            ParamType::Star(StarParamType::UnpackedTuple(tup)) => match &tup.args {
+                TupleArgs::FixedLen(ts) => result.extend(ts.iter().map(positional)),
+                TupleArgs::ArbitraryLen(t) => result.push(star((**t).clone())),
+                TupleArgs::WithUnpack(with_unpack) => {
+                    result.extend(with_unpack.before.iter().map(positional));
+                    // The items after the unpack cannot be expressed with params. They are
+                    // simply ignored, which can only make the params overlap more.
+                    result.push(star(match &with_unpack.unpack {
+                        TupleUnpack::ArbitraryLen(t) => t.clone(),
+                        TupleUnpack::TypeVarTuple(_) => Type::Any(AnyCause::Internal),
+                    }))
+                }
+            },
+            ParamType::StarStar(StarStarParamType::UnpackTypedDict(td)) => {
+                let members = td.members(db);
+                result.extend(members.named.iter().map(|member| CallableParam {
+                    has_default: !member.required,
+                    ..CallableParam::new(
+                        DbString::StringSlice(member.name),
+                        ParamType::KeywordOnly(member.type_.clone()),
+                    )
+                }));
+                if let Some(extra) = &members.extra_items
+                    && !extra.t.is_never()
+                {
+                    result.push(CallableParam::new_anonymous(ParamType::StarStar(
+                        StarStarParamType::ValueType(extra.t.clone()),
+                    )))
+                }
+            }
+            _ => result.push(p.clone()),
+        }
+    }
+    Cow::Owned(result)
+}
+
 fn overload_has_overlapping_params<'db: 'x, 'x, P1: Param<'x>, P2: Param<'x>>(
     i_s: &InferenceState<'db, '_>,
     matcher: &mut Matcher,
@@ -802,11 +956,13 @@ fn overload_has_overlapping_params<'db: 'x, 'x, P1: Param<'x>, P2: Param<'x>>(
         | WrappedParamType::KeywordOnly(t2)
         | WrappedParamType::Star(WrappedStar::ArbitraryLen(t2))
         | WrappedParamType::StarStar(WrappedStarStar::ValueType(t2)) => t2,
-        // TODO work on these
-        WrappedParamType::Star(WrappedStar::ParamSpecArgs(_u)) => unimplemented!(),
-        WrappedParamType::Star(WrappedStar::UnpackedTuple(_u)) => unimplemented!(),
-        WrappedParamType::StarStar(WrappedStarStar::ParamSpecKwargs(_u)) => unimplemented!(),
-        WrappedParamType::StarStar(WrappedStarStar::UnpackTypedDict(_u)) => unimplemented!(),
+        // A ParamSpec can be anything and therefore overlaps with everything. Unpacks are
+        // flattened before (see flatten_unpacked_params), but in case they are still here,
+        // overlapping is the safe answer.
+        WrappedParamType::Star(WrappedStar::ParamSpecArgs(_) | WrappedStar::UnpackedTuple(_))
+        | WrappedParamType::StarStar(
+            WrappedStarStar::ParamSpecKwargs(_) | WrappedStarStar::UnpackTypedDict(_),
+        ) => None,
     };
     let mut check_type = |i_s: &InferenceState<'db, '_>, t1: Option<&Type>, p2: P2| {
         if let Some(t1) = t1
@@ -834,7 +990,7 @@ fn overload_has_overlapping_params<'db: 'x, 'x, P1: Param<'x>, P2: Param<'x>>(
             !has_default
         })
         .peekable();
-    let mut unused_keyword_params: Vec<P2> = vec![];
+    let mut unused_keyword_params: ParamBuffer<P2> = SmallVec::new();
     for param1 in params1.filter(|p| !p.has_default()) {
         match param1.specific(i_s.db) {
             WrappedParamType::PositionalOrKeyword(t1) | WrappedParamType::PositionalOnly(t1) => {
@@ -915,11 +1071,16 @@ fn overload_has_overlapping_params<'db: 'x, 'x, P1: Param<'x>, P2: Param<'x>>(
                     }
                 }
             }
-            WrappedParamType::Star(WrappedStar::UnpackedTuple(_u)) => {
-                // TODO
+            // *args: P.args accepts all positional arguments
+            WrappedParamType::Star(WrappedStar::ParamSpecArgs(_)) => {
+                while params2.peek().is_some_and(|p| {
+                    !matches!(p.kind(db), ParamKind::KeywordOnly | ParamKind::StarStar)
+                }) {
+                    params2.next();
+                }
             }
-            WrappedParamType::Star(WrappedStar::ParamSpecArgs(_u)) => {
-                // TODO
+            WrappedParamType::Star(WrappedStar::UnpackedTuple(_)) => {
+                // Flattened before, see flatten_unpacked_params
             }
             WrappedParamType::StarStar(WrappedStarStar::ValueType(t1)) => {
                 for param2 in params2 {
@@ -929,11 +1090,12 @@ fn overload_has_overlapping_params<'db: 'x, 'x, P1: Param<'x>, P2: Param<'x>>(
                 }
                 return !had_any_fallback_with_default;
             }
-            WrappedParamType::StarStar(WrappedStarStar::ParamSpecKwargs(_u)) => {
-                // TODO
+            // **kwargs: P.kwargs accepts all remaining arguments
+            WrappedParamType::StarStar(WrappedStarStar::ParamSpecKwargs(_)) => {
+                return !had_any_fallback_with_default;
             }
-            WrappedParamType::StarStar(WrappedStarStar::UnpackTypedDict(_u)) => {
-                // TODO
+            WrappedParamType::StarStar(WrappedStarStar::UnpackTypedDict(_)) => {
+                // Flattened before, see flatten_unpacked_params
             }
         }
     }
@@ -1015,12 +1177,17 @@ pub(crate) struct InferrableParamIterator<'db, 'a, I, P, AI: Iterator> {
     arguments: AI,
     current_arg: Option<Arg<'db, 'a>>,
     params: I,
-    pub unused_keyword_arguments: Vec<Arg<'db, 'a>>,
+    pub unused_keyword_arguments: ParamBuffer<Arg<'db, 'a>>,
     current_starred_param: Option<P>,
     current_double_starred_param: Option<P>,
     pub too_many_positional_arguments: bool,
     arbitrary_length_handled: bool,
     pub unused_unpack_typed_dict: UnpackTypedDictState,
+    // The `**b` in `foo(**a, **b)`, which is matched against the same params as `**a`.
+    additional_arbitrary_kwargs: Vec<Arg<'db, 'a>>,
+    pending_params: Vec<InferrableParam<'db, 'a, P>>,
+    unpacked_keys: ParamBuffer<(usize, &'db str)>,
+    pub duplicate_keyword_arguments: Vec<Arg<'db, 'a>>,
 }
 
 impl<'db, 'a, I, P, AI: Iterator<Item = Arg<'db, 'a>>> InferrableParamIterator<'db, 'a, I, P, AI> {
@@ -1030,12 +1197,16 @@ impl<'db, 'a, I, P, AI: Iterator<Item = Arg<'db, 'a>>> InferrableParamIterator<'
             arguments,
             current_arg: None,
             params,
-            unused_keyword_arguments: vec![],
+            unused_keyword_arguments: SmallVec::new(),
             current_starred_param: None,
             current_double_starred_param: None,
             too_many_positional_arguments: false,
             arbitrary_length_handled: true,
             unused_unpack_typed_dict: UnpackTypedDictState::Used,
+            additional_arbitrary_kwargs: vec![],
+            pending_params: vec![],
+            unpacked_keys: SmallVec::new(),
+            duplicate_keyword_arguments: vec![],
         }
     }
 
@@ -1057,19 +1228,47 @@ impl<'db, 'a, I, P, AI: Iterator<Item = Arg<'db, 'a>>> InferrableParamIterator<'
         self.arbitrary_length_handled
     }
 
+    fn next_argument(&mut self) -> Option<Arg<'db, 'a>> {
+        loop {
+            let arg = self.arguments.next()?;
+            if let ArgKind::Inferred {
+                position,
+                is_keyword: Some(Some(key)),
+                in_args_or_kwargs_and_arbitrary_len: false,
+                ..
+            } = &arg.kind
+                && arg.is_from_star_star_args()
+            {
+                // The same key in two different TypedDicts like `foo(**td1, **td2)`
+                let key = key.as_str(self.db);
+                if self
+                    .unpacked_keys
+                    .iter()
+                    .any(|(p, k)| p != position && *k == key)
+                {
+                    self.duplicate_keyword_arguments.push(arg);
+                    continue;
+                }
+                self.unpacked_keys.push((*position, key));
+            }
+            return Some(arg);
+        }
+    }
+
     pub fn next_arg(&mut self) -> Option<Arg<'db, 'a>> {
-        let arg = self.current_arg.take().or_else(|| self.arguments.next())?;
+        let arg = self.current_arg.take().or_else(|| self.next_argument())?;
         if arg.in_args_or_kwargs_and_arbitrary_len() {
             self.arbitrary_length_handled = false;
             self.current_arg = Some(arg.clone());
             if arg.is_arbitrary_kwargs() {
                 // A **kwargs
-                for next_arg in self.arguments.by_ref() {
-                    if next_arg.is_from_star_star_args() {
-                        debug!("TODO currently b in foo(**a, **b) is just ignored");
+                while let Some(next_arg) = self.next_argument() {
+                    if next_arg.is_arbitrary_kwargs() {
+                        self.additional_arbitrary_kwargs.push(next_arg);
                     } else {
                         debug_assert!(next_arg.is_keyword_argument());
-                        // This is y in `foo(**x, y=3)`
+                        // This is y in `foo(**x, y=3)` or a key of b in `foo(**x, **b)` if b is
+                        // a TypedDict.
                         return Some(next_arg);
                     }
                 }
@@ -1103,6 +1302,35 @@ where
     type Item = InferrableParam<'db, 'a, P>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(pending) = self.pending_params.pop() {
+            return Some(pending);
+        }
+        let p = self.next_param()?;
+        if let ParamArgument::Argument(arg) = &p.argument
+            && arg.is_arbitrary_kwargs()
+        {
+            // All mappings of `foo(**a, **b)` are checked against the param.
+            self.pending_params
+                .extend(
+                    self.additional_arbitrary_kwargs
+                        .iter()
+                        .map(|kwargs| InferrableParam {
+                            param: p.param,
+                            argument: ParamArgument::Argument(kwargs.clone()),
+                        }),
+                );
+        }
+        Some(p)
+    }
+}
+
+impl<'db: 'x, 'a, 'x, I, P, AI> InferrableParamIterator<'db, 'a, I, P, AI>
+where
+    I: Iterator<Item = P>,
+    P: Param<'x>,
+    AI: Iterator<Item = Arg<'db, 'a>>,
+{
+    fn next_param(&mut self) -> Option<InferrableParam<'db, 'a, P>> {
         if let Some(param) = self.current_starred_param {
             if let Some(argument) = self.maybe_exact_multi_arg(false) {
                 if let ArgKind::ParamSpec {
@@ -1143,7 +1371,7 @@ where
                 if !matches!(self.unused_unpack_typed_dict, UnpackTypedDictState::Used) {
                     for (i, unused) in self.unused_keyword_arguments.iter().enumerate() {
                         if let Some(key) = unused.keyword_name(self.db)
-                            && let Some(entry) = td.find_entry(self.db, key)
+                            && let Some(entry) = td.find_accepted_entry(self.db, key)
                         {
                             self.unused_unpack_typed_dict =
                                 UnpackTypedDictState::CheckingUnusedKwArgs;
@@ -1160,7 +1388,7 @@ where
                 }
                 while let Some(argument) = self.next_arg() {
                     if let Some(key) = argument.keyword_name(self.db) {
-                        if let Some(entry) = td.find_entry(self.db, key) {
+                        if let Some(entry) = td.find_accepted_entry(self.db, key) {
                             self.unused_unpack_typed_dict = UnpackTypedDictState::Used;
                             return Some(InferrableParam {
                                 param,
@@ -1351,23 +1579,63 @@ where
 }
 
 #[derive(Clone, Copy, Debug)]
-struct TypedDictMemberParam<'member>(&'member TypedDictMember);
+enum TypedDictMemberParam<'member> {
+    Member(&'member TypedDictMember),
+    // The extra_items of a TypedDict that is not closed behave like **kwargs.
+    ExtraItems(&'member Type),
+}
+
+impl<'member> TypedDictMemberParam<'member> {
+    fn iter_params(
+        members: &'member TypedDictMembers,
+    ) -> impl Iterator<Item = TypedDictMemberParam<'member>> + Clone {
+        let extra_items = members
+            .extra_items
+            .as_ref()
+            .filter(|e| !e.t.is_never())
+            .map(|e| Self::ExtraItems(&e.t));
+        members.named.iter().map(Self::Member).chain(extra_items)
+    }
+
+    fn type_(&self) -> &'member Type {
+        match self {
+            Self::Member(member) => &member.type_,
+            Self::ExtraItems(t) => t,
+        }
+    }
+}
 
 impl<'member> Param<'member> for TypedDictMemberParam<'member> {
     fn has_default(&self) -> bool {
-        !self.0.required
+        match self {
+            Self::Member(member) => !member.required,
+            Self::ExtraItems(_) => false,
+        }
     }
 
     fn name(&self, db: &'member Database) -> Option<&str> {
-        Some(self.0.name.as_str(db))
+        match self {
+            Self::Member(member) => Some(member.name.as_str(db)),
+            Self::ExtraItems(_) => None,
+        }
     }
 
     fn specific<'db: 'member>(&self, _: &'db Database) -> WrappedParamType<'member> {
-        WrappedParamType::KeywordOnly(Some(Cow::Borrowed(&self.0.type_)))
+        match self {
+            Self::Member(member) => {
+                WrappedParamType::KeywordOnly(Some(Cow::Borrowed(&member.type_)))
+            }
+            Self::ExtraItems(t) => {
+                WrappedParamType::StarStar(WrappedStarStar::ValueType(Some(Cow::Borrowed(t))))
+            }
+        }
     }
 
     fn kind(&self, _: &Database) -> ParamKind {
-        ParamKind::KeywordOnly
+        match self {
+            Self::Member(_) => ParamKind::KeywordOnly,
+            Self::ExtraItems(_) => ParamKind::StarStar,
+        }
     }
 
     fn into_callable_param(self) -> CallableParam {
@@ -1375,7 +1643,7 @@ impl<'member> Param<'member> for TypedDictMemberParam<'member> {
     }
 
     fn has_self_type(&self, db: &Database) -> bool {
-        self.0.type_.has_self_type(db)
+        self.type_().has_self_type(db)
     }
 }
 
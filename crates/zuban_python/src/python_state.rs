@@ -196,6 +196,7 @@ pub(crate) struct PythonState {
     typing_supports_index_index: NodeIndex,
     typing_overload_index: NodeIndex,
     typing_override_index: Option<NodeIndex>,
+    typing_extensions_override_index: Option<NodeIndex>,
     typing_final_index: NodeIndex,
     typing_typed_dict_index: NodeIndex,
     typing_mapping_index: NodeIndex,
@@ -233,6 +234,7 @@ pub(crate) struct PythonState {
     dataclasses_capital_field_index: NodeIndex,
     dataclasses_replace_index: NodeIndex,
     warnings_deprecated_index: Option<NodeIndex>,
+    typing_extensions_deprecated_index: Option<NodeIndex>,
     pub type_of_object: Type, // TODO currently unused
     pub type_of_any: Type,
     pub type_of_self: Type,
@@ -251,6 +253,7 @@ pub(crate) struct PythonState {
     pub generator_with_any_generics: Type,
     pub async_generator_with_any_generics: Type,
     pub valid_getattr_supertype: Type,
+    pub valid_module_dir_supertype: Type,
     pub iterable_of_str: Type,
     pub empty_type_var_likes: TypeVarLikes,
     pub dataclass_fields_type: Type,
@@ -325,6 +328,7 @@ impl PythonState {
             typing_cast_index: 0,
             typing_overload_index: 0,
             typing_override_index: None,
+            typing_extensions_override_index: None,
             typing_final_index: 0,
             typing_typed_dict_index: 0,
             typing_container_index: 0,
@@ -365,6 +369,7 @@ impl PythonState {
             dataclasses_capital_field_index: 0,
             dataclasses_replace_index: 0,
             warnings_deprecated_index: None,
+            typing_extensions_deprecated_index: None,
             type_of_object: Type::None, // Will be set later
             type_of_any: Type::Type(Arc::new(Type::Any(AnyCause::Todo))),
             type_of_self: Type::Type(Arc::new(Type::Self_)),
@@ -390,6 +395,7 @@ impl PythonState {
             generator_with_any_generics: Type::None, // Will be set later
             async_generator_with_any_generics: Type::None, // Will be set later
             valid_getattr_supertype: Type::None,     // Will be set later
+            valid_module_dir_supertype: Type::None,  // Will be set later
             iterable_of_str: Type::None,             // Will be set later
             empty_type_var_likes,
             dataclass_fields_type: Type::None, // Will be set later
@@ -736,13 +742,27 @@ impl PythonState {
         cache_index!(builtins_hasattr_index, builtins, "hasattr", true);
         cache_index!(builtins_len_index, builtins, "len", true);
 
+        // typing_extensions reexports these if they are available in the stdlib, otherwise they
+        // are defined there.
         cache_optional_index!(typing_override_index, typing, "override", true);
+        if db.python_state.typing_override_index.is_none() {
+            cache_optional_index!(
+                typing_extensions_override_index,
+                typing_extensions,
+                "override",
+                true
+            );
+        }
 
         cache_index!(dataclasses_replace_index, dataclasses_file, "replace", true);
 
         cache_optional_index!(warnings_deprecated_index, warnings, "deprecated");
         if db.python_state.warnings_deprecated_index.is_none() {
-            cache_optional_index!(warnings_deprecated_index, typing_extensions, "deprecated");
+            cache_optional_index!(
+                typing_extensions_deprecated_index,
+                typing_extensions,
+                "deprecated"
+            );
         }
 
         cache_index!(abc_abstractmethod_index, abc, "abstractmethod", true);
@@ -837,6 +857,14 @@ impl PythonState {
             Type::Any(AnyCause::Internal),
         )));
         s.iterable_of_str = new_class!(s.iterable_link(), s.str_type(),);
+        s.valid_module_dir_supertype = Type::Callable(Arc::new(CallableContent::new_simple(
+            None,
+            None,
+            PointLink::new(FileIndex(0), 0),
+            s.empty_type_var_likes.clone(),
+            CallableParams::new_simple(Arc::new([])),
+            s.iterable_of_str.clone(),
+        )));
 
         s.dataclass_fields_type = new_class!(
             s.dict_node_ref().as_link(),
@@ -856,15 +884,17 @@ impl PythonState {
 
         // Set promotions
         let s = &db.python_state;
-        s.int()
-            .use_cached_class_infos(db)
-            .set_promote_to(Some(s.float_node_ref().as_link()));
-        s.bool()
-            .use_cached_class_infos(db)
-            .set_promote_to(Some(s.float_node_ref().as_link()));
-        s.float()
-            .use_cached_class_infos(db)
-            .set_promote_to(Some(s.complex_node_ref().as_link()));
+        if !db.project.flags.strict_numeric_promotion {
+            s.int()
+                .use_cached_class_infos(db)
+                .set_promote_to(Some(s.float_node_ref().as_link()));
+            s.bool()
+                .use_cached_class_infos(db)
+                .set_promote_to(Some(s.float_node_ref().as_link()));
+            s.float()
+                .use_cached_class_infos(db)
+                .set_promote_to(Some(s.complex_node_ref().as_link()));
+        }
         if !db.project.flags.disable_memoryview_promotion {
             s.memoryview_class_with_generics_to_be_defined()
                 .use_cached_class_infos(db)
@@ -1028,8 +1058,18 @@ impl PythonState {
     class_node_ref!(typing, pub mapping_node_ref, typing_mapping_index);
     class_node_ref!(typing, pub mutable_mapping_node_ref, typing_mutable_mapping_index);
     class_node_ref!(typing, pub keys_view_node_ref, typing_keys_view_index);
-    optional_attribute_node_ref!(typing, pub typing_override, typing_override_index);
-    optional_attribute_node_ref!(warnings, pub deprecated, warnings_deprecated_index);
+    optional_attribute_node_ref!(typing, typing_override_of_typing, typing_override_index);
+    optional_attribute_node_ref!(
+        typing_extensions,
+        typing_override_of_typing_extensions,
+        typing_extensions_override_index
+    );
+    optional_attribute_node_ref!(warnings, deprecated_of_warnings, warnings_deprecated_index);
+    optional_attribute_node_ref!(
+        typing_extensions,
+        deprecated_of_typing_extensions,
+        typing_extensions_deprecated_index
+    );
     attribute_node_ref!(typing, pub typing_final, typing_final_index);
     class_node_ref!(typing, pub generator_node_ref, typing_generator_index);
     attribute_node_ref!(typing, pub iterable_node_ref, typing_iterable_index);
@@ -1098,7 +1138,20 @@ impl PythonState {
         dataclasses_capital_field_link,
         dataclasses_capital_field_index
     );
-    optional_attribute_link!(warnings, pub deprecated_link, warnings_deprecated_index);
+
+    pub fn typing_override(&self) -> Option<NodeRef<'_>> {
+        self.typing_override_of_typing()
+            .or_else(|| self.typing_override_of_typing_extensions())
+    }
+
+    pub fn deprecated(&self) -> Option<NodeRef<'_>> {
+        self.deprecated_of_warnings()
+            .or_else(|| self.deprecated_of_typing_extensions())
+    }
+
+    pub fn deprecated_link(&self) -> Option<PointLink> {
+        self.deprecated().map(|node_ref| node_ref.as_link())
+    }
 
     node_ref_to_class!(pub object_class, object_node_ref);
     node_ref_to_class!(int, int_node_ref);
@@ -1240,43 +1293,15 @@ fn typing_changes(
     typing_extensions: &PythonFile,
     mypy_extensions: &PythonFile,
 ) {
-    set_typing_inference(typing, "Protocol", Specific::TypingProtocol);
-    set_typing_inference(typing, "Generic", Specific::TypingGeneric);
-    set_typing_inference(typing, "ClassVar", Specific::TypingClassVar);
-
-    set_typing_inference(typing, "Union", Specific::TypingUnion);
-    set_typing_inference(typing, "Optional", Specific::TypingOptional);
-    set_typing_inference(typing, "Any", Specific::TypingAny);
-    set_typing_inference(typing, "Callable", Specific::TypingCallable);
-    set_typing_inference(typing, "Type", Specific::TypingType);
+    // Special forms are recognized the same way, whether they are imported from typing or from
+    // typing_extensions. Names that typing_extensions simply reexports from typing are skipped.
+    for module in [typing, typing_extensions] {
+        for (name, specific) in SHARED_SPECIAL_FORMS {
+            set_typing_inference(module, name, specific);
+        }
+    }
+    // NewType from typing_extensions is special, see legacy_new_type.
     set_typing_inference(typing, "NewType", Specific::TypingNewType);
-    set_typing_inference(typing, "TypeVar", Specific::TypingTypeVarClass);
-    set_typing_inference(typing, "TypeVarTuple", Specific::TypingTypeVarTupleClass);
-    set_typing_inference(typing, "Concatenate", Specific::TypingConcatenateClass);
-    set_typing_inference(typing, "ParamSpec", Specific::TypingParamSpecClass);
-    set_typing_inference(typing, "LiteralString", Specific::TypingLiteralString);
-    set_typing_inference(typing, "Literal", Specific::TypingLiteral);
-    set_typing_inference(typing, "Final", Specific::TypingFinal);
-    set_typing_inference(typing, "NamedTuple", Specific::TypingNamedTuple);
-    set_typing_inference(typing, "Unpack", Specific::TypingUnpack);
-    set_typing_inference(typing, "TypeAlias", Specific::TypingTypeAlias);
-    set_typing_inference(typing, "TypeAliasType", Specific::TypingTypeAliasType);
-    set_typing_inference(typing, "Self", Specific::TypingSelf);
-    set_typing_inference(typing, "Annotated", Specific::TypingAnnotated);
-    set_typing_inference(typing, "Never", Specific::TypingNeverOrNoReturn);
-    set_typing_inference(typing, "NoReturn", Specific::TypingNeverOrNoReturn);
-    set_typing_inference(typing, "Required", Specific::TypingRequired);
-    set_typing_inference(typing, "NotRequired", Specific::TypingNotRequired);
-    set_typing_inference(typing, "ReadOnly", Specific::TypingReadOnly);
-    set_typing_inference(typing, "TypeGuard", Specific::TypingTypeGuard);
-    set_typing_inference(typing, "TypeIs", Specific::TypingTypeIs);
-    set_typing_inference(typing, "reveal_type", Specific::RevealTypeFunction);
-    set_typing_inference(typing, "assert_type", Specific::AssertTypeFunction);
-    set_typing_inference(
-        typing,
-        "dataclass_transform",
-        Specific::TypingDataclassTransform,
-    );
 
     set_typing_inference(builtins, "tuple", Specific::TypingTuple);
     set_typing_inference(builtins, "type", Specific::BuiltinsType);
@@ -1284,8 +1309,6 @@ fn typing_changes(
     set_typing_inference(builtins, "isinstance", Specific::BuiltinsIsinstance);
     set_typing_inference(builtins, "issubclass", Specific::BuiltinsIssubclass);
 
-    set_typing_inference(typing, "cast", Specific::TypingCast);
-
     //set_typing_inference(dataclasses, "replace", Specific::DataclassesReplace);
     set_custom_behavior(
         dataclasses,
@@ -1310,35 +1333,11 @@ fn typing_changes(
     setup_type_alias(typing, "Deque", collections, "deque");
     setup_type_alias(typing, "OrderedDict", collections, "OrderedDict");
 
-    let t = typing_extensions;
+    setup_type_alias(typing_extensions, "SupportsIndex", typing, "SupportsIndex");
     // TODO this is completely wrong, but for now it's good enough
-    setup_type_alias(t, "SupportsIndex", builtins, "int");
-    set_typing_inference(t, "Literal", Specific::TypingLiteral);
-    set_typing_inference(t, "Final", Specific::TypingFinal);
-    set_typing_inference(t, "ParamSpec", Specific::TypingParamSpecClass);
-    set_typing_inference(t, "TypeVar", Specific::TypingTypeVarClass);
-    set_typing_inference(t, "TypeVarTuple", Specific::TypingTypeVarTupleClass);
-    set_typing_inference(t, "Annotated", Specific::TypingAnnotated);
-    set_typing_inference(t, "Protocol", Specific::TypingProtocol);
-    set_typing_inference(t, "TypeGuard", Specific::TypingTypeGuard);
-    set_typing_inference(t, "TypeIs", Specific::TypingTypeIs);
-    set_typing_inference(t, "Self", Specific::TypingSelf);
-    set_typing_inference(t, "TypeAliasType", Specific::TypingTypeAliasType);
     setup_type_alias(typing_extensions, "final", typing, "final");
-    set_typing_inference(t, "Concatenate", Specific::TypingConcatenateClass);
-    set_typing_inference(t, "TypeAlias", Specific::TypingTypeAlias);
-    set_typing_inference(t, "LiteralString", Specific::TypingLiteralString);
-    set_typing_inference(t, "NamedTuple", Specific::TypingNamedTuple);
-    set_typing_inference(t, "Unpack", Specific::TypingUnpack);
-    set_typing_inference(t, "reveal_type", Specific::RevealTypeFunction);
-    set_typing_inference(t, "assert_type", Specific::AssertTypeFunction);
-    set_typing_inference(t, "NotRequired", Specific::TypingNotRequired);
-    set_typing_inference(t, "Required", Specific::TypingRequired);
-    set_typing_inference(t, "dataclass_transform", Specific::TypingDataclassTransform);
-
-    for module in [typing, mypy_extensions, typing_extensions] {
-        set_typing_inference(module, "TypedDict", Specific::TypingTypedDict);
-    }
+
+    set_typing_inference(mypy_extensions, "TypedDict", Specific::TypingTypedDict);
     set_typing_inference(mypy_extensions, "NoReturn", Specific::TypingNeverOrNoReturn);
     set_typing_inference(
         mypy_extensions,
@@ -1347,6 +1346,42 @@ fn typing_changes(
     );
 }
 
+const SHARED_SPECIAL_FORMS: [(&str, Specific); 33] = [
+    ("Protocol", Specific::TypingProtocol),
+    ("Generic", Specific::TypingGeneric),
+    ("ClassVar", Specific::TypingClassVar),
+    ("Union", Specific::TypingUnion),
+    ("Optional", Specific::TypingOptional),
+    ("Any", Specific::TypingAny),
+    ("Callable", Specific::TypingCallable),
+    ("Type", Specific::TypingType),
+    ("TypeVar", Specific::TypingTypeVarClass),
+    ("TypeVarTuple", Specific::TypingTypeVarTupleClass),
+    ("Concatenate", Specific::TypingConcatenateClass),
+    ("ParamSpec", Specific::TypingParamSpecClass),
+    ("LiteralString", Specific::TypingLiteralString),
+    ("Literal", Specific::TypingLiteral),
+    ("Final", Specific::TypingFinal),
+    ("NamedTuple", Specific::TypingNamedTuple),
+    ("Unpack", Specific::TypingUnpack),
+    ("TypeAlias", Specific::TypingTypeAlias),
+    ("TypeAliasType", Specific::TypingTypeAliasType),
+    ("Self", Specific::TypingSelf),
+    ("Annotated", Specific::TypingAnnotated),
+    ("Never", Specific::TypingNeverOrNoReturn),
+    ("NoReturn", Specific::TypingNeverOrNoReturn),
+    ("Required", Specific::TypingRequired),
+    ("NotRequired", Specific::TypingNotRequired),
+    ("ReadOnly", Specific::TypingReadOnly),
+    ("TypeGuard", Specific::TypingTypeGuard),
+    ("TypeIs", Specific::TypingTypeIs),
+    ("reveal_type", Specific::RevealTypeFunction),
+    ("assert_type", Specific::AssertTypeFunction),
+    ("dataclass_transform", Specific::TypingDataclassTransform),
+    ("cast", Specific::TypingCast),
+    ("TypedDict", Specific::TypingTypedDict),
+];
+
 fn set_typing_inference(file: &PythonFile, name: &str, specific: Specific) {
     if let Some(node_index) = file.symbol_table.lookup_symbol(name) {
         let name_def = Name::by_index(&file.tree, node_index).name_def().unwrap();
@@ -28,6 +28,24 @@ pub(crate) fn diagnostics_for_relevant_files<'db>(
         .unwrap_or_default())
 }
 
+pub(crate) fn relevant_files(db: &Database) -> anyhow::Result<Vec<&PythonFile>> {
+    FileSelector::find_files(db)
+}
+
+/// Files matching a generated code exclude are not checked, but counted with the reason of the
+/// exclude in the summary.
+pub(crate) fn generated_code_reason<'x>(db: &'x Database, file: &'x PythonFile) -> Option<&'x str> {
+    let excludes = &file.flags(db).generated_code_excludes;
+    if excludes.is_empty() {
+        return None;
+    }
+    let rel_path = file.file_entry(db).relative_path(&*db.vfs.handler);
+    excludes
+        .iter()
+        .find(|exclude| exclude.matches(&rel_path))
+        .map(|exclude| exclude.reason.as_str())
+}
+
 fn should_skip(flags: &TypeCheckerFlags, rel_path: &str) -> bool {
     if !rel_path.ends_with(".py") && !rel_path.ends_with(".pyi") {
         return true;
@@ -238,7 +256,7 @@ fn find_imports_and_preload_files(
                 }
             }
             ImportResult::Namespace(_) => (),
-            ImportResult::PyTypedMissing => (),
+            ImportResult::PyTypedMissing | ImportResult::ExtensionModuleWithoutStub(_) => (),
         })
     }
     need_to_load_files.into_par_iter().for_each(|file_index| {
@@ -0,0 +1,97 @@
+use parsa_python_cst::{Name, NameParent, Scope, TypeLike, TypeParamKind};
+
+use crate::{
+    database::Database,
+    file::{BaseLookup, File as _, PointResolution, PythonFile},
+    goto::with_i_s_non_self,
+    name::Range,
+    type_::TypeVarLike,
+};
+
+pub struct SemanticToken<'a> {
+    pub range: Range<'a>,
+    pub kind: SemanticTokenKind,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SemanticTokenKind {
+    Class,
+    TypeVar,
+    TypeVarTuple,
+    ParamSpec,
+    Function,
+    Parameter,
+}
+
+/// Classifies the names of a file that are definitions or plain references. Attributes and names
+/// that cannot be resolved (e.g. in unreachable code) are not part of the result. The tokens are
+/// sorted by their position.
+pub(crate) fn semantic_tokens<'db>(
+    db: &'db Database,
+    file: &'db PythonFile,
+) -> Vec<SemanticToken<'db>> {
+    // Names in functions are only resolved when the file is checked.
+    if file.ensure_calculated_diagnostics(db).is_err() {
+        return vec![];
+    }
+    let tokens = with_i_s_non_self(db, file, Scope::Module, |i_s| {
+        let name_resolution = file.name_resolution_for_types(i_s);
+        file.tree
+            .filter_all_names()
+            .filter(|name| matches!(name.parent(), NameParent::NameDef(_) | NameParent::Atom(_)))
+            .filter_map(|name| {
+                let resolution = name_resolution.resolve_point_without_narrowing(name.index())?;
+                let kind = match &resolution {
+                    PointResolution::Param { .. } => Some(SemanticTokenKind::Parameter),
+                    PointResolution::NameDef { node_ref, .. } => {
+                        kind_of_definition(node_ref.expect_name_def().expect_type())
+                    }
+                    _ => None,
+                }
+                .or_else(|| {
+                    kind_of_base_lookup(name_resolution.point_resolution_to_base_lookup(resolution))
+                })?;
+                Some(SemanticToken {
+                    range: name_range(db, file, name),
+                    kind,
+                })
+            })
+            .collect::<Vec<_>>()
+    });
+    debug_assert!(tokens.is_sorted_by_key(|token| token.range.0.byte_position));
+    tokens
+}
+
+/// Definitions that can be classified without inferring anything.
+fn kind_of_definition(type_like: TypeLike) -> Option<SemanticTokenKind> {
+    Some(match type_like {
+        TypeLike::ClassDef(_) => SemanticTokenKind::Class,
+        TypeLike::Function(_) => SemanticTokenKind::Function,
+        TypeLike::ParamName(_) => SemanticTokenKind::Parameter,
+        TypeLike::TypeParam(type_param) => match type_param.unpack().1 {
+            TypeParamKind::TypeVar(..) => SemanticTokenKind::TypeVar,
+            TypeParamKind::TypeVarTuple(_) => SemanticTokenKind::TypeVarTuple,
+            TypeParamKind::ParamSpec(_) => SemanticTokenKind::ParamSpec,
+        },
+        _ => return None,
+    })
+}
+
+fn kind_of_base_lookup(lookup: BaseLookup) -> Option<SemanticTokenKind> {
+    Some(match lookup {
+        BaseLookup::Class(_) | BaseLookup::TypeVarLikeClass | BaseLookup::GenericOrProtocol => {
+            SemanticTokenKind::Class
+        }
+        BaseLookup::TypeVarLike(TypeVarLike::TypeVar(_)) => SemanticTokenKind::TypeVar,
+        BaseLookup::TypeVarLike(TypeVarLike::TypeVarTuple(_)) => SemanticTokenKind::TypeVarTuple,
+        BaseLookup::TypeVarLike(TypeVarLike::ParamSpec(_)) => SemanticTokenKind::ParamSpec,
+        BaseLookup::Module(_) | BaseLookup::Literal | BaseLookup::Other => return None,
+    })
+}
+
+fn name_range<'db>(db: &'db Database, file: &'db PythonFile, name: Name) -> Range<'db> {
+    (
+        file.byte_to_position_infos(db, name.start()),
+        file.byte_to_position_infos(db, name.end()),
+    )
+}
@@ -0,0 +1,297 @@
+use std::{ops::Range, sync::Arc};
+
+use parsa_python_cst::{CallAtArgument, CodeIndex, Primary, PrimaryContent};
+
+use crate::{
+    arguments::{Args as _, SimpleArgs},
+    database::Database,
+    file::PythonFile,
+    format_data::FormatData,
+    goto::with_i_s_non_self,
+    inference_state::InferenceState,
+    inferred::Inferred,
+    params::Param as _,
+    type_::{
+        CallableContent, CallableLike, CallableParam, CallableParams, ParamType,
+        format_param_annotation,
+    },
+};
+
+pub struct SignatureHelp {
+    pub signatures: Vec<Signature>,
+    pub active_signature: usize,
+}
+
+pub struct Signature {
+    /// E.g. `foo(x: int, *, y: str = ...) -> None`
+    pub label: String,
+    /// The byte ranges of the params in the label.
+    pub params: Vec<Range<usize>>,
+    pub active_param: Option<usize>,
+}
+
+enum ActiveArgument {
+    Positional(usize),
+    Keyword(String),
+}
+
+/// The callables of a call together with its arguments, which is used for signature help and
+/// completions in calls.
+pub(crate) struct CallInfos {
+    callables: Vec<Arc<CallableContent>>,
+    /// The positional arguments before the argument at the position.
+    positional_count: usize,
+    /// The keyword arguments except the one at the position.
+    keywords: Vec<String>,
+    active_argument: ActiveArgument,
+}
+
+impl CallInfos {
+    pub fn new(
+        i_s: &InferenceState,
+        file: &PythonFile,
+        call: Primary,
+        argument_start: CodeIndex,
+        position: CodeIndex,
+    ) -> Option<Self> {
+        let db = i_s.db;
+        let PrimaryContent::Execution(details) = call.second() else {
+            unreachable!()
+        };
+        let inf = file.inference(i_s).infer_primary_or_atom(call.first());
+        let callables = callables(i_s, &inf)?;
+
+        // Arguments unpacked from tuples with `*` are counted for every item.
+        let args = SimpleArgs::new(*i_s, file, call.index(), details);
+        let mut active_argument = None;
+        let mut positional_count = 0;
+        let mut keywords = vec![];
+        for arg in args.iter(i_s.mode) {
+            let Ok(node_ref) = arg.as_node_ref() else {
+                continue;
+            };
+            let start = node_ref.node_start_position();
+            let key = arg.keyword_name(db);
+            if start >= argument_start && start <= position {
+                if let Some(key) = key {
+                    active_argument = Some(ActiveArgument::Keyword(key.to_string()));
+                }
+            } else if let Some(key) = key {
+                keywords.push(key.to_string());
+            } else if start < argument_start && !arg.is_keyword_argument() {
+                positional_count += 1;
+            }
+        }
+        Some(Self {
+            callables,
+            positional_count,
+            keywords,
+            active_argument: active_argument
+                .unwrap_or(ActiveArgument::Positional(positional_count)),
+        })
+    }
+
+    /// Unclosed calls like `foo(1, ba` cannot be matched with params, because they are error
+    /// nodes, so their arguments are only known syntactically.
+    pub fn for_call_at_argument(
+        i_s: &InferenceState,
+        file: &PythonFile,
+        call: CallAtArgument,
+        position: CodeIndex,
+    ) -> Option<Self> {
+        match call {
+            CallAtArgument::Closed {
+                call,
+                argument_start,
+            } => Self::new(i_s, file, call, argument_start, position),
+            CallAtArgument::Unclosed(unclosed) => {
+                let inf = file.inference(i_s).infer_primary_or_atom(unclosed.callee);
+                Some(Self {
+                    callables: callables(i_s, &inf)?,
+                    positional_count: unclosed.positional_count,
+                    keywords: unclosed.keywords.into_iter().map(String::from).collect(),
+                    active_argument: match unclosed.active_keyword {
+                        Some(keyword) => ActiveArgument::Keyword(keyword.to_string()),
+                        None => ActiveArgument::Positional(unclosed.positional_count),
+                    },
+                })
+            }
+        }
+    }
+
+    /// The callables with params that accept the arguments that are not at the position, e.g.
+    /// the overloads that might match.
+    pub fn matching_callables<'x>(
+        &'x self,
+        db: &'x Database,
+    ) -> impl Iterator<Item = &'x [CallableParam]> + 'x {
+        self.callables.iter().filter_map(|callable| {
+            let CallableParams::Simple(params) = &callable.params else {
+                return None;
+            };
+            self.accepts_arguments(db, params).then_some(&**params)
+        })
+    }
+
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    fn accepts_arguments(&self, db: &Database, params: &[CallableParam]) -> bool {
+        let has_param = |check: fn(&ParamType) -> bool| params.iter().any(|p| check(&p.type_));
+        let positional_params = params
+            .iter()
+            .filter(|p| {
+                matches!(
+                    p.type_,
+                    ParamType::PositionalOnly(_) | ParamType::PositionalOrKeyword(_)
+                )
+            })
+            .count();
+        (self.positional_count <= positional_params
+            || has_param(|t| matches!(t, ParamType::Star(_))))
+            && (has_param(|t| matches!(t, ParamType::StarStar(_)))
+                || self
+                    .keywords
+                    .iter()
+                    .all(|key| keyword_param_index(db, params, key).is_some()))
+    }
+
+    /// The index of the param that the argument at the position is assigned to.
+    pub fn active_param(&self, db: &Database, params: &[CallableParam]) -> Option<usize> {
+        let position_of =
+            |check: fn(&ParamType) -> bool| params.iter().position(|p| check(&p.type_));
+        match &self.active_argument {
+            ActiveArgument::Positional(index) => params
+                .iter()
+                .enumerate()
+                .filter(|(_, p)| {
+                    matches!(
+                        p.type_,
+                        ParamType::PositionalOnly(_) | ParamType::PositionalOrKeyword(_)
+                    )
+                })
+                .nth(*index)
+                .map(|(i, _)| i)
+                .or_else(|| position_of(|t| matches!(t, ParamType::Star(_)))),
+            ActiveArgument::Keyword(key) => keyword_param_index(db, params, key)
+                .or_else(|| position_of(|t| matches!(t, ParamType::StarStar(_)))),
+        }
+    }
+}
+
+fn callables(i_s: &InferenceState, inf: &Inferred) -> Option<Vec<Arc<CallableContent>>> {
+    Some(match inf.as_cow_type(i_s).maybe_callable(i_s)? {
+        CallableLike::Callable(c) => vec![c],
+        CallableLike::Overload(overload) => overload.iter_functions().cloned().collect(),
+    })
+}
+
+fn keyword_param_index(db: &Database, params: &[CallableParam], key: &str) -> Option<usize> {
+    params.iter().position(|p| {
+        matches!(
+            p.type_,
+            ParamType::PositionalOrKeyword(_) | ParamType::KeywordOnly(_)
+        ) && p.name(db) == Some(key)
+    })
+}
+
+/// Returns the signatures of the call around the position. Overloaded functions have a
+/// signature for every overload.
+pub(crate) fn signature_help(
+    db: &Database,
+    file: &PythonFile,
+    position: CodeIndex,
+) -> Option<SignatureHelp> {
+    let (scope, call, argument_start) = file.tree.call_at_position(position)?;
+    // Calls in functions are only inferred when the file is checked.
+    file.ensure_calculated_diagnostics(db).ok()?;
+    with_i_s_non_self(db, file, scope, |i_s| {
+        let infos = CallInfos::new(i_s, file, call, argument_start, position)?;
+        let format_data = &FormatData::new_short(db);
+        let mut signatures = vec![];
+        let mut active_signature = None;
+        for callable in &infos.callables {
+            let CallableParams::Simple(params) = &callable.params else {
+                continue;
+            };
+            let active_param = infos.active_param(db, params);
+            if active_signature.is_none() && active_param.is_some() {
+                active_signature = Some(signatures.len());
+            }
+            signatures.push(signature(format_data, callable, params, active_param));
+        }
+        if signatures.is_empty() {
+            return None;
+        }
+        Some(SignatureHelp {
+            signatures,
+            active_signature: active_signature.unwrap_or(0),
+        })
+    })
+}
+
+fn signature(
+    format_data: &FormatData,
+    callable: &CallableContent,
+    params: &[CallableParam],
+    active_param: Option<usize>,
+) -> Signature {
+    let db = format_data.db;
+    let mut label = callable
+        .name
+        .as_ref()
+        .map(|name| name.as_str(db))
+        .unwrap_or("")
+        .to_string();
+    label.push('(');
+    let mut ranges = vec![];
+    let mut had_star = false;
+    for (i, param) in params.iter().enumerate() {
+        if i != 0 {
+            label += ", ";
+        }
+        let previous_was_positional_only =
+            i != 0 && matches!(params[i - 1].type_, ParamType::PositionalOnly(_));
+        if previous_was_positional_only && !matches!(param.type_, ParamType::PositionalOnly(_)) {
+            label += "/, ";
+        }
+        match &param.type_ {
+            ParamType::Star(_) => had_star = true,
+            ParamType::KeywordOnly(_) if !had_star => {
+                had_star = true;
+                label += "*, ";
+            }
+            _ => (),
+        }
+        let start = label.len();
+        label += match param.type_ {
+            ParamType::Star(_) => "*",
+            ParamType::StarStar(_) => "**",
+            _ => "",
+        };
+        if let Some(name) = param.name(db) {
+            label += name;
+            label += ": ";
+        }
+        let annotation = format_param_annotation(format_data, &param.specific(db));
+        label += annotation.as_deref().unwrap_or("Any");
+        if param.has_default {
+            label += " = ...";
+        }
+        ranges.push(start..label.len());
+    }
+    if matches!(params.last(), Some(p) if matches!(p.type_, ParamType::PositionalOnly(_))) {
+        label += ", /";
+    }
+    label += ") -> ";
+    match &callable.guard {
+        Some(guard) => label += &guard.format(format_data),
+        None => label += &callable.return_type.format(format_data),
+    }
+    Signature {
+        label,
+        params: ranges,
+        active_param,
+    }
+}
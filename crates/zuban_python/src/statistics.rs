@@ -0,0 +1,149 @@
+use std::{
+    fmt,
+    mem::size_of,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
+
+use vfs::FileIndex;
+
+use crate::{
+    database::{ComplexPoint, Database, Point},
+    type_::Type,
+};
+
+// A parser node consists of a few u32/u16 fields, this is only used for estimates.
+const ESTIMATED_TREE_NODE_SIZE: usize = 16;
+
+// Point lookups happen all the time and counting them on shared counters is not free, so they
+// are only counted while profiling.
+static RECORD_POINT_CACHE_LOOKUPS: AtomicBool = AtomicBool::new(false);
+static POINT_CACHE_HITS: AtomicUsize = AtomicUsize::new(0);
+static POINT_CACHE_MISSES: AtomicUsize = AtomicUsize::new(0);
+
+pub fn record_point_cache_statistics(enable: bool) {
+    RECORD_POINT_CACHE_LOOKUPS.store(enable, Ordering::Relaxed)
+}
+
+#[inline]
+pub(crate) fn record_point_cache_lookup(hit: bool) {
+    if !RECORD_POINT_CACHE_LOOKUPS.load(Ordering::Relaxed) {
+        return;
+    }
+    let counter = if hit {
+        &POINT_CACHE_HITS
+    } else {
+        &POINT_CACHE_MISSES
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleStatistics {
+    pub name: String,
+    pub nodes: usize,
+    pub calculated_points: usize,
+    pub complex_points: usize,
+    pub estimated_bytes: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProjectStatistics {
+    /// Sorted by the estimated memory usage, the biggest modules first.
+    pub modules: Vec<ModuleStatistics>,
+    pub cached_types: usize,
+    pub cached_callables: usize,
+    pub cached_classes: usize,
+    pub point_cache_hits: usize,
+    pub point_cache_misses: usize,
+}
+
+impl ProjectStatistics {
+    pub(crate) fn new(db: &Database) -> Self {
+        let mut stats = Self {
+            point_cache_hits: POINT_CACHE_HITS.load(Ordering::Relaxed),
+            point_cache_misses: POINT_CACHE_MISSES.load(Ordering::Relaxed),
+            ..Default::default()
+        };
+        for index in 0..db.vfs.files.len() {
+            let Some(file) = db.vfs.file(FileIndex(index as u32)) else {
+                continue;
+            };
+            let mut complex_points = 0;
+            // This is fine, because we are not modifying the database while iterating.
+            for complex in unsafe { file.complex_points.iter() } {
+                complex_points += 1;
+                match complex {
+                    ComplexPoint::Class(_) => stats.cached_classes += 1,
+                    ComplexPoint::FunctionOverload(_) => stats.cached_callables += 1,
+                    ComplexPoint::TypeInstance(Type::Callable(_) | Type::FunctionOverload(_)) => {
+                        stats.cached_callables += 1
+                    }
+                    ComplexPoint::TypeInstance(_) => stats.cached_types += 1,
+                    _ => (),
+                }
+            }
+            let nodes = file.tree.length();
+            stats.modules.push(ModuleStatistics {
+                name: file.qualified_name(db),
+                nodes,
+                calculated_points: file.points.iter().filter(|p| p.calculated()).count(),
+                complex_points,
+                estimated_bytes: file.tree.code().len()
+                    + nodes * (ESTIMATED_TREE_NODE_SIZE + size_of::<Point>())
+                    + complex_points * size_of::<ComplexPoint>(),
+            })
+        }
+        stats
+            .modules
+            .sort_by(|m1, m2| m2.estimated_bytes.cmp(&m1.estimated_bytes));
+        stats
+    }
+
+    pub fn estimated_bytes(&self) -> usize {
+        self.modules.iter().map(|m| m.estimated_bytes).sum()
+    }
+
+    pub fn point_cache_hit_rate(&self) -> f64 {
+        let lookups = self.point_cache_hits + self.point_cache_misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.point_cache_hits as f64 / lookups as f64
+    }
+}
+
+impl fmt::Display for ProjectStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mib = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+        writeln!(
+            f,
+            "Loaded modules: {} (~{:.1} MiB)",
+            self.modules.len(),
+            mib(self.estimated_bytes())
+        )?;
+        writeln!(
+            f,
+            "Cached types: {}, callables: {}, classes: {}",
+            self.cached_types, self.cached_callables, self.cached_classes
+        )?;
+        writeln!(
+            f,
+            "Point cache: {} hits, {} misses ({:.1}% hit rate)",
+            self.point_cache_hits,
+            self.point_cache_misses,
+            self.point_cache_hit_rate() * 100.0
+        )?;
+        for module in &self.modules {
+            writeln!(
+                f,
+                "  {:>8.2} MiB  {} ({} nodes, {} calculated, {} complex)",
+                mib(module.estimated_bytes),
+                module.name,
+                module.nodes,
+                module.calculated_points,
+                module.complex_points
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -908,27 +908,7 @@ pub fn format_callable_params<'db: 'x, 'x, P: Param<'x>>(
     let mut had_kwargs_separator = false;
     let mut args = join_with_commas(params.enumerate().map(|(i, p)| {
         let specific = p.specific(db);
-        let annotation_str = match &specific {
-            WrappedParamType::PositionalOnly(t)
-            | WrappedParamType::PositionalOrKeyword(t)
-            | WrappedParamType::KeywordOnly(t)
-            | WrappedParamType::Star(WrappedStar::ArbitraryLen(t))
-            | WrappedParamType::StarStar(WrappedStarStar::ValueType(t)) => {
-                t.as_ref().map(|t| t.format(format_data))
-            }
-            WrappedParamType::Star(WrappedStar::ParamSpecArgs(u)) => {
-                Some(format!("{}.args", u.param_spec.name(db)).into())
-            }
-            WrappedParamType::Star(WrappedStar::UnpackedTuple(tup)) => {
-                Some(tup.format_with_simplified_unpack(format_data))
-            }
-            WrappedParamType::StarStar(WrappedStarStar::UnpackTypedDict(td)) => {
-                Some(format!("Unpack[{}]", td.format(format_data)).into())
-            }
-            WrappedParamType::StarStar(WrappedStarStar::ParamSpecKwargs(u)) => {
-                Some(format!("{}.kwargs", u.param_spec.name(db)).into())
-            }
-        };
+        let annotation_str = format_param_annotation(format_data, &specific);
         let current_kind = p.kind(db);
         let stars = match current_kind {
             ParamKind::Star => "*",
@@ -977,6 +957,36 @@ pub fn format_callable_params<'db: 'x, 'x, P: Param<'x>>(
     args
 }
 
+/// Formats the annotation of a param without the stars of `*args` and `**kwargs`. Returns
+/// `None` for params without an annotation.
+pub(crate) fn format_param_annotation(
+    format_data: &FormatData,
+    specific: &WrappedParamType,
+) -> Option<Box<str>> {
+    let db = format_data.db;
+    match specific {
+        WrappedParamType::PositionalOnly(t)
+        | WrappedParamType::PositionalOrKeyword(t)
+        | WrappedParamType::KeywordOnly(t)
+        | WrappedParamType::Star(WrappedStar::ArbitraryLen(t))
+        | WrappedParamType::StarStar(WrappedStarStar::ValueType(t)) => {
+            t.as_ref().map(|t| t.format(format_data))
+        }
+        WrappedParamType::Star(WrappedStar::ParamSpecArgs(u)) => {
+            Some(format!("{}.args", u.param_spec.name(db)).into())
+        }
+        WrappedParamType::Star(WrappedStar::UnpackedTuple(tup)) => {
+            Some(tup.format_with_simplified_unpack(format_data))
+        }
+        WrappedParamType::StarStar(WrappedStarStar::UnpackTypedDict(td)) => {
+            Some(format!("Unpack[{}]", td.format(format_data)).into())
+        }
+        WrappedParamType::StarStar(WrappedStarStar::ParamSpecKwargs(u)) => {
+            Some(format!("{}.kwargs", u.param_spec.name(db)).into())
+        }
+    }
+}
+
 pub fn format_params_as_param_spec(
     format_data: &FormatData,
     params: &[CallableParam],
@@ -6,7 +6,7 @@ use std::{
 
 use parsa_python_cst::{
     ArgumentsDetails, AssignmentContent, AssignmentRightSide, ExpressionContent, ExpressionPart,
-    NodeIndex, ParamKind, PrimaryContent, StarExpressionContent,
+    NodeIndex, ParamKind, Primary, PrimaryContent, StarExpressionContent,
 };
 use utils::FastHashMap;
 
@@ -297,12 +297,66 @@ fn calculate_init_of_dataclass(db: &Database, dataclass: &Arc<Dataclass>) -> Ini
         is_init_var: bool, // e.g. InitVar[int]
     }
 
+    // attrs allows fields without annotations like `x = attr.ib()`. Like attrs does for
+    // `auto_attribs`, only assignments to field specifiers are fields if there is such a field.
+    let only_field_specifiers = dataclass
+        .options
+        .transform_field_specifiers
+        .as_ref()
+        .is_some_and(|specifiers| {
+            class_symbol_table.iter().any(|(_, name_index)| {
+                let name = NodeRef::new(file, *name_index).expect_name();
+                name.maybe_assignment_definition_name()
+                    .is_some_and(|assignment| match assignment.unpack() {
+                        AssignmentContent::Normal(_, right_side) => {
+                            is_field_specifier_call(i_s, file, Some(right_side), specifiers)
+                        }
+                        _ => false,
+                    })
+            })
+        });
+    let skip_non_field_specifier = |right_side| {
+        only_field_specifiers
+            && !is_field_specifier_call(
+                i_s,
+                file,
+                right_side,
+                dataclass
+                    .options
+                    .transform_field_specifiers
+                    .as_ref()
+                    .unwrap(),
+            )
+    };
+
     for (_, name_index) in class_symbol_table.iter() {
         let name = NodeRef::new(file, *name_index).expect_name();
-        if let Some(assignment) = name.maybe_assignment_definition_name()
+        if only_field_specifiers
+            && let Some(assignment) = name.maybe_assignment_definition_name()
+            && let AssignmentContent::Normal(_, right_side) = assignment.unpack()
+        {
+            if skip_non_field_specifier(Some(right_side)) {
+                continue;
+            }
+            let field_options =
+                calculate_field_arg(i_s, file, Some(right_side), &dataclass.options);
+            let name_str = field_options.alias_name.clone().unwrap_or_else(|| {
+                DbString::StringSlice(StringSlice::from_name(cls.node_ref.file_index(), name))
+            });
+            with_indexes.push(Annotated {
+                name_index: *name_index,
+                t: inference.infer_name_of_definition(name).as_type(i_s),
+                name: name_str,
+                field_options,
+                is_init_var: false,
+            });
+        } else if let Some(assignment) = name.maybe_assignment_definition_name()
             && let AssignmentContent::WithAnnotation(target, annotation, right_side) =
                 assignment.unpack()
         {
+            if skip_non_field_specifier(right_side) {
+                continue;
+            }
             inference.ensure_cached_annotation(annotation, right_side.is_some());
             let field_options = calculate_field_arg(i_s, file, right_side, &dataclass.options);
             let point = file.points.get(annotation.index());
@@ -458,14 +512,20 @@ fn calculate_init_of_dataclass(db: &Database, dataclass: &Arc<Dataclass>) -> Ini
                 let DbString::StringSlice(name) = name else {
                     unreachable!();
                 };
-                if name.file_index == file.file_index {
+                let class_node = cls.node();
+                if name.file_index == file.file_index
+                    && class_node.start() <= name.start
+                    && name.end <= class_node.end()
+                {
                     file.add_issue(
                         i_s,
                         Issue::from_start_stop(name.start, name.end, issue_type),
                     );
                 } else {
                     // The class arguments are always set, because we are working with params from
-                    // a different file, which means inheritance.
+                    // a base class, which means inheritance. The issue is reported on the bases
+                    // even if they are in the same file, because the base itself is fine, only
+                    // the combination of the bases is not.
                     let arguments = cls.node().arguments().unwrap();
                     NodeRef::new(file, arguments.index()).add_issue(i_s, issue_type);
                 }
@@ -570,37 +630,66 @@ impl Default for FieldOptions {
     }
 }
 
-fn calculate_field_arg(
+fn maybe_field_call<'db>(
     i_s: &InferenceState,
     file: &PythonFile,
-    right_side: Option<AssignmentRightSide>,
-    options: &DataclassOptions,
-) -> FieldOptions {
+    right_side: Option<AssignmentRightSide<'db>>,
+) -> Option<(Primary<'db>, ArgumentsDetails<'db>, Inferred)> {
     if let Some(AssignmentRightSide::StarExpressions(star_exprs)) = right_side
         && let StarExpressionContent::Expression(expr) = star_exprs.unpack()
         && let ExpressionContent::ExpressionPart(ExpressionPart::Primary(primary)) = expr.unpack()
         && let PrimaryContent::Execution(details) = primary.second()
     {
         let left = file.inference(i_s).infer_primary_or_atom(primary.first());
+        return Some((primary, details, left));
+    }
+    None
+}
+
+fn is_field_specifier_call(
+    i_s: &InferenceState,
+    file: &PythonFile,
+    right_side: Option<AssignmentRightSide>,
+    specifiers: &FieldSpecifiers,
+) -> bool {
+    maybe_field_call(i_s, file, right_side)
+        .is_some_and(|(_, _, left)| is_field_specifier(i_s, &left, specifiers))
+}
+
+fn is_field_specifier(i_s: &InferenceState, left: &Inferred, specifiers: &FieldSpecifiers) -> bool {
+    if left
+        .maybe_saved_link()
+        .is_some_and(|link| specifiers.contains(&link))
+    {
+        return true;
+    }
+    // Aliases like `ib = attrib` in the attrs stubs are not saved as the function itself.
+    match left.as_cow_type(i_s).as_ref() {
+        Type::Callable(c) => specifiers.contains(&c.defined_at),
+        Type::FunctionOverload(o) => o
+            .iter_functions()
+            .any(|c| specifiers.contains(&c.defined_at)),
+        _ => false,
+    }
+}
+
+fn calculate_field_arg(
+    i_s: &InferenceState,
+    file: &PythonFile,
+    right_side: Option<AssignmentRightSide>,
+    options: &DataclassOptions,
+) -> FieldOptions {
+    if let Some((primary, details, left)) = maybe_field_call(i_s, file, right_side) {
         if let Some(specifiers) = &options.transform_field_specifiers {
-            for specifier in specifiers.iter() {
-                if left.maybe_saved_link() == Some(*specifier) {
-                    let mut options = FieldOptions::default();
-                    apply_default_options_from_dataclass_transform_field(
-                        i_s,
-                        left,
-                        &mut options,
-                        &SimpleArgs::from_primary(*i_s, file, primary),
-                    );
-                    return field_options_from_args(
-                        i_s,
-                        file,
-                        primary.index(),
-                        details,
-                        true,
-                        options,
-                    );
-                }
+            if is_field_specifier(i_s, &left, specifiers) {
+                let mut options = FieldOptions::default();
+                apply_default_options_from_dataclass_transform_field(
+                    i_s,
+                    left,
+                    &mut options,
+                    &SimpleArgs::from_primary(*i_s, file, primary),
+                );
+                return field_options_from_args(i_s, file, primary.index(), details, true, options);
             }
         } else if left.is_name_defined_in_module(i_s.db, "dataclasses", "field") {
             return field_options_from_args(
@@ -763,48 +852,7 @@ pub(crate) fn dataclasses_replace<'db>(
             Some(positional.node_ref),
             &inferred.as_cow_type(i_s),
             &mut |dataclass| {
-                let mut replace_func = dataclass_init_func(dataclass, i_s.db).clone();
-                let mut params: Vec<_> = replace_func.expect_simple_params().into();
-                for param in params.iter_mut() {
-                    let t = param.type_.maybe_type().unwrap();
-                    param.type_ = ParamType::KeywordOnly(t.clone());
-                    // All normal dataclass arguments are optional, because they can be
-                    // overridden or just be left in place. However this is different for
-                    // InitVars, which always need to be there. To check if something is an
-                    // InitVar, we use this hack and check if the attribute exists on the
-                    // dataclass. If not, it's an InitVar.
-                    if lookup_on_dataclass(
-                        dataclass,
-                        i_s,
-                        |issue| args.add_issue(i_s, issue),
-                        param.name.as_ref().unwrap().as_str(i_s.db),
-                    )
-                    .lookup
-                    .is_some()
-                    {
-                        param.has_default = true;
-                    }
-                }
-                params.insert(
-                    0,
-                    CallableParam::new_anonymous(ParamType::PositionalOnly(Type::Any(
-                        AnyCause::Todo,
-                    ))),
-                );
-                replace_func.params = CallableParams::new_simple(params.into());
-                Callable::new(&replace_func, Some(dataclass.class(i_s.db))).execute_internal(
-                    i_s,
-                    args,
-                    false,
-                    on_type_error.with_custom_generate_diagnostic_string(&|_, _| {
-                        Some(format!(
-                            r#""replace" of "{}""#,
-                            dataclass.class(i_s.db).format_short(i_s.db)
-                        ))
-                    }),
-                    &mut ResultContext::Unknown,
-                    None,
-                );
+                execute_replace_on_dataclass(i_s, args, dataclass, "replace", on_type_error)
             },
         );
         if successful {
@@ -822,6 +870,103 @@ pub(crate) fn dataclasses_replace<'db>(
         .execute(i_s, args, result_context, on_type_error)
 }
 
+pub(crate) fn attrs_evolve<'db>(
+    i_s: &InferenceState<'db, '_>,
+    evolve: &Inferred,
+    args: &dyn Args<'db>,
+    result_context: &mut ResultContext,
+    on_type_error: OnTypeError,
+) -> Inferred {
+    // `attrs.evolve(inst, **changes)` accepts any keyword arguments in the attrs stubs. attrs
+    // classes are dataclass transforms, so the changes are checked like with
+    // `dataclasses.replace`. Other instances are not reported, the stubs accept them.
+    if let Some(first) = args.iter(i_s.mode).next()
+        && let ArgKind::Positional(positional) = &first.kind
+    {
+        let inferred = positional.infer(&mut ResultContext::Unknown);
+        let t = inferred.as_cow_type(i_s);
+        if run_on_dataclass(i_s, None, &t, &mut |_| ()) {
+            run_on_dataclass(i_s, None, &t, &mut |dataclass| {
+                execute_replace_on_dataclass(i_s, args, dataclass, "evolve", on_type_error)
+            });
+            return inferred;
+        }
+    }
+    evolve.execute_with_details(i_s, args, result_context, on_type_error)
+}
+
+fn execute_replace_on_dataclass<'db>(
+    i_s: &InferenceState<'db, '_>,
+    args: &dyn Args<'db>,
+    dataclass: &Arc<Dataclass>,
+    func_name: &str,
+    on_type_error: OnTypeError,
+) {
+    let mut replace_func = dataclass_init_func(dataclass, i_s.db).clone();
+    let mut params = replace_params(dataclass, i_s, &|issue| args.add_issue(i_s, issue));
+    params.insert(
+        0,
+        CallableParam::new_anonymous(ParamType::PositionalOnly(Type::Any(AnyCause::Todo))),
+    );
+    replace_func.params = CallableParams::new_simple(params.into());
+    Callable::new(&replace_func, Some(dataclass.class(i_s.db))).execute_internal(
+        i_s,
+        args,
+        false,
+        on_type_error.with_custom_generate_diagnostic_string(&|_, _| {
+            Some(format!(
+                r#""{func_name}" of "{}""#,
+                dataclass.class(i_s.db).format_short(i_s.db)
+            ))
+        }),
+        &mut ResultContext::Unknown,
+        None,
+    );
+}
+
+fn replace_params(
+    dataclass: &Arc<Dataclass>,
+    i_s: &InferenceState,
+    add_issue: &dyn Fn(IssueKind),
+) -> Vec<CallableParam> {
+    let mut params: Vec<_> = dataclass_init_func(dataclass, i_s.db)
+        .expect_simple_params()
+        .into();
+    for param in params.iter_mut() {
+        let t = param.type_.maybe_type().unwrap();
+        param.type_ = ParamType::KeywordOnly(t.clone());
+        // All normal dataclass arguments are optional, because they can be
+        // overridden or just be left in place. However this is different for
+        // InitVars, which always need to be there. To check if something is an
+        // InitVar, we use this hack and check if the attribute exists on the
+        // dataclass. If not, it's an InitVar.
+        if lookup_on_dataclass(
+            dataclass,
+            i_s,
+            add_issue,
+            param.name.as_ref().unwrap().as_str(i_s.db),
+        )
+        .lookup
+        .is_some()
+        {
+            param.has_default = true;
+        }
+    }
+    params
+}
+
+fn dunder_replace_func(self_: Arc<Dataclass>, i_s: &InferenceState) -> LookupResult {
+    // Python 3.13 synthesizes `__replace__`, which is what `copy.replace` uses. It accepts the
+    // same keyword arguments as `dataclasses.replace`.
+    let params = replace_params(&self_, i_s, &|_| ());
+    let mut replace_func = dataclass_init_func(&self_, i_s.db).clone();
+    replace_func.name = Some(DbString::Static("__replace__"));
+    replace_func.class_name = Some(self_.class(i_s.db).name_string_slice());
+    replace_func.params = CallableParams::new_simple(params.into());
+    replace_func.return_type = Type::Dataclass(self_);
+    LookupResult::UnknownName(Inferred::from_type(Type::Callable(Arc::new(replace_func))))
+}
+
 fn run_on_dataclass(
     i_s: &InferenceState,
     from: Option<NodeRef>,
@@ -855,7 +1000,9 @@ fn run_on_dataclass(
                 }
                 result
             }
-            TypeVarKind::Constraints(_) => unimplemented!(),
+            TypeVarKind::Constraints(mut constraints) => {
+                constraints.all(|t| run_on_dataclass(i_s, None, t, callback))
+            }
             TypeVarKind::Unrestricted => type_var_error(&tv.type_var),
         },
         _ => {
@@ -1072,6 +1219,20 @@ fn lookup_symbol_internal(
             AttributeKind::Attribute,
         );
     }
+    if name == "__replace__"
+        && i_s
+            .db
+            .project
+            .settings
+            .python_version_or_default()
+            .at_least_3_dot(13)
+        && self_.class(i_s.db).lookup_symbol(i_s, name).is_none()
+    {
+        return (
+            dunder_replace_func(self_, i_s),
+            AttributeKind::DefMethod { is_final: false },
+        );
+    }
     (LookupResult::None, AttributeKind::Attribute)
 }
 
@@ -4,9 +4,11 @@ use std::{
 };
 
 use super::{
-    AnyCause, CallableLike, DbString, FormatStyle, Literal, LiteralKind, LookupResult, Type,
+    AnyCause, CallableLike, DbString, FormatStyle, Literal, LiteralKind, LookupResult, NeverCause,
+    Type,
 };
 use crate::{
+    arguments::{ArgKind, Args},
     database::{Database, ParentScope, PointLink},
     debug,
     diagnostics::IssueKind,
@@ -14,7 +16,7 @@ use crate::{
     format_data::FormatData,
     inference_state::InferenceState,
     inferred::{AttributeKind, Inferred},
-    matching::LookupKind,
+    matching::{LookupKind, ResultContext},
     node_ref::NodeRef,
     type_helpers::{
         Class, ClassLookupOptions, Instance, InstanceLookupOptions, LookupDetails, TypeOrClass,
@@ -232,6 +234,19 @@ impl Enum {
         EnumKind::Normal
     }
 
+    pub fn is_flag(&self, i_s: &InferenceState) -> bool {
+        let class = self.class(i_s.db);
+        for (_, in_mro) in class.mro(i_s.db) {
+            if let TypeOrClass::Class(in_mro) = in_mro
+                && in_mro.node_ref.file_index() == i_s.db.python_state.enum_file().file_index()
+                && in_mro.name() == "Flag"
+            {
+                return true;
+            }
+        }
+        false
+    }
+
     pub fn is_from_functional_definition(&self, db: &Database) -> bool {
         self.class.file == db.python_state.enum_file().file_index()
     }
@@ -255,6 +270,116 @@ pub(crate) enum EnumKind {
     StrEnum,
 }
 
+pub(crate) fn execute_enum<'db>(
+    i_s: &InferenceState<'db, '_>,
+    args: &dyn Args<'db>,
+    enum_: &Arc<Enum>,
+) -> Type {
+    // `MyEnum(value)` looks up the member with the given value. Mypy does not check the value
+    // at all and we cannot say anything about enums with a custom __new__.
+    let enum_t = Type::Enum(enum_.clone());
+    if i_s.db.project.settings.mypy_compatible || enum_.has_customized_new(i_s) {
+        return enum_t;
+    }
+    let enum_name = enum_.name.as_str(i_s.db);
+    let all_args: Vec<_> = args.iter(i_s.mode).collect();
+    let arg = match all_args.as_slice() {
+        [] => {
+            args.add_issue(
+                i_s,
+                IssueKind::TooFewArguments(format!(r#" for "{enum_name}""#).into()),
+            );
+            return enum_t;
+        }
+        [arg] => arg,
+        _ if all_args
+            .iter()
+            .all(|arg| matches!(arg.kind, ArgKind::Positional(_))) =>
+        {
+            args.add_issue(
+                i_s,
+                IssueKind::TooManyArguments(format!(r#" for "{enum_name}""#).into()),
+            );
+            return enum_t;
+        }
+        _ => return enum_t,
+    };
+    let ArgKind::Positional(positional) = &arg.kind else {
+        return enum_t;
+    };
+    let value = positional.infer(&mut ResultContext::Unknown);
+    let value_t = value.as_cow_type(i_s);
+    if value_t.is_any() {
+        return enum_t;
+    }
+    if value_t.is_simple_sub_type_of(i_s, &enum_t).bool() {
+        // Passing a member returns the member itself.
+        return value_t.into_owned();
+    }
+    let mut expected = Type::Never(NeverCause::Other);
+    let mut overlaps = false;
+    for index in 0..enum_.members.len() {
+        let member = EnumMember::new(enum_.clone(), index, false);
+        let member_value = member.infer_value(i_s).as_type(i_s);
+        if member_value.is_any() {
+            return enum_t;
+        }
+        if let (Type::Literal(l1), Type::Literal(l2)) = (&member_value, value_t.as_ref())
+            && l1.value(i_s.db) == l2.value(i_s.db)
+        {
+            return Type::EnumMember(member);
+        }
+        overlaps |= member_value.simple_overlaps(i_s, &value_t);
+        expected.union_in_place(match member_value {
+            Type::Literal(l) => l.fallback_type(i_s.db),
+            t => t,
+        });
+    }
+    // Flags can be combined (e.g. `Perm(3)` or `Perm(0)`), so any value of the member type works.
+    if overlaps || enum_.is_flag(i_s) && expected.simple_overlaps(i_s, &value_t) {
+        return enum_t;
+    }
+    if let Some(t) = custom_missing_return_type(i_s, enum_) {
+        return t;
+    }
+    arg.add_issue(
+        i_s,
+        IssueKind::ArgumentTypeIssue(
+            format!(
+                r#"Argument 1 to "{enum_name}" has incompatible type "{}"; expected "{}""#,
+                value_t.format_short(i_s.db),
+                expected.format_short(i_s.db),
+            )
+            .into(),
+        ),
+    );
+    enum_t
+}
+
+fn custom_missing_return_type(i_s: &InferenceState, enum_: &Arc<Enum>) -> Option<Type> {
+    // Values without a member are passed to `_missing_`, which is only relevant if it is not the
+    // default implementation from the enum module. If it returns a specific part of the enum, that
+    // is the result, otherwise it's just the enum.
+    let details = enum_
+        .class(i_s.db)
+        .lookup(i_s, "_missing_", ClassLookupOptions::new(&|_| ()));
+    let TypeOrClass::Class(defined_in) = details.class else {
+        return None;
+    };
+    if defined_in.node_ref.file_index() == i_s.db.python_state.enum_file().file_index() {
+        return None;
+    }
+    let enum_t = Type::Enum(enum_.clone());
+    let inf = details.lookup.into_maybe_inferred()?;
+    if let Some(CallableLike::Callable(c)) = inf.as_cow_type(i_s).maybe_callable(i_s) {
+        let return_type = c.return_type.remove_none(i_s.db);
+        if !return_type.is_never() && enum_t.is_simple_super_type_of(i_s, &return_type).bool() {
+            return Some(return_type.into_owned());
+        }
+    }
+    Some(enum_t)
+}
+
 pub(crate) fn lookup_on_enum_class<'a>(
     i_s: &InferenceState<'a, '_>,
     add_issue: impl Fn(IssueKind),
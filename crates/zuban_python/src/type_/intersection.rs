@@ -285,6 +285,9 @@ impl Intersection {
         result_context: &mut ResultContext,
         on_type_error: OnTypeError,
     ) -> Inferred {
+        if let Some(callable) = self.maybe_partial_callable(i_s) {
+            return callable.execute(i_s, None, args, result_context, on_type_error);
+        }
         self.wrap_first_non_failing(|t, _| {
             t.execute(i_s, None, args, result_context, on_type_error)
         })
@@ -294,6 +297,26 @@ impl Intersection {
         })
     }
 
+    /// The remaining callable of a `functools.partial` object, which is an intersection of the
+    /// `partial` instance and the callable. The callable describes the call more precisely.
+    fn maybe_partial_callable(&self, i_s: &InferenceState) -> Option<&Type> {
+        let [first, second] = self.entries.as_ref() else {
+            return None;
+        };
+        match (first, second) {
+            (callable @ Type::Callable(_), Type::Class(c))
+                if c.class(i_s.db).node_ref.is_name_defined_in_module(
+                    i_s.db,
+                    "functools",
+                    "partial",
+                ) =>
+            {
+                Some(callable)
+            }
+            _ => None,
+        }
+    }
+
     pub(crate) fn iter(&self, i_s: &InferenceState, infos: IterInfos) -> IteratorContent {
         self.wrap_first_non_failing(|t, add_issue| {
             t.iter(i_s, infos.with_different_add_issue(add_issue))
@@ -10,8 +10,8 @@ use crate::{
     file::ClassNodeRef,
     inference_state::InferenceState,
     matching::{
-        ErrorStrs, ErrorTypes, GotType, Match, Matcher, MismatchReason, avoid_protocol_mismatch,
-        format_got_expected,
+        ErrorStrs, ErrorTypes, ExplainStep, GotType, Match, Matcher, MismatchReason,
+        avoid_protocol_mismatch, format_got_expected, format_match_result,
     },
     params::matches_params,
     recoverable_error,
@@ -250,13 +250,21 @@ impl Type {
         matcher: &mut Matcher,
         value_type: &Self,
     ) -> Match {
-        // 1. Check if the type is part of the mro.
+        let explain = ExplainStep::start();
         let debug_message_for_result = |result| {
             if cfg!(feature = "zuban_debug") {
                 let ErrorStrs { got, expected } = format_got_expected(i_s.db, self, value_type);
                 debug!("Match covariant {got} :> {expected} -> {result:?}",)
             }
+            explain.finish(|| {
+                let ErrorStrs { got, expected } = format_got_expected(i_s.db, self, value_type);
+                format!(
+                    r#"Is "{expected}" assignable to "{got}"? {}"#,
+                    format_match_result(result)
+                )
+            });
         };
+        // 1. Check if the type is part of the mro.
         let mut m = Match::new_false();
         let mut mro = value_type.mro(i_s.db);
         // Protocols contain no object in its MRO, therefore we add that here.
@@ -362,6 +370,7 @@ impl Type {
         matcher: &mut Matcher,
         value_type: &Self,
     ) -> Match {
+        let explain = ExplainStep::start();
         let m = self.matches_internal(i_s, matcher, value_type, Variance::Invariant);
         let result = m.or(|| {
             self.check_protocol_and_other_side(i_s, matcher, value_type, Variance::Invariant)
@@ -370,6 +379,13 @@ impl Type {
             let ErrorStrs { got, expected } = format_got_expected(i_s.db, self, value_type);
             debug!("Match invariant {got} ≡ {expected} -> {result:?}");
         }
+        explain.finish(|| {
+            let ErrorStrs { got, expected } = format_got_expected(i_s.db, self, value_type);
+            format!(
+                r#"Is "{expected}" the same type as "{got}" (invariant)? {}"#,
+                format_match_result(&result)
+            )
+        });
         result
     }
 
@@ -993,6 +1009,7 @@ fn match_unpack_internal(
                     reason,
                     expected: t1,
                     got: GotType::Type(t2),
+                    explanation: &[],
                 },
                 index,
             );
@@ -1011,6 +1028,7 @@ fn match_unpack_internal(
                     reason,
                     expected: &Type::Tuple(Tuple::new(TupleArgs::WithUnpack(with_unpack1.clone()))),
                     got: GotType::Type(&Type::Tuple(Tuple::new(args))),
+                    explanation: &[],
                 },
                 with_unpack1.before.len() as isize,
             );
@@ -1088,6 +1106,7 @@ fn match_unpack_internal(
                                     reason: &MismatchReason::None,
                                     expected: &with_unpack1.before[len_before_2],
                                     got: GotType::Starred(Type::Tuple(Tuple::new(tuple2.clone()))),
+                                    explanation: &[],
                                 },
                                 len_before_1 as isize,
                             );
@@ -33,16 +33,17 @@ pub(crate) use self::{
     callable::{
         CallableContent, CallableParam, CallableParams, ParamType, ParamTypeDetails, StarParamType,
         StarStarParamType, TypeGuardInfo, WrongPositionalCount, add_param_spec_to_params,
-        format_callable_params, format_params_as_param_spec, merge_class_type_vars,
+        format_callable_params, format_param_annotation, format_params_as_param_spec,
+        merge_class_type_vars,
     },
     dataclass::{
-        Dataclass, DataclassOptions, DataclassTransformObj, dataclass_converter_fields_lookup,
-        dataclass_init_func, dataclass_initialize, dataclass_post_init_func, dataclasses_replace,
-        ensure_calculated_dataclass, lookup_dataclass_symbol, lookup_on_dataclass,
-        lookup_on_dataclass_type,
+        Dataclass, DataclassOptions, DataclassTransformObj, attrs_evolve,
+        dataclass_converter_fields_lookup, dataclass_init_func, dataclass_initialize,
+        dataclass_post_init_func, dataclasses_replace, ensure_calculated_dataclass,
+        lookup_dataclass_symbol, lookup_on_dataclass, lookup_on_dataclass_type,
     },
     enum_::{
-        Enum, EnumKind, EnumMember, EnumMemberDefinition, lookup_on_enum_class,
+        Enum, EnumKind, EnumMember, EnumMemberDefinition, execute_enum, lookup_on_enum_class,
         lookup_on_enum_instance, lookup_on_enum_member_instance,
     },
     intersection::Intersection,
@@ -80,7 +81,7 @@ use crate::{
     inferred::Inferred,
     matching::{
         ErrorStrs, ErrorTypes, Generic, Generics, GotType, Match, Matcher, MismatchReason,
-        OnTypeError, ResultContext,
+        OnTypeError, ResultContext, record_match_explanation,
     },
     new_class,
     node_ref::NodeRef,
@@ -1416,13 +1417,22 @@ impl Type {
         mut on_error: impl FnMut(&ErrorTypes, &MismatchReason) -> Option<IssueKind>,
     ) {
         let value_type = value.as_cow_type(i_s);
-        let matches = self.is_super_type_of(i_s, matcher, &value_type);
+        let mut explanation = vec![];
+        let matches = if i_s.db.project.flags.explain_mismatch {
+            let (matches, trail) =
+                record_match_explanation(|| self.is_super_type_of(i_s, matcher, &value_type));
+            explanation = trail;
+            matches
+        } else {
+            self.is_super_type_of(i_s, matcher, &value_type)
+        };
         if let Match::False { ref reason, .. } = matches {
             let error_types = ErrorTypes {
                 expected: self,
                 got: GotType::Type(&value_type),
                 matcher: Some(matcher),
                 reason,
+                explanation: &explanation,
             };
             if cfg!(feature = "zuban_debug") {
                 let ErrorStrs { expected, got } = error_types.as_boxed_strs(i_s.db);
@@ -1433,7 +1443,7 @@ impl Type {
             }
             if let Some(error) = on_error(&error_types, reason) {
                 add_issue(error);
-                error_types.add_mismatch_notes(add_issue)
+                error_types.add_mismatch_notes(i_s.db, &add_issue);
             }
         }
     }
@@ -1,18 +1,20 @@
 use std::{iter::repeat_n, sync::Arc};
 
+use parsa_python_cst::NodeIndex;
+
 use super::{
-    AnyCause, LookupResult, Namespace, NewType, TupleArgs, Type, TypeVarKind, dataclass_initialize,
-    initialize_typed_dict, lookup_dataclass_symbol, lookup_on_dataclass, lookup_on_dataclass_type,
-    lookup_on_enum_class, lookup_on_enum_instance, lookup_on_enum_member_instance,
-    lookup_on_typed_dict,
+    AnyCause, CallableContent, Enum, LookupResult, Namespace, NewType, TupleArgs, Type,
+    TypeVarKind, dataclass_initialize, execute_enum, initialize_typed_dict,
+    lookup_dataclass_symbol, lookup_on_dataclass, lookup_on_dataclass_type, lookup_on_enum_class,
+    lookup_on_enum_instance, lookup_on_enum_member_instance, lookup_on_typed_dict,
     tuple::{lookup_on_tuple, lookup_tuple_magic_methods},
 };
 use crate::{
     arguments::{Args, NoArgs},
-    database::{Database, Specific},
+    database::{Database, PointKind, Specific},
     debug,
     diagnostics::IssueKind,
-    file::PythonFile,
+    file::{OtherDefinitionIterator, PythonFile},
     getitem::SliceType,
     imports::{ImportResult, namespace_import},
     inference_state::InferenceState,
@@ -238,11 +240,9 @@ impl Type {
             ),
             Type::Callable(_) | Type::FunctionOverload(_) => callable(
                 self,
-                Instance::new(i_s.db.python_state.function_class(), None).lookup(
-                    i_s,
-                    name,
-                    options(),
-                ),
+                Instance::new(i_s.db.python_state.function_class(), None)
+                    .lookup(i_s, name, options())
+                    .or_else(|| lookup_arbitrary_function_attribute(i_s, self, name)),
             ),
             Type::Module(file_index) => {
                 let lookup = i_s
@@ -561,7 +561,7 @@ impl Type {
                         *slice_type,
                         result_context,
                     ),
-                t @ Type::Enum(_) => {
+                t @ Type::Enum(enum_) => {
                     let enum_index = slice_type.infer(i_s);
                     if !enum_index
                         .as_cow_type(i_s)
@@ -571,6 +571,17 @@ impl Type {
                         add_issue(IssueKind::EnumIndexShouldBeAString {
                             actual: enum_index.format_short(i_s),
                         });
+                    } else if !i_s.db.project.settings.mypy_compatible
+                        && let Some(name) = enum_index.maybe_string_literal(i_s)
+                    {
+                        let name = name.as_str(i_s.db);
+                        if let Some(member) = Enum::lookup(enum_, i_s.db, name, false) {
+                            return Inferred::from_type(Type::EnumMember(member));
+                        }
+                        add_issue(IssueKind::EnumHasNoMemberNamed {
+                            enum_name: enum_.name.as_str(i_s.db).into(),
+                            name: name.into(),
+                        });
                     }
                     Inferred::from_type(t.clone())
                 }
@@ -661,11 +672,28 @@ impl Type {
             Type::Type(cls) => {
                 execute_type_of_type(i_s, args, result_context, on_type_error, cls.as_ref())
             }
-            Type::Union(union) => Inferred::gather_simplified_union(i_s, |gather| {
-                for entry in union.iter() {
-                    gather(entry.execute(i_s, None, args, result_context, on_type_error))
+            Type::Union(union) => {
+                // Mypy reports "None" not callable, we want to be more precise and only infer
+                // the result of the callable items.
+                let skip_none = !i_s.db.project.settings.mypy_compatible
+                    && union.iter().any(|t| matches!(t, Type::None));
+                if skip_none {
+                    args.add_issue(
+                        i_s,
+                        IssueKind::NoneInUnionNotCallable {
+                            union: self.format_short(i_s.db),
+                        },
+                    );
                 }
-            }),
+                Inferred::gather_simplified_union(i_s, |gather| {
+                    for entry in union.iter() {
+                        if skip_none && matches!(entry, Type::None) {
+                            continue;
+                        }
+                        gather(entry.execute(i_s, None, args, result_context, on_type_error))
+                    }
+                })
+            }
             Type::Callable(content) => {
                 Callable::new(content, None).execute(i_s, args, on_type_error, result_context)
             }
@@ -1342,10 +1370,7 @@ pub(crate) fn execute_type_of_type<'db>(
                 Arc::new(NamedTuple::new(nt.name, __new__))
             }))
         }
-        Type::Enum(_enum_) => {
-            debug!("TODO did not check arguments in execution of enum");
-            Inferred::from_type(type_.clone())
-        }
+        Type::Enum(enum_) => Inferred::from_type(execute_enum(i_s, args, enum_)),
         Type::Union(union) => Inferred::gather_simplified_union(i_s, |gather| {
             for t in union.iter() {
                 gather(execute_type_of_type(
@@ -1428,12 +1453,68 @@ impl NewType {
                     reason,
                     got: GotType::Type(&other),
                     expected: t,
+                    explanation: &[],
                 },
             );
         }
     }
 }
 
+fn lookup_arbitrary_function_attribute(
+    i_s: &InferenceState,
+    func: &Type,
+    name: &str,
+) -> LookupDetails<'static> {
+    if !i_s.flags().allow_function_attributes {
+        return LookupDetails::none();
+    }
+    let defined_at = match func {
+        Type::Callable(c) => c.defined_at,
+        Type::FunctionOverload(o) => o.iter_functions().next().unwrap().defined_at,
+        _ => unreachable!(),
+    };
+    // Only actual function objects can have arbitrary attributes, not callables in general.
+    let node_ref = NodeRef::from_link(i_s.db, defined_at);
+    let Some(func_def) = node_ref.maybe_function() else {
+        return LookupDetails::none();
+    };
+    let t = match name {
+        // Set by functools.wraps, which is typically the decorated function.
+        "__wrapped__" => Type::Callable(Arc::new(CallableContent::new_any(
+            i_s.db.python_state.empty_type_var_likes.clone(),
+            AnyCause::Unannotated,
+        ))),
+        _ if is_assigned_on_function(node_ref.file, func_def.name().index(), name) => {
+            Type::Any(AnyCause::Unannotated)
+        }
+        _ => return LookupDetails::none(),
+    };
+    LookupDetails::new(
+        func.clone(),
+        LookupResult::UnknownName(Inferred::from_type(t)),
+        AttributeKind::Attribute,
+    )
+}
+
+fn is_assigned_on_function(file: &PythonFile, func_name_index: NodeIndex, name: &str) -> bool {
+    // Searches the module of the function for assignments like `func.name = ...`.
+    file.tree
+        .root()
+        .search_potential_attribute_assignments()
+        .any(|(func_name, attribute)| {
+            if attribute.as_code() != name {
+                return false;
+            }
+            let p = file.points.get(func_name.index());
+            p.calculated()
+                && p.kind() == PointKind::Redirect
+                && p.file_index() == file.file_index
+                && (p.node_index() == func_name_index
+                    || OtherDefinitionIterator::new(&file.points, p.node_index())
+                        .any(|index| index == func_name_index))
+        })
+}
+
 fn lookup_in_namespace(
     db: &Database,
     from_file: &PythonFile,
@@ -1448,6 +1529,8 @@ fn lookup_in_namespace(
         ImportResult::Namespace(namespace) => {
             LookupResult::UnknownName(Inferred::from_type(Type::Namespace(namespace)))
         }
-        ImportResult::PyTypedMissing => LookupResult::any(AnyCause::FromError),
+        ImportResult::PyTypedMissing | ImportResult::ExtensionModuleWithoutStub(_) => {
+            LookupResult::any(AnyCause::FromError)
+        }
     }
 }
@@ -814,6 +814,23 @@ fn tuple_add_internal<'db>(
                         after: u.after.iter().chain(ts2.iter()).cloned().collect(),
                     })
                 }
+                // tuple[int, str] + tuple[bytes, ...] == tuple[int, str, *tuple[bytes, ...]]
+                (TupleArgs::FixedLen(ts1), TupleArgs::ArbitraryLen(t)) if !ts1.is_empty() => {
+                    TupleArgs::WithUnpack(WithUnpack {
+                        before: ts1.clone(),
+                        unpack: TupleUnpack::ArbitraryLen((**t).clone()),
+                        after: Arc::new([]),
+                    })
+                }
+                (TupleArgs::ArbitraryLen(t), TupleArgs::FixedLen(ts2)) if !ts2.is_empty() => {
+                    TupleArgs::WithUnpack(WithUnpack {
+                        before: Arc::new([]),
+                        unpack: TupleUnpack::ArbitraryLen((**t).clone()),
+                        after: ts2.clone(),
+                    })
+                }
+                (TupleArgs::FixedLen(ts1), _) if ts1.is_empty() => tuple2.args.clone(),
+                (_, TupleArgs::FixedLen(ts2)) if ts2.is_empty() => tuple1.args.clone(),
                 _ => return None,
             },
         ))));
@@ -860,10 +877,36 @@ fn tuple_mul_internal<'db>(
             ))))
         }),
         TupleArgs::ArbitraryLen(_) => Some(Inferred::from_type(Type::Tuple(tuple))),
-        TupleArgs::WithUnpack(_) => {
-            debug!("TODO How do we multiply with unpack tuples?");
-            None
-        }
+        TupleArgs::WithUnpack(with_unpack) => first.run_on_int_literals(i_s, |int| match int {
+            ..=0 => Some(Inferred::from_type(Type::Tuple(Tuple::new_fixed_length(
+                Arc::new([]),
+            )))),
+            1 => Some(Inferred::from_type(Type::Tuple(tuple.clone()))),
+            _ => {
+                // The items of a TypeVarTuple are not known, so the fallback is used.
+                let TupleUnpack::ArbitraryLen(t) = &with_unpack.unpack else {
+                    return None;
+                };
+                // tuple[A, *tuple[B, ...], C] * 2 is
+                // tuple[A, *tuple[B, ...], C, A, *tuple[B, ...], C], which is simplified to
+                // tuple[A, *tuple[A | B | C, ...], C].
+                let middle = simplified_union_from_iterators(
+                    i_s,
+                    with_unpack
+                        .before
+                        .iter()
+                        .chain(std::iter::once(t))
+                        .chain(with_unpack.after.iter()),
+                );
+                Some(Inferred::from_type(Type::Tuple(Tuple::new(
+                    TupleArgs::WithUnpack(WithUnpack {
+                        before: with_unpack.before.clone(),
+                        unpack: TupleUnpack::ArbitraryLen(middle),
+                        after: with_unpack.after.clone(),
+                    }),
+                ))))
+            }
+        }),
     }
 }
 
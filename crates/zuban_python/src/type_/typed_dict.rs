@@ -281,6 +281,12 @@ impl TypedDict {
         }
     }
 
+    /// Like `find_entry`, but a closed TypedDict does not have an entry for unknown keys.
+    pub fn find_accepted_entry(&self, db: &Database, name: &str) -> Option<TypedDictEntry<'_>> {
+        self.find_entry(db, name)
+            .filter(|entry| entry.name.is_some() || !entry.type_.is_never())
+    }
+
     fn qualified_name(&self, db: &Database) -> Option<String> {
         let name = self.name?;
         let module = db.loaded_python_file(name.file_index).qualified_name(db);
@@ -1296,10 +1302,10 @@ pub(crate) fn check_typed_dict_call<'db>(
     for member in m.named.iter() {
         if member.required {
             let expected_name = member.name.as_str(i_s.db);
-            if !args
-                .iter(i_s.mode)
-                .any(|arg| arg.keyword_name(i_s.db) == Some(expected_name))
-            {
+            if !args.iter(i_s.mode).any(|arg| {
+                // Keys unpacked from another TypedDict with **td need to be required there.
+                arg.keyword_name(i_s.db) == Some(expected_name) && !arg.is_not_required_key
+            }) {
                 missing_keys.push(expected_name.into())
             }
         }
@@ -28,9 +28,10 @@ use crate::{
         ResultContext, calc_callable_dunder_init_type_vars, calc_callable_type_vars,
         calc_class_dunder_init_type_vars, format_got_expected, maybe_class_usage,
     },
+    new_class,
     node_ref::NodeRef,
     type_::{
-        AnyCause, CallableContent, CallableLike, ClassGenerics, Dataclass, FormatStyle,
+        AnyCause, CallableContent, CallableLike, ClassGenerics, Dataclass, DbString, FormatStyle,
         FunctionOverload, GenericClass, GenericItem, GenericsList, LookupResult, NamedTuple,
         NeverCause, ParamSpecArg, ParamSpecUsage, ReplaceTypeVarLikes, Tuple, TupleArgs, Type,
         TypeVarIndex, TypeVarLike, TypeVarLikeUsage, TypeVarLikes, TypedDict, TypedDictGenerics,
@@ -309,8 +310,15 @@ impl<'db: 'a, 'a> Class<'a> {
         matcher: &mut Matcher,
         other: &Type,
     ) -> Match {
-        const SHOW_MAX_MISMATCHES: usize = 2;
-        const MAX_MISSING_MEMBERS: usize = 2;
+        // Mypy only shows a very limited amount of conflicts. Outside of Mypy compatibility mode
+        // we list all of the members that are missing or incompatible, which makes it way easier
+        // to understand why a structural match failed.
+        let (show_max_mismatches, max_missing_members) = if i_s.db.project.settings.mypy_compatible
+        {
+            (2, 2)
+        } else {
+            (usize::MAX, usize::MAX)
+        };
         let mut missing_members = vec![];
         let mut mismatches = 0;
         let mut notes = vec![];
@@ -320,6 +328,13 @@ impl<'db: 'a, 'a> Class<'a> {
         let ignore_positional_param_names_old = matcher.ignore_positional_param_names;
         matcher.ignore_positional_param_names = true;
 
+        // When a class object is matched against a protocol, the members of the protocol are
+        // still bound to an instance of the class (e.g. for classmethods returning Self).
+        let protocol_self = match other {
+            Type::Type(t) if t.maybe_class(i_s.db).is_some() => t.as_ref(),
+            _ => other,
+        };
+
         let mut protocol_member_count = 0;
         for (_, c) in self.mro_maybe_without_object(i_s.db, true) {
             let TypeOrClass::Class(c) = c else {
@@ -372,7 +387,7 @@ impl<'db: 'a, 'a> Class<'a> {
                     i_s,
                     name,
                     InstanceLookupOptions::new(&|_| had_binding_error.set(true))
-                        .with_as_self_instance(&|| other.clone())
+                        .with_as_self_instance(&|| protocol_self.clone())
                         .with_avoid_inferring_return_types(),
                 );
                 let protocol_inf = protocol_lookup_details.lookup.into_inferred();
@@ -386,6 +401,18 @@ impl<'db: 'a, 'a> Class<'a> {
                     continue;
                 }
 
+                if name == "__iter__"
+                    && !i_s.db.project.settings.mypy_compatible
+                    && let Some(legacy_iter) = legacy_getitem_iter_method(i_s, other)
+                    && protocol_inf
+                        .as_cow_type(i_s)
+                        .matches(i_s, matcher, &legacy_iter, protocol_member.variance)
+                        .bool()
+                {
+                    had_at_least_one_member_with_same_name = true;
+                    continue;
+                }
+
                 other.run_after_lookup_on_each_union_member(
                     i_s,
                     None,
@@ -444,7 +471,7 @@ impl<'db: 'a, 'a> Class<'a> {
                             {
                                 maybe_add_conflict_note(&mut notes);
                                 mismatch = true;
-                                if mismatches < SHOW_MAX_MISMATCHES {
+                                if mismatches < show_max_mismatches {
                                     match other.maybe_class(i_s.db) {
                                         Some(cls) => add_protocol_mismatch(
                                             i_s,
@@ -501,7 +528,7 @@ impl<'db: 'a, 'a> Class<'a> {
                                 let p_t = proto_setter_type.unwrap_or(&protocol_t);
                                 if !p_t.is_sub_type_of(i_s, matcher, o_t).bool() {
                                     mismatch = true;
-                                    if mismatches < SHOW_MAX_MISMATCHES {
+                                    if mismatches < show_max_mismatches {
                                         maybe_add_conflict_note(&mut notes);
                                         notes.push(
                                             format!(
@@ -522,7 +549,7 @@ impl<'db: 'a, 'a> Class<'a> {
                                 && !protocol_lookup_details.attr_kind.is_read_only_property()
                             {
                                 mismatch = true;
-                                if mismatches < SHOW_MAX_MISMATCHES {
+                                if mismatches < show_max_mismatches {
                                     notes.push(
                                         format!(
                                         "Protocol member {}.{name} expected settable variable, \
@@ -538,7 +565,7 @@ impl<'db: 'a, 'a> Class<'a> {
                                 if !matches!(lookup_details.attr_kind, AttributeKind::ClassVar)
                                 {
                                     mismatch = true;
-                                    if mismatches < SHOW_MAX_MISMATCHES {
+                                    if mismatches < show_max_mismatches {
                                         notes.push(
                                             format!(
                                                 "Protocol member {}.{name} expected class variable, \
@@ -550,7 +577,7 @@ impl<'db: 'a, 'a> Class<'a> {
                                     }
                                 } else if other.maybe_type_of_class(i_s.db).is_some() {
                                     mismatch = true;
-                                    if mismatches < SHOW_MAX_MISMATCHES {
+                                    if mismatches < show_max_mismatches {
                                         notes.push(
                                             format!(
                                                 "ClassVar protocol member {}.{name} can never be \
@@ -568,7 +595,7 @@ impl<'db: 'a, 'a> Class<'a> {
                                && other.maybe_class(i_s.db).is_some()
                             {
                                 mismatch = true;
-                                if mismatches < SHOW_MAX_MISMATCHES {
+                                if mismatches < show_max_mismatches {
                                     notes.push(
                                         format!(
                                             "Protocol member {}.{name} expected instance variable, \
@@ -585,7 +612,7 @@ impl<'db: 'a, 'a> Class<'a> {
                                 && !lookup_details.attr_kind.classmethod_or_staticmethod()
                             {
                                 mismatch = true;
-                                if mismatches < SHOW_MAX_MISMATCHES {
+                                if mismatches < show_max_mismatches {
                                     notes.push(
                                         format!(
                                         "Protocol member {}.{name} expected class or static method",
@@ -598,7 +625,7 @@ impl<'db: 'a, 'a> Class<'a> {
                             if lookup_details.attr_kind == AttributeKind::AnnotatedAttribute
                                 && let Type::Type(t) = other {
                                     mismatch = true;
-                                    if mismatches < SHOW_MAX_MISMATCHES {
+                                    if mismatches < show_max_mismatches {
                                         notes.push(
                                             format!(
                                             "Only class variables allowed for class object access \
@@ -611,7 +638,7 @@ impl<'db: 'a, 'a> Class<'a> {
                                 }
                             if is_final_mismatch {
                                 mismatch = true;
-                                if mismatches < SHOW_MAX_MISMATCHES {
+                                if mismatches < show_max_mismatches {
                                     notes.push(
                                         format!(
                                         "Protocol member {}.{name} expected settable variable, \
@@ -626,7 +653,7 @@ impl<'db: 'a, 'a> Class<'a> {
                     },
                 );
                 if let Some(issue) = had_error.take() {
-                    if mismatches < SHOW_MAX_MISMATCHES {
+                    if mismatches < show_max_mismatches {
                         notes.push(issue.into());
                     }
                     mismatch = true;
@@ -643,11 +670,11 @@ impl<'db: 'a, 'a> Class<'a> {
         if !had_at_least_one_member_with_same_name && !missing_members.is_empty() {
             return Match::new_false();
         }
-        if mismatches > SHOW_MAX_MISMATCHES {
+        if mismatches > show_max_mismatches {
             notes.push(
                 format!(
                     "    <{} more conflict(s) not shown>",
-                    mismatches - SHOW_MAX_MISMATCHES
+                    mismatches - show_max_mismatches
                 )
                 .into(),
             );
@@ -655,7 +682,7 @@ impl<'db: 'a, 'a> Class<'a> {
         let missing_members_empty = missing_members.is_empty();
         if !missing_members_empty
             && protocol_member_count > 1
-            && missing_members.len() <= MAX_MISSING_MEMBERS
+            && missing_members.len() <= max_missing_members
         {
             let tmp;
             notes.push(
@@ -2145,6 +2172,27 @@ fn add_protocol_mismatch(
     }
 }
 
+fn legacy_getitem_iter_method(i_s: &InferenceState, other: &Type) -> Option<Type> {
+    // Classes that are iterable via __getitem__ structurally have an __iter__ method returning
+    // an iterator of the items.
+    let Type::Class(c) = other else {
+        return None;
+    };
+    let instance = Instance::new(c.class(i_s.db), None);
+    if instance.type_lookup(i_s, |_| (), "__iter__").is_some() {
+        return None;
+    }
+    let item = instance.legacy_getitem_iteration(i_s)?.as_type(i_s);
+    Some(Type::Callable(Arc::new(CallableContent::new_non_generic(
+        i_s.db,
+        Some(DbString::Static("__iter__")),
+        None,
+        c.link,
+        vec![],
+        new_class!(i_s.db.python_state.iterator_link(), item),
+    ))))
+}
+
 fn protocol_conflict_note(db: &Database, other: &Type) -> Box<str> {
     match other {
         Type::Module(file_index) => format!(
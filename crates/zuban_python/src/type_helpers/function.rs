@@ -17,7 +17,7 @@ use crate::{
     file::{
         FLOW_ANALYSIS, FuncNodeRef, FuncParent, OtherDefinitionIterator, PythonFile,
         RedefinitionResult, TypeVarCallbackReturn, first_defined_name_of_multi_def,
-        on_argument_type_error, use_cached_param_annotation_type,
+        is_binary_dunder_method, on_argument_type_error, use_cached_param_annotation_type,
     },
     format_data::FormatData,
     inference_state::{InferenceState, Mode},
@@ -223,6 +223,10 @@ impl<'db: 'a + 'class, 'a, 'class> Function<'a, 'class> {
         let inference = self.node_ref.file.inference(inner_i_s);
         let mut generator: Option<Inferred> = None;
         let mut result: Option<Inferred> = None;
+        // `return NotImplemented` in binary dunders falls back to the reflected operation and is
+        // therefore not part of the result.
+        let mut not_implemented: Option<Inferred> = None;
+        let is_binary_dunder = is_binary_dunder_method(self.name());
         for return_or_yield in self.iter_return_or_yield() {
             match return_or_yield {
                 ReturnOrYield::Return(ret) => {
@@ -232,6 +236,10 @@ impl<'db: 'a + 'class, 'a, 'class> Function<'a, 'class> {
                     } else {
                         Inferred::new_none()
                     };
+                    if is_binary_dunder && inf.is_not_implemented(inner_i_s) {
+                        not_implemented = Some(inf);
+                        continue;
+                    }
                     result = Some(if let Some(r) = result {
                         inf.simplified_union(inner_i_s, r)
                     } else {
@@ -256,6 +264,7 @@ impl<'db: 'a + 'class, 'a, 'class> Function<'a, 'class> {
                 }
             }
         }
+        result = result.or(not_implemented);
         let needs_async_remap = if let Some(generator) = generator {
             let t = generator
                 .as_type(i_s)
@@ -1400,7 +1409,13 @@ impl<'db: 'a + 'class, 'a, 'class> Function<'a, 'class> {
         let mut params = self.iter_params().peekable();
         let needs_self_type = match options.first_param {
             FirstParamProperties::Skip { .. } => {
-                params.next();
+                // *args receives the instance, but still accepts all the other arguments.
+                if params
+                    .peek()
+                    .is_some_and(|p| p.param.kind() != ParamKind::Star)
+                {
+                    params.next();
+                }
                 false
             }
             FirstParamProperties::None => {
@@ -1,7 +1,5 @@
 use std::{borrow::Cow, cell::Cell, sync::Arc};
 
-use parsa_python_cst::Name;
-
 use super::{Class, ClassLookupOptions, FirstParamKind, Function, MroIterator, class::TypeOrClass};
 use crate::{
     arguments::{Args, CombinedArgs, InferredArg, KnownArgs, KnownArgsWithCustomAddIssue},
@@ -47,12 +45,11 @@ impl<'a> Instance<'a> {
         &self,
         i_s: &InferenceState,
         from: NodeRef,
-        name: Name,
+        name_str: &str,
         value: &Inferred,
     ) -> bool {
         let add_issue = |issue| from.add_issue(i_s, issue);
 
-        let name_str = name.as_str();
         let property_is_read_only = |class_name| {
             add_issue(IssueKind::PropertyIsReadOnly {
                 class_name,
@@ -211,7 +208,7 @@ impl<'a> Instance<'a> {
                             is_attribute: true,
                         });
                     } else if !lookup_details.attr_kind.is_overwritable() {
-                        add_issue(IssueKind::CannotAssignToAMethod);
+                        add_issue(method_assignment_issue(i_s, name_str));
                     }
                 }
             }
@@ -320,12 +317,32 @@ impl<'a> Instance<'a> {
                 FoundOnClass::UnresolvedType(t) => t.iter(i_s, infos),
             };
         }
+        if !i_s.db.project.settings.mypy_compatible
+            && let Some(item) = self.legacy_getitem_iteration(i_s)
+        {
+            return IteratorContent::Inferred(item);
+        }
         if !self.class.incomplete_mro(i_s.db) {
             infos.add_not_iterable_issue(i_s.db, original_t);
         }
         IteratorContent::Any(AnyCause::Todo)
     }
 
+    pub(crate) fn legacy_getitem_iteration(&self, i_s: &InferenceState) -> Option<Inferred> {
+        // Without __iter__, Python still iterates over objects by calling __getitem__ with
+        // 0, 1, 2, ... until an IndexError is raised (the legacy sequence protocol).
+        let getitem = self
+            .type_lookup(i_s, |_| (), "__getitem__")
+            .into_maybe_inferred()?;
+        let int = Inferred::from_type(i_s.db.python_state.int_type());
+        let had_error = Cell::new(false);
+        let item = getitem.execute(
+            i_s,
+            &KnownArgsWithCustomAddIssue::new(&int, &|_| had_error.set(true)),
+        );
+        (!had_error.get()).then_some(item)
+    }
+
     pub(crate) fn lookup(
         &self,
         i_s: &'a InferenceState,
@@ -631,6 +648,14 @@ impl<'a> Instance<'a> {
     }
 }
 
+pub(crate) fn method_assignment_issue(i_s: &InferenceState, name: &str) -> IssueKind {
+    if i_s.flags().disallow_monkeypatching {
+        IssueKind::Monkeypatching { name: name.into() }
+    } else {
+        IssueKind::CannotAssignToAMethod
+    }
+}
+
 fn calculate_descriptor(
     i_s: &InferenceState,
     from: NodeRef,
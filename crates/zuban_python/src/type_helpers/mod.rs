@@ -16,7 +16,7 @@ pub(crate) use function::{
 };
 pub(crate) use instance::{
     Instance, InstanceLookupOptions, LookupDetails, execute_isinstance, execute_issubclass,
-    execute_super,
+    execute_super, method_assignment_issue,
 };
 pub(crate) use overload::{OverloadResult, OverloadedFunction};
 pub(crate) use typing::{execute_assert_type, execute_cast, execute_reveal_type};
@@ -15,7 +15,10 @@ use crate::{
         SignatureMatch, calc_callable_dunder_init_type_vars, calc_callable_type_vars,
         replace_class_type_vars_in_callable,
     },
-    type_::{AnyCause, FunctionOverload, NeverCause, ReplaceSelf, Type},
+    type_::{
+        AnyCause, CallableParam, CallableParams, FunctionOverload, LiteralKind, NeverCause,
+        ParamType, ReplaceSelf, Type,
+    },
     utils::debug_indent,
 };
 
@@ -114,7 +117,16 @@ impl<'db: 'a, 'a> OverloadedFunction<'a> {
         let mut multi_any_match: Option<(_, Box<_>)> = None;
         let mut had_error_in_func = None;
         let points_backup = args.points_backup();
+        let arg_shape = (!search_init)
+            .then(|| ArgShape::new(args.iter(i_s.mode)))
+            .flatten();
         for (i, callable) in self.overload.iter_functions().enumerate() {
+            if let Some(arg_shape) = &arg_shape
+                && !arg_shape.could_match(i_s.db, &callable.params, skip_first_argument)
+            {
+                debug!("Overload #{i} mismatch, because of the number or names of the arguments");
+                continue;
+            }
             debug!("Checking overload item #{i}");
             let callable = Callable::new(callable, self.class);
             let (calculated_type_args, had_error) =
@@ -177,7 +189,13 @@ impl<'db: 'a, 'a> OverloadedFunction<'a> {
                     debug!("Overload #{i} mismatch, is not similar.");
                 }
             }
-            args.reset_points_from_backup(&points_backup);
+            if had_error {
+                // Errors are only reported while inferring, so everything needs to be inferred
+                // again.
+                args.reset_points_from_backup(&points_backup);
+            } else {
+                args.reset_points_from_backup_except_names(&points_backup);
+            }
         }
         if let Some((callable, _)) = multi_any_match {
             debug!(
@@ -290,6 +308,75 @@ impl<'db: 'a, 'a> OverloadedFunction<'a> {
         OverloadResult::NotFound
     }
 
+    /// Overloads like `def open(..., binary: Literal[True]) -> bytes` are usually combined with a
+    /// fallback for `bool`. Passing a `bool` variable silently selects that fallback, which is
+    /// often not what the user expects. This is only called after the arguments were matched
+    /// against the chosen overload, so their types are taken from the cache.
+    fn check_non_literal_flags(
+        &self,
+        i_s: &InferenceState<'db, '_>,
+        args: &dyn Args<'db>,
+        callable: &Callable,
+    ) {
+        if !i_s.db.project.flags.is_error_code_enabled("overload-flag") {
+            return;
+        }
+        let Some(overload_index) = self
+            .overload
+            .iter_functions()
+            .position(|c| std::ptr::eq(&**c, callable.content))
+        else {
+            return;
+        };
+        let is_bool_literal_param = |param: &CallableParam| {
+            param.type_.maybe_type().is_some_and(|t| {
+                t.iter_with_unpacked_unions(i_s.db).any(
+                    |t| matches!(t, Type::Literal(l) if matches!(l.kind, LiteralKind::Bool(_))),
+                )
+            })
+        };
+        let is_bool =
+            |t: &Type| matches!(t, Type::Class(c) if c.link == i_s.db.python_state.bool_link());
+        let CallableParams::Simple(params) = &callable.content.params else {
+            return;
+        };
+        for arg in args.iter(i_s.mode) {
+            let Some(key) = arg.keyword_name(i_s.db) else {
+                continue;
+            };
+            let has_key =
+                |p: &CallableParam| p.name.as_ref().is_some_and(|n| n.as_str(i_s.db) == key);
+            let Some(param) = params.iter().find(|p| has_key(p)) else {
+                continue;
+            };
+            if !param.type_.maybe_type().is_some_and(is_bool) {
+                continue;
+            }
+            let discriminates_on_literals = self.overload.iter_functions().any(|c| {
+                matches!(&c.params, CallableParams::Simple(params) if params
+                    .iter()
+                    .any(|p| has_key(p) && is_bool_literal_param(p)))
+            });
+            if !discriminates_on_literals {
+                continue;
+            }
+            let is_plain_bool = match arg.infer(&mut ResultContext::Unknown) {
+                InferredArg::Inferred(inf) => is_bool(&inf.as_cow_type(i_s)),
+                _ => false,
+            };
+            if is_plain_bool {
+                arg.add_issue(
+                    i_s,
+                    IssueKind::NonLiteralOverloadFlag {
+                        param: key.into(),
+                        function: self.name(i_s.db).into(),
+                        overload_index: overload_index + 1,
+                    },
+                );
+            }
+        }
+    }
+
     fn check_union_math<'x>(
         &self,
         i_s: &InferenceState<'db, '_>,
@@ -334,6 +421,8 @@ impl<'db: 'a, 'a> OverloadedFunction<'a> {
                 let nxt_arg: &'x Arg<'db, 'x> = unsafe { std::mem::transmute(&next_arg) };
                 non_union_args.push(Arg {
                     index: next_arg.index,
+                    unpacked_index: next_arg.unpacked_index,
+                    is_not_required_key: next_arg.is_not_required_key,
                     kind: ArgKind::Overridden {
                         original: nxt_arg,
                         inferred: Inferred::new_any(AnyCause::Todo),
@@ -581,6 +670,7 @@ impl<'db: 'a, 'a> OverloadedFunction<'a> {
                     result_context,
                     Some(replace_self),
                 );
+                self.check_non_literal_flags(i_s, args, &callable);
                 if callable.content.return_type.is_never() {
                     FLOW_ANALYSIS.with(|fa| fa.mark_current_frame_unreachable())
                 }
@@ -603,6 +693,85 @@ impl<'db: 'a, 'a> OverloadedFunction<'a> {
     }
 }
 
+// A cheap summary of the arguments of a call. Overloads with a lot of items (e.g. in numpy or
+// pandas stubs) are often not matching, because the number of arguments or the keyword names are
+// different. These items can be skipped without running the type matcher, because they would
+// anyways be a mismatch that is not "similar".
+struct ArgShape<'a> {
+    positional: usize,
+    keywords: Vec<&'a str>,
+}
+
+impl<'a> ArgShape<'a> {
+    fn new(args: ArgIterator<'_, 'a>) -> Option<Self> {
+        let mut positional = 0;
+        let mut keywords = vec![];
+        for arg in args {
+            match arg.kind {
+                ArgKind::Positional(_)
+                | ArgKind::Inferred {
+                    is_keyword: None,
+                    in_args_or_kwargs_and_arbitrary_len: false,
+                    ..
+                } => positional += 1,
+                ArgKind::Keyword(kwarg) => keywords.push(kwarg.key),
+                // Unpacked arguments and the like cannot be screened.
+                _ => return None,
+            }
+        }
+        Some(Self {
+            positional,
+            keywords,
+        })
+    }
+
+    fn could_match(&self, db: &Database, params: &CallableParams, skip_first_param: bool) -> bool {
+        let CallableParams::Simple(params) = params else {
+            return true;
+        };
+        let mut positional_params = 0;
+        let mut has_star = false;
+        let mut has_star_star = false;
+        for p in params.iter().skip(skip_first_param as usize) {
+            let name = p.name.as_ref().map(|n| n.as_str(db));
+            let given_as_keyword = name.is_some_and(|n| self.keywords.contains(&n));
+            match &p.type_ {
+                ParamType::PositionalOnly(_) => {
+                    positional_params += 1;
+                    if !p.has_default && positional_params > self.positional {
+                        return false;
+                    }
+                }
+                ParamType::PositionalOrKeyword(_) => {
+                    positional_params += 1;
+                    if !p.has_default && positional_params > self.positional && !given_as_keyword {
+                        return false;
+                    }
+                }
+                ParamType::KeywordOnly(_) => {
+                    if !p.has_default && !given_as_keyword {
+                        return false;
+                    }
+                }
+                ParamType::Star(_) => has_star = true,
+                ParamType::StarStar(_) => has_star_star = true,
+            }
+        }
+        if !has_star && self.positional > positional_params {
+            return false;
+        }
+        has_star_star
+            || self.keywords.iter().all(|key| {
+                params.iter().skip(skip_first_param as usize).any(|p| {
+                    matches!(
+                        p.type_,
+                        ParamType::PositionalOrKeyword(_) | ParamType::KeywordOnly(_)
+                    ) && p.name.as_ref().is_some_and(|n| n.as_str(db) == *key)
+                })
+            })
+    }
+}
+
 fn are_any_arguments_ambiguous_in_overload(
     a: &[ArgumentIndexWithParam],
     b: &[ArgumentIndexWithParam],
@@ -291,6 +291,35 @@ impl TestCase<'_, '_> {
             "--follow-untyped-imports",
         );
         set_bool_flag(&mut config.use_joins, "--use-joins");
+        set_bool_flag(&mut config.explain_mismatch, "--explain-mismatch");
+        set_bool_flag(
+            &mut config.strict_signature_matching,
+            "--strict-signature-matching",
+        );
+        set_bool_flag(
+            &mut config.disallow_untyped_attributes,
+            "--disallow-untyped-attributes",
+        );
+        set_bool_flag(
+            &mut config.union_untyped_attributes,
+            "--union-untyped-attributes",
+        );
+        set_bool_flag(
+            &mut config.disallow_monkeypatching,
+            "--disallow-monkeypatching",
+        );
+        set_bool_flag(
+            &mut config.allow_function_attributes,
+            "--allow-function-attributes",
+        );
+        set_bool_flag(
+            &mut config.disallow_untyped_imports,
+            "--disallow-untyped-imports",
+        );
+        set_bool_flag(
+            &mut config.strict_numeric_promotion,
+            "--strict-numeric-promotion",
+        );
         set_bool_flag(&mut config.warn_no_return, "--warn-no-return");
         set_reverse_bool_flag(&mut config.warn_no_return, "--no-warn-no-return");
         set_reverse_bool_flag(&mut config.strict_optional, "--no-strict-optional");
@@ -341,6 +370,18 @@ impl TestCase<'_, '_> {
         gather_list(&mut config.always_false_symbols, "--always-false");
         gather_list(&mut config.enabled_error_codes, "--enable-error-code");
         gather_list(&mut config.disabled_error_codes, "--disable-error-code");
+        gather_list(
+            &mut config.untyped_extension_modules,
+            "--untyped-extension-module",
+        );
+        gather_list(
+            &mut config.allowed_untyped_imports,
+            "--allowed-untyped-import",
+        );
+        gather_list(
+            &mut config.generated_code_markers,
+            "--generated-code-marker",
+        );
 
         if self.file_name == "check-recursive-types" {
             // This feels very broken, but for now we disable these errors, because they don't feel
@@ -2,14 +2,33 @@
 
 //! Advertises the capabilities of the LSP Server.
 use lsp_types::{
-    CompletionOptions, DeclarationCapability, HoverProviderCapability,
-    ImplementationProviderCapability, OneOf, PositionEncodingKind, RenameOptions,
-    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
-    TypeDefinitionProviderCapability, WorkDoneProgressOptions,
+    CodeActionKind, CodeActionOptions, CodeActionProviderCapability, CodeLensOptions,
+    CompletionOptions, DeclarationCapability, ExecuteCommandOptions,
+    FoldingRangeProviderCapability, HoverProviderCapability, ImplementationProviderCapability,
+    OneOf, PositionEncodingKind, RenameOptions, SelectionRangeProviderCapability,
+    SemanticTokenModifier, SemanticTokenType, SemanticTokensFullOptions, SemanticTokensLegend,
+    SemanticTokensOptions, SemanticTokensServerCapabilities, ServerCapabilities,
+    SignatureHelpOptions, TextDocumentSyncCapability, TextDocumentSyncKind,
+    TextDocumentSyncOptions, TypeDefinitionProviderCapability, WorkDoneProgressOptions,
     WorkspaceFileOperationsServerCapabilities, WorkspaceFoldersServerCapabilities,
     WorkspaceServerCapabilities,
 };
 
+use crate::request_handlers::CHECK_FILE_COMMAND;
+
+/// The indexes of these are used as the token types of semantic tokens.
+pub(crate) const SEMANTIC_TOKEN_TYPES: [SemanticTokenType; 4] = [
+    SemanticTokenType::CLASS,
+    SemanticTokenType::TYPE_PARAMETER,
+    SemanticTokenType::FUNCTION,
+    SemanticTokenType::PARAMETER,
+];
+/// Type parameters are either TypeVars or one of these.
+pub(crate) const SEMANTIC_TOKEN_MODIFIERS: [SemanticTokenModifier; 2] = [
+    SemanticTokenModifier::new("typeVarTuple"),
+    SemanticTokenModifier::new("paramSpec"),
+];
+
 pub(crate) fn server_capabilities(client_capabilities: &ClientCapabilities) -> ServerCapabilities {
     ServerCapabilities {
         position_encoding: Some(client_capabilities.negotiated_encoding().into()),
@@ -36,22 +55,36 @@ pub(crate) fn server_capabilities(client_capabilities: &ClientCapabilities) -> S
             completion_item: None,
             work_done_progress_options: Default::default(),
         }),
-        signature_help_provider: None, // TODO
+        signature_help_provider: Some(SignatureHelpOptions {
+            trigger_characters: Some(vec!["(".to_owned(), ",".to_owned()]),
+            retrigger_characters: None,
+            work_done_progress_options: Default::default(),
+        }),
         declaration_provider: Some(DeclarationCapability::Simple(true)),
         definition_provider: Some(OneOf::Left(true)),
         type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(true)),
         implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
         references_provider: Some(OneOf::Left(true)),
         document_highlight_provider: Some(OneOf::Left(true)),
-        document_symbol_provider: None,             // TODO
-        workspace_symbol_provider: None,            // TODO
-        code_action_provider: None,                 // TODO
-        code_lens_provider: None,                   // TODO
+        document_symbol_provider: None,  // TODO
+        workspace_symbol_provider: None, // TODO
+        code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+            code_action_kinds: Some(vec![
+                CodeActionKind::QUICKFIX,
+                CodeActionKind::REFACTOR_EXTRACT,
+                CodeActionKind::SOURCE,
+            ]),
+            work_done_progress_options: Default::default(),
+            resolve_provider: None,
+        })),
+        code_lens_provider: Some(CodeLensOptions {
+            resolve_provider: Some(true),
+        }),
         document_formatting_provider: None,         // TODO
         document_range_formatting_provider: None,   // TODO
         document_on_type_formatting_provider: None, // TODO?
-        selection_range_provider: None,             // TODO
-        folding_range_provider: None,               // TODO
+        selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+        folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
         rename_provider: Some(OneOf::Right(RenameOptions {
             prepare_provider: Some(true),
             work_done_progress_options: Default::default(),
@@ -59,7 +92,10 @@ pub(crate) fn server_capabilities(client_capabilities: &ClientCapabilities) -> S
         linked_editing_range_provider: None,
         document_link_provider: None,
         color_provider: None,
-        execute_command_provider: None,
+        execute_command_provider: Some(ExecuteCommandOptions {
+            commands: vec![CHECK_FILE_COMMAND.to_owned()],
+            work_done_progress_options: Default::default(),
+        }),
         workspace: Some(WorkspaceServerCapabilities {
             workspace_folders: Some(WorkspaceFoldersServerCapabilities {
                 supported: Some(true),
@@ -75,8 +111,20 @@ pub(crate) fn server_capabilities(client_capabilities: &ClientCapabilities) -> S
                 will_delete: None,
             }),
         }),
-        call_hierarchy_provider: None,  // TODO
-        semantic_tokens_provider: None, // TODO
+        call_hierarchy_provider: None, // TODO
+        semantic_tokens_provider: Some(SemanticTokensServerCapabilities::SemanticTokensOptions(
+            SemanticTokensOptions {
+                work_done_progress_options: WorkDoneProgressOptions {
+                    work_done_progress: None,
+                },
+                legend: SemanticTokensLegend {
+                    token_types: SEMANTIC_TOKEN_TYPES.to_vec(),
+                    token_modifiers: SEMANTIC_TOKEN_MODIFIERS.to_vec(),
+                },
+                range: None,
+                full: Some(SemanticTokensFullOptions::Bool(true)),
+            },
+        )),
         moniker_provider: None,
         inlay_hint_provider: None, // TODO
         inline_value_provider: None,
@@ -101,17 +149,26 @@ pub(crate) struct ClientCapabilities {
     caps: lsp_types::ClientCapabilities,
     negotiated_encoding: NegotiatedEncoding,
     should_push_diagnostics: bool,
+    is_vs_code: bool,
 }
 
 impl ClientCapabilities {
-    pub(crate) fn new(caps: lsp_types::ClientCapabilities) -> Self {
+    pub(crate) fn new(
+        caps: lsp_types::ClientCapabilities,
+        client_info: Option<&lsp_types::ClientInfo>,
+    ) -> Self {
         let negotiated_encoding = Self::negotiate_encoding(&caps);
         tracing::info!("Negotiated encoding to {negotiated_encoding:?}");
         let should_push_diagnostics = !Self::text_document_diagnostic(&caps);
+        // Also matches the Insiders build ("Visual Studio Code - Insiders").
+        let is_vs_code = client_info.is_some_and(|info| {
+            info.name.starts_with("Visual Studio Code") || info.name == "VSCodium"
+        });
         Self {
             caps,
             negotiated_encoding,
             should_push_diagnostics,
+            is_vs_code,
         }
     }
 
@@ -137,6 +194,12 @@ impl ClientCapabilities {
         self.negotiated_encoding
     }
 
+    /// Commands like `editor.action.showReferences` are only executed by VS Code, other clients
+    /// send them back to the server.
+    pub(crate) fn is_vs_code(&self) -> bool {
+        self.is_vs_code
+    }
+
     pub(crate) fn workspace_edit_resource_operations(
         &self,
     ) -> Option<&[lsp_types::ResourceOperationKind]> {
@@ -5,5 +5,5 @@ mod request_handlers;
 mod server;
 
 pub use crate::server::{
-    GLOBAL_NOTIFY_EVENT_COUNTER, run_server, run_server_with_custom_connection,
+    GLOBAL_NOTIFY_EVENT_COUNTER, PROFILE, run_server, run_server_with_custom_connection,
 };
@@ -42,7 +42,7 @@ impl GlobalState<'_> {
     }
 
     fn store_in_memory_file(&mut self, uri: lsp_types::Uri, code: Box<str>) -> anyhow::Result<()> {
-        let project = self.project();
+        let project = self.project_for_uri(&uri);
         let path = Self::uri_to_path(project, uri)?;
         tracing::info!("Loading {}", path.as_uri());
         project.store_in_memory_file(path, code);
@@ -54,7 +54,7 @@ impl GlobalState<'_> {
         params: DidCloseTextDocumentParams,
     ) -> anyhow::Result<()> {
         let _p = tracing::info_span!("handle_did_change_text_document").entered();
-        let project = self.project();
+        let project = self.project_for_uri(&params.text_document.uri);
         let path = Self::uri_to_path(project, params.text_document.uri)?;
         tracing::info!("Closing {}", path.as_uri());
 
@@ -1,24 +1,34 @@
-use std::str::FromStr;
+use std::{
+    hash::{DefaultHasher, Hash as _, Hasher as _},
+    str::FromStr,
+};
 
 use anyhow::bail;
 use lsp_server::ErrorCode;
 use lsp_types::{
-    CompletionItem, CompletionParams, CompletionResponse, CompletionTextEdit, Diagnostic,
-    DiagnosticSeverity, DocumentChangeOperation, DocumentChanges, DocumentDiagnosticParams,
-    DocumentDiagnosticReport, DocumentDiagnosticReportResult, DocumentHighlight,
-    DocumentHighlightKind, DocumentHighlightParams, FullDocumentDiagnosticReport,
-    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams, Location,
-    LocationLink, MarkupContent, MarkupKind, OneOf, OptionalVersionedTextDocumentIdentifier,
-    Position, PrepareRenameResponse, ReferenceParams, RelatedFullDocumentDiagnosticReport,
-    RenameFile, RenameParams, ResourceOp, ResourceOperationKind, TextDocumentEdit,
-    TextDocumentIdentifier, TextDocumentPositionParams, TextEdit, Uri, WorkspaceEdit,
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
+    CodeLens, CodeLensParams, Command, CompletionItem, CompletionParams, CompletionResponse,
+    CompletionTextEdit, Diagnostic, DiagnosticSeverity, DiagnosticTag, DocumentChangeOperation,
+    DocumentChanges, DocumentDiagnosticParams, DocumentDiagnosticReport,
+    DocumentDiagnosticReportResult, DocumentHighlight, DocumentHighlightKind,
+    DocumentHighlightParams, Documentation, ExecuteCommandParams, FoldingRange, FoldingRangeParams,
+    FullDocumentDiagnosticReport, GotoDefinitionParams, GotoDefinitionResponse, Hover,
+    HoverContents, HoverParams, Location, LocationLink, MarkupContent, MarkupKind, OneOf,
+    OptionalVersionedTextDocumentIdentifier, ParameterInformation, ParameterLabel, Position,
+    PrepareRenameResponse, ReferenceParams, RelatedFullDocumentDiagnosticReport,
+    RelatedUnchangedDocumentDiagnosticReport, RenameFile, RenameParams, ResourceOp,
+    ResourceOperationKind, SelectionRange, SelectionRangeParams, SemanticToken, SemanticTokens,
+    SemanticTokensParams, SemanticTokensResult, SignatureHelp, SignatureHelpParams,
+    SignatureInformation, TextDocumentEdit, TextDocumentIdentifier, TextDocumentPositionParams,
+    TextEdit, UnchangedDocumentDiagnosticReport, Uri, WorkspaceEdit,
     request::{
         GotoDeclarationParams, GotoDeclarationResponse, GotoImplementationParams,
         GotoImplementationResponse, GotoTypeDefinitionParams, GotoTypeDefinitionResponse,
     },
 };
 use zuban_python::{
-    Document, GotoGoal, InputPosition, Name, PositionInfos, ReferencesGoal, Severity,
+    Document, GotoGoal, InputPosition, Name, PositionInfos, ReferencesGoal, SemanticTokenKind,
+    Severity,
 };
 
 use crate::{
@@ -39,12 +49,27 @@ impl GlobalState<'_> {
         );
         let encoding = self.client_capabilities.negotiated_encoding();
         let document = self.document(params.text_document)?;
+        // Diagnostics of unchanged files are cached, so recalculating them is cheap. The result
+        // id is derived from the diagnostics, which means that the client only needs to receive
+        // them again if they actually changed.
+        let items = Self::diagnostics_for_file(document, encoding);
+        let result_id = diagnostics_result_id(&items);
+        if params.previous_result_id.as_ref() == Some(&result_id) {
+            return Ok(DocumentDiagnosticReportResult::Report(
+                DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                    related_documents: None,
+                    unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                        result_id,
+                    },
+                }),
+            ));
+        }
         Ok(DocumentDiagnosticReportResult::Report(
             DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
                 related_documents: None,
                 full_document_diagnostic_report: FullDocumentDiagnosticReport {
-                    result_id: None,
-                    items: Self::diagnostics_for_file(document, encoding),
+                    result_id: Some(result_id),
+                    items,
                 },
             }),
         ))
@@ -72,7 +97,7 @@ impl GlobalState<'_> {
         mut document: Document,
         encoding: NegotiatedEncoding,
     ) -> Vec<Diagnostic> {
-        document
+        let mut diagnostics: Vec<_> = document
             .diagnostics()
             .iter()
             .map(|issue| Diagnostic {
@@ -93,11 +118,28 @@ impl GlobalState<'_> {
                 tags: None,
                 data: None,
             })
-            .collect()
+            .collect();
+        diagnostics.extend(
+            document
+                .unreachable_ranges()
+                .into_iter()
+                .map(|range| Diagnostic {
+                    range: Self::to_range(encoding, range),
+                    severity: Some(DiagnosticSeverity::HINT),
+                    code: None,
+                    code_description: None,
+                    source: Some("zubanls".to_owned()),
+                    message: "Code is unreachable".to_owned(),
+                    related_information: None,
+                    tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+                    data: None,
+                }),
+        );
+        diagnostics
     }
 
     fn document(&mut self, text_document: TextDocumentIdentifier) -> anyhow::Result<Document<'_>> {
-        let project = self.project();
+        let project = self.project_for_uri(&text_document.uri);
         let path = Self::uri_to_path(project, text_document.uri)?;
         let Some(document) = project.document(&path) else {
             tracing::error!("File {} does not exist", path.as_uri());
@@ -115,19 +157,22 @@ impl GlobalState<'_> {
     ) -> anyhow::Result<Option<CompletionResponse>> {
         let encoding = self.client_capabilities.negotiated_encoding();
         let (document, pos) = self.document_with_pos(params.text_document_position)?;
-        let mut completions = document.complete(pos, false, |replace_range, completion| {
-            CompletionItem {
+        let mut completions =
+            document.complete(pos, false, |replace_range, completion| CompletionItem {
                 label: completion.label().to_string(),
                 kind: Some(completion.kind()),
                 text_edit: Some(CompletionTextEdit::Edit(TextEdit {
                     range: Self::to_range(encoding, replace_range),
                     new_text: completion.insert_text(),
                 })),
-                // TODO
-                // documentation: Some(Documentation::String(completion.documentation().unwrap_or_else())),
+                documentation: completion.documentation().map(|documentation| {
+                    Documentation::MarkupContent(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: documentation.into_owned(),
+                    })
+                }),
                 ..Default::default()
-            }
-        })?;
+            })?;
         if completions.is_empty() {
             return Ok(None);
         }
@@ -158,6 +203,61 @@ impl GlobalState<'_> {
         }))
     }
 
+    pub fn handle_signature_help(
+        &mut self,
+        params: SignatureHelpParams,
+    ) -> anyhow::Result<Option<SignatureHelp>> {
+        let encoding = self.client_capabilities.negotiated_encoding();
+        let label_offsets = self.client_capabilities.signature_help_label_offsets();
+        let (document, pos) = self.document_with_pos(params.text_document_position_params)?;
+        let Some(help) = document.signature_help(pos)? else {
+            return Ok(None);
+        };
+        // Offsets in labels are counted in the negotiated encoding like positions.
+        let to_offset = |label: &str, byte: usize| {
+            let before = &label[..byte];
+            (match encoding {
+                NegotiatedEncoding::UTF8 => before.len(),
+                NegotiatedEncoding::UTF16 => before.encode_utf16().count(),
+                NegotiatedEncoding::UTF32 => before.chars().count(),
+            }) as u32
+        };
+        let to_label = |label: &str, range: &std::ops::Range<usize>| {
+            if label_offsets {
+                ParameterLabel::LabelOffsets([
+                    to_offset(label, range.start),
+                    to_offset(label, range.end),
+                ])
+            } else {
+                ParameterLabel::Simple(label[range.clone()].to_owned())
+            }
+        };
+        let signatures = help
+            .signatures
+            .into_iter()
+            .map(|signature| SignatureInformation {
+                parameters: Some(
+                    signature
+                        .params
+                        .iter()
+                        .map(|range| ParameterInformation {
+                            label: to_label(&signature.label, range),
+                            documentation: None,
+                        })
+                        .collect(),
+                ),
+                active_parameter: signature.active_param.map(|i| i as u32),
+                label: signature.label,
+                documentation: None,
+            })
+            .collect();
+        Ok(Some(SignatureHelp {
+            signatures,
+            active_signature: Some(help.active_signature as u32),
+            active_parameter: None,
+        }))
+    }
+
     pub fn handle_goto_declaration(
         &mut self,
         params: GotoDeclarationParams,
@@ -258,14 +358,18 @@ impl GlobalState<'_> {
         &mut self,
         position: TextDocumentPositionParams,
     ) -> anyhow::Result<(Document<'_>, InputPosition)> {
-        let line = position.position.line as usize;
-        let column = position.position.character as usize;
-        let pos = match self.client_capabilities.negotiated_encoding() {
+        let pos = self.to_input_position(position.position);
+        Ok((self.document(position.text_document)?, pos))
+    }
+
+    fn to_input_position(&self, position: Position) -> InputPosition {
+        let line = position.line as usize;
+        let column = position.character as usize;
+        match self.client_capabilities.negotiated_encoding() {
             NegotiatedEncoding::UTF8 => InputPosition::Utf8Bytes { line, column },
             NegotiatedEncoding::UTF16 => InputPosition::Utf16CodeUnits { line, column },
             NegotiatedEncoding::UTF32 => InputPosition::CodePoints { line, column },
-        };
-        Ok((self.document(position.text_document)?, pos))
+        }
     }
 
     pub fn handle_references(
@@ -312,6 +416,197 @@ impl GlobalState<'_> {
         Ok(Some(result))
     }
 
+    pub fn handle_selection_range(
+        &mut self,
+        params: SelectionRangeParams,
+    ) -> anyhow::Result<Option<Vec<SelectionRange>>> {
+        let encoding = self.client_capabilities.negotiated_encoding();
+        let positions: Vec<_> = params
+            .positions
+            .into_iter()
+            .map(|position| self.to_input_position(position))
+            .collect();
+        let document = self.document(params.text_document)?;
+        let mut result = vec![];
+        for pos in positions {
+            // The ranges are ordered from the innermost to the outermost range, but the LSP
+            // wants the innermost range with links to its parents.
+            let mut selection_range: Option<SelectionRange> = None;
+            for range in document.selection_ranges(pos)?.into_iter().rev() {
+                selection_range = Some(SelectionRange {
+                    range: Self::to_range(encoding, range),
+                    parent: selection_range.map(Box::new),
+                });
+            }
+            let Some(selection_range) = selection_range else {
+                return Ok(None);
+            };
+            result.push(selection_range);
+        }
+        Ok(Some(result))
+    }
+
+    pub fn handle_folding_range(
+        &mut self,
+        params: FoldingRangeParams,
+    ) -> anyhow::Result<Option<Vec<FoldingRange>>> {
+        let document = self.document(params.text_document)?;
+        let result = document
+            .folding_ranges()
+            .into_iter()
+            .filter(|(start, end)| start.line_zero_based() < end.line_zero_based())
+            .map(|(start, end)| FoldingRange {
+                start_line: start.line_zero_based() as u32,
+                start_character: None,
+                end_line: end.line_zero_based() as u32,
+                end_character: None,
+                kind: None,
+                collapsed_text: None,
+            })
+            .collect();
+        Ok(Some(result))
+    }
+
+    pub fn handle_semantic_tokens_full(
+        &mut self,
+        params: SemanticTokensParams,
+    ) -> anyhow::Result<Option<SemanticTokensResult>> {
+        let encoding = self.client_capabilities.negotiated_encoding();
+        let document = self.document(params.text_document)?;
+        let mut data = vec![];
+        let mut previous = Position::new(0, 0);
+        for token in document.semantic_tokens() {
+            let range = Self::to_range(encoding, token.range);
+            let (token_type, token_modifiers_bitset) = match token.kind {
+                SemanticTokenKind::Class => (0, 0),
+                SemanticTokenKind::TypeVar => (1, 0),
+                SemanticTokenKind::TypeVarTuple => (1, 0b01),
+                SemanticTokenKind::ParamSpec => (1, 0b10),
+                SemanticTokenKind::Function => (2, 0),
+                SemanticTokenKind::Parameter => (3, 0),
+            };
+            // Tokens are relative to the previous token. They are sorted by position, but a
+            // broken order should never crash the server.
+            let delta_line = range.start.line.saturating_sub(previous.line);
+            data.push(SemanticToken {
+                delta_line,
+                delta_start: match delta_line {
+                    0 => range.start.character.saturating_sub(previous.character),
+                    _ => range.start.character,
+                },
+                length: range.end.character.saturating_sub(range.start.character),
+                token_type,
+                token_modifiers_bitset,
+            });
+            previous = range.start;
+        }
+        Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: None,
+            data,
+        })))
+    }
+
+    pub fn handle_code_lens(
+        &mut self,
+        params: CodeLensParams,
+    ) -> anyhow::Result<Option<Vec<CodeLens>>> {
+        let encoding = self.client_capabilities.negotiated_encoding();
+        let uri = params.text_document.uri.clone();
+        let document = self.document(params.text_document)?;
+        let check_lens = CodeLens {
+            range: Default::default(),
+            command: Some(Command::new(
+                "Check file".to_owned(),
+                CHECK_FILE_COMMAND.to_owned(),
+                Some(vec![serde_json::json!(uri)]),
+            )),
+            data: None,
+        };
+        // Counting references is expensive, so it only happens when the lens is resolved.
+        let reference_lenses = document
+            .function_and_class_name_ranges()
+            .into_iter()
+            .map(|range| {
+                let range = Self::to_range(encoding, range);
+                CodeLens {
+                    range,
+                    command: None,
+                    data: Some(serde_json::json!([uri, range.start])),
+                }
+            });
+        Ok(Some(
+            std::iter::once(check_lens)
+                .chain(reference_lenses)
+                .collect(),
+        ))
+    }
+
+    pub fn handle_code_lens_resolve(&mut self, mut lens: CodeLens) -> anyhow::Result<CodeLens> {
+        let Some(data) = lens.data.take() else {
+            return Ok(lens);
+        };
+        let (uri, position): (Uri, Position) = serde_json::from_value(data)?;
+        let encoding = self.client_capabilities.negotiated_encoding();
+        let pos = self.to_input_position(position);
+        let document = self.document(TextDocumentIdentifier::new(uri.clone()))?;
+        let locations = document.references(
+            pos,
+            ReferencesGoal::OnlyTypeCheckedWorkspaces,
+            false,
+            |name| {
+                Location::new(
+                    Uri::from_str(&name.file_uri()).expect("Expected a valid URI"),
+                    Self::to_range(encoding, name.name_range()),
+                )
+            },
+        )?;
+        let title = match locations.len() {
+            1 => "1 reference".to_owned(),
+            n => format!("{n} references"),
+        };
+        lens.command = Some(if self.client_capabilities.is_vs_code() {
+            Command::new(
+                title,
+                SHOW_REFERENCES_COMMAND.to_owned(),
+                Some(vec![
+                    serde_json::json!(uri),
+                    serde_json::json!(position),
+                    serde_json::json!(locations),
+                ]),
+            )
+        } else {
+            // Other clients cannot show the references, so the lens only displays the count.
+            Command::new(title, String::new(), None)
+        });
+        Ok(lens)
+    }
+
+    pub fn handle_execute_command(
+        &mut self,
+        params: ExecuteCommandParams,
+    ) -> anyhow::Result<Option<serde_json::Value>> {
+        match params.command.as_str() {
+            CHECK_FILE_COMMAND => {
+                let Some(uri) = params.arguments.into_iter().next() else {
+                    bail!("Expected a document for {CHECK_FILE_COMMAND}");
+                };
+                let uri: Uri = serde_json::from_value(uri)?;
+                // Without a working watcher, changes on disk are only noticed here.
+                let project = self.project_for_uri(&uri);
+                let changed = project.invalidate_paths_changed_on_disk();
+                tracing::info!("Check {}, {changed} files changed on disk", uri.as_str());
+                let path = Self::uri_to_path(project, uri)?;
+                self.resend_diagnostics(path);
+                Ok(None)
+            }
+            command => Err(LspError {
+                code: ErrorCode::InvalidParams as i32,
+                message: format!("Unknown command {command}"),
+            }
+            .into()),
+        }
+    }
+
     pub fn prepare_rename(
         &mut self,
         params: TextDocumentPositionParams,
@@ -376,12 +671,106 @@ impl GlobalState<'_> {
         })
     }
 
+    pub fn handle_code_action(
+        &mut self,
+        params: CodeActionParams,
+    ) -> anyhow::Result<Option<CodeActionResponse>> {
+        // All actions are edits, which are only sent to clients that support code action
+        // literals.
+        if !self.client_capabilities.code_action_literals() {
+            return Ok(None);
+        }
+        let is_requested = |kind: &CodeActionKind| {
+            params.context.only.as_ref().is_none_or(|only| {
+                only.iter().any(|o| {
+                    kind.as_str() == o.as_str()
+                        || kind.as_str().starts_with(&format!("{}.", o.as_str()))
+                })
+            })
+        };
+        let encoding = self.client_capabilities.negotiated_encoding();
+        let uri = params.text_document.uri.clone();
+        let start = self.to_input_position(params.range.start);
+        let end = self.to_input_position(params.range.end);
+        let document = self.document(params.text_document)?;
+        let actions = document
+            .code_actions(start, end)?
+            .into_iter()
+            .filter(|action| is_requested(&action.kind))
+            .map(|action| {
+                let edits = action
+                    .edits
+                    .into_iter()
+                    .map(|edit| TextEdit {
+                        range: Self::to_range(encoding, edit.range),
+                        new_text: edit.new_text,
+                    })
+                    .collect();
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    title: action.title,
+                    kind: Some(action.kind),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some([(uri.clone(), edits)].into_iter().collect()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        Ok(Some(actions))
+    }
+
+    pub(crate) fn handle_statistics(
+        &mut self,
+        params: Option<TextDocumentIdentifier>,
+    ) -> anyhow::Result<serde_json::Value> {
+        // Every project has its own caches, the statistics are those of the document's project.
+        let stats = match params {
+            Some(text_document) => self.project_for_uri(&text_document.uri).statistics(),
+            None => self.project().statistics(),
+        };
+        Ok(serde_json::json!({
+            "estimatedBytes": stats.estimated_bytes(),
+            "cachedTypes": stats.cached_types,
+            "cachedCallables": stats.cached_callables,
+            "cachedClasses": stats.cached_classes,
+            "pointCacheHits": stats.point_cache_hits,
+            "pointCacheMisses": stats.point_cache_misses,
+            "pointCacheHitRate": stats.point_cache_hit_rate(),
+            "modules": stats.modules.iter().map(|module| serde_json::json!({
+                "name": module.name,
+                "estimatedBytes": module.estimated_bytes,
+                "nodes": module.nodes,
+                "calculatedPoints": module.calculated_points,
+                "complexPoints": module.complex_points,
+            })).collect::<Vec<_>>(),
+        }))
+    }
+
     pub(crate) fn handle_shutdown(&mut self, _: ()) -> anyhow::Result<()> {
+        self.log_statistics_if_profiling();
         self.shutdown_requested = true;
         Ok(())
     }
 }
 
+/// Checks a file again, after reloading the files that changed on disk.
+pub(crate) const CHECK_FILE_COMMAND: &str = "zuban.checkFile";
+/// VS Code's built-in command, which is executed by the client (not the server) with the
+/// document, the position and the locations of the references. Only sent to VS Code.
+const SHOW_REFERENCES_COMMAND: &str = "editor.action.showReferences";
+
+/// Reports the memory usage of the loaded modules and cache statistics, helpful to decide which
+/// packages should be followed on machines with little memory. If a document is passed, the
+/// statistics are those of the project the document belongs to.
+pub(crate) enum Statistics {}
+
+impl lsp_types::request::Request for Statistics {
+    type Params = Option<TextDocumentIdentifier>;
+    type Result = serde_json::Value;
+    const METHOD: &'static str = "zuban/statistics";
+}
+
 fn ensure_valid_workspace_edit(
     cap: &ClientCapabilities,
     edit: &WorkspaceEdit,
@@ -429,3 +818,11 @@ fn resource_ops_supported(
 
     Ok(())
 }
+
+fn diagnostics_result_id(items: &[Diagnostic]) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(items)
+        .expect("Diagnostics should always be serializable")
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
@@ -6,12 +6,12 @@ use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::str::FromStr;
-use std::sync::atomic::AtomicI64;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
 use std::sync::{Arc, RwLock};
 
 use anyhow::bail;
 use config::ProjectOptions;
-use crossbeam_channel::{Receiver, Sender, never, select};
+use crossbeam_channel::{Receiver, Select, Sender};
 use fluent_uri::Scheme;
 use lsp_server::{Connection, ExtractError, Message, Request};
 use lsp_types::Uri;
@@ -24,12 +24,15 @@ use zuban_python::{Mode, PanicRecovery, Project};
 use crate::capabilities::{ClientCapabilities, server_capabilities};
 use crate::notification_handlers::TestPanic;
 use crate::panic_hooks;
+use crate::request_handlers::Statistics;
 
 // Since we currently don't do garbage collection, we simply delete the project and reindex,
 // because it's not that expensive after a specific amount of diagnostics.
 const REINDEX_AFTER_N_DIAGNOSTICS: usize = 1000;
 
 pub static GLOBAL_NOTIFY_EVENT_COUNTER: AtomicI64 = AtomicI64::new(0);
+// Set by `zuban server --profile`, logs memory and cache statistics of the project.
+pub static PROFILE: AtomicBool = AtomicBool::new(false);
 
 fn version() -> &'static str {
     env!("CARGO_PKG_VERSION")
@@ -41,6 +44,7 @@ pub fn run_server_with_custom_connection(
     cleanup: impl FnOnce() -> anyhow::Result<()>,
 ) -> anyhow::Result<()> {
     tracing::info!("Server version {} will start", version());
+    zuban_python::record_point_cache_statistics(PROFILE.load(Ordering::Relaxed));
 
     let (initialize_id, initialize_params) = match connection.initialize_start() {
         Ok(it) => it,
@@ -82,21 +86,26 @@ pub fn run_server_with_custom_connection(
     };
     let workspace_roots = match workspace_roots.filter(|workspaces| !workspaces.is_empty()) {
         Some(r) => r,
-        None => {
-            let root_path = match root_uri.as_ref().map(patch_path_prefix) {
-                Some(it) => it?,
-                None => {
-                    let cwd = std::env::current_dir()?;
-                    cwd.into_os_string().into_string().map_err(|e| {
-                        anyhow::anyhow!("Invalid non utf-8 working directory: {e:?}")
-                    })?
+        None => match root_uri.as_ref().map(patch_path_prefix) {
+            Some(root_path) => Rc::new([root_path?]),
+            None => match std::env::current_dir() {
+                Ok(cwd) => Rc::new([cwd
+                    .into_os_string()
+                    .into_string()
+                    .map_err(|e| anyhow::anyhow!("Invalid non utf-8 working directory: {e:?}"))?]),
+                Err(err) => {
+                    // Single file mode, opened files are checked with the default settings, see
+                    // GlobalState::project_at.
+                    tracing::info!(
+                        "No workspace was opened and there is no working directory: {err}"
+                    );
+                    Rc::new([])
                 }
-            };
-            Rc::new([root_path.clone()])
-        }
+            },
+        },
     };
 
-    let client_capabilities = ClientCapabilities::new(capabilities);
+    let client_capabilities = ClientCapabilities::new(capabilities, client_info.as_ref());
     let server_capabilities = server_capabilities(&client_capabilities);
 
     let initialize_result = lsp_types::InitializeResult {
@@ -203,18 +212,86 @@ struct NotificationDispatcher<'a, 'sender> {
 }
 
 pub(crate) struct GlobalState<'sender> {
-    paths_that_invalidate_whole_project: HashSet<PathBuf>,
     sender: &'sender Sender<lsp_server::Message>,
-    roots: Rc<[String]>,
     typeshed_path: Option<Arc<NormalizedPath>>,
     pub client_capabilities: ClientCapabilities,
-    project: Option<Project>,
-    panic_recovery: Option<PanicRecovery>,
+    // The first entry contains the first workspace root, all other entries are roots with their
+    // own config, see ProjectSlot::from_roots.
+    projects: Vec<ProjectSlot>,
     pub sent_diagnostic_count: usize,
     changed_in_memory_files: Arc<RwLock<Vec<PathWithScheme>>>,
+    // Set when the settings were reloaded and clients that pull diagnostics need to be told.
+    needs_diagnostics_refresh: bool,
+    sent_request_count: i32,
     pub shutdown_requested: bool,
 }
 
+/// Workspace roots that are checked together with the settings of the first root.
+struct ProjectSlot {
+    roots: Rc<[String]>,
+    project: Option<Project>,
+    panic_recovery: Option<PanicRecovery>,
+    paths_that_invalidate_whole_project: HashSet<PathBuf>,
+}
+
+impl ProjectSlot {
+    fn new(roots: Rc<[String]>) -> Self {
+        Self {
+            roots,
+            project: None,
+            panic_recovery: None,
+            paths_that_invalidate_whole_project: Default::default(),
+        }
+    }
+
+    /// Additional workspace roots with a config that differs from the one of the first root are
+    /// independent projects (e.g. packages in a monorepo) with their own settings, search paths
+    /// and caches. All other roots are checked together with the first root, so they can import
+    /// each other.
+    fn from_roots(roots: &[String]) -> Vec<Self> {
+        let vfs = LocalFS::without_watcher();
+        let config_of = |root: &String| {
+            config::find_workspace_config(&vfs, &vfs.unchecked_abs_path(root), |_| ()).ok()
+        };
+        let first_config = roots.first().and_then(config_of);
+        let (independent, shared): (Vec<_>, Vec<_>) = roots
+            .iter()
+            .enumerate()
+            .partition(|(i, root)| *i > 0 && config_of(root) != first_config);
+        let shared: Rc<[String]> = shared.into_iter().map(|(_, r)| r.clone()).collect();
+        std::iter::once(Self::new(shared))
+            .chain(
+                independent
+                    .into_iter()
+                    .map(|(_, root)| Self::new(Rc::new([root.clone()]))),
+            )
+            .collect()
+    }
+
+    /// The length of the longest root that contains the path.
+    fn matching_root_len(&self, path: &str) -> Option<usize> {
+        self.roots
+            .iter()
+            .filter(|root| {
+                path.strip_prefix(root.as_str()).is_some_and(|rest| {
+                    rest.is_empty() || rest.starts_with(['/', std::path::MAIN_SEPARATOR])
+                })
+            })
+            .map(|root| root.len())
+            .max()
+    }
+
+    fn notify_receiver(&self) -> Option<&Receiver<NotifyEvent>> {
+        self.project.as_ref()?.vfs_handler().notify_receiver()
+    }
+
+    fn recover_from_panic(&mut self) {
+        if let Some(project) = self.project.take() {
+            self.panic_recovery = Some(project.into_panic_recovery());
+        }
+    }
+}
+
 impl<'sender> GlobalState<'sender> {
     fn new(
         sender: &'sender Sender<lsp_server::Message>,
@@ -222,15 +299,21 @@ impl<'sender> GlobalState<'sender> {
         roots: Rc<[String]>,
         typeshed_path: Option<Arc<NormalizedPath>>,
     ) -> Self {
+        let projects = ProjectSlot::from_roots(&roots);
+        if projects.len() > 1 {
+            tracing::info!(
+                "Using {} independent projects for the workspace roots",
+                projects.len()
+            );
+        }
         GlobalState {
-            paths_that_invalidate_whole_project: Default::default(),
             sender,
-            roots,
             typeshed_path,
             client_capabilities,
-            project: None,
-            panic_recovery: None,
+            projects,
             changed_in_memory_files: Default::default(),
+            needs_diagnostics_refresh: false,
+            sent_request_count: 0,
             sent_diagnostic_count: 0,
             shutdown_requested: false,
         }
@@ -238,40 +321,85 @@ impl<'sender> GlobalState<'sender> {
 
     fn event_loop(&mut self, receiver: &Receiver<Message>) -> anyhow::Result<()> {
         loop {
-            // Make sure the project is basically loaded
-            self.project();
+            // Make sure the projects are basically loaded
+            for index in 0..self.projects.len() {
+                self.project_at(index);
+            }
 
-            select! {
-                recv(receiver) -> msg => {
-                    if self.on_lsp_message_and_return_on_shutdown(msg?) {
+            // Every project has its own watcher, so the amount of channels is only known at
+            // runtime.
+            let notify_receivers = self
+                .projects
+                .iter()
+                .enumerate()
+                .filter_map(|(i, slot)| Some((i, slot.notify_receiver()?)))
+                .collect::<Vec<_>>();
+            let mut select = Select::new();
+            select.recv(receiver);
+            for &(_, notify_receiver) in &notify_receivers {
+                select.recv(notify_receiver);
+            }
+            let operation = select.select();
+            match operation.index() {
+                0 => {
+                    if self.on_lsp_message_and_return_on_shutdown(operation.recv(receiver)?) {
                         return Ok(());
                     }
-                },
-                recv(self.notify_receiver().unwrap_or(&never())) -> msg =>
-                    self.on_notify_events(msg?)
+                }
+                n => {
+                    let (index, notify_receiver) = notify_receivers[n - 1];
+                    let msg = operation.recv(notify_receiver)?;
+                    self.on_notify_events(index, msg)
+                }
             }
             // See comment on REINDEX_AFTER_N_DIAGNOSTICS
             if self.sent_diagnostic_count > REINDEX_AFTER_N_DIAGNOSTICS {
                 self.sent_diagnostic_count = 0;
+                self.log_statistics_if_profiling();
                 tracing::info!("Reindex for performance reasons");
                 self.recover_from_panic();
             }
 
             self.publish_diagnostics_if_necessary();
+            self.refresh_diagnostics_if_necessary();
         }
     }
 
-    fn notify_receiver(&self) -> Option<&Receiver<NotifyEvent>> {
-        self.project.as_ref()?.vfs_handler().notify_receiver()
+    /// The project of the first workspace root
+    pub(crate) fn project(&mut self) -> &mut Project {
+        self.project_at(0)
     }
 
-    pub(crate) fn project(&mut self) -> &mut Project {
-        let project = &mut self.project;
+    /// The project that is responsible for a document. Paths outside of all roots belong to the
+    /// project of the first workspace root.
+    pub(crate) fn project_for_uri(&mut self, uri: &Uri) -> &mut Project {
+        let index = match unpack_uri(uri) {
+            Ok((_, path)) => self.project_index_for_path(&path),
+            Err(_) => 0,
+        };
+        self.project_at(index)
+    }
+
+    fn project_index_for_path(&self, path: &str) -> usize {
+        self.projects
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slot)| Some((i, slot.matching_root_len(path)?)))
+            .max_by_key(|(_, len)| *len)
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    fn project_at(&mut self, index: usize) -> &mut Project {
+        let sender = self.sender;
+        let typeshed_path = self.typeshed_path.clone();
+        let new_changed_files = self.changed_in_memory_files.clone();
+        let should_push = self.client_capabilities.should_push_diagnostics();
+        let slot = &mut self.projects[index];
+        let project = &mut slot.project;
         if let Some(p) = project {
             p
         } else {
-            let new_changed_files = self.changed_in_memory_files.clone();
-            let should_push = self.client_capabilities.should_push_diagnostics();
             let vfs_handler = LocalFS::with_watcher(move |path| {
                 if should_push {
                     let mut changed_files = new_changed_files.as_ref().write().unwrap();
@@ -281,56 +409,61 @@ impl<'sender> GlobalState<'sender> {
                     }
                 }
             });
-            let first_root = self
+            let first_root = slot
                 .roots
                 .first()
-                .expect("There should always be at least one root at this point");
-            let first_root = vfs_handler.unchecked_abs_path(first_root);
-            let mut config = config::find_workspace_config(&vfs_handler, &first_root, |path| {
-                // Watch the file itself to make sure that we can invalidate when it changes.
-                let path = Path::new(&**path);
-                vfs_handler.watch(path);
-                // Since these are config files there should always be a parent
-                let parent_dir = path.parent().unwrap();
-                // This function is executed even when a file is not found. Therefore we watch the
-                // directory as well, if the file suddenly gets inserted.
-                // Don't delete this line of code, it might not be necessary in most cases, because
-                // the base directory is typically already watched, but I'm not sure this will
-                // always be the case.
-                match std::fs::canonicalize(parent_dir) {
-                    Ok(parent_dir) => {
-                        vfs_handler.watch(&parent_dir);
-                        let path = parent_dir.join(path.file_name().expect(
-                            "config files where hand generated and should therefore always exist",
-                        ));
-                        vfs_handler.watch(&path);
-                        self.paths_that_invalidate_whole_project.insert(path);
+                .map(|root| vfs_handler.unchecked_abs_path(root));
+            let paths_that_invalidate_whole_project = &mut slot.paths_that_invalidate_whole_project;
+            let mut config = if let Some(first_root) = &first_root {
+                config::find_workspace_config(&vfs_handler, first_root, |path| {
+                    // Watch the file itself to make sure that we can invalidate when it changes.
+                    let path = Path::new(&**path);
+                    vfs_handler.watch(path);
+                    // Since these are config files there should always be a parent
+                    let parent_dir = path.parent().unwrap();
+                    // This function is executed even when a file is not found. Therefore we watch
+                    // the directory as well, if the file suddenly gets inserted.
+                    // Don't delete this line of code, it might not be necessary in most cases,
+                    // because the base directory is typically already watched, but I'm not sure
+                    // this will always be the case.
+                    match std::fs::canonicalize(parent_dir) {
+                        Ok(parent_dir) => {
+                            vfs_handler.watch(&parent_dir);
+                            let path = parent_dir.join(path.file_name().expect(
+                                "config files where hand generated and should therefore always exist",
+                            ));
+                            vfs_handler.watch(&path);
+                            paths_that_invalidate_whole_project.insert(path);
+                        }
+                        Err(err) => tracing::info!(
+                            "Canonicalizing of path that invalidates the whole project failed: {err}"
+                        ),
                     }
-                    Err(err) => tracing::info!(
-                        "Canonicalizing of path that invalidates the whole project failed: {err}"
-                    ),
-                }
-            })
-            .unwrap_or_else(|err| {
-                use lsp_types::{
-                    MessageType, ShowMessageParams,
-                    notification::{Notification, ShowMessage},
-                };
-                tracing::warn!("Error while loading config: {}", err.to_string());
-                let not = lsp_server::Notification::new(
-                    ShowMessage::METHOD.to_owned(),
-                    ShowMessageParams {
-                        typ: MessageType::WARNING,
-                        message: err.to_string(),
-                    },
-                );
-                self.sender
-                    .send(lsp_server::Message::Notification(not))
-                    .unwrap();
+                })
+                .unwrap_or_else(|err| {
+                    use lsp_types::{
+                        MessageType, ShowMessageParams,
+                        notification::{Notification, ShowMessage},
+                    };
+                    tracing::warn!("Error while loading config: {}", err.to_string());
+                    let not = lsp_server::Notification::new(
+                        ShowMessage::METHOD.to_owned(),
+                        ShowMessageParams {
+                            typ: MessageType::WARNING,
+                            message: err.to_string(),
+                        },
+                    );
+                    sender.send(lsp_server::Message::Notification(not)).unwrap();
+                    ProjectOptions::default()
+                })
+            } else {
+                // The client did not open a workspace (e.g. a single file was opened). There is
+                // therefore no config and opened files are checked with the default settings.
+                tracing::info!("No workspace roots, using the default settings");
                 ProjectOptions::default()
-            });
+            };
 
-            tracing::info!("Using workspace roots {:?}", &self.roots);
+            tracing::info!("Using workspace roots {:?}", &slot.roots);
             // I'm not sure if this is correct. The problem is that the mypy_path currently does
             // two things:
             //
@@ -340,7 +473,7 @@ impl<'sender> GlobalState<'sender> {
             // It's questionable that we want those two things. And maybe there will also be a need
             // for the type checker to understand what the mypy_path originally was.
             if config.settings.mypy_path.is_empty() {
-                config.settings.mypy_path = self
+                config.settings.mypy_path = slot
                     .roots
                     .iter()
                     .map(|p| {
@@ -348,15 +481,17 @@ impl<'sender> GlobalState<'sender> {
                     })
                     .collect();
             }
-            config.settings.typeshed_path = self.typeshed_path.clone();
-            config.settings.try_to_find_environment_if_not_defined(
-                &vfs_handler,
-                &first_root,
-                |n| std::env::var(n),
-            );
+            config.settings.typeshed_path = typeshed_path;
+            if let Some(first_root) = &first_root {
+                config.settings.try_to_find_environment_if_not_defined(
+                    &vfs_handler,
+                    first_root,
+                    |n| std::env::var(n),
+                );
+            }
 
             let vfs = Box::new(vfs_handler);
-            *project = Some(if let Some(recovery) = self.panic_recovery.take() {
+            *project = Some(if let Some(recovery) = slot.panic_recovery.take() {
                 Project::from_recovery(vfs, config, recovery)
             } else {
                 Project::new(vfs, config, Mode::LanguageServer)
@@ -403,14 +538,23 @@ impl<'sender> GlobalState<'sender> {
         .on_sync_mut::<DocumentDiagnosticRequest>(GlobalState::handle_document_diagnostics)
         .on_sync_mut::<Completion>(GlobalState::handle_completion)
         .on_sync_mut::<HoverRequest>(GlobalState::handle_hover)
+        .on_sync_mut::<SignatureHelpRequest>(GlobalState::handle_signature_help)
         .on_sync_mut::<GotoDeclaration>(GlobalState::handle_goto_declaration)
         .on_sync_mut::<GotoDefinition>(GlobalState::handle_goto_definition)
         .on_sync_mut::<GotoTypeDefinition>(GlobalState::handle_goto_type_definition)
         .on_sync_mut::<GotoImplementation>(GlobalState::handle_goto_implementation)
         .on_sync_mut::<References>(GlobalState::handle_references)
         .on_sync_mut::<DocumentHighlightRequest>(GlobalState::handle_document_highlight)
+        .on_sync_mut::<SelectionRangeRequest>(GlobalState::handle_selection_range)
+        .on_sync_mut::<FoldingRangeRequest>(GlobalState::handle_folding_range)
+        .on_sync_mut::<SemanticTokensFullRequest>(GlobalState::handle_semantic_tokens_full)
+        .on_sync_mut::<CodeActionRequest>(GlobalState::handle_code_action)
+        .on_sync_mut::<CodeLensRequest>(GlobalState::handle_code_lens)
+        .on_sync_mut::<CodeLensResolve>(GlobalState::handle_code_lens_resolve)
+        .on_sync_mut::<ExecuteCommand>(GlobalState::handle_execute_command)
         .on_sync_mut::<PrepareRenameRequest>(GlobalState::prepare_rename)
         .on_sync_mut::<Rename>(GlobalState::rename)
+        .on_sync_mut::<Statistics>(GlobalState::handle_statistics)
         .on_sync_mut::<Shutdown>(GlobalState::handle_shutdown)
         .finish();
     }
@@ -455,21 +599,33 @@ impl<'sender> GlobalState<'sender> {
         })
     }
 
+    pub(crate) fn log_statistics_if_profiling(&self) {
+        if PROFILE.load(Ordering::Relaxed) {
+            for project in self
+                .projects
+                .iter()
+                .filter_map(|slot| slot.project.as_ref())
+            {
+                tracing::info!("Project statistics:\n{}", project.statistics());
+            }
+        }
+    }
+
     fn recover_from_panic(&mut self) {
         self.changed_in_memory_files
             .as_ref()
             .write()
             .unwrap()
             .clear();
-        if let Some(project) = self.project.take() {
-            self.panic_recovery = Some(project.into_panic_recovery());
+        for slot in &mut self.projects {
+            slot.recover_from_panic();
         }
     }
 
-    fn on_notify_events(&mut self, event: NotifyEvent) {
-        self.on_notify_event(event);
+    fn on_notify_events(&mut self, index: usize, event: NotifyEvent) {
+        self.on_notify_event(index, event);
         // Check all events in the Notify queue
-        while let Some(next) = self.notify_receiver().and_then(|n| {
+        while let Some(next) = self.projects[index].notify_receiver().and_then(|n| {
             if cfg!(target_os = "windows") {
                 // On Windows some events simply cause multiple events (e.g. rename), but also writes
                 // to files may be a Create + Modify, so we simply wait. This is useful for tests, but
@@ -480,12 +636,13 @@ impl<'sender> GlobalState<'sender> {
                 n.try_recv().ok()
             }
         }) {
-            self.on_notify_event(next);
+            self.on_notify_event(index, next);
         }
     }
 
-    fn on_notify_event(&mut self, event: NotifyEvent) {
-        if let Some(project) = &mut self.project {
+    fn on_notify_event(&mut self, index: usize, event: NotifyEvent) {
+        let slot = &mut self.projects[index];
+        if let Some(project) = &mut slot.project {
             match event {
                 Ok(event) => {
                     match event.kind {
@@ -496,7 +653,7 @@ impl<'sender> GlobalState<'sender> {
 
                             tracing::info!("Notify Event: {event:?}");
                             for path in event.paths.into_iter() {
-                                if self.paths_that_invalidate_whole_project.contains(&path) {
+                                if slot.paths_that_invalidate_whole_project.contains(&path) {
                                     // Since invalidating the whole project is as bad as a panic we
                                     // just use that mechanism to recover from such a worst case
                                     // change. This might be something like changing the used
@@ -510,7 +667,10 @@ impl<'sender> GlobalState<'sender> {
                                     tracing::info!(
                                         "Reindex because a file was changed that invalidates the whole project: {path:?}"
                                     );
-                                    self.recover_from_panic();
+                                    slot.recover_from_panic();
+                                    // Push diagnostics are sent again for all open files by the
+                                    // recovery, pulling clients need to be asked to pull again.
+                                    self.needs_diagnostics_refresh = true;
                                     return;
                                 }
                                 if let Some(p) = path.to_str() {
@@ -535,7 +695,7 @@ impl<'sender> GlobalState<'sender> {
                     tracing::error!(
                         "Invalidating project, because of a notify event error: {err:?}"
                     );
-                    self.project = None;
+                    slot.project = None;
                 }
             }
         }
@@ -551,7 +711,10 @@ impl<'sender> GlobalState<'sender> {
     }
 
     fn complete_request(&mut self, response: lsp_server::Response) {
-        tracing::error!("unhandled request: {:?}", response);
+        // The only requests sent to the client are diagnostic refreshes, which have no result.
+        if let Some(err) = &response.error {
+            tracing::warn!("The client responded with an error: {err:?}");
+        }
     }
 
     fn publish_diagnostics_if_necessary(&mut self) {
@@ -565,8 +728,8 @@ impl<'sender> GlobalState<'sender> {
             );
             for path in files {
                 self.sent_diagnostic_count += 1;
-                let project = self.project();
-                let Some(document) = project.document(&path) else {
+                let index = self.project_index_for_path(path.path());
+                let Some(document) = self.project_at(index).document(&path) else {
                     tracing::info!(
                         "Wanted to publish diagnostics for {}, but it does not exist anymore",
                         path.as_uri()
@@ -610,6 +773,37 @@ impl<'sender> GlobalState<'sender> {
         }
     }
 
+    /// Makes sure that the client receives the diagnostics of the file again, even if nothing
+    /// was invalidated.
+    pub(crate) fn resend_diagnostics(&mut self, path: PathWithScheme) {
+        if self.client_capabilities.should_push_diagnostics() {
+            let mut changed_files = self.changed_in_memory_files.as_ref().write().unwrap();
+            if !changed_files.contains(&path) {
+                changed_files.push(path)
+            }
+        } else {
+            self.needs_diagnostics_refresh = true;
+        }
+    }
+
+    fn refresh_diagnostics_if_necessary(&mut self) {
+        if !std::mem::take(&mut self.needs_diagnostics_refresh)
+            || self.client_capabilities.should_push_diagnostics()
+            || !self.client_capabilities.diagnostics_refresh()
+        {
+            return;
+        }
+        use lsp_types::request::{Request, WorkspaceDiagnosticRefresh};
+        tracing::info!("Ask the client to pull diagnostics again");
+        self.sent_request_count += 1;
+        let request = lsp_server::Request::new(
+            lsp_server::RequestId::from(format!("zubanls/{}", self.sent_request_count)),
+            WorkspaceDiagnosticRefresh::METHOD.to_owned(),
+            (),
+        );
+        _ = self.sender.send(request.into());
+    }
+
     pub(crate) fn uri_to_path(
         project: &Project,
         uri: lsp_types::Uri,
@@ -51,9 +51,17 @@ impl Connection {
         roots: &[&str],
         position_encodings: Option<Vec<lsp_types::PositionEncodingKind>>,
         pull_diagnostics: bool,
+        diagnostics_refresh: bool,
+        client_name: Option<&str>,
     ) -> Self {
         let slf = Self::new();
-        slf.initialize(roots, position_encodings, pull_diagnostics);
+        slf.initialize(
+            roots,
+            position_encodings,
+            pull_diagnostics,
+            diagnostics_refresh,
+            client_name,
+        );
         slf
     }
 
@@ -62,6 +70,8 @@ impl Connection {
         roots: &[&str],
         position_encodings: Option<Vec<lsp_types::PositionEncodingKind>>,
         pull_diagnostics: bool,
+        diagnostics_refresh: bool,
+        client_name: Option<&str>,
     ) -> InitializeResult {
         let capabilities = lsp_types::ClientCapabilities {
             workspace: Some(lsp_types::WorkspaceClientCapabilities {
@@ -79,6 +89,9 @@ impl Connection {
                     ]),
                     ..Default::default()
                 }),
+                diagnostic: Some(lsp_types::DiagnosticWorkspaceClientCapabilities {
+                    refresh_support: Some(diagnostics_refresh),
+                }),
                 ..Default::default()
             }),
             general: Some(lsp_types::GeneralClientCapabilities {
@@ -87,6 +100,18 @@ impl Connection {
             }),
             text_document: Some(TextDocumentClientCapabilities {
                 diagnostic: pull_diagnostics.then(DiagnosticClientCapabilities::default),
+                code_action: Some(lsp_types::CodeActionClientCapabilities {
+                    code_action_literal_support: Some(lsp_types::CodeActionLiteralSupport {
+                        code_action_kind: lsp_types::CodeActionKindLiteralSupport {
+                            value_set: vec![
+                                "quickfix".to_owned(),
+                                "refactor.extract".to_owned(),
+                                "source".to_owned(),
+                            ],
+                        },
+                    }),
+                    ..Default::default()
+                }),
                 ..Default::default()
             }),
             ..Default::default()
@@ -107,6 +132,10 @@ impl Connection {
                     .collect(),
             ),
             capabilities,
+            client_info: client_name.map(|name| lsp_types::ClientInfo {
+                name: name.to_owned(),
+                version: None,
+            }),
             ..Default::default()
         };
         let response = self.request::<lsp_types::request::Initialize>(initialize_params);
@@ -205,6 +234,18 @@ impl Connection {
         }
     }
 
+    pub fn expect_request<R: lsp_types::request::Request>(
+        &self,
+    ) -> (lsp_server::RequestId, R::Params) {
+        match self.recv_timeout() {
+            Ok(Message::Request(req)) => req
+                .extract::<R::Params>(R::METHOD)
+                .unwrap_or_else(|err| panic!("Wanted {}, got {err:?}", R::METHOD)),
+            Ok(msg) => panic!("Unexpected message, expected request: {msg:?}"),
+            Err(err) => panic!("Expected the request {}, but got: {err:?}", R::METHOD),
+        }
+    }
+
     pub(crate) fn expect_notification_message(&self) -> lsp_types::ShowMessageParams {
         self.expect_notification::<lsp_types::notification::ShowMessage>()
     }
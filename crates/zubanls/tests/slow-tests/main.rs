@@ -11,15 +11,19 @@ use std::str::FromStr;
 
 use lsp_server::Response;
 use lsp_types::{
-    CompletionItemKind, CompletionParams, DiagnosticServerCapabilities, DocumentDiagnosticParams,
-    DocumentDiagnosticReport, DocumentDiagnosticReportResult, DocumentHighlightKind,
-    DocumentHighlightParams, GotoDefinitionParams, HoverParams, NumberOrString,
+    CodeActionContext, CodeActionKind, CodeActionParams, CodeLens, CodeLensParams,
+    CompletionItemKind, CompletionParams, DiagnosticServerCapabilities, DiagnosticSeverity,
+    DiagnosticTag, DocumentDiagnosticParams, DocumentDiagnosticReport,
+    DocumentDiagnosticReportResult, DocumentHighlightKind, DocumentHighlightParams,
+    ExecuteCommandParams, FoldingRangeParams, GotoDefinitionParams, HoverParams, NumberOrString,
     PartialResultParams, Position, PositionEncodingKind, ReferenceContext, ReferenceParams,
-    RenameParams, TextDocumentIdentifier, TextDocumentPositionParams, Uri, WorkDoneProgressParams,
+    RenameParams, SelectionRangeParams, SemanticTokensParams, SignatureHelpParams,
+    TextDocumentIdentifier, TextDocumentPositionParams, Uri, WorkDoneProgressParams,
     request::{
-        Completion, DocumentDiagnosticRequest, DocumentHighlightRequest, GotoDeclaration,
+        CodeActionRequest, CodeLensRequest, CodeLensResolve, Completion, DocumentDiagnosticRequest,
+        DocumentHighlightRequest, ExecuteCommand, FoldingRangeRequest, GotoDeclaration,
         GotoDefinition, GotoImplementation, GotoTypeDefinition, HoverRequest, PrepareRenameRequest,
-        References, Rename,
+        References, Rename, SelectionRangeRequest, SemanticTokensFullRequest, SignatureHelpRequest,
     },
 };
 
@@ -40,7 +44,7 @@ use support::Project;
 #[parallel]
 fn basic_server_setup() {
     let con = Connection::new();
-    let response = con.initialize(&["/foo/bar"], None, true);
+    let response = con.initialize(&["/foo/bar"], None, true, false, None);
 
     // Check diagnostic capabilities
     {
@@ -67,7 +71,7 @@ fn basic_server_setup() {
 #[test]
 #[parallel]
 fn request_after_shutdown_is_invalid() {
-    let con = Connection::initialized(&["/foo/bar"], None, true);
+    let con = Connection::initialized(&["/foo/bar"], None, true, false, None);
     con.request::<lsp_types::request::Shutdown>(());
 
     let expect_shutdown_already_requested = |response: Response| {
@@ -99,10 +103,29 @@ fn request_after_shutdown_is_invalid() {
 #[test]
 #[parallel]
 fn exit_without_shutdown() {
-    let con = Connection::initialized(&["/foo/bar"], None, true);
+    let con = Connection::initialized(&["/foo/bar"], None, true, false, None);
     con.notify::<lsp_types::notification::Exit>(());
 }
 
+enum Statistics {}
+
+impl lsp_types::request::Request for Statistics {
+    type Params = Option<TextDocumentIdentifier>;
+    type Result = serde_json::Value;
+    const METHOD: &'static str = "zuban/statistics";
+}
+
+#[test]
+#[parallel]
+fn statistics() {
+    let con = Connection::initialized(&["/foo/bar"], None, true, false, None);
+    let stats = con.request::<Statistics>(None);
+    assert!(stats["estimatedBytes"].as_u64().unwrap() > 0);
+    let modules = stats["modules"].as_array().unwrap();
+    assert!(modules.iter().any(|module| module["name"] == "builtins"));
+    con.shutdown_and_exit()
+}
+
 #[test]
 #[serial]
 fn diagnostics_for_saved_files() {
@@ -167,7 +190,8 @@ fn diagnostics_for_saved_files() {
                   "source": "zubanls"
                 },
             ],
-            "kind": "full"
+            "kind": "full",
+            "resultId": "[..]"
         }),
     );
 
@@ -285,6 +309,64 @@ fn in_memory_file_changes() {
     assert_eq!(error.code, lsp_server::ErrorCode::InvalidParams as i32);
 }
 
+#[test]
+#[parallel]
+fn diagnostics_with_previous_result_id() {
+    let server = Project::with_fixture(
+        r#"
+        [file pyproject.toml]
+
+        [file foo.py]
+        1()
+        "#,
+    )
+    .into_server();
+
+    let request = |previous_result_id| {
+        server.request::<DocumentDiagnosticRequest>(DocumentDiagnosticParams {
+            text_document: server.doc_id("foo.py"),
+            identifier: None,
+            previous_result_id,
+            partial_result_params: PartialResultParams::default(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        })
+    };
+    let full_report = |res: DocumentDiagnosticReportResult| {
+        let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(report)) = res
+        else {
+            unreachable!()
+        };
+        report.full_document_diagnostic_report
+    };
+
+    let first = full_report(request(None));
+    assert_eq!(first.items.len(), 1);
+    let result_id = first.result_id.expect("Expected a result id");
+
+    // Nothing changed, the client can keep its diagnostics
+    let DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Unchanged(unchanged)) =
+        request(Some(result_id.clone()))
+    else {
+        unreachable!()
+    };
+    assert_eq!(
+        unchanged.unchanged_document_diagnostic_report.result_id,
+        result_id
+    );
+
+    // Changes that do not change the diagnostics also do not need to be sent again
+    server.open_in_memory_file("foo.py", "1()\n\n");
+    assert!(matches!(
+        request(Some(result_id.clone())),
+        DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Unchanged(_))
+    ));
+
+    server.change_in_memory_file("foo.py", "1.0()\n");
+    let changed = full_report(request(Some(result_id.clone())));
+    assert_eq!(changed.items[0].message, "\"float\" not callable");
+    assert_ne!(changed.result_id, Some(result_id));
+}
+
 #[test]
 #[serial]
 fn change_config_file() {
@@ -335,6 +417,30 @@ fn change_config_file() {
     assert_eq!(req("in_mem.py"), vec![NOT_CALLABLE]);
 }
 
+#[test]
+#[serial]
+fn change_config_file_refreshes_pulled_diagnostics() {
+    let server = Project::with_fixture(
+        r#"
+        [file mypy.ini]
+
+        [file foo.py]
+        def foo(x: int): ...
+        "#,
+    )
+    .with_diagnostics_refresh()
+    .into_server();
+
+    assert!(server.diagnostics_for_file("foo.py").is_empty());
+
+    server.write_file_and_wait("mypy.ini", "[mypy]\nstrict = True");
+    server.expect_diagnostics_refresh();
+    assert_eq!(
+        server.diagnostics_for_file("foo.py"),
+        ["Function is missing a return type annotation"]
+    );
+}
+
 #[test]
 #[serial]
 fn check_rename_without_symlinks() {
@@ -445,6 +551,96 @@ fn multi_roots() {
     assert_eq!(d(), vec![UNDEF.to_string()]);
 }
 
+#[test]
+#[parallel]
+fn multi_roots_with_own_config() {
+    let server = Project::with_fixture(
+        r#"
+        [file app/check.py]
+        import lib_mod
+        def f(x: int): ...
+
+        [file lib/mypy.ini]
+        [mypy]
+        strict = True
+
+        [file lib/lib_mod.py]
+        import check
+        def g(x: int): ...
+        "#,
+    )
+    .root("app")
+    .root("lib")
+    .into_server();
+
+    // Roots with their own config are independent projects, so they neither share their settings
+    // nor their search paths.
+    assert_eq!(
+        server.diagnostics_for_file("app/check.py"),
+        ["Cannot find implementation or library stub for module named \"lib_mod\""]
+    );
+    assert_eq!(
+        server.diagnostics_for_file("lib/lib_mod.py"),
+        [
+            "Cannot find implementation or library stub for module named \"check\"",
+            "Function is missing a return type annotation",
+        ]
+    );
+
+    // Every project has its own caches.
+    let module_names = |rel_path| {
+        let stats = server.request::<Statistics>(Some(server.doc_id(rel_path)));
+        stats["modules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|module| module["name"].as_str().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+    let app_modules = module_names("app/check.py");
+    assert!(app_modules.contains(&"check".to_owned()));
+    assert!(!app_modules.contains(&"lib_mod".to_owned()));
+    let lib_modules = module_names("lib/lib_mod.py");
+    assert!(lib_modules.contains(&"lib_mod".to_owned()));
+    assert!(!lib_modules.contains(&"check".to_owned()));
+}
+
+#[test]
+#[parallel]
+fn multi_roots_with_the_same_config() {
+    let server = Project::with_fixture(
+        r#"
+        [file app/mypy.ini]
+        [mypy]
+        strict = True
+
+        [file app/check.py]
+        import lib_mod
+        def f(x: int): ...
+
+        [file lib/mypy.ini]
+        [mypy]
+        strict = True
+
+        [file lib/lib_mod.py]
+        import check
+        "#,
+    )
+    .root("app")
+    .root("lib")
+    .into_server();
+
+    // Roots with the same config are checked together and can import each other.
+    assert_eq!(
+        server.diagnostics_for_file("app/check.py"),
+        ["Function is missing a return type annotation"]
+    );
+    assert_eq!(
+        server.diagnostics_for_file("lib/lib_mod.py"),
+        Vec::<String>::new()
+    );
+}
+
 #[test]
 #[serial]
 fn files_outside_of_root() {
@@ -538,6 +734,28 @@ fn files_outside_of_root() {
     }
 }
 
+#[test]
+#[parallel]
+fn single_file_mode_without_workspace() {
+    let server = Project::with_fixture(
+        r#"
+        [file mypy.ini]
+        [mypy]
+        strict = True
+        "#,
+    )
+    .without_workspace()
+    .into_server();
+
+    // Without a workspace the working directory is the root. Files outside of it are checked with
+    // its settings and not with the config of their directory.
+    server.open_in_memory_file("foo.py", "import os\ndef f(x): ...\nos.getcwd()()");
+    assert_eq!(
+        server.diagnostics_for_file("foo.py"),
+        [r#""str" not callable"#]
+    );
+}
+
 #[test]
 #[serial]
 fn files_outside_of_root_with_push_diagnostics() {
@@ -714,6 +932,44 @@ fn diagnostics_positions() {
     }
 }
 
+#[test]
+#[parallel]
+fn unreachable_code_diagnostics() {
+    let server = Project::with_fixture(
+        r#"
+        [file m.py]
+        import sys
+
+        def f(x: int) -> None:
+            if not isinstance(x, int):
+                print(x)
+                x = 1
+            return
+
+        if sys.version_info < (3, 0):
+            import foo
+        else:
+            pass
+        "#,
+    )
+    .into_server();
+
+    let diagnostics = server.full_diagnostics_for_file("m.py");
+    let unreachable: Vec<_> = diagnostics
+        .iter()
+        .map(|d| {
+            assert_eq!(d.message, "Code is unreachable");
+            assert_eq!(d.severity, Some(DiagnosticSeverity::HINT));
+            assert_eq!(d.tags, Some(vec![DiagnosticTag::UNNECESSARY]));
+            (
+                (d.range.start.line, d.range.start.character),
+                (d.range.end.line, d.range.end.character),
+            )
+        })
+        .collect();
+    assert_eq!(unreachable, vec![((4, 8), (5, 13)), ((9, 4), (9, 14))]);
+}
+
 #[test]
 #[serial]
 fn check_panic_recovery() {
@@ -1763,6 +2019,10 @@ fn check_completions() {
           {
             "kind": CompletionItemKind::CLASS,
             "label": "MyClass",
+            "documentation": {
+              "kind": "markdown",
+              "value": "doc 🫶 love",
+            },
             "sortText": "00000",
             "textEdit": {
               "newText": "MyClass",
@@ -1925,3 +2185,800 @@ fn check_completions() {
         ]),
     );
 }
+
+#[test]
+#[parallel]
+fn check_selection_and_folding_ranges() {
+    let server = Project::with_fixture("").into_server();
+
+    let path = "n.py";
+    server.open_in_memory_file(
+        path,
+        "class C:\n    def f(self, x):\n        if x:\n            return foo(\n                x,\n            )\n        return 1\n",
+    );
+
+    let range = |start: (u32, u32), end: (u32, u32)| {
+        json!({
+            "start": {"line": start.0, "character": start.1},
+            "end": {"line": end.0, "character": end.1},
+        })
+    };
+    // From the outermost to the innermost range
+    let mut expected: Option<serde_json::Value> = None;
+    for (start, end) in [
+        ((0, 0), (7, 0)),
+        ((0, 0), (6, 16)),
+        ((1, 4), (6, 16)),
+        ((2, 8), (6, 16)),
+        ((2, 8), (5, 13)),
+        ((3, 12), (5, 13)),
+        ((3, 19), (5, 13)),
+        ((3, 19), (3, 22)),
+    ] {
+        expected = Some(match expected {
+            Some(parent) => json!({"range": range(start, end), "parent": parent}),
+            None => json!({"range": range(start, end)}),
+        });
+    }
+    server.request_and_expect_json::<SelectionRangeRequest>(
+        SelectionRangeParams {
+            text_document: server.doc_id(path),
+            positions: vec![Position::new(3, 20)],
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        json!([expected.unwrap()]),
+    );
+
+    server.request_and_expect_json::<FoldingRangeRequest>(
+        FoldingRangeParams {
+            text_document: server.doc_id(path),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        json!([
+            {"startLine": 0, "endLine": 6},
+            {"startLine": 1, "endLine": 6},
+            {"startLine": 2, "endLine": 5},
+            {"startLine": 3, "endLine": 5},
+        ]),
+    );
+}
+
+#[test]
+#[parallel]
+fn check_semantic_tokens() {
+    let server = Project::with_fixture("").into_server();
+
+    let path = "n.py";
+    server.open_in_memory_file(
+        path,
+        "from typing import ParamSpec, TypeVar\nT = TypeVar(\"T\")\nP = ParamSpec(\"P\")\nclass C:\n    def f(self, x: T) -> T:\n        return x\n",
+    );
+
+    // Every token is (delta_line, delta_start, length, token_type, token_modifiers)
+    server.request_and_expect_json::<SemanticTokensFullRequest>(
+        SemanticTokensParams {
+            text_document: server.doc_id(path),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        json!({
+            "data": [
+                0, 19, 9, 0, 0,
+                0, 11, 7, 0, 0,
+                1, 0, 1, 1, 0,
+                0, 4, 7, 0, 0,
+                1, 0, 1, 1, 2,
+                0, 4, 9, 0, 0,
+                1, 6, 1, 0, 0,
+                1, 8, 1, 2, 0,
+                0, 2, 4, 3, 0,
+                0, 6, 1, 3, 0,
+                0, 3, 1, 1, 0,
+                0, 6, 1, 1, 0,
+                1, 15, 1, 3, 0,
+            ]
+        }),
+    );
+}
+
+#[test]
+#[parallel]
+fn check_hover_documentation() {
+    let server = Project::with_fixture(
+        r#"
+        [file m.py]
+        from typing import Annotated
+        from typing_extensions import Doc
+
+        CONSTANT = 1
+        """The constant"""
+
+        annotated: Annotated[int, Doc("The annotated")] = 1
+
+        [file m.pyi]
+        CONSTANT: int
+        "#,
+    )
+    .into_server();
+
+    let path = "n.py";
+    server.open_in_memory_file(
+        path,
+        "from m import CONSTANT, annotated\nCONSTANT\nannotated",
+    );
+
+    for (line, name, value) in [
+        (1, "CONSTANT", "(variable) CONSTANT: int\n---\nThe constant"),
+        (
+            2,
+            "annotated",
+            "(variable) annotated: int\n---\nThe annotated",
+        ),
+    ] {
+        server.request_and_expect_json::<HoverRequest>(
+            HoverParams {
+                text_document_position_params: TextDocumentPositionParams::new(
+                    server.doc_id(path),
+                    Position::new(line, 0),
+                ),
+                work_done_progress_params: Default::default(),
+            },
+            json!({
+                "contents": {
+                    "kind": "markdown",
+                    "value": value,
+                },
+                "range": {
+                    "start": {"line": line, "character": 0},
+                    "end": {"line": line, "character": name.len()},
+                }
+            }),
+        );
+    }
+}
+
+#[test]
+#[parallel]
+fn check_signature_help() {
+    let server = Project::with_fixture(
+        r#"
+        [file m.py]
+        from typing import overload
+
+        @overload
+        def f(x: int) -> int: ...
+        @overload
+        def f(x: str, *, y: int = 1) -> str: ...
+        def f(x, *, y=1): ...
+        "#,
+    )
+    .into_server();
+
+    let path = "n.py";
+    server.open_in_memory_file(path, "from m import f\nf('', y=2)\n");
+
+    let first = json!({"label": "f(x: int) -> int", "parameters": [{"label": "x: int"}]});
+    let second = json!({
+        "label": "f(x: str, *, y: int = ...) -> str",
+        "parameters": [{"label": "x: str"}, {"label": "y: int = ..."}],
+    });
+    let with_active = |signature: &serde_json::Value, active_parameter: u32| {
+        let mut signature = signature.clone();
+        signature["activeParameter"] = json!(active_parameter);
+        signature
+    };
+    for (character, signatures, active_signature) in [
+        (2, [with_active(&first, 0), with_active(&second, 0)], 0),
+        // The keyword argument is only accepted by the second overload.
+        (6, [first, with_active(&second, 1)], 1),
+    ] {
+        server.request_and_expect_json::<SignatureHelpRequest>(
+            SignatureHelpParams {
+                context: None,
+                text_document_position_params: TextDocumentPositionParams::new(
+                    server.doc_id(path),
+                    Position::new(1, character),
+                ),
+                work_done_progress_params: Default::default(),
+            },
+            json!({
+                "signatures": signatures,
+                "activeSignature": active_signature,
+            }),
+        );
+    }
+}
+
+#[test]
+#[parallel]
+fn check_code_lenses() {
+    let server = Project::with_fixture("")
+        .with_client_name("Visual Studio Code")
+        .into_server();
+
+    let path = "n.py";
+    server.open_in_memory_file(
+        path,
+        "def f(): ...\n\nclass C:\n    def method(self): ...\n\nf()\nf()\nC().method()\n",
+    );
+
+    let range = |line: u32, start: u32, end: u32| {
+        json!({
+            "start": {"line": line, "character": start},
+            "end": {"line": line, "character": end},
+        })
+    };
+    server.request_and_expect_json::<CodeLensRequest>(
+        CodeLensParams {
+            text_document: server.doc_id(path),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        json!([
+            {
+                "range": range(0, 0, 0),
+                "command": {
+                    "title": "Check file",
+                    "command": "zuban.checkFile",
+                    "arguments": ["{...}"],
+                },
+            },
+            {"range": range(0, 4, 5), "data": ["{...}", {"line": 0, "character": 4}]},
+            {"range": range(2, 6, 7), "data": ["{...}", {"line": 2, "character": 6}]},
+            {"range": range(3, 8, 14), "data": ["{...}", {"line": 3, "character": 8}]},
+        ]),
+    );
+
+    let uri = server.doc_id(path).uri;
+    let resolve = |line: u32, character: u32| {
+        serde_json::from_value::<CodeLens>(json!({
+            "range": range(line, character, character),
+            "data": [uri, {"line": line, "character": character}],
+        }))
+        .unwrap()
+    };
+    server.request_and_expect_json::<CodeLensResolve>(
+        resolve(0, 4),
+        json!({
+            "range": range(0, 4, 4),
+            "command": {
+                "title": "2 references",
+                "command": "editor.action.showReferences",
+                "arguments": [
+                    "{...}",
+                    {"line": 0, "character": 4},
+                    [
+                        {"uri": "{...}", "range": range(5, 0, 1)},
+                        {"uri": "{...}", "range": range(6, 0, 1)},
+                    ],
+                ],
+            },
+        }),
+    );
+    server.request_and_expect_json::<CodeLensResolve>(
+        resolve(3, 8),
+        json!({
+            "range": range(3, 8, 8),
+            "command": {
+                "title": "1 reference",
+                "command": "editor.action.showReferences",
+                "arguments": [
+                    "{...}",
+                    {"line": 3, "character": 8},
+                    [{"uri": "{...}", "range": range(7, 4, 10)}],
+                ],
+            },
+        }),
+    );
+
+    // Checking the file again simply sends the diagnostics again.
+    server.request_and_expect_json::<ExecuteCommand>(
+        ExecuteCommandParams {
+            command: "zuban.checkFile".to_owned(),
+            arguments: vec![json!(uri)],
+            work_done_progress_params: Default::default(),
+        },
+        json!(null),
+    );
+}
+
+#[test]
+#[parallel]
+fn check_code_lenses_of_other_clients() {
+    let server = Project::with_fixture("")
+        .with_client_name("Neovim")
+        .into_server();
+
+    let path = "n.py";
+    server.open_in_memory_file(path, "def f(): ...\n\nf()\n");
+    let range = json!({
+        "start": {"line": 0, "character": 4},
+        "end": {"line": 0, "character": 4},
+    });
+    let uri = server.doc_id(path).uri;
+    // The references cannot be shown by the server, so only the title is sent.
+    server.request_and_expect_json::<CodeLensResolve>(
+        serde_json::from_value::<CodeLens>(json!({
+            "range": range,
+            "data": [uri, {"line": 0, "character": 4}],
+        }))
+        .unwrap(),
+        json!({
+            "range": range,
+            "command": {"title": "1 reference", "command": ""},
+        }),
+    );
+}
+
+#[test]
+#[serial]
+fn check_extract_type_alias() {
+    let range = |start: (u32, u32), end: (u32, u32)| {
+        json!({
+            "start": {"line": start.0, "character": start.1},
+            "end": {"line": end.0, "character": end.1},
+        })
+    };
+    let code_action_params = |server: &support::Server, path, start, end| CodeActionParams {
+        text_document: server.doc_id(path),
+        range: serde_json::from_value(range(start, end)).unwrap(),
+        context: CodeActionContext {
+            only: Some(vec![CodeActionKind::REFACTOR]),
+            ..Default::default()
+        },
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    };
+
+    let server = Project::with_fixture("").into_server();
+    let path = "m.py";
+    server.open_in_memory_file(
+        path,
+        "def f(x: int | str | None) -> int | str | None: ...\n\ndef g[T](x: list[T]) -> T: ...\n",
+    );
+    let uri = server.doc_id(path).uri;
+    // Without a selection the whole annotation is extracted and used everywhere.
+    server.request_and_expect_json::<CodeActionRequest>(
+        code_action_params(&server, path, (0, 12), (0, 12)),
+        json!([{
+            "title": "Extract type alias",
+            "kind": "refactor.extract",
+            "edit": {"changes": {uri.as_str(): [
+                {"range": range((0, 0), (0, 0)), "newText": "type Alias = int | str | None\n\n\n"},
+                {"range": range((0, 9), (0, 25)), "newText": "Alias"},
+                {"range": range((0, 30), (0, 46)), "newText": "Alias"},
+            ]}},
+        }]),
+    );
+    // Simple names and annotations with type variables are not extracted.
+    server.request_and_expect_json::<CodeActionRequest>(
+        code_action_params(&server, path, (0, 9), (0, 12)),
+        json!([]),
+    );
+    server.request_and_expect_json::<CodeActionRequest>(
+        code_action_params(&server, path, (2, 12), (2, 19)),
+        json!([]),
+    );
+    drop(server);
+
+    let server = Project::with_fixture(
+        r#"
+        [file mypy.ini]
+        [mypy]
+        python_version = 3.11
+        "#,
+    )
+    .into_server();
+    server.open_in_memory_file(
+        path,
+        "from typing import Callable\n\ndef f(x: Callable[[int], str] | None) -> None: ...\n",
+    );
+    let uri = server.doc_id(path).uri;
+    // Before Python 3.12 there is no type statement.
+    server.request_and_expect_json::<CodeActionRequest>(
+        code_action_params(&server, path, (2, 9), (2, 29)),
+        json!([{
+            "title": "Extract type alias",
+            "kind": "refactor.extract",
+            "edit": {"changes": {uri.as_str(): [
+                {"range": range((1, 0), (1, 0)), "newText": "from typing import TypeAlias\n"},
+                {
+                    "range": range((2, 0), (2, 0)),
+                    "newText": "Alias: TypeAlias = Callable[[int], str]\n\n\n",
+                },
+                {"range": range((2, 9), (2, 29)), "newText": "Alias"},
+            ]}},
+        }]),
+    );
+}
+
+#[test]
+#[serial]
+fn check_move_imports_to_type_checking_block() {
+    let server = Project::with_fixture("").into_server();
+    let range = |start: (u32, u32), end: (u32, u32)| {
+        json!({
+            "start": {"line": start.0, "character": start.1},
+            "end": {"line": end.0, "character": end.1},
+        })
+    };
+    let request = |path: &str, expected_edits| {
+        let uri = server.doc_id(path).uri;
+        server.request_and_expect_json::<CodeActionRequest>(
+            CodeActionParams {
+                text_document: server.doc_id(path),
+                range: serde_json::from_value(range((0, 0), (0, 0))).unwrap(),
+                context: CodeActionContext::default(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+            json!([{
+                "title": "Move imports only used in annotations into a TYPE_CHECKING block",
+                "kind": "source",
+                "edit": {"changes": {uri.as_str(): expected_edits}},
+            }]),
+        );
+    };
+
+    // Parameter annotations are evaluated at runtime without the future import.
+    server.open_in_memory_file(
+        "m.py",
+        "from os import PathLike, getcwd\n\
+         from collections.abc import Sequence\n\
+         import json\n\
+         \n\
+         def f(x: PathLike[str]) -> Sequence[int]:\n    return json.loads(getcwd())\n",
+    );
+    request(
+        "m.py",
+        json!([
+            {"range": range((0, 0), (0, 0)), "newText": "from __future__ import annotations\n"},
+            {"range": range((0, 0), (1, 0)), "newText": "from os import getcwd\n"},
+            {"range": range((1, 0), (2, 0)), "newText": ""},
+            {
+                "range": range((3, 0), (3, 0)),
+                "newText": "from typing import TYPE_CHECKING\n\nif TYPE_CHECKING:\n    \
+                            from os import PathLike\n    \
+                            from collections.abc import Sequence\n",
+            },
+        ]),
+    );
+
+    // An existing block is reused and annotations of variables in functions are never
+    // evaluated.
+    server.open_in_memory_file(
+        "n.py",
+        "from typing import TYPE_CHECKING\n\
+         from decimal import Decimal\n\
+         \n\
+         if TYPE_CHECKING:\n    pass\n\
+         \n\
+         def f() -> None:\n    x: Decimal\n",
+    );
+    request(
+        "n.py",
+        json!([
+            {"range": range((1, 0), (2, 0)), "newText": ""},
+            {"range": range((5, 0), (5, 0)), "newText": "    from decimal import Decimal\n"},
+        ]),
+    );
+
+    // Annotations in class bodies and of singledispatch functions are inspected at runtime.
+    server.open_in_memory_file(
+        "o.py",
+        "from dataclasses import dataclass\n\
+         from decimal import Decimal\n\
+         from functools import singledispatch\n\
+         from pathlib import Path\n\
+         from collections.abc import Sequence\n\
+         \n\
+         @dataclass\nclass C:\n    x: Decimal\n\
+         \n\
+         @singledispatch\ndef f(x: object) -> None: ...\n\
+         \n\
+         @f.register\ndef _(x: Path) -> None: ...\n\
+         \n\
+         def g(x: Sequence[int]) -> None: ...\n",
+    );
+    request(
+        "o.py",
+        json!([
+            {"range": range((0, 0), (0, 0)), "newText": "from __future__ import annotations\n"},
+            {"range": range((4, 0), (5, 0)), "newText": ""},
+            {
+                "range": range((5, 0), (5, 0)),
+                "newText": "from typing import TYPE_CHECKING\n\nif TYPE_CHECKING:\n    \
+                            from collections.abc import Sequence\n",
+            },
+        ]),
+    );
+
+    // All annotations might be resolved with get_type_hints.
+    server.open_in_memory_file(
+        "p.py",
+        "from typing import get_type_hints\n\
+         from collections.abc import Sequence\n\
+         \n\
+         def g(x: Sequence[int]) -> None: ...\n\
+         \n\
+         get_type_hints(g)\n",
+    );
+    server.request_and_expect_json::<CodeActionRequest>(
+        CodeActionParams {
+            text_document: server.doc_id("p.py"),
+            range: serde_json::from_value(range((0, 0), (0, 0))).unwrap(),
+            context: CodeActionContext::default(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+        json!([]),
+    );
+}
+
+#[test]
+#[serial]
+fn check_fix_spelling() {
+    let server = Project::with_fixture(
+        r#"
+        [file m.py]
+        def compute() -> None: ...
+        def compose() -> None: ...
+        "#,
+    )
+    .into_server();
+    let range = |start: (u32, u32), end: (u32, u32)| {
+        json!({
+            "start": {"line": start.0, "character": start.1},
+            "end": {"line": end.0, "character": end.1},
+        })
+    };
+    let path = "n.py";
+    server.open_in_memory_file(path, "from m import comptue\nprnt(1)\n");
+    let uri = server.doc_id(path).uri;
+    let request = |position: (u32, u32), expected| {
+        server.request_and_expect_json::<CodeActionRequest>(
+            CodeActionParams {
+                text_document: server.doc_id(path),
+                range: serde_json::from_value(range(position, position)).unwrap(),
+                context: CodeActionContext {
+                    only: Some(vec![CodeActionKind::QUICKFIX]),
+                    ..Default::default()
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+            expected,
+        );
+    };
+    let fix = |title: &str, edit_range, new_text: &str| {
+        json!({
+            "title": title,
+            "kind": "quickfix",
+            "edit": {"changes": {uri.as_str(): [{"range": edit_range, "newText": new_text}]}},
+        })
+    };
+
+    // The closest names are suggested first.
+    request(
+        (0, 16),
+        json!([
+            fix(r#"Change to "compute""#, range((0, 14), (0, 21)), "compute"),
+            fix(r#"Change to "compose""#, range((0, 14), (0, 21)), "compose"),
+            fix(
+                r#"Ignore "attr-defined" errors on this line"#,
+                range((0, 21), (0, 21)),
+                "  # type: ignore[attr-defined]",
+            ),
+        ]),
+    );
+    request(
+        (1, 2),
+        json!([
+            fix(r#"Change to "print""#, range((1, 0), (1, 4)), "print"),
+            fix(
+                r#"Ignore "name-defined" errors on this line"#,
+                range((1, 7), (1, 7)),
+                "  # type: ignore[name-defined]",
+            ),
+        ]),
+    );
+    request((1, 6), json!([]));
+}
+
+#[test]
+#[serial]
+fn check_quick_fixes() {
+    let server = Project::with_fixture("").into_server();
+    let range = |start: (u32, u32), end: (u32, u32)| {
+        json!({
+            "start": {"line": start.0, "character": start.1},
+            "end": {"line": end.0, "character": end.1},
+        })
+    };
+    let path = "n.py";
+    server.open_in_memory_file(
+        path,
+        "def f(x: int = None, *, y: str) -> None: ...\n\
+         f(1)\n\
+         l: List[int] = []  # type: ignore[misc]\n\
+         z = qqqq + \\\n\
+         1  # noqa\n",
+    );
+    let uri = server.doc_id(path).uri;
+    let request = |position: (u32, u32), expected| {
+        server.request_and_expect_json::<CodeActionRequest>(
+            CodeActionParams {
+                text_document: server.doc_id(path),
+                range: serde_json::from_value(range(position, position)).unwrap(),
+                context: CodeActionContext {
+                    only: Some(vec![CodeActionKind::QUICKFIX]),
+                    ..Default::default()
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+            expected,
+        );
+    };
+    let fix = |title: &str, edit_range, new_text: &str| {
+        json!({
+            "title": title,
+            "kind": "quickfix",
+            "edit": {"changes": {uri.as_str(): [{"range": edit_range, "newText": new_text}]}},
+        })
+    };
+
+    request(
+        (0, 16),
+        json!([
+            fix(r#"Make "x" optional"#, range((0, 9), (0, 12)), "int | None"),
+            fix(
+                r#"Ignore "assignment" errors on this line"#,
+                range((0, 44), (0, 44)),
+                "  # type: ignore[assignment]",
+            ),
+        ]),
+    );
+    request(
+        (1, 1),
+        json!([
+            fix(
+                r#"Add missing argument "y""#,
+                range((1, 3), (1, 3)),
+                ", y=..."
+            ),
+            fix(
+                r#"Ignore "call-arg" errors on this line"#,
+                range((1, 4), (1, 4)),
+                "  # type: ignore[call-arg]",
+            ),
+        ]),
+    );
+    // Codes are added to existing ignore comments.
+    request(
+        (2, 4),
+        json!([
+            fix(r#"Change to "list""#, range((2, 3), (2, 7)), "list"),
+            fix(
+                r#"Import "List" from "typing""#,
+                range((0, 0), (0, 0)),
+                "from typing import List\n",
+            ),
+            fix(
+                r#"Ignore "name-defined" errors on this line"#,
+                range((2, 38), (2, 38)),
+                ", name-defined",
+            ),
+        ]),
+    );
+    // Lines continued with a backslash cannot have comments and ignores are placed before
+    // other comments.
+    request(
+        (3, 6),
+        json!([fix(
+            r#"Ignore "name-defined" errors on this line"#,
+            range((4, 3), (4, 3)),
+            "# type: ignore[name-defined]  ",
+        )]),
+    );
+}
+
+#[test]
+#[serial]
+fn check_unused_quick_fixes() {
+    let server = Project::with_fixture(
+        r#"
+        [file mypy.ini]
+        [mypy]
+        enable_error_code = unused-variable, unused-parameter, unused-import
+        "#,
+    )
+    .into_server();
+    let range = |start: (u32, u32), end: (u32, u32)| {
+        json!({
+            "start": {"line": start.0, "character": start.1},
+            "end": {"line": end.0, "character": end.1},
+        })
+    };
+    let path = "n.py";
+    server.open_in_memory_file(
+        path,
+        "import os, sys\n\
+         print(sys)\n\
+         def f(x: int) -> None:\n    y = len('a')\n\
+         def g(x: int, /) -> None:\n    print(1)\n",
+    );
+    let uri = server.doc_id(path).uri;
+    let request = |position: (u32, u32), expected| {
+        server.request_and_expect_json::<CodeActionRequest>(
+            CodeActionParams {
+                text_document: server.doc_id(path),
+                range: serde_json::from_value(range(position, position)).unwrap(),
+                context: CodeActionContext {
+                    only: Some(vec![CodeActionKind::QUICKFIX]),
+                    ..Default::default()
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            },
+            expected,
+        );
+    };
+    let fix = |title: &str, edit_range, new_text: &str| {
+        json!({
+            "title": title,
+            "kind": "quickfix",
+            "edit": {"changes": {uri.as_str(): [{"range": edit_range, "newText": new_text}]}},
+        })
+    };
+
+    request(
+        (0, 7),
+        json!([
+            fix(
+                r#"Remove unused import "os""#,
+                range((0, 0), (1, 0)),
+                "import sys\n"
+            ),
+            fix(
+                r#"Ignore "unused-import" errors on this line"#,
+                range((0, 14), (0, 14)),
+                "  # type: ignore[unused-import]",
+            ),
+        ]),
+    );
+    // Only positional-only parameters can be renamed without breaking callers.
+    request(
+        (2, 6),
+        json!([fix(
+            r#"Ignore "unused-parameter" errors on this line"#,
+            range((2, 22), (2, 22)),
+            "  # type: ignore[unused-parameter]",
+        )]),
+    );
+    request(
+        (4, 6),
+        json!([
+            fix(r#"Rename "x" to "_x""#, range((4, 6), (4, 6)), "_"),
+            fix(
+                r#"Ignore "unused-parameter" errors on this line"#,
+                range((4, 25), (4, 25)),
+                "  # type: ignore[unused-parameter]",
+            ),
+        ]),
+    );
+    // The right side is kept, because it might have side effects.
+    request(
+        (3, 4),
+        json!([
+            fix(r#"Remove assignment to "y""#, range((3, 4), (3, 8)), ""),
+            fix(
+                r#"Ignore "unused-variable" errors on this line"#,
+                range((3, 16), (3, 16)),
+                "  # type: ignore[unused-variable]",
+            ),
+        ]),
+    );
+}
@@ -31,6 +31,9 @@ pub(crate) struct Project<'a> {
     roots: Vec<String>,
     root_dir_contains_symlink: bool,
     push_diagnostics: bool,
+    diagnostics_refresh: bool,
+    without_workspace: bool,
+    client_name: Option<&'a str>,
 }
 
 impl<'a> Project<'a> {
@@ -41,6 +44,9 @@ impl<'a> Project<'a> {
             roots: vec![],
             root_dir_contains_symlink: false,
             push_diagnostics: false,
+            diagnostics_refresh: false,
+            without_workspace: false,
+            client_name: None,
         }
     }
 
@@ -59,6 +65,23 @@ impl<'a> Project<'a> {
         self
     }
 
+    pub(crate) fn with_diagnostics_refresh(mut self) -> Self {
+        self.diagnostics_refresh = true;
+        self
+    }
+
+    /// Like opening a single file in an editor, the client sends no workspace folders.
+    pub(crate) fn without_workspace(mut self) -> Self {
+        self.without_workspace = true;
+        self
+    }
+
+    /// The name the client sends in its `clientInfo`, e.g. "Visual Studio Code".
+    pub(crate) fn with_client_name(mut self, name: &'a str) -> Self {
+        self.client_name = Some(name);
+        self
+    }
+
     pub(crate) fn into_server(self) -> Server {
         self.into_server_detailed(None)
     }
@@ -82,7 +105,7 @@ impl<'a> Project<'a> {
                     .unwrap()
             })
             .collect::<Vec<String>>();
-        if roots.is_empty() {
+        if roots.is_empty() && !self.without_workspace {
             roots.push(tmp_dir_path);
         }
         Server {
@@ -91,6 +114,8 @@ impl<'a> Project<'a> {
                 &roots.iter().map(|root| root.as_str()).collect::<Vec<_>>(),
                 client_encodings,
                 !self.push_diagnostics,
+                self.diagnostics_refresh,
+                self.client_name,
             ),
             version_incrementor: Default::default(),
         }
@@ -204,6 +229,11 @@ impl Server {
         }
     }
 
+    pub fn expect_diagnostics_refresh(&self) {
+        let (id, ()) = self.expect_request::<lsp_types::request::WorkspaceDiagnosticRefresh>();
+        self.send(lsp_server::Response::new_ok(id, ()));
+    }
+
     pub fn expect_publish_diagnostics_for_file(&self, for_file: &str) -> Vec<String> {
         let (file, diags) = self.expect_publish_diagnostics();
         assert_eq!(for_file, file);